@@ -1,98 +1,1454 @@
-use clap::Parser;
+use crate::rewrite::common::RewriteSummary;
+use crate::utils::commit_history::{touches_path, HistoryCache};
+use crate::utils::theme::Theme;
+use crate::utils::validator::normalize_email;
+use clap::error::ErrorKind;
+use clap::parser::ValueSource;
+use clap::{ArgGroup, CommandFactory, FromArgMatches, Parser, Subcommand, ValueEnum};
 use colored::Colorize;
+use std::cell::RefCell;
+use std::ffi::OsString;
+use std::path::Path;
 use tempfile::TempDir;
 
-#[derive(Parser)]
+#[derive(Parser, Default)]
 #[command(author, version, about)]
+#[command(
+    after_help = "Exit codes:\n  0  Success\n  1  Error\n  2  Cancelled by the user\n  3  Nothing to do"
+)]
+#[command(group(
+    ArgGroup::new("mode").args([
+        "pick_specific_commits",
+        "range",
+        "interactive",
+        "docs",
+        "apply_plan",
+        "continue_rewrite",
+        "replace_in_files",
+        "author_date_order",
+        "round",
+        "list_backups",
+        "prune_backups",
+    ])
+))]
 pub struct Args {
+    #[command(subcommand)]
+    pub command: Option<Command>,
+
     #[arg(
         short = 'r',
         long = "repo-path",
+        global = true,
         help = "Path or URI to the repository"
     )]
     pub repo_path: Option<String>,
 
-    #[arg(long, help = "Email associated with the commits")]
-    pub email: Option<String>,
+    #[arg(
+        long = "clone-user",
+        value_name = "USER",
+        global = true,
+        help = "Username for cloning a private repository over HTTPS, with the password or token in GIT_TOKEN"
+    )]
+    pub clone_user: Option<String>,
+
+    #[arg(
+        long = "keep-clone",
+        value_name = "PATH",
+        global = true,
+        help = "Clone a --repo-path URL into PATH and keep it after the run instead of using a temporary directory"
+    )]
+    pub keep_clone: Option<String>,
+
+    #[arg(
+        long = "config",
+        value_name = "FILE",
+        global = true,
+        help = "Config file with default options (defaults to ~/.config/git-editor/config.toml)"
+    )]
+    pub config: Option<String>,
+
+    #[arg(
+        long = "no-color",
+        global = true,
+        help = "Disable colored output (also disabled by NO_COLOR or when stdout is not a terminal)"
+    )]
+    pub no_color: bool,
+
+    #[arg(
+        short = 'q',
+        long = "quiet",
+        global = true,
+        help = "Only print errors and the updated ref"
+    )]
+    pub quiet: bool,
+
+    #[arg(
+        short = 'y',
+        long = "yes",
+        alias = "assume-yes",
+        global = true,
+        help = "Answer yes to every confirmation prompt; without it, a prompt with no terminal to read from is an error"
+    )]
+    pub yes: bool,
+
+    #[arg(
+        long = "ref",
+        value_name = "REF",
+        global = true,
+        help = "Branch to rewrite instead of the current one (HEAD for a detached HEAD)"
+    )]
+    pub rewrite_ref: Option<String>,
+
+    #[arg(
+        long = "count",
+        global = true,
+        help = "Only print how many commits the chosen mode and filters select, without simulating or rewriting"
+    )]
+    pub count: bool,
+
+    // Without a subcommand these describe the full rewrite; resolve_command
+    // copies them into the fields the rest of the tool reads
+    #[command(flatten)]
+    pub identity_options: IdentityOptions,
+
+    #[command(flatten)]
+    pub full_rewrite_options: FullRewriteOptions,
+
+    #[command(flatten)]
+    pub commit_options: CommitOptions,
+
+    #[command(flatten)]
+    pub apply_options: ApplyOptions,
+
+    #[command(flatten)]
+    pub show_options: ShowOptions,
+
+    #[arg(
+        long = "min-gap-minutes",
+        value_name = "MINUTES",
+        help = "Smallest gap between two generated timestamps, and how far --round moves a tie, in minutes [default: 180]"
+    )]
+    pub min_gap_minutes: Option<u32>,
+
+    #[arg(
+        long = "rev",
+        value_name = "REV",
+        requires = "author_date_order",
+        conflicts_with_all = ["fix", "rewrite_ref"],
+        help = "Check the history of any revision, e.g. origin/main or a tag, with --author-date-order"
+    )]
+    pub rev: Option<String>,
+
+    // Deprecated mode flags, kept hidden for one release in favour of the subcommands
+    #[arg(
+        short = 'p',
+        long = "pick-specific-commits",
+        conflicts_with_all = ["IdentityOptions", "FullRewriteOptions"],
+        hide = true
+    )]
+    pub pick_specific_commits: bool,
+
+    #[arg(
+        short = 'x',
+        long = "range",
+        conflicts_with = "FullRewriteOptions",
+        hide = true
+    )]
+    pub range: bool,
+
+    #[arg(
+        short = 'i',
+        long = "interactive",
+        conflicts_with = "FullRewriteOptions",
+        hide = true
+    )]
+    pub interactive: bool,
+
+    #[arg(
+        long = "simulate",
+        alias = "dry-run",
+        conflicts_with = "ApplyOptions",
+        hide = true
+    )]
+    pub simulate: bool,
+
+    #[arg(long = "show-diff", conflicts_with = "ApplyOptions", hide = true)]
+    pub show_diff: bool,
+
+    #[arg(long = "message", requires = "range", hide = true)]
+    pub edit_message: bool,
+
+    #[arg(long = "author", requires = "range", hide = true)]
+    pub edit_author: bool,
+
+    #[arg(long = "time", requires = "range", hide = true)]
+    pub edit_time: bool,
+
+    #[arg(long = "docs", hide = true)]
+    pub docs: bool,
+
+    // Modes without a subcommand of their own. A subcommand never accepts
+    // them, and the `mode` group keeps them apart.
+    #[arg(
+        long = "apply-plan",
+        value_name = "FILE",
+        conflicts_with_all = ["IdentityOptions", "FullRewriteOptions"],
+        help = "Apply the per-commit edits described in a JSON plan file"
+    )]
+    pub apply_plan: Option<String>,
+
+    #[arg(
+        long = "continue",
+        conflicts_with_all = ["simulate", "count", "IdentityOptions", "FullRewriteOptions"],
+        help = "Resume a pick or plan rewrite that was interrupted before it finished"
+    )]
+    pub continue_rewrite: bool,
+
+    #[arg(
+        long = "replace-in-files",
+        value_name = "PATTERN=REPLACEMENT",
+        conflicts_with_all = [
+            "count",
+            "IdentityOptions",
+            "FullRewriteOptions",
+            "signoff",
+            "preserve_committer_identity",
+        ],
+        help = "Replace text in every file across the history, keeping commit metadata (repeatable)"
+    )]
+    pub replace_in_files: Vec<String>,
+
+    #[arg(
+        long = "author-date-order",
+        conflicts_with_all = [
+            "simulate",
+            "count",
+            "IdentityOptions",
+            "FullRewriteOptions",
+            "signoff",
+            "preserve_committer_identity",
+        ],
+        help = "Report commits whose author date is earlier than a parent's"
+    )]
+    pub author_date_order: bool,
+
+    #[arg(
+        long = "fix",
+        requires = "author_date_order",
+        help = "With --author-date-order, move those author dates just past their latest parent"
+    )]
+    pub fix: bool,
+
+    #[arg(
+        long = "round",
+        alias = "fixup-timestamps-only",
+        value_enum,
+        value_name = "GRANULARITY",
+        conflicts_with_all = ["simulate", "count", "IdentityOptions", "FullRewriteOptions"],
+        help = "Round every author and committer date to the minute, hour or day, keeping commits in order"
+    )]
+    pub round: Option<Granularity>,
+
+    #[arg(
+        long = "list-backups",
+        conflicts_with_all = [
+            "simulate",
+            "count",
+            "IdentityOptions",
+            "FullRewriteOptions",
+            "ApplyOptions",
+        ],
+        help = "List the backup refs under refs/git-editor/backup/ with their branch, time and target"
+    )]
+    pub list_backups: bool,
+
+    #[arg(
+        long = "prune-backups",
+        requires = "keep_backups",
+        conflicts_with_all = [
+            "simulate",
+            "count",
+            "IdentityOptions",
+            "FullRewriteOptions",
+            "ApplyOptions",
+        ],
+        help = "Delete all but the newest --keep backups of every branch"
+    )]
+    pub prune_backups: bool,
+
+    #[arg(
+        long = "keep",
+        value_name = "N",
+        help = "How many backups per branch --prune-backups keeps"
+    )]
+    pub keep_backups: Option<usize>,
+
+    // Set from the option groups above, or from those of the subcommand
+    #[clap(skip)]
+    pub email: Option<String>,
+
+    #[clap(skip)]
+    pub name: Option<String>,
+
+    #[clap(skip)]
+    pub start: Option<String>,
+
+    #[clap(skip)]
+    pub end: Option<String>,
+
+    #[clap(skip)]
+    pub show_history: bool,
+
+    #[clap(skip)]
+    pub graph: bool,
+
+    #[clap(skip)]
+    pub format: OutputFormat,
+
+    #[clap(skip)]
+    pub distribution: Option<Distribution>,
+
+    #[clap(skip)]
+    pub seed: Option<u64>,
+
+    #[clap(skip)]
+    pub date_format: Option<String>,
+
+    #[clap(skip)]
+    pub verbose: u8,
+
+    #[clap(skip)]
+    pub strict: bool,
+
+    #[clap(skip)]
+    pub no_future: bool,
+
+    #[clap(skip)]
+    pub exclude_author: Vec<String>,
+
+    #[clap(skip)]
+    pub only_author: Vec<String>,
+
+    #[clap(skip)]
+    pub modified_after: Option<String>,
+
+    #[clap(skip)]
+    pub modified_before: Option<String>,
+
+    #[clap(skip)]
+    pub preserve_committer_identity: bool,
+
+    #[clap(skip)]
+    pub signoff: bool,
+
+    #[clap(skip)]
+    pub allowed_email_domains: Vec<String>,
+
+    #[clap(skip)]
+    pub allow_empty_email: bool,
+
+    #[clap(skip)]
+    pub path_filter: Option<String>,
+
+    #[clap(skip)]
+    pub mailmap: Option<String>,
+
+    #[clap(skip)]
+    pub keep_author: bool,
+
+    #[clap(skip)]
+    pub find_author: Option<String>,
+
+    #[clap(skip)]
+    pub replace_name: Option<String>,
+
+    #[clap(skip)]
+    pub replace_email: Option<String>,
+
+    #[clap(skip)]
+    pub normalize_emails: bool,
+
+    #[clap(skip)]
+    pub lowercase_local_part: bool,
+
+    #[clap(skip)]
+    pub anonymize: bool,
+
+    #[clap(skip)]
+    pub anonymize_map: Option<String>,
+
+    #[clap(skip)]
+    pub limit: Option<usize>,
+
+    #[clap(skip)]
+    pub preview_rows: Option<usize>,
+
+    #[clap(skip)]
+    pub range_from: Option<String>,
+
+    #[clap(skip)]
+    pub range_to: Option<String>,
+
+    #[clap(skip)]
+    pub range_tail: Option<usize>,
+
+    #[clap(skip)]
+    pub range_head: Option<usize>,
+
+    #[clap(skip)]
+    pub shift_anchor: Option<String>,
+
+    #[clap(skip)]
+    pub batch: bool,
+
+    #[clap(skip)]
+    pub committer_column: bool,
+
+    #[clap(skip)]
+    pub message_prepend: Option<String>,
+
+    #[clap(skip)]
+    pub message_append: Option<String>,
+
+    #[clap(skip)]
+    pub message_strip: Option<String>,
+
+    #[clap(skip)]
+    pub output_remap: Option<String>,
+
+    #[clap(skip)]
+    pub update_refs: bool,
+
+    #[clap(skip)]
+    pub rewrite_notes: bool,
+
+    #[clap(skip)]
+    pub print_head: bool,
+
+    #[clap(skip)]
+    pub summary: bool,
+
+    #[clap(skip)]
+    pub allow_dirty: bool,
+
+    #[clap(skip)]
+    pub no_backup: bool,
+
+    // Set by `history --stats-only`
+    #[clap(skip)]
+    pub stats_only: bool,
+
+    // Set by `pick --show-patch`
+    #[clap(skip)]
+    pub show_patch: bool,
+
+    // Set by `pick --interactive-pick`
+    #[clap(skip)]
+    pub interactive_pick: bool,
+
+    // Colors from the config file
+    #[clap(skip)]
+    pub theme: Theme,
+
+    #[clap(skip)]
+    pub _temp_dir: Option<TempDir>,
+
+    // Commits from the last history walk, so the date-range probe and the mode
+    // itself do not both walk a large history
+    #[clap(skip)]
+    pub history_cache: HistoryCache,
+
+    // Filled in by a rewrite for --summary to print once the operation succeeds
+    #[clap(skip)]
+    pub rewrite_summary: RefCell<Option<RewriteSummary>>,
+}
+
+/// How a full rewrite spreads the generated timestamps over the date range.
+#[derive(ValueEnum, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Distribution {
+    /// Commits land at uniformly random points of the range
+    Uniform,
+    /// Random gaps between consecutive commits
+    #[default]
+    Random,
+    /// Evenly spaced commits
+    Linear,
+    /// Commits cluster around the middle of the range
+    Gaussian,
+}
+
+/// The step `--round` rounds commit dates to.
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Granularity {
+    Minute,
+    Hour,
+    Day,
+}
+
+/// How the history view prints the commits.
+#[derive(ValueEnum, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum OutputFormat {
+    /// Colored, human readable summary
+    #[default]
+    Text,
+    /// A JSON array on stdout, for scripts
+    Json,
+}
+
+// The identity and date range of a full rewrite; range mode pre-fills its
+// editor with them
+#[derive(clap::Args, Debug, Clone, Default, PartialEq, Eq)]
+pub struct IdentityOptions {
+    #[arg(long, help = "Email associated with the commits")]
+    pub email: Option<String>,
+
+    #[arg(short = 'n', long = "name", help = "Name associated with the commits")]
+    pub name: Option<String>,
+
+    #[arg(
+        short = 'b',
+        long = "begin",
+        help = "Start date for the commits in YYYY-MM-DD format"
+    )]
+    pub start: Option<String>,
+
+    #[arg(
+        short = 'e',
+        long = "end",
+        help = "End date for the commits in YYYY-MM-DD format"
+    )]
+    pub end: Option<String>,
+}
+
+// How a full rewrite generates timestamps and which commits and identities it touches
+#[derive(clap::Args, Debug, Clone, Default, PartialEq, Eq)]
+pub struct FullRewriteOptions {
+    #[arg(
+        long = "distribution",
+        value_enum,
+        help = "How generated timestamps are spread between --begin and --end [default: random]"
+    )]
+    pub distribution: Option<Distribution>,
+
+    #[arg(
+        long = "seed",
+        help = "Seed for the timestamp generator, so the same inputs give the same timestamps"
+    )]
+    pub seed: Option<u64>,
+
+    #[arg(
+        long = "strict",
+        help = "Treat validation warnings, such as a --name that looks like an email, as errors"
+    )]
+    pub strict: bool,
+
+    #[arg(
+        long = "no-future",
+        help = "Reject a --begin, --end or generated timestamp later than the current UTC time"
+    )]
+    pub no_future: bool,
+
+    #[arg(
+        long = "exclude-author",
+        value_name = "NAME_OR_EMAIL",
+        help = "Leave commits by this author untouched (repeatable)"
+    )]
+    pub exclude_author: Vec<String>,
+
+    #[arg(
+        long = "only-author",
+        value_name = "NAME_OR_EMAIL",
+        help = "Only rewrite commits by this author (repeatable)"
+    )]
+    pub only_author: Vec<String>,
+
+    #[arg(
+        long = "modified-after",
+        value_name = "DATE",
+        help = "Only rewrite commits authored at or after this date"
+    )]
+    pub modified_after: Option<String>,
+
+    #[arg(
+        long = "modified-before",
+        value_name = "DATE",
+        help = "Only rewrite commits authored before this date"
+    )]
+    pub modified_before: Option<String>,
+
+    #[arg(
+        long = "path",
+        value_name = "PATH",
+        help = "Only rewrite commits whose changes touch this file or directory"
+    )]
+    pub path_filter: Option<String>,
+
+    #[arg(
+        long = "mailmap",
+        value_name = "FILE",
+        conflicts_with_all = ["name", "email"],
+        help = "Map each author and committer through a git mailmap file instead of using --name/--email"
+    )]
+    pub mailmap: Option<String>,
+
+    #[arg(
+        long = "keep-author",
+        conflicts_with_all = ["mailmap", "name", "email"],
+        help = "Keep each commit's own author and committer, only changing the timestamps"
+    )]
+    pub keep_author: bool,
+
+    #[arg(
+        long = "find-author",
+        value_name = "NAME_OR_EMAIL",
+        requires_all = ["replace_name", "replace_email"],
+        conflicts_with_all = ["mailmap", "keep_author", "name", "email"],
+        help = "Only rewrite the identity of commits by this author, leaving every other commit intact"
+    )]
+    pub find_author: Option<String>,
+
+    #[arg(
+        long = "replace-name",
+        value_name = "NAME",
+        requires = "find_author",
+        help = "New name for the author matched by --find-author"
+    )]
+    pub replace_name: Option<String>,
+
+    #[arg(
+        long = "replace-email",
+        value_name = "EMAIL",
+        requires = "find_author",
+        help = "New email for the author matched by --find-author"
+    )]
+    pub replace_email: Option<String>,
+
+    #[arg(
+        long = "normalize-emails",
+        visible_alias = "fixup-emails",
+        conflicts_with_all = ["mailmap", "keep_author", "find_author", "name", "email"],
+        help = "Trim every author and committer email and lowercase its domain, leaving names untouched"
+    )]
+    pub normalize_emails: bool,
+
+    #[arg(
+        long = "lowercase-local-part",
+        requires = "normalize_emails",
+        help = "With --normalize-emails, lowercase the part before the @ as well"
+    )]
+    pub lowercase_local_part: bool,
+
+    // A commit left out would keep the identity the rest of the history hides
+    #[arg(
+        long = "anonymize",
+        conflicts_with_all = [
+            "mailmap",
+            "keep_author",
+            "find_author",
+            "normalize_emails",
+            "name",
+            "email",
+            "preserve_committer_identity",
+            "only_author",
+            "exclude_author",
+            "modified_after",
+            "modified_before",
+            "path_filter",
+        ],
+        help = "Replace every author and committer with a placeholder like 'Author 1 <author1@anon.invalid>', one per original email"
+    )]
+    pub anonymize: bool,
+
+    #[arg(
+        long = "anonymize-map",
+        value_name = "FILE",
+        requires = "anonymize",
+        help = "With --anonymize, write the placeholders and the identities they replace to FILE as a mailmap"
+    )]
+    pub anonymize_map: Option<String>,
+}
+
+// How edited commits are written, wherever commits are edited
+#[derive(clap::Args, Debug, Clone, Default, PartialEq, Eq)]
+pub struct CommitOptions {
+    #[arg(
+        long = "date-format",
+        alias = "input-date-format",
+        value_name = "STRFTIME",
+        help = "Format of the dates given to --begin, --end and --shift-anchor and typed at prompts [default: %Y-%m-%d %H:%M:%S]"
+    )]
+    pub date_format: Option<String>,
+
+    #[arg(
+        long = "signoff",
+        help = "Add a Signed-off-by trailer for the new author to every rewritten commit"
+    )]
+    pub signoff: bool,
+
+    #[arg(
+        long = "preserve-committer-identity",
+        help = "Keep each commit's original committer name and email when its author changes"
+    )]
+    pub preserve_committer_identity: bool,
+
+    #[arg(
+        long = "allowed-email-domain",
+        value_name = "DOMAIN",
+        help = "Reject any new author email whose domain is not DOMAIN (repeatable)"
+    )]
+    pub allowed_email_domains: Vec<String>,
+
+    #[arg(
+        long = "allow-empty-email",
+        help = "Accept an empty author email when editing a commit, and keep it empty when the commit is rewritten"
+    )]
+    pub allow_empty_email: bool,
+}
+
+// How a rewrite moves the branch and reports what it did; a preview has no use for them
+#[derive(clap::Args, Debug, Clone, Default, PartialEq, Eq)]
+pub struct ApplyOptions {
+    #[arg(
+        short = 'v',
+        long = "verbose",
+        action = clap::ArgAction::Count,
+        conflicts_with = "quiet",
+        help = "List every rewritten commit with the fields that changed; -vv also shows the old and new values and the commits kept as they were"
+    )]
+    pub verbose: u8,
+
+    #[arg(
+        long = "output-remap",
+        value_name = "FILE",
+        help = "Write the old -> new oid of every rewritten commit to FILE"
+    )]
+    pub output_remap: Option<String>,
+
+    #[arg(
+        long = "update-refs",
+        help = "Also move other branches and tags that point at rewritten commits"
+    )]
+    pub update_refs: bool,
+
+    #[arg(
+        long = "rewrite-notes",
+        help = "Copy the notes in refs/notes/commits from every rewritten commit to its new oid"
+    )]
+    pub rewrite_notes: bool,
+
+    #[arg(
+        long = "print-head",
+        conflicts_with = "count",
+        help = "Print the full oid of the rewritten tip on its own line instead of the updated-ref line"
+    )]
+    pub print_head: bool,
+
+    #[arg(
+        long = "summary",
+        conflicts_with = "count",
+        help = "Print a one-line digest of what the rewrite changed when it finishes"
+    )]
+    pub summary: bool,
+
+    #[arg(
+        long = "allow-dirty",
+        help = "Rewrite even if the working tree or index has uncommitted changes"
+    )]
+    pub allow_dirty: bool,
+
+    #[arg(
+        long = "no-backup",
+        help = "Do not keep the old tip as a backup ref under refs/git-editor/backup/"
+    )]
+    pub no_backup: bool,
+}
+
+// Showing the history once an edit is done
+#[derive(clap::Args, Debug, Clone, Default, PartialEq, Eq)]
+pub struct ShowOptions {
+    #[arg(
+        short = 's',
+        long = "show-history",
+        help = "Show updated commit history after rewriting"
+    )]
+    pub show_history: bool,
+
+    #[arg(
+        long = "graph",
+        requires = "show_history",
+        help = "Draw the branch and merge structure next to the listed commits"
+    )]
+    pub graph: bool,
+}
+
+// Message edits applied on top of those made in pick and range
+#[derive(clap::Args, Debug, Clone, Default, PartialEq, Eq)]
+pub struct MessageOptions {
+    #[arg(
+        long = "message-prepend",
+        value_name = "TEXT",
+        help = "Prepend TEXT to the message of every commit in the range or picked commit"
+    )]
+    pub message_prepend: Option<String>,
+
+    #[arg(
+        long = "message-append",
+        value_name = "TEXT",
+        help = "Append TEXT to the message of every commit in the range or picked commit"
+    )]
+    pub message_append: Option<String>,
+
+    #[arg(
+        long = "message-strip",
+        value_name = "REGEX",
+        help = "Remove every match of REGEX from the message of every commit in the range or picked commit"
+    )]
+    pub message_strip: Option<String>,
+}
+
+impl IdentityOptions {
+    fn fold_into(self, args: &mut Args) {
+        args.email = self.email;
+        args.name = self.name;
+        args.start = self.start;
+        args.end = self.end;
+    }
+}
+
+impl FullRewriteOptions {
+    fn fold_into(self, args: &mut Args) {
+        args.distribution = self.distribution;
+        args.seed = self.seed;
+        args.strict = self.strict;
+        args.no_future = self.no_future;
+        args.exclude_author = self.exclude_author;
+        args.only_author = self.only_author;
+        args.modified_after = self.modified_after;
+        args.modified_before = self.modified_before;
+        args.path_filter = self.path_filter;
+        args.mailmap = self.mailmap;
+        args.keep_author = self.keep_author;
+        args.find_author = self.find_author;
+        args.replace_name = self.replace_name;
+        args.replace_email = self.replace_email;
+        args.normalize_emails = self.normalize_emails;
+        args.lowercase_local_part = self.lowercase_local_part;
+        args.anonymize = self.anonymize;
+        args.anonymize_map = self.anonymize_map;
+    }
+}
+
+impl CommitOptions {
+    fn fold_into(self, args: &mut Args) {
+        args.date_format = self.date_format;
+        args.signoff = self.signoff;
+        args.preserve_committer_identity = self.preserve_committer_identity;
+        args.allowed_email_domains = self.allowed_email_domains;
+        args.allow_empty_email = self.allow_empty_email;
+    }
+}
+
+impl ApplyOptions {
+    fn fold_into(self, args: &mut Args) {
+        args.verbose = self.verbose;
+        args.output_remap = self.output_remap;
+        args.update_refs = self.update_refs;
+        args.rewrite_notes = self.rewrite_notes;
+        args.print_head = self.print_head;
+        args.summary = self.summary;
+        args.allow_dirty = self.allow_dirty;
+        args.no_backup = self.no_backup;
+    }
+}
+
+impl ShowOptions {
+    fn fold_into(self, args: &mut Args) {
+        args.show_history = self.show_history;
+        args.graph = self.graph;
+    }
+}
+
+impl MessageOptions {
+    fn fold_into(self, args: &mut Args) {
+        args.message_prepend = self.message_prepend;
+        args.message_append = self.message_append;
+        args.message_strip = self.message_strip;
+    }
+}
+
+#[derive(Subcommand, Debug, Clone, PartialEq, Eq)]
+pub enum Command {
+    /// Show commit history with statistics
+    History {
+        #[arg(
+            long = "stats-only",
+            help = "Print per-author statistics instead of every commit"
+        )]
+        stats_only: bool,
+
+        #[arg(
+            long = "graph",
+            conflicts_with_all = ["stats_only", "format", "path_filter"],
+            help = "Draw the branch and merge structure next to the listed commits"
+        )]
+        graph: bool,
+
+        #[arg(
+            long = "format",
+            value_enum,
+            default_value_t = OutputFormat::Text,
+            help = "Output format of the history view"
+        )]
+        format: OutputFormat,
+
+        #[arg(
+            long = "limit",
+            value_name = "N",
+            help = "Only load the N most recent commits"
+        )]
+        limit: Option<usize>,
+
+        #[arg(
+            long = "path",
+            value_name = "PATH",
+            help = "Only list commits whose changes touch this file or directory"
+        )]
+        path_filter: Option<String>,
+
+        #[arg(
+            long = "rev",
+            value_name = "REV",
+            conflicts_with = "rewrite_ref",
+            help = "Read the history from any revision, e.g. origin/main or a tag, instead of the current branch"
+        )]
+        rev: Option<String>,
+    },
+    /// Interactively pick a single commit to edit
+    Pick {
+        #[arg(
+            long = "simulate",
+            visible_alias = "dry-run",
+            conflicts_with = "ApplyOptions",
+            help = "Preview the edit without applying it"
+        )]
+        simulate: bool,
+
+        #[arg(
+            long = "show-diff",
+            conflicts_with = "ApplyOptions",
+            help = "Show a detailed diff preview (implies --simulate)"
+        )]
+        show_diff: bool,
+
+        #[arg(
+            long = "show-patch",
+            help = "Print the changes of the selected commit against its first parent"
+        )]
+        show_patch: bool,
+
+        #[arg(
+            long = "interactive-pick",
+            help = "Pick several commits from a list (Space toggles, Enter confirms) and give them all the same edits"
+        )]
+        interactive_pick: bool,
+
+        #[arg(
+            long = "limit",
+            value_name = "N",
+            help = "Only load the N most recent commits"
+        )]
+        limit: Option<usize>,
+
+        #[arg(
+            long = "path",
+            value_name = "PATH",
+            help = "Only list commits whose changes touch this file or directory"
+        )]
+        path_filter: Option<String>,
+
+        #[arg(
+            long = "preview-rows",
+            value_name = "N",
+            requires = "show_diff",
+            help = "Only list the first N changed commits in the --show-diff preview"
+        )]
+        preview_rows: Option<usize>,
+
+        #[command(flatten)]
+        commit_options: CommitOptions,
+
+        #[command(flatten)]
+        apply_options: ApplyOptions,
+
+        #[command(flatten)]
+        show_options: ShowOptions,
+
+        #[command(flatten)]
+        message_options: MessageOptions,
+    },
+    /// Edit a range of commits in the table editor
+    #[command(group(
+        ArgGroup::new("bounds")
+            .args(["range_from", "range_to", "range_tail", "range_head"])
+            .multiple(true)
+    ))]
+    Range {
+        #[arg(long = "message", help = "Edit only commit messages")]
+        message: bool,
+
+        #[arg(long = "author", help = "Edit only author name and email")]
+        author: bool,
+
+        #[arg(long = "time", help = "Edit only timestamps")]
+        time: bool,
+
+        #[arg(
+            long = "simulate",
+            visible_alias = "dry-run",
+            conflicts_with = "ApplyOptions",
+            help = "Preview the edits without applying them"
+        )]
+        simulate: bool,
+
+        #[arg(
+            long = "show-diff",
+            conflicts_with = "ApplyOptions",
+            help = "Show a detailed diff preview (implies --simulate)"
+        )]
+        show_diff: bool,
+
+        #[arg(
+            long = "from",
+            value_name = "COMMIT",
+            help = "Oldest commit of the range to edit (skips the range prompt)"
+        )]
+        range_from: Option<String>,
+
+        #[arg(
+            long = "to",
+            value_name = "COMMIT",
+            help = "Newest commit of the range to edit (skips the range prompt)"
+        )]
+        range_to: Option<String>,
+
+        #[arg(
+            long = "tail",
+            value_name = "N",
+            conflicts_with_all = ["range_from", "range_to"],
+            help = "Edit the N most recent commits (skips the range prompt)"
+        )]
+        range_tail: Option<usize>,
+
+        #[arg(
+            long = "head",
+            value_name = "N",
+            conflicts_with_all = ["range_from", "range_to", "range_tail"],
+            help = "Edit the N oldest commits (skips the range prompt)"
+        )]
+        range_head: Option<usize>,
 
-    #[arg(short = 'n', long = "name", help = "Name associated with the commits")]
-    pub name: Option<String>,
+        #[arg(
+            long = "shift-anchor",
+            value_name = "TIMESTAMP",
+            help = "Move the range so its oldest commit lands at TIMESTAMP, keeping the gaps between commits"
+        )]
+        shift_anchor: Option<String>,
 
-    #[arg(
-        short = 'b',
-        long = "begin",
-        help = "Start date for the commits in YYYY-MM-DD format"
-    )]
-    pub start: Option<String>,
+        // Stdin carries the edits, so the range cannot be picked at a prompt
+        #[arg(
+            long = "batch",
+            requires = "bounds",
+            help = "Read edits as <index>:<field>=<value> lines from stdin instead of opening the table editor"
+        )]
+        batch: bool,
 
-    #[arg(
-        short = 'e',
-        long = "end",
-        help = "End date for the commits in YYYY-MM-DD format"
-    )]
-    pub end: Option<String>,
+        #[arg(
+            long = "committer-column",
+            conflicts_with_all = ["simulate", "show_diff"],
+            help = "Show the committer date as its own column in the table editor, so author and committer dates are edited separately"
+        )]
+        committer_column: bool,
 
-    #[arg(
-        short = 's',
-        long = "show-history",
-        help = "Show updated commit history after rewriting"
-    )]
-    pub show_history: bool,
+        #[arg(
+            long = "limit",
+            value_name = "N",
+            help = "Only load the N most recent commits"
+        )]
+        limit: Option<usize>,
 
-    #[arg(
-        short = 'p',
-        long = "pick-specific-commits",
-        help = "Pick specific commits to rewrite. Provide a comma-separated list of commit hashes."
-    )]
-    pub pick_specific_commits: bool,
+        #[arg(
+            long = "preview-rows",
+            value_name = "N",
+            requires = "show_diff",
+            help = "Only list the first N changed commits in the --show-diff preview"
+        )]
+        preview_rows: Option<usize>,
 
-    #[arg(
-        short = 'x',
-        long = "range",
-        help = "Edit a range of commits (e.g., --range to interactively select range)"
-    )]
-    pub range: bool,
+        #[command(flatten)]
+        identity_options: IdentityOptions,
 
-    #[arg(
-        long = "simulate",
-        help = "Show what changes would be made without applying them (dry-run mode)"
-    )]
-    pub simulate: bool,
+        #[command(flatten)]
+        commit_options: CommitOptions,
 
-    #[arg(
-        long = "show-diff",
-        help = "Show detailed diff preview in simulation mode (requires --simulate)"
-    )]
-    pub show_diff: bool,
+        #[command(flatten)]
+        apply_options: ApplyOptions,
 
-    #[arg(
-        long = "message",
-        help = "Edit only commit messages in range mode (-x)"
-    )]
-    pub edit_message: bool,
+        #[command(flatten)]
+        show_options: ShowOptions,
 
-    #[arg(
-        long = "author",
-        help = "Edit only author name and email in range mode (-x)"
-    )]
-    pub edit_author: bool,
+        #[command(flatten)]
+        message_options: MessageOptions,
+    },
+    /// Rewrite the author and timestamps of the whole history
+    Rewrite {
+        #[arg(
+            short = 'i',
+            long = "interactive",
+            conflicts_with_all = ["FullRewriteOptions", "min_gap_minutes"],
+            help = "Edit the entire history in the interactive table editor"
+        )]
+        interactive: bool,
 
-    #[arg(long = "time", help = "Edit only timestamps in range mode (-x)")]
-    pub edit_time: bool,
+        #[arg(
+            long = "committer-column",
+            requires = "interactive",
+            help = "Show the committer date as its own column in the table editor, so author and committer dates are edited separately"
+        )]
+        committer_column: bool,
 
-    #[arg(
-        long = "docs",
-        help = "Open comprehensive documentation in the browser"
-    )]
-    pub docs: bool,
+        #[arg(
+            long = "min-gap-minutes",
+            value_name = "MINUTES",
+            help = "Smallest gap between two generated timestamps, in minutes [default: 180]"
+        )]
+        min_gap_minutes: Option<u32>,
 
-    #[clap(skip)]
-    pub _temp_dir: Option<TempDir>,
+        #[command(flatten)]
+        identity_options: IdentityOptions,
+
+        #[command(flatten)]
+        full_rewrite_options: FullRewriteOptions,
+
+        #[command(flatten)]
+        commit_options: CommitOptions,
+
+        #[command(flatten)]
+        apply_options: ApplyOptions,
+
+        #[command(flatten)]
+        show_options: ShowOptions,
+    },
+    /// Show what a rewrite would change without applying it
+    #[command(visible_alias = "dry-run")]
+    Simulate {
+        #[arg(long = "show-diff", help = "Show a detailed diff preview")]
+        show_diff: bool,
+
+        #[arg(
+            long = "apply-plan",
+            value_name = "FILE",
+            conflicts_with_all = ["IdentityOptions", "FullRewriteOptions", "min_gap_minutes"],
+            help = "Preview the edits of a JSON plan file instead of a full rewrite"
+        )]
+        apply_plan: Option<String>,
+
+        #[arg(
+            long = "format",
+            value_enum,
+            default_value_t = OutputFormat::Text,
+            help = "Print the changes as text or as a JSON rewrite plan"
+        )]
+        format: OutputFormat,
+
+        #[arg(
+            long = "rev",
+            value_name = "REV",
+            conflicts_with = "rewrite_ref",
+            help = "Read the history from any revision, e.g. origin/main or a tag, instead of the current branch"
+        )]
+        rev: Option<String>,
+
+        #[arg(
+            long = "preview-rows",
+            value_name = "N",
+            requires = "show_diff",
+            help = "Only list the first N changed commits in the --show-diff preview"
+        )]
+        preview_rows: Option<usize>,
+
+        #[arg(
+            long = "min-gap-minutes",
+            value_name = "MINUTES",
+            help = "Smallest gap between two generated timestamps, in minutes [default: 180]"
+        )]
+        min_gap_minutes: Option<u32>,
+
+        #[command(flatten)]
+        identity_options: IdentityOptions,
+
+        #[command(flatten)]
+        full_rewrite_options: FullRewriteOptions,
+
+        #[command(flatten)]
+        commit_options: CommitOptions,
+    },
+    /// Open comprehensive documentation in the browser
+    Docs,
 }
 
 impl Args {
+    /// Parses the command line like `Parser::parse` and folds the subcommand in;
+    /// see `try_parse_resolved`.
+    pub fn parse_resolved() -> Args {
+        Args::try_parse_resolved(std::env::args_os()).unwrap_or_else(|error| error.exit())
+    }
+
+    /// Parses `argv` and folds the chosen subcommand into the fields the rest
+    /// of the tool reads. Options given before a subcommand belong to the full
+    /// rewrite or to a mode without a subcommand, so only the global ones may
+    /// be combined with it.
+    pub fn try_parse_resolved<I, T>(argv: I) -> std::result::Result<Args, clap::Error>
+    where
+        I: IntoIterator<Item = T>,
+        T: Into<OsString> + Clone,
+    {
+        let mut command = Args::command();
+        let matches = command.try_get_matches_from_mut(argv)?;
+        let mut args = Args::from_arg_matches(&matches)?;
+
+        if let Some((name, _)) = matches.subcommand() {
+            let top_level = command.get_arguments().find(|arg| {
+                !arg.is_global_set()
+                    && matches.value_source(arg.get_id().as_str()) == Some(ValueSource::CommandLine)
+            });
+            if let Some(arg) = top_level {
+                let flag = arg.get_long().map_or_else(
+                    || format!("-{}", arg.get_short().unwrap_or_default()),
+                    |long| format!("--{long}"),
+                );
+                return Err(command.error(
+                    ErrorKind::ArgumentConflict,
+                    format!("the argument '{flag}' cannot be used with the '{name}' subcommand"),
+                ));
+            }
+        }
+
+        args.resolve_command();
+        Ok(args)
+    }
+
+    // Folds the option groups of the chosen subcommand, or of the full rewrite
+    // without one, into the fields the rest of the tool reads. Without a
+    // subcommand the deprecated flags still work but print a warning.
+    fn resolve_command(&mut self) {
+        let Some(command) = self.command.clone() else {
+            std::mem::take(&mut self.identity_options).fold_into(self);
+            std::mem::take(&mut self.full_rewrite_options).fold_into(self);
+            std::mem::take(&mut self.commit_options).fold_into(self);
+            std::mem::take(&mut self.apply_options).fold_into(self);
+            std::mem::take(&mut self.show_options).fold_into(self);
+            for (flag, replacement) in self.legacy_flags_used() {
+                eprintln!(
+                    "{} {flag} is deprecated and will be removed in the next release, use `git-editor {replacement}` instead",
+                    "Warning:".yellow().bold()
+                );
+            }
+            self.validate_simulation_args();
+            return;
+        };
+
+        match command {
+            Command::History {
+                stats_only,
+                graph,
+                format,
+                limit,
+                path_filter,
+                rev,
+            } => {
+                self.show_history = true;
+                self.stats_only = stats_only;
+                self.graph = graph;
+                self.format = format;
+                self.limit = limit;
+                self.path_filter = path_filter;
+                self.rev = rev;
+            }
+            Command::Pick {
+                simulate,
+                show_diff,
+                show_patch,
+                interactive_pick,
+                limit,
+                path_filter,
+                preview_rows,
+                commit_options,
+                apply_options,
+                show_options,
+                message_options,
+            } => {
+                self.pick_specific_commits = true;
+                self.simulate = simulate;
+                self.show_diff = show_diff;
+                self.show_patch = show_patch;
+                self.interactive_pick = interactive_pick;
+                self.limit = limit;
+                self.path_filter = path_filter;
+                self.preview_rows = preview_rows;
+                commit_options.fold_into(self);
+                apply_options.fold_into(self);
+                show_options.fold_into(self);
+                message_options.fold_into(self);
+            }
+            Command::Range {
+                message,
+                author,
+                time,
+                simulate,
+                show_diff,
+                range_from,
+                range_to,
+                range_tail,
+                range_head,
+                shift_anchor,
+                batch,
+                committer_column,
+                limit,
+                preview_rows,
+                identity_options,
+                commit_options,
+                apply_options,
+                show_options,
+                message_options,
+            } => {
+                self.range = true;
+                self.edit_message = message;
+                self.edit_author = author;
+                self.edit_time = time;
+                self.simulate = simulate;
+                self.show_diff = show_diff;
+                self.range_from = range_from;
+                self.range_to = range_to;
+                self.range_tail = range_tail;
+                self.range_head = range_head;
+                self.shift_anchor = shift_anchor;
+                self.batch = batch;
+                self.committer_column = committer_column;
+                self.limit = limit;
+                self.preview_rows = preview_rows;
+                identity_options.fold_into(self);
+                commit_options.fold_into(self);
+                apply_options.fold_into(self);
+                show_options.fold_into(self);
+                message_options.fold_into(self);
+            }
+            Command::Rewrite {
+                interactive,
+                committer_column,
+                min_gap_minutes,
+                identity_options,
+                full_rewrite_options,
+                commit_options,
+                apply_options,
+                show_options,
+            } => {
+                self.interactive = interactive;
+                self.committer_column = committer_column;
+                self.min_gap_minutes = min_gap_minutes;
+                identity_options.fold_into(self);
+                full_rewrite_options.fold_into(self);
+                commit_options.fold_into(self);
+                apply_options.fold_into(self);
+                show_options.fold_into(self);
+            }
+            Command::Simulate {
+                show_diff,
+                apply_plan,
+                format,
+                rev,
+                preview_rows,
+                min_gap_minutes,
+                identity_options,
+                full_rewrite_options,
+                commit_options,
+            } => {
+                self.simulate = true;
+                self.show_diff = show_diff;
+                self.apply_plan = apply_plan;
+                self.format = format;
+                self.rev = rev;
+                self.preview_rows = preview_rows;
+                self.min_gap_minutes = min_gap_minutes;
+                identity_options.fold_into(self);
+                full_rewrite_options.fold_into(self);
+                commit_options.fold_into(self);
+            }
+            Command::Docs => self.docs = true,
+        }
+        self.validate_simulation_args();
+    }
+
+    fn legacy_flags_used(&self) -> Vec<(&'static str, &'static str)> {
+        let flags = [
+            (
+                self.pick_specific_commits,
+                "--pick-specific-commits",
+                "pick",
+            ),
+            (self.range, "--range", "range"),
+            (self.interactive, "--interactive", "rewrite --interactive"),
+            // --replace-in-files has no subcommand, so --dry-run stays how it is previewed
+            (
+                self.simulate && self.replace_in_files.is_empty(),
+                "--simulate",
+                "simulate",
+            ),
+            (self.show_diff, "--show-diff", "simulate --show-diff"),
+            (self.edit_message, "--message", "range --message"),
+            (self.edit_author, "--author", "range --author"),
+            (self.edit_time, "--time", "range --time"),
+            (self.docs, "--docs", "docs"),
+        ];
+        let mut used: Vec<_> = flags
+            .into_iter()
+            .filter(|(set, _, _)| *set)
+            .map(|(_, flag, replacement)| (flag, replacement))
+            .collect();
+
+        // On its own -s used to select the history view rather than modify another mode
+        if self.command.is_none() && self.is_history_mode() && used.is_empty() {
+            used.push(("--show-history", "history"));
+        }
+        used
+    }
+
+    // Whether the history view was requested, as opposed to -s showing the
+    // rewritten history after another mode
+    pub fn is_history_mode(&self) -> bool {
+        match &self.command {
+            Some(command) => matches!(command, Command::History { .. }),
+            None => {
+                self.show_history
+                    && !(self.pick_specific_commits
+                        || self.range
+                        || self.interactive
+                        || self.apply_plan.is_some()
+                        || self.continue_rewrite
+                        || !self.replace_in_files.is_empty()
+                        || self.author_date_order
+                        || self.round.is_some()
+                        || self.is_backup_mode())
+            }
+        }
+    }
+
+    // --list-backups and --prune-backups only look at refs, never at history
+    pub fn is_backup_mode(&self) -> bool {
+        self.list_backups || self.prune_backups
+    }
+
     pub fn ensure_all_args_present(&mut self) -> crate::utils::types::Result<()> {
-        use crate::utils::git_clone::{clone_repository, get_repo_name_from_url, is_git_url};
+        use crate::utils::git_clone::{get_repo_name_from_url, is_git_url};
         use crate::utils::git_config::{get_git_user_email, get_git_user_name};
         use crate::utils::prompt::{prompt_for_missing_arg, prompt_with_default};
 
@@ -103,31 +1459,66 @@ impl Args {
         // Handle Git URL cloning
         let repo_path = self.repo_path.as_ref().unwrap();
         if is_git_url(repo_path) {
-            println!("{}", "🔍 Git URL detected - cloning repository...".cyan());
-            let repo_name = get_repo_name_from_url(repo_path);
-            println!("{} {}", "Repository:".bold(), repo_name.yellow());
+            if !self.quiet {
+                println!("{}", "🔍 Git URL detected - cloning repository...".cyan());
+                let repo_name = get_repo_name_from_url(repo_path);
+                println!("{} {}", "Repository:".bold(), repo_name.yellow());
+            }
 
-            let temp_dir = clone_repository(repo_path)?;
-            // Store the temporary directory path
-            self.repo_path = Some(temp_dir.path().to_string_lossy().to_string());
+            self.clone_remote_repo()?;
+        } else if self.keep_clone.is_some() {
+            return Err("--keep-clone is only supported when --repo-path is a Git URL".into());
+        } else {
+            self.discover_repo();
+        }
 
-            // Keep the temporary directory alive for the duration of the program
-            self._temp_dir = Some(temp_dir);
+        // Nothing to prompt for when there is no history to rewrite, or when
+        // the selected commits are only counted
+        if self.has_no_commits() || self.count {
+            return Ok(());
         }
 
         // Skip prompting for email, name, start, and end if using show_history, pick_specific_commits, simulation, or docs modes
-        if self.show_history || self.pick_specific_commits || self.simulate || self.docs {
+        if self.is_history_mode() || self.pick_specific_commits || self.simulate || self.docs {
             return Ok(());
         }
 
-        // Range mode will prompt for its own parameters interactively
-        if self.range {
+        // A plan file or an interrupted rewrite carries all of its edits itself, a content rewrite,
+        // date order repair or rounding keeps every commit's identity, and
+        // backups are only refs
+        if self.apply_plan.is_some()
+            || self.continue_rewrite
+            || !self.replace_in_files.is_empty()
+            || self.author_date_order
+            || self.round.is_some()
+            || self.is_backup_mode()
+        {
+            return Ok(());
+        }
+
+        // Range and interactive modes collect their edits in the table editor
+        if self.range || self.interactive {
+            return Ok(());
+        }
+
+        // --find-author, --normalize-emails and --anonymize replace identities
+        // and keep every timestamp unless a date range is given
+        if self.find_author.is_some() || self.normalize_emails || self.anonymize {
+            self.start
+                .get_or_insert_with(|| "KEEP_ORIGINAL".to_string());
+            self.end.get_or_insert_with(|| "KEEP_ORIGINAL".to_string());
             return Ok(());
         }
 
+        // A mailmap or each commit's own identity replaces --name/--email, so
+        // only the date range is needed
+        if self.mailmap.is_some() || self.keep_author {
+            return self.ensure_date_range_present();
+        }
+
         if self.email.is_none() {
             // Try to get email from git config first
-            if let Some(git_email) = get_git_user_email() {
+            if let Some(git_email) = get_git_user_email(self.repo_path.as_deref().unwrap()) {
                 self.email = Some(prompt_with_default("Email", &git_email)?);
             } else {
                 self.email = Some(prompt_for_missing_arg("email")?);
@@ -136,21 +1527,29 @@ impl Args {
 
         if self.name.is_none() {
             // Try to get name from git config first
-            if let Some(git_name) = get_git_user_name() {
+            if let Some(git_name) = get_git_user_name(self.repo_path.as_deref().unwrap()) {
                 self.name = Some(prompt_with_default("Name", &git_name)?);
             } else {
                 self.name = Some(prompt_for_missing_arg("name")?);
             }
         }
 
+        self.ensure_date_range_present()
+    }
+
+    fn ensure_date_range_present(&mut self) -> crate::utils::types::Result<()> {
+        use crate::utils::datetime::format_hint;
+        use crate::utils::prompt::{prompt_for_missing_arg, prompt_with_default};
+
         if self.start.is_none() || self.end.is_none() {
+            let hint = format_hint(self.date_format());
             // Get the repository's commit date range to provide smart defaults
             let date_range = self.get_repository_date_range()?;
 
             if self.start.is_none() {
                 if let Some((ref start_date, _)) = date_range {
                     let input = prompt_with_default(
-                        "Start date (YYYY-MM-DD HH:MM:SS, press Enter to keep original timestamps)",
+                        &format!("Start date ({hint}, press Enter to keep original timestamps)"),
                         start_date,
                     )?;
                     // If user input exactly matches the default, set a special flag
@@ -160,14 +1559,14 @@ impl Args {
                         self.start = Some(input);
                     }
                 } else {
-                    self.start = Some(prompt_for_missing_arg("start date (YYYY-MM-DD HH:MM:SS)")?);
+                    self.start = Some(prompt_for_missing_arg(&format!("start date ({hint})"))?);
                 }
             }
 
             if self.end.is_none() {
                 if let Some((_, ref end_date)) = date_range {
                     let input = prompt_with_default(
-                        "End date (YYYY-MM-DD HH:MM:SS, press Enter to keep original timestamps)",
+                        &format!("End date ({hint}, press Enter to keep original timestamps)"),
                         end_date,
                     )?;
                     // If user input exactly matches the default, set a special flag
@@ -185,7 +1584,7 @@ impl Args {
                         self.end = Some(input);
                     }
                 } else {
-                    self.end = Some(prompt_for_missing_arg("end date (YYYY-MM-DD HH:MM:SS)")?);
+                    self.end = Some(prompt_for_missing_arg(&format!("end date ({hint})"))?);
                 }
             }
         }
@@ -193,35 +1592,84 @@ impl Args {
         Ok(())
     }
 
+    /// Clones the `--repo-path` URL and points `repo_path` at the clone: into
+    /// `--keep-clone`, which outlives the run, or else a temporary directory
+    /// that is removed when the `Args` are dropped.
+    pub fn clone_remote_repo(&mut self) -> crate::utils::types::Result<()> {
+        use crate::utils::git_clone::{clone_into, clone_repository};
+
+        let git_url = self.repo_path.as_ref().unwrap();
+        let clone_user = self.clone_user.as_deref();
+        if let Some(keep_clone) = &self.keep_clone {
+            clone_into(git_url, Path::new(keep_clone), clone_user, self.quiet)?;
+            let kept = std::fs::canonicalize(keep_clone)?;
+            self.repo_path = Some(kept.to_string_lossy().to_string());
+        } else {
+            let temp_dir = clone_repository(git_url, clone_user, self.quiet)?;
+            self.repo_path = Some(temp_dir.path().to_string_lossy().to_string());
+            self._temp_dir = Some(temp_dir);
+        }
+        Ok(())
+    }
+
+    /// Points a local `repo_path` at the top of the repository containing it,
+    /// found the way git finds it, so git-editor also runs from a subdirectory.
+    /// A path outside any repository is left for `validate_inputs` to report.
+    pub fn discover_repo(&mut self) {
+        let Some(repo) = self
+            .repo_path
+            .as_deref()
+            .and_then(|path| git2::Repository::discover(path).ok())
+        else {
+            return;
+        };
+        let root = repo.workdir().unwrap_or(repo.path());
+        self.repo_path = Some(root.to_string_lossy().to_string());
+    }
+
+    /// The unborn branch HEAD points at when the repository has no commits
+    /// yet and the history would be read from it: from HEAD itself, or from
+    /// `--ref`/`--rev` naming that branch. Repositories that cannot be opened
+    /// are left for `validate_inputs` to report, and so is an unborn HEAD next
+    /// to existing branches, which only needs `--ref`.
+    pub fn unborn_branch(&self) -> Option<String> {
+        let repo = git2::Repository::open(self.repo_path.as_ref()?).ok()?;
+        let branch = crate::utils::git_ref::unborn_branch(&repo)?;
+
+        let requested = self
+            .rev
+            .as_deref()
+            .or(self.rewrite_ref.as_deref())
+            .unwrap_or("HEAD");
+        let names_branch = requested == "HEAD"
+            || requested == branch
+            || requested.strip_prefix("refs/heads/") == Some(branch.as_str());
+        names_branch.then_some(branch)
+    }
+
+    /// Whether the history to work on has no commits yet; see `unborn_branch`.
+    pub fn has_no_commits(&self) -> bool {
+        self.unborn_branch().is_some()
+    }
+
+    /// The strftime format dates are parsed with: `--date-format`, or the default.
+    pub fn date_format(&self) -> &str {
+        self.date_format
+            .as_deref()
+            .unwrap_or(crate::utils::datetime::DEFAULT_DATE_FORMAT)
+    }
+
     pub fn should_keep_original_timestamps(&self) -> bool {
         self.start.as_ref() == Some(&"KEEP_ORIGINAL".to_string())
             && self.end.as_ref() == Some(&"KEEP_ORIGINAL".to_string())
     }
 
     fn get_repository_date_range(&self) -> crate::utils::types::Result<Option<(String, String)>> {
-        use crate::utils::commit_history::get_commit_history;
-
-        if let Some(repo_path) = &self.repo_path {
-            // Create a temporary Args instance for getting commit history
-            let temp_args = Args {
-                repo_path: Some(repo_path.clone()),
-                email: None,
-                name: None,
-                start: None,
-                end: None,
-                show_history: true, // Use show_history mode to avoid validation requirements
-                pick_specific_commits: false,
-                range: false,
-                simulate: false,
-                show_diff: false,
-                edit_message: false,
-                edit_author: false,
-                edit_time: false,
-                docs: false,
-                _temp_dir: None,
-            };
+        use crate::utils::commit_history::collect_commits;
 
-            match get_commit_history(&temp_args, false) {
+        if self.repo_path.is_some() {
+            // The whole history, whatever --limit says; the walk is cached for the run itself
+            match collect_commits(self, None) {
                 Ok(commits) => {
                     if commits.is_empty() {
                         return Ok(None);
@@ -232,13 +1680,14 @@ impl Args {
                     let oldest_commit = &commits[commits.len() - 1];
 
                     // Format the dates as strings
+                    // In the format the answer is parsed with, so the default can be accepted as is
                     let start_date = oldest_commit
                         .timestamp
-                        .format("%Y-%m-%d %H:%M:%S")
+                        .format(self.date_format())
                         .to_string();
                     let end_date = newest_commit
                         .timestamp
-                        .format("%Y-%m-%d %H:%M:%S")
+                        .format(self.date_format())
                         .to_string();
 
                     Ok(Some((start_date, end_date)))
@@ -250,11 +1699,95 @@ impl Args {
         }
     }
 
-    pub fn validate_simulation_args(&self) -> crate::utils::types::Result<()> {
-        if self.show_diff && !self.simulate {
-            return Err("--show-diff requires --simulate to be enabled".into());
+    // A diff preview only makes sense for a simulation, so --show-diff on its
+    // own turns one on rather than being rejected
+    pub fn validate_simulation_args(&mut self) {
+        if self.show_diff {
+            self.simulate = true;
+        }
+    }
+
+    // Whether a commit by the given author passes the --only-author/--exclude-author filters.
+    // Names match exactly, emails match case-insensitively.
+    pub fn is_author_selected(&self, name: &str, email: &str) -> bool {
+        let matches = |filter: &String| filter == name || filter.eq_ignore_ascii_case(email);
+
+        if !self.only_author.is_empty() && !self.only_author.iter().any(matches) {
+            return false;
+        }
+        if self.find_author.as_ref().is_some_and(|find| !matches(find)) {
+            return false;
+        }
+        !self.exclude_author.iter().any(matches)
+    }
+
+    // The --replace-name/--replace-email identity for a signature matched by
+    // --find-author, or the signature's own identity otherwise
+    pub fn replace_found_author(&self, name: &str, email: &str) -> (String, String) {
+        match (&self.find_author, &self.replace_name, &self.replace_email) {
+            (Some(find), Some(new_name), Some(new_email))
+                if find == name || find.eq_ignore_ascii_case(email) =>
+            {
+                (new_name.clone(), new_email.clone())
+            }
+            _ => (name.to_string(), email.to_string()),
+        }
+    }
+
+    // Whether an original author date falls in the --modified-after/--modified-before
+    // window; the start is included, the end is not
+    pub fn is_date_selected(
+        &self,
+        authored: chrono::NaiveDateTime,
+    ) -> crate::utils::types::Result<bool> {
+        use crate::utils::datetime::parse_datetime;
+
+        if let Some(after) = &self.modified_after {
+            if authored < parse_datetime(after, self.date_format())? {
+                return Ok(false);
+            }
+        }
+        if let Some(before) = &self.modified_before {
+            if authored >= parse_datetime(before, self.date_format())? {
+                return Ok(false);
+            }
+        }
+        Ok(true)
+    }
+
+    // Whether a commit passes the author and date filters and, with --path, changes that path
+    pub fn is_commit_selected(
+        &self,
+        repo: &git2::Repository,
+        commit: &git2::Commit,
+    ) -> crate::utils::types::Result<bool> {
+        let author = commit.author();
+        if !self.is_author_selected(
+            author.name().unwrap_or("Unknown"),
+            author.email().unwrap_or("unknown@email.com"),
+        ) {
+            return Ok(false);
+        }
+        let authored = chrono::DateTime::from_timestamp(author.when().seconds(), 0)
+            .unwrap_or_default()
+            .naive_utc();
+        if !self.is_date_selected(authored)? {
+            return Ok(false);
+        }
+        // --normalize-emails only touches commits with an email to fix
+        if self.normalize_emails {
+            let needs_fix = |sig: git2::Signature| {
+                let email = sig.email().unwrap_or("unknown@email.com");
+                normalize_email(email, self.lowercase_local_part) != email
+            };
+            if !needs_fix(author) && !needs_fix(commit.committer()) {
+                return Ok(false);
+            }
+        }
+        match &self.path_filter {
+            Some(path) => touches_path(repo, commit, path),
+            None => Ok(true),
         }
-        Ok(())
     }
 
     pub fn get_editable_fields(&self) -> (bool, bool, bool, bool) {
@@ -298,7 +1831,7 @@ mod tests {
             edit_author: false,
             edit_time: false,
             docs: false,
-            _temp_dir: None,
+            ..Default::default()
         };
 
         assert_eq!(args.repo_path, None);
@@ -328,7 +1861,7 @@ mod tests {
             edit_author: false,
             edit_time: false,
             docs: false,
-            _temp_dir: None,
+            ..Default::default()
         };
 
         assert_eq!(args.repo_path, Some("/test/repo".to_string()));
@@ -353,7 +1886,7 @@ mod tests {
             edit_author: false,
             edit_time: false,
             docs: false,
-            _temp_dir: None,
+            ..Default::default()
         };
 
         assert_eq!(args.repo_path, Some("/test/repo".to_string()));
@@ -378,7 +1911,7 @@ mod tests {
             edit_author: false,
             edit_time: false,
             docs: false,
-            _temp_dir: None,
+            ..Default::default()
         };
 
         assert_eq!(args.repo_path, Some("/test/repo".to_string()));
@@ -405,7 +1938,7 @@ mod tests {
             edit_author: false,
             edit_time: false,
             docs: false,
-            _temp_dir: None,
+            ..Default::default()
         };
 
         assert_eq!(args.repo_path, Some("/test/repo".to_string()));
@@ -431,7 +1964,7 @@ mod tests {
             edit_author: false,
             edit_time: false,
             docs: false,
-            _temp_dir: None,
+            ..Default::default()
         };
 
         assert!(args.simulate);
@@ -440,7 +1973,7 @@ mod tests {
 
     #[test]
     fn test_validate_simulation_args_valid() {
-        let args = Args {
+        let mut args = Args {
             repo_path: Some("/test/repo".to_string()),
             email: None,
             name: None,
@@ -455,16 +1988,17 @@ mod tests {
             edit_author: false,
             edit_time: false,
             docs: false,
-            _temp_dir: None,
+            ..Default::default()
         };
 
-        let result = args.validate_simulation_args();
-        assert!(result.is_ok());
+        args.validate_simulation_args();
+        assert!(args.simulate);
+        assert!(args.show_diff);
     }
 
     #[test]
-    fn test_validate_simulation_args_invalid() {
-        let args = Args {
+    fn test_validate_simulation_args_show_diff_implies_simulate() {
+        let mut args = Args {
             repo_path: Some("/test/repo".to_string()),
             email: None,
             name: None,
@@ -479,15 +2013,12 @@ mod tests {
             edit_author: false,
             edit_time: false,
             docs: false,
-            _temp_dir: None,
+            ..Default::default()
         };
 
-        let result = args.validate_simulation_args();
-        assert!(result.is_err());
-        assert!(result
-            .unwrap_err()
-            .to_string()
-            .contains("--show-diff requires --simulate"));
+        args.validate_simulation_args();
+        assert!(args.simulate);
+        assert!(args.show_diff);
     }
 
     #[test]
@@ -507,7 +2038,7 @@ mod tests {
             edit_author: false,
             edit_time: false,
             docs: true,
-            _temp_dir: None,
+            ..Default::default()
         };
 
         assert!(args.docs);
@@ -534,11 +2065,402 @@ mod tests {
             edit_author: false,
             edit_time: false,
             docs: true,
-            _temp_dir: None,
+            ..Default::default()
         };
 
         // This should not fail even though repo_path is None, because docs mode skips validation
         let result = args.ensure_all_args_present();
         assert!(result.is_ok());
     }
+
+    #[test]
+    fn test_is_author_selected() {
+        let args = Args::default();
+        assert!(args.is_author_selected("Alice", "alice@example.com"));
+
+        let args = Args {
+            exclude_author: vec!["Bob".to_string(), "carol@example.com".to_string()],
+            ..Default::default()
+        };
+        assert!(args.is_author_selected("Alice", "alice@example.com"));
+        assert!(!args.is_author_selected("Bob", "bob@example.com"));
+        assert!(!args.is_author_selected("Carol", "Carol@Example.com"));
+
+        let args = Args {
+            only_author: vec!["alice@example.com".to_string()],
+            ..Default::default()
+        };
+        assert!(args.is_author_selected("Alice", "alice@example.com"));
+        assert!(!args.is_author_selected("Bob", "bob@example.com"));
+
+        let args = Args {
+            only_author: vec!["Alice".to_string(), "Bob".to_string()],
+            exclude_author: vec!["Bob".to_string()],
+            ..Default::default()
+        };
+        assert!(args.is_author_selected("Alice", "alice@example.com"));
+        assert!(!args.is_author_selected("Bob", "bob@example.com"));
+    }
+
+    #[test]
+    fn test_replace_found_author() {
+        let args = Args {
+            find_author: Some("old@example.com".to_string()),
+            replace_name: Some("Alice".to_string()),
+            replace_email: Some("alice@example.com".to_string()),
+            ..Default::default()
+        };
+        assert!(args.is_author_selected("Old Name", "Old@Example.com"));
+        assert!(!args.is_author_selected("Bob", "bob@example.com"));
+        assert_eq!(
+            args.replace_found_author("Old Name", "old@example.com"),
+            ("Alice".to_string(), "alice@example.com".to_string())
+        );
+        assert_eq!(
+            args.replace_found_author("Bob", "bob@example.com"),
+            ("Bob".to_string(), "bob@example.com".to_string())
+        );
+    }
+
+    fn parse(argv: &[&str]) -> std::result::Result<Args, clap::Error> {
+        Args::try_parse_resolved(argv)
+    }
+
+    #[test]
+    fn test_subcommands_set_mode_flags() {
+        let args = parse(&["git-editor", "range", "--message", "-r", "/repo"]).unwrap();
+        assert!(args.range);
+        assert!(args.edit_message);
+        assert!(!args.edit_author);
+        assert_eq!(args.repo_path.as_deref(), Some("/repo"));
+
+        let args = parse(&["git-editor", "history", "--limit", "5"]).unwrap();
+        assert!(args.is_history_mode());
+        assert_eq!(args.limit, Some(5));
+
+        let args = parse(&["git-editor", "simulate", "--show-diff"]).unwrap();
+        assert!(args.simulate);
+        assert!(args.show_diff);
+
+        // -s after a rewrite only shows the result, it does not switch to the history view
+        let args = parse(&["git-editor", "rewrite", "-s"]).unwrap();
+        assert!(args.show_history);
+        assert!(!args.is_history_mode());
+    }
+
+    #[test]
+    fn test_dry_run_alias_and_standalone_show_diff() {
+        let simulate = parse(&["git-editor", "--simulate", "--show-diff"]).unwrap();
+        for argv in [
+            &["git-editor", "--dry-run", "--show-diff"][..],
+            &["git-editor", "--show-diff"][..],
+        ] {
+            let args = parse(argv).unwrap();
+            assert_eq!(
+                (args.simulate, args.show_diff),
+                (simulate.simulate, simulate.show_diff)
+            );
+        }
+
+        for command in ["pick", "range"] {
+            let args = parse(&["git-editor", command, "--dry-run"]).unwrap();
+            assert!(args.simulate);
+            assert!(!args.show_diff);
+
+            let args = parse(&["git-editor", command, "--show-diff"]).unwrap();
+            assert!(args.simulate);
+            assert!(args.show_diff);
+        }
+
+        let args = parse(&["git-editor", "dry-run", "--show-diff"]).unwrap();
+        assert!(matches!(
+            args.command,
+            Some(Command::Simulate {
+                show_diff: true,
+                apply_plan: None,
+                ..
+            })
+        ));
+        assert!(args.simulate);
+    }
+
+    #[test]
+    fn test_legacy_mode_flags_still_parse() {
+        let args = parse(&["git-editor", "-x", "--time"]).unwrap();
+        assert!(args.range);
+        assert!(args.edit_time);
+
+        let args = parse(&["git-editor", "-s"]).unwrap();
+        assert!(args.is_history_mode());
+    }
+
+    #[test]
+    fn test_conflicting_modes_are_parse_errors() {
+        let err = parse(&["git-editor", "-p", "-x"]).err().unwrap();
+        assert_eq!(err.kind(), ErrorKind::ArgumentConflict);
+
+        let err = parse(&["git-editor", "-p", "range"]).err().unwrap();
+        assert_eq!(err.kind(), ErrorKind::ArgumentConflict);
+
+        assert!(parse(&["git-editor", "history", "pick"]).is_err());
+    }
+
+    #[test]
+    fn test_mode_flags_stay_out_of_subcommands() {
+        for argv in [
+            &["git-editor", "--apply-plan", "plan.json"][..],
+            &["git-editor", "--continue"][..],
+            &["git-editor", "--replace-in-files", "secret=***"][..],
+            &["git-editor", "--author-date-order", "--fix"][..],
+            &["git-editor", "--round", "hour"][..],
+            &["git-editor", "--list-backups"][..],
+            &["git-editor", "--prune-backups", "--keep", "3"][..],
+            &["git-editor", "--apply-plan", "plan.json", "--dry-run"][..],
+            &["git-editor", "--replace-in-files", "a=b", "--dry-run"][..],
+        ] {
+            assert!(parse(argv).is_ok(), "{argv:?}");
+        }
+
+        // After a subcommand they are unknown to it, before one they conflict
+        let err = parse(&[
+            "git-editor",
+            "history",
+            "--apply-plan",
+            "plan.json",
+            "--yes",
+        ])
+        .err()
+        .unwrap();
+        assert_eq!(err.kind(), ErrorKind::UnknownArgument);
+        let err = parse(&["git-editor", "--apply-plan", "plan.json", "history"])
+            .err()
+            .unwrap();
+        assert_eq!(err.kind(), ErrorKind::ArgumentConflict);
+        let err = parse(&["git-editor", "--round", "hour", "rewrite"])
+            .err()
+            .unwrap();
+        assert_eq!(err.kind(), ErrorKind::ArgumentConflict);
+
+        let args = parse(&["git-editor", "simulate", "--apply-plan", "plan.json"]).unwrap();
+        assert!(args.simulate);
+        assert_eq!(args.apply_plan.as_deref(), Some("plan.json"));
+    }
+
+    #[test]
+    fn test_conflicting_mode_flags_are_parse_errors() {
+        for argv in [
+            &["git-editor", "--continue", "-p"][..],
+            &["git-editor", "--continue", "--dry-run"][..],
+            &["git-editor", "--continue", "--keep-author"][..],
+            &["git-editor", "--replace-in-files", "a=b", "-x"][..],
+            &["git-editor", "--author-date-order", "--round", "hour"][..],
+            &["git-editor", "--round", "hour", "--mailmap", "map"][..],
+            &[
+                "git-editor",
+                "--list-backups",
+                "--prune-backups",
+                "--keep",
+                "3",
+            ][..],
+            &["git-editor", "--list-backups", "--count"][..],
+            &["git-editor", "--apply-plan", "plan.json", "-i"][..],
+        ] {
+            let err = parse(argv).err().unwrap();
+            assert_eq!(err.kind(), ErrorKind::ArgumentConflict, "{argv:?}");
+        }
+
+        for argv in [
+            &["git-editor", "--fix"][..],
+            &["git-editor", "--prune-backups"][..],
+        ] {
+            let err = parse(argv).err().unwrap();
+            assert_eq!(err.kind(), ErrorKind::MissingRequiredArgument, "{argv:?}");
+        }
+    }
+
+    #[test]
+    fn test_options_are_scoped_to_their_subcommands() {
+        for argv in [
+            &["git-editor", "history", "--begin", "2024-01-01 00:00:00"][..],
+            &["git-editor", "history", "--signoff"][..],
+            &["git-editor", "history", "--batch"][..],
+            &["git-editor", "history", "--message-prepend", "x"][..],
+            &["git-editor", "rewrite", "--limit", "3"][..],
+            &["git-editor", "range", "--path", "src"][..],
+            &["git-editor", "pick", "--format", "json"][..],
+            &["git-editor", "range", "--format", "json"][..],
+            &["git-editor", "simulate", "--mailmap", "map", "--summary"][..],
+        ] {
+            let err = parse(argv).err().unwrap();
+            assert_eq!(err.kind(), ErrorKind::UnknownArgument, "{argv:?}");
+        }
+
+        // Shared options are accepted on either side of the subcommand
+        let args = parse(&[
+            "git-editor",
+            "--repo-path",
+            "repo",
+            "history",
+            "--limit",
+            "3",
+            "--quiet",
+        ])
+        .unwrap();
+        assert!(args.show_history);
+        assert_eq!(args.repo_path.as_deref(), Some("repo"));
+        assert_eq!(args.limit, Some(3));
+        assert!(args.quiet);
+
+        let err = parse(&["git-editor", "--email", "a@b.com", "rewrite"])
+            .err()
+            .unwrap();
+        assert_eq!(err.kind(), ErrorKind::ArgumentConflict);
+    }
+
+    #[test]
+    fn test_option_combinations_are_parse_errors() {
+        for argv in [
+            &["git-editor", "rewrite", "--mailmap", "map", "--name", "A"][..],
+            &[
+                "git-editor",
+                "rewrite",
+                "--keep-author",
+                "--email",
+                "a@b.com",
+            ][..],
+            &["git-editor", "--normalize-emails", "--keep-author"][..],
+            &["git-editor", "--anonymize", "--only-author", "a@b.com"][..],
+            &["git-editor", "rewrite", "-i", "--mailmap", "map"][..],
+            &["git-editor", "-x", "--mailmap", "map"][..],
+            &[
+                "git-editor",
+                "--author-date-order",
+                "--fix",
+                "--rev",
+                "v1.0",
+            ][..],
+            &[
+                "git-editor",
+                "--author-date-order",
+                "--rev",
+                "v1.0",
+                "--ref",
+                "main",
+            ][..],
+            &["git-editor", "simulate", "--rev", "v1.0", "--ref", "main"][..],
+            &["git-editor", "range", "--tail", "2", "--head", "1"][..],
+            &["git-editor", "range", "--tail", "2", "--from", "abc"][..],
+            &["git-editor", "range", "--committer-column", "--simulate"][..],
+            &["git-editor", "history", "--graph", "--format", "json"][..],
+            &["git-editor", "rewrite", "-q", "-v"][..],
+            &["git-editor", "pick", "--summary", "--count"][..],
+        ] {
+            let err = parse(argv).err().unwrap();
+            assert_eq!(err.kind(), ErrorKind::ArgumentConflict, "{argv:?}");
+        }
+
+        for argv in [
+            &["git-editor", "rewrite", "--find-author", "a@b.com"][..],
+            &["git-editor", "rewrite", "--lowercase-local-part"][..],
+            &["git-editor", "rewrite", "--anonymize-map", "map.csv"][..],
+            &["git-editor", "--rev", "v1.0"][..],
+            &["git-editor", "pick", "--preview-rows", "5"][..],
+            &["git-editor", "range", "--batch"][..],
+            &["git-editor", "rewrite", "--committer-column"][..],
+            &["git-editor", "rewrite", "--graph"][..],
+            &["git-editor", "--message"][..],
+        ] {
+            let err = parse(argv).err().unwrap();
+            assert_eq!(err.kind(), ErrorKind::MissingRequiredArgument, "{argv:?}");
+        }
+
+        for argv in [
+            &["git-editor", "--author-date-order", "--rev", "v1.0"][..],
+            &["git-editor", "history", "--rev", "v1.0", "--format", "json"][..],
+            &["git-editor", "range", "--tail", "2", "--batch"][..],
+            &["git-editor", "rewrite", "-i", "--committer-column"][..],
+            &["git-editor", "pick", "--show-diff", "--preview-rows", "5"][..],
+            &["git-editor", "-x", "--message", "--email", "a@b.com"][..],
+        ] {
+            assert!(parse(argv).is_ok(), "{argv:?}");
+        }
+    }
+
+    #[test]
+    fn test_keep_clone_outlives_the_args() {
+        let source = tempfile::TempDir::new().unwrap();
+        let repo = git2::Repository::init(source.path()).unwrap();
+        let tree = repo
+            .find_tree(repo.index().unwrap().write_tree().unwrap())
+            .unwrap();
+        let sig = git2::Signature::now("Test User", "test@example.com").unwrap();
+        let oid = repo
+            .commit(Some("HEAD"), &sig, &sig, "Initial", &tree, &[])
+            .unwrap();
+
+        let target = tempfile::TempDir::new().unwrap();
+        let kept = target.path().join("kept");
+        let url = format!("file://{}", source.path().display());
+        let clone_args = || Args {
+            repo_path: Some(url.clone()),
+            keep_clone: Some(kept.to_string_lossy().to_string()),
+            show_history: true,
+            quiet: true,
+            ..Default::default()
+        };
+
+        let mut args = clone_args();
+        args.ensure_all_args_present().unwrap();
+        let repo_path = std::fs::canonicalize(&kept).unwrap();
+        assert_eq!(
+            args.repo_path,
+            Some(repo_path.to_string_lossy().to_string())
+        );
+        assert!(args._temp_dir.is_none());
+        drop(args);
+
+        let cloned = git2::Repository::open(&kept).unwrap();
+        assert_eq!(cloned.head().unwrap().target(), Some(oid));
+
+        // An existing clone is never overwritten
+        let err = clone_args().ensure_all_args_present().unwrap_err();
+        assert!(err.to_string().contains("not an empty directory"), "{err}");
+
+        let mut local = Args {
+            repo_path: Some(source.path().to_string_lossy().to_string()),
+            ..clone_args()
+        };
+        assert!(local.ensure_all_args_present().is_err());
+    }
+
+    #[test]
+    fn test_repo_is_discovered_from_a_subdirectory() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let repo = git2::Repository::init(temp_dir.path()).unwrap();
+        let tree = repo
+            .find_tree(repo.index().unwrap().write_tree().unwrap())
+            .unwrap();
+        let sig = git2::Signature::now("Test User", "test@example.com").unwrap();
+        repo.commit(Some("HEAD"), &sig, &sig, "Initial", &tree, &[])
+            .unwrap();
+
+        let nested = temp_dir.path().join("src").join("nested");
+        std::fs::create_dir_all(&nested).unwrap();
+        let mut args = Args {
+            repo_path: Some(nested.to_string_lossy().to_string()),
+            show_history: true,
+            ..Default::default()
+        };
+        args.ensure_all_args_present().unwrap();
+
+        let found = std::fs::canonicalize(args.repo_path.as_ref().unwrap()).unwrap();
+        assert_eq!(found, std::fs::canonicalize(temp_dir.path()).unwrap());
+        assert!(crate::utils::validator::validate_inputs(&args).is_ok());
+        assert_eq!(
+            crate::utils::commit_history::get_commit_history(&args, false)
+                .unwrap()
+                .len(),
+            1
+        );
+    }
 }
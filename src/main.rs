@@ -5,229 +5,465 @@ pub mod docs;
 pub mod rewrite;
 pub mod utils;
 
+use crate::rewrite::rewrite_content::rewrite_content;
+use crate::rewrite::rewrite_continue::{continue_rewrite, offer_to_resume, warn_if_interrupted};
+use crate::rewrite::rewrite_date_order::fix_author_date_order;
+use crate::rewrite::rewrite_plan::apply_plan;
 use crate::rewrite::rewrite_range::rewrite_range_commits;
+use crate::rewrite::rewrite_round::{granularity_name, round_commit_timestamps};
 use crate::rewrite::rewrite_specific::rewrite_specific_commits;
-use crate::utils::datetime::generate_timestamps;
-use crate::utils::types::Result;
+use crate::utils::config::Config;
+use crate::utils::datetime::{generate_timestamps, time_travel_warning};
+use crate::utils::types::{Outcome, Result};
 use crate::utils::validator::validate_inputs;
-use args::Args;
-use clap::Parser;
-use rewrite::rewrite_all::rewrite_all_commits;
-
-fn main() -> Result<()> {
-    run().unwrap_or_else(|error| {
-        eprintln!("{} {}", "Error:".red().bold(), error.to_string().red());
-        std::process::exit(1);
-    });
-    Ok(())
+use args::{Args, OutputFormat};
+use rewrite::rewrite_all::{rewrite_all_commits, rewrite_all_interactive};
+use std::io::IsTerminal;
+
+fn main() {
+    let code = match run() {
+        Ok(outcome) => outcome.exit_code(),
+        Err(error) => {
+            eprintln!("{} {}", "Error:".red().bold(), error.to_string().red());
+            1
+        }
+    };
+    std::process::exit(code);
 }
 
-fn run() -> Result<()> {
-    let mut args = Args::parse();
+fn run() -> Result<Outcome> {
+    let mut args = Args::parse_resolved();
+    if !color_enabled(args.no_color) {
+        colored::control::set_override(false);
+    }
 
+    // Config file defaults sit between the command line and the git config
+    Config::load(args.config.as_deref())?.apply(&mut args);
     args.ensure_all_args_present()?;
-    args.validate_simulation_args()?;
+
+    // Every mode except docs needs history to work on. Listing an empty
+    // history is still a successful listing.
+    if let Some(branch) = args.unborn_branch().filter(|_| !args.docs) {
+        if args.count {
+            println!("0");
+        } else if args.format == OutputFormat::Json && args.simulate {
+            println!(r#"{{"changes": []}}"#);
+        } else if args.format == OutputFormat::Json {
+            println!("[]");
+        } else if !args.quiet {
+            let nothing = if args.is_history_mode() {
+                "nothing to show"
+            } else {
+                "nothing to rewrite"
+            };
+            println!(
+                "{}",
+                format!("Branch '{branch}' has no commits yet; {nothing}.").yellow()
+            );
+        }
+        if args.is_history_mode() {
+            return Ok(Outcome::Done);
+        }
+        return Ok(Outcome::NothingToDo);
+    }
     validate_inputs(&args)?;
+    if args.count {
+        return execute_count_operation(&args);
+    }
+
+    let mode = determine_operation_mode(&args);
+    // A rewrite left unfinished is offered before another one that records
+    // its progress starts, and pointed out before any other rewrite
+    let resumed = if mode.records_progress() {
+        offer_to_resume(&mut args)?
+    } else {
+        if mode.rewrites(&args) {
+            warn_if_interrupted(&args)?;
+        }
+        None
+    };
 
-    match determine_operation_mode(&args) {
-        OperationMode::Docs => execute_docs_operation(),
-        OperationMode::Range => execute_range_operation(&args),
-        OperationMode::PickSpecific => execute_pick_specific_operation(&args),
-        OperationMode::ShowHistory => execute_show_history_operation(&args),
-        OperationMode::FullRewrite => execute_full_rewrite_operation(&mut args),
-        OperationMode::Simulate => execute_simulation_operation(&mut args),
+    let outcome = match resumed {
+        Some(outcome) => Ok(outcome),
+        None => match mode {
+            OperationMode::Docs => execute_docs_operation(),
+            OperationMode::Continue => continue_rewrite(&mut args),
+            OperationMode::ApplyPlan => execute_apply_plan_operation(&args),
+            OperationMode::ReplaceContent => execute_replace_content_operation(&args),
+            OperationMode::AuthorDateOrder => execute_author_date_order_operation(&args),
+            OperationMode::Round => execute_round_operation(&args),
+            OperationMode::Backups => execute_backups_operation(&args),
+            OperationMode::Range => execute_range_operation(&args),
+            OperationMode::PickSpecific => execute_pick_specific_operation(&args),
+            OperationMode::Interactive => execute_interactive_operation(&args),
+            OperationMode::ShowHistory => execute_show_history_operation(&args),
+            OperationMode::FullRewrite => execute_full_rewrite_operation(&mut args),
+            OperationMode::Simulate => execute_simulation_operation(&mut args),
+        },
     }?;
 
-    if !args.simulate && !args.docs {
+    // JSON output stays machine readable
+    if outcome == Outcome::Done
+        && !args.simulate
+        && !args.docs
+        && !args.quiet
+        && args.format != OutputFormat::Json
+    {
         println!("{}", "Operation completed successfully!".green().bold());
     }
-    Ok(())
+    if let Some(summary) = args.rewrite_summary.borrow().as_ref() {
+        println!("{}", summary.to_string().bold());
+    }
+    if args.keep_clone.is_some() && args.format != OutputFormat::Json {
+        println!(
+            "{} {}",
+            "Clone kept at:".green(),
+            args.repo_path.as_deref().unwrap_or_default().cyan()
+        );
+    }
+    Ok(outcome)
 }
 
 #[derive(Debug)]
 enum OperationMode {
     Docs,
+    Continue,
+    ApplyPlan,
+    ReplaceContent,
+    AuthorDateOrder,
+    Round,
+    Backups,
     Range,
     PickSpecific,
+    Interactive,
     ShowHistory,
     FullRewrite,
     Simulate,
 }
 
+impl OperationMode {
+    // Whether the mode writes history, as opposed to only reading it;
+    // --author-date-order only writes with --fix
+    fn rewrites(&self, args: &Args) -> bool {
+        match self {
+            OperationMode::AuthorDateOrder => args.fix,
+            OperationMode::ReplaceContent => !args.simulate,
+            _ => !matches!(
+                self,
+                OperationMode::Docs
+                    | OperationMode::Continue
+                    | OperationMode::Backups
+                    | OperationMode::ShowHistory
+                    | OperationMode::Simulate
+            ),
+        }
+    }
+
+    // Pick and --apply-plan save their progress as they go, so only they can
+    // leave a rewrite for --continue to resume
+    fn records_progress(&self) -> bool {
+        matches!(self, OperationMode::PickSpecific | OperationMode::ApplyPlan)
+    }
+}
+
+// Colors are only worth emitting to an interactive terminal; NO_COLOR
+// (https://no-color.org) disables them whenever it is set to a non-empty value
+fn color_enabled(no_color_flag: bool) -> bool {
+    let no_color_env = std::env::var_os("NO_COLOR").is_some_and(|value| !value.is_empty());
+    !no_color_flag && !no_color_env && std::io::stdout().is_terminal()
+}
+
 fn determine_operation_mode(args: &Args) -> OperationMode {
     if args.docs {
         OperationMode::Docs
+    } else if args.continue_rewrite {
+        OperationMode::Continue
+    } else if !args.replace_in_files.is_empty() {
+        // Previews its own changes under --simulate
+        OperationMode::ReplaceContent
     } else if args.simulate {
         OperationMode::Simulate
+    } else if args.apply_plan.is_some() {
+        OperationMode::ApplyPlan
+    } else if args.author_date_order {
+        OperationMode::AuthorDateOrder
+    } else if args.round.is_some() {
+        OperationMode::Round
+    } else if args.is_backup_mode() {
+        OperationMode::Backups
     } else if args.range {
         OperationMode::Range
     } else if args.pick_specific_commits {
         OperationMode::PickSpecific
-    } else if args.show_history {
+    } else if args.interactive {
+        OperationMode::Interactive
+    } else if args.is_history_mode() {
         OperationMode::ShowHistory
     } else {
         OperationMode::FullRewrite
     }
 }
 
-fn execute_docs_operation() -> Result<()> {
-    crate::docs::execute_docs_operation()
+// Prints only the number, so scripts can read it; no selected commit means nothing to do
+fn execute_count_operation(args: &Args) -> Result<Outcome> {
+    use crate::rewrite::common::count_selected_commits;
+    let count = count_selected_commits(args)?;
+    println!("{count}");
+    Ok(if count > 0 {
+        Outcome::Done
+    } else {
+        Outcome::NothingToDo
+    })
 }
 
-fn execute_range_operation(args: &Args) -> Result<()> {
-    println!("{}", "Editing commit range...".cyan());
-    rewrite_range_commits(args)
+fn execute_docs_operation() -> Result<Outcome> {
+    crate::docs::execute_docs_operation()?;
+    Ok(Outcome::Done)
 }
 
-fn execute_pick_specific_operation(args: &Args) -> Result<()> {
-    println!("{}", "Picking specific commits...".cyan());
-    rewrite_specific_commits(args)
+fn execute_apply_plan_operation(args: &Args) -> Result<Outcome> {
+    if !args.quiet {
+        println!("{}", "Applying rewrite plan...".cyan());
+    }
+    apply_plan(args)
 }
 
-fn execute_show_history_operation(args: &Args) -> Result<()> {
-    println!("{}", "Showing commit history...".cyan());
-    use crate::utils::commit_history::get_commit_history;
-    get_commit_history(args, true)?;
-    Ok(())
+fn execute_replace_content_operation(args: &Args) -> Result<Outcome> {
+    if !args.quiet {
+        println!("{}", "Replacing file contents across history...".cyan());
+    }
+    rewrite_content(args)
 }
 
-fn execute_full_rewrite_operation(args: &mut Args) -> Result<()> {
-    use crate::utils::commit_history::get_commit_history;
-    use crate::utils::prompt::prompt_for_input;
-    use crate::utils::simulation::{
-        create_full_rewrite_simulation, create_specific_commit_simulation, print_detailed_diff,
-    };
+fn execute_author_date_order_operation(args: &Args) -> Result<Outcome> {
+    use crate::utils::commit_history::{find_author_date_violations, print_date_order_violations};
 
-    // First, show a summary of what will be changed
-    println!("{}", "📊 SUMMARY OF PLANNED CHANGES".bold().cyan());
-    println!("{}", "Analyzing repository...".cyan());
-
-    let commits = get_commit_history(args, false)?;
-    if commits.is_empty() {
-        println!("{}", "No commits found in repository.".yellow());
-        return Ok(());
+    let violations = find_author_date_violations(args)?;
+    if !args.quiet {
+        print_date_order_violations(&violations);
+    }
+    if !args.fix {
+        return Ok(Outcome::Done);
+    }
+    if violations.is_empty() {
+        return Ok(Outcome::NothingToDo);
     }
 
-    // Check if user wants to keep original timestamps
-    if args.should_keep_original_timestamps() {
-        println!("{}", "✅ Keeping original timestamps as requested.".green());
-
-        // Create a simulation showing that only author info will change
-        let simulation_result = create_specific_commit_simulation(
-            &commits,
-            0,
-            args.name.clone(),
-            args.email.clone(),
-            None, // No timestamp changes
-            None, // No message changes
-        )?;
-
-        // Show summary
-        simulation_result
-            .stats
-            .print_summary("Author Information Update");
-        print_detailed_diff(&simulation_result);
+    if !args.quiet {
+        println!("{}", "Repairing author date order...".cyan());
+    }
+    fix_author_date_order(args)
+}
 
-        // Ask for confirmation
-        println!(
-            "\n{}",
-            "⚠️  This operation will rewrite Git history!"
-                .yellow()
-                .bold()
-        );
+fn execute_round_operation(args: &Args) -> Result<Outcome> {
+    if !args.quiet {
+        let granularity = granularity_name(args.round.unwrap());
         println!(
             "{}",
-            "Only author information will be changed, timestamps will remain the same.".cyan()
-        );
-        println!(
-            "{}",
-            "Make sure you have backed up your repository.".yellow()
+            format!("Rounding timestamps to the {granularity}...").cyan()
         );
+    }
+    round_commit_timestamps(args)
+}
 
-        let confirmation = prompt_for_input("\nDo you want to proceed? (yes/no)")?;
+fn execute_backups_operation(args: &Args) -> Result<Outcome> {
+    use crate::utils::backup::{list_backups, print_backups, prune_backups};
 
-        if confirmation.to_lowercase() != "yes" && confirmation.to_lowercase() != "y" {
-            println!("{}", "❌ Operation cancelled by user.".red());
-            return Ok(());
-        }
+    let repo = git2::Repository::open(args.repo_path.as_ref().unwrap())?;
+    if args.list_backups {
+        print_backups(&list_backups(&repo)?);
+        return Ok(Outcome::Done);
+    }
 
+    let pruned = prune_backups(&repo, args.keep_backups.unwrap())?;
+    if pruned.is_empty() {
+        if !args.quiet {
+            println!("{}", "No backups to prune.".yellow());
+        }
+        return Ok(Outcome::NothingToDo);
+    }
+    if !args.quiet {
+        print_backups(&pruned);
         println!(
             "{}",
-            "\n🚀 Proceeding with author information update..."
-                .green()
-                .bold()
+            format!("✓ Deleted {} backup ref(s)", pruned.len()).green()
         );
-        println!("{}", "Updating author information...".cyan());
+    }
+    Ok(Outcome::Done)
+}
+
+fn execute_range_operation(args: &Args) -> Result<Outcome> {
+    if !args.quiet {
+        println!("{}", "Editing commit range...".cyan());
+    }
+    rewrite_range_commits(args)
+}
+
+fn execute_pick_specific_operation(args: &Args) -> Result<Outcome> {
+    println!("{}", "Picking specific commits...".cyan());
+    rewrite_specific_commits(args)
+}
+
+fn execute_interactive_operation(args: &Args) -> Result<Outcome> {
+    println!("{}", "Editing full history interactively...".cyan());
+    rewrite_all_interactive(args)
+}
+
+fn execute_show_history_operation(args: &Args) -> Result<Outcome> {
+    if args.format == OutputFormat::Text && !args.quiet {
+        println!("{}", "Showing commit history...".cyan());
+    }
+    use crate::utils::commit_history::print_commit_history;
+    print_commit_history(args)?;
+    Ok(Outcome::Done)
+}
 
-        // Use the original timestamps (get them from the commits)
-        let original_timestamps: Vec<chrono::NaiveDateTime> =
-            commits.iter().map(|c| c.timestamp).collect();
-        rewrite_all_commits(args, original_timestamps)
+fn execute_full_rewrite_operation(args: &mut Args) -> Result<Outcome> {
+    use crate::utils::commit_history::collect_commits;
+    use crate::utils::prompt::confirm;
+    use crate::utils::simulation::{create_full_rewrite_simulation, print_detailed_diff};
+
+    // First, show a summary of what will be changed
+    if !args.quiet {
+        println!("{}", "📊 SUMMARY OF PLANNED CHANGES".bold().cyan());
+        println!("{}", "Analyzing repository...".cyan());
+    }
+
+    // The whole history, so the commits line up with the generated timestamps
+    // even where --path leaves some of them untouched
+    let commits = collect_commits(args, None)?;
+    if commits.is_empty() {
+        if !args.quiet {
+            println!("{}", "No commits found in repository.".yellow());
+        }
+        return Ok(Outcome::NothingToDo);
+    }
+
+    // With KEEP_ORIGINAL no timestamps are generated; each commit keeps its own
+    let keep_original = args.should_keep_original_timestamps();
+    let timestamps = if keep_original {
+        Vec::new()
     } else {
-        let timestamps = generate_timestamps(args)?;
+        generate_timestamps(args)?
+    };
+
+    // --quiet only silences the preview; the confirmation still stands
+    if !args.quiet {
+        if keep_original {
+            println!("{}", "✅ Keeping original timestamps as requested.".green());
+        }
+        print_time_travel_warning(args, &timestamps)?;
         let simulation_result = create_full_rewrite_simulation(&commits, &timestamps, args)?;
 
         // Show summary
-        simulation_result
-            .stats
-            .print_summary("Full History Rewrite");
-        print_detailed_diff(&simulation_result);
+        simulation_result.stats.print_summary(if keep_original {
+            "Author Information Update"
+        } else {
+            "Full History Rewrite"
+        });
+        print_detailed_diff(&simulation_result, None);
 
-        // Ask for confirmation
         println!(
             "\n{}",
             "⚠️  This operation will rewrite Git history permanently!"
                 .yellow()
                 .bold()
         );
+        if keep_original {
+            println!(
+                "{}",
+                "Only author information will be changed, timestamps will remain the same.".cyan()
+            );
+        }
         println!(
             "{}",
             "Make sure you have backed up your repository.".yellow()
         );
+    }
 
-        let confirmation = prompt_for_input("\nDo you want to proceed? (yes/no)")?;
-
-        if confirmation.to_lowercase() != "yes" && confirmation.to_lowercase() != "y" {
-            println!("{}", "❌ Operation cancelled by user.".red());
-            return Ok(());
-        }
+    if !confirm("Do you want to proceed?", args.yes)? {
+        println!("{}", "❌ Operation cancelled by user.".red());
+        return Ok(Outcome::Cancelled);
+    }
 
+    if !args.quiet {
         println!("{}", "\n🚀 Proceeding with rewrite...".green().bold());
         println!("{}", "Rewriting commits...".cyan());
-        rewrite_all_commits(args, timestamps)
     }
+    rewrite_all_commits(args, timestamps)
 }
 
-fn execute_simulation_operation(args: &mut Args) -> Result<()> {
-    use crate::utils::commit_history::get_commit_history;
-    use crate::utils::simulation::{create_full_rewrite_simulation, print_detailed_diff};
+fn print_time_travel_warning(args: &Args, timestamps: &[chrono::NaiveDateTime]) -> Result<()> {
+    if let Some(warning) = time_travel_warning(args, timestamps)? {
+        // Kept off stdout when it carries JSON
+        if args.format == OutputFormat::Json {
+            eprintln!("{} {}", "⚠️  Warning:".yellow().bold(), warning.yellow());
+        } else {
+            println!("{} {}", "⚠️  Warning:".yellow().bold(), warning.yellow());
+        }
+    }
+    Ok(())
+}
+
+fn execute_simulation_operation(args: &mut Args) -> Result<Outcome> {
+    use crate::utils::commit_history::collect_commits;
+    use crate::utils::simulation::{create_full_rewrite_simulation, print_simulation_result};
+
+    // JSON output is the plan alone, so it stays machine readable
+    let json = args.format == OutputFormat::Json;
+    if !json {
+        println!("{}", "🔍 SIMULATION MODE".bold().cyan());
+        println!("{}", "Analyzing repository to preview changes...".cyan());
+    }
 
-    println!("{}", "🔍 SIMULATION MODE".bold().cyan());
-    println!("{}", "Analyzing repository to preview changes...".cyan());
+    // Range and pick gather their edits interactively and stop before writing
+    if args.range {
+        return rewrite_range_commits(args);
+    }
+    if args.pick_specific_commits {
+        return rewrite_specific_commits(args);
+    }
 
-    let commits = get_commit_history(args, false)?;
+    // The whole history, so the commits line up with the generated timestamps
+    // even where --path leaves some of them untouched
+    let commits = collect_commits(args, None)?;
 
     if commits.is_empty() {
-        println!("{}", "No commits found in repository.".yellow());
-        return Ok(());
+        if json {
+            println!(r#"{{"changes": []}}"#);
+        } else {
+            println!("{}", "No commits found in repository.".yellow());
+        }
+        return Ok(Outcome::NothingToDo);
     }
 
     // Determine what kind of simulation we can perform based on available arguments
-    let simulation_result = if args.range {
-        // Range simulation - show that no changes would be made without proper setup
-        use crate::utils::simulation::create_specific_commit_simulation;
-        create_specific_commit_simulation(&commits, 0, None, None, None, None)?
-    } else if args.pick_specific_commits {
-        // Pick specific simulation - show that no changes would be made
-        use crate::utils::simulation::create_specific_commit_simulation;
-        create_specific_commit_simulation(&commits, 0, None, None, None, None)?
+    let simulation_result = if let Some(plan_path) = &args.apply_plan {
+        // Plan simulation - show exactly what the plan would change
+        use crate::rewrite::rewrite_plan::{create_plan_simulation, load_plan, sign_off_plan};
+        let mut plan = load_plan(plan_path, &commits, args.date_format())?;
+        if args.signoff {
+            sign_off_plan(&mut plan, &commits);
+        }
+        create_plan_simulation(&commits, &plan)?
     } else {
         // Full rewrite simulation - check if we have the required arguments
-        if args.email.is_some() && args.name.is_some() && args.start.is_some() && args.end.is_some()
-        {
+        let has_identity = args.mailmap.is_some()
+            || args.keep_author
+            || args.find_author.is_some()
+            || args.normalize_emails
+            || args.anonymize
+            || (args.email.is_some() && args.name.is_some());
+        if has_identity && args.start.is_some() && args.end.is_some() {
             // We have all required arguments, do full simulation
             let timestamps = generate_timestamps(args)?;
+            print_time_travel_warning(args, &timestamps)?;
             create_full_rewrite_simulation(&commits, &timestamps, args)?
+        } else if json {
+            return Err(
+                "--format json needs --apply-plan or a complete full rewrite (--name, --email, --begin, --end)"
+                    .into(),
+            );
         } else {
             // Missing required arguments - show what's needed
             println!(
@@ -292,19 +528,15 @@ fn execute_simulation_operation(args: &mut Args) -> Result<()> {
             };
 
             result.stats.print_summary(&result.operation_mode);
-            return Ok(());
+            return Ok(Outcome::Done);
         }
     };
 
-    // Print summary statistics
-    simulation_result
-        .stats
-        .print_summary(&simulation_result.operation_mode);
-
-    // Print detailed diff if requested
-    if args.show_diff {
-        print_detailed_diff(&simulation_result);
+    if json {
+        use crate::rewrite::rewrite_plan::simulation_plan;
+        println!("{}", simulation_plan(&simulation_result)?);
+    } else {
+        print_simulation_result(&simulation_result, args.show_diff, args.preview_rows);
     }
-
-    Ok(())
+    Ok(Outcome::Done)
 }
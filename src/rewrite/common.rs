@@ -0,0 +1,1072 @@
+use crate::args::Args;
+use crate::utils::backup::create_backup;
+use crate::utils::change_summary::print_change;
+use crate::utils::git_ref::RewriteTarget;
+use crate::utils::prompt::confirm;
+use crate::utils::types::display_email;
+use crate::utils::types::Result;
+use chrono::{DateTime, FixedOffset, Local};
+use colored::Colorize;
+use git2::{Commit, ObjectType, Oid, Repository, Signature, StatusOptions, Time};
+use std::collections::{BTreeSet, HashMap};
+
+/// Checks run before any rewrite: the target must be confirmed when HEAD is
+/// implicitly detached, and a checked-out target needs a clean working tree.
+pub fn prepare_rewrite(args: &Args) -> Result<()> {
+    let repo = Repository::open(args.repo_path.as_ref().unwrap())?;
+    let target = RewriteTarget::resolve(&repo, args)?;
+
+    if target == RewriteTarget::DetachedHead && args.rewrite_ref.is_none() {
+        let tip = target.tip(&repo)?;
+        let question = format!(
+            "HEAD is detached at {}. Rewrite its history and move HEAD to the new tip?",
+            &tip.to_string()[..8]
+        );
+        if !confirm(&question, args.yes)? {
+            return Err("Rewrite cancelled: HEAD is detached (use --ref to pick a branch)".into());
+        }
+    }
+
+    if target.is_checked_out(&repo)? {
+        ensure_clean_worktree(args)?;
+    }
+    Ok(())
+}
+
+/// Refuses to rewrite while the index or working tree has uncommitted changes:
+/// once the branch moves they no longer line up with HEAD and are easy to lose.
+/// Untracked files are unaffected by a rewrite and are ignored.
+pub fn ensure_clean_worktree(args: &Args) -> Result<()> {
+    let repo = Repository::open(args.repo_path.as_ref().unwrap())?;
+    if args.allow_dirty || repo.is_bare() {
+        return Ok(());
+    }
+
+    let mut options = StatusOptions::new();
+    options
+        .include_untracked(false)
+        .include_ignored(false)
+        .exclude_submodules(true);
+    let statuses = repo.statuses(Some(&mut options))?;
+
+    let dirty: Vec<String> = statuses
+        .iter()
+        .filter(|entry| !entry.status().is_empty())
+        .map(|entry| entry.path().unwrap_or("(non-utf8 path)").to_string())
+        .collect();
+    if dirty.is_empty() {
+        return Ok(());
+    }
+
+    let mut listing = dirty.iter().take(5).cloned().collect::<Vec<_>>().join(", ");
+    if dirty.len() > 5 {
+        listing.push_str(&format!(" and {} more", dirty.len() - 5));
+    }
+    Err(format!(
+        "Working tree has uncommitted changes ({listing}). Commit or stash them first, or pass --allow-dirty"
+    )
+    .into())
+}
+
+// libgit2 refuses a signature with an empty email although git itself
+// accepts `Name <>`, so such an email travels through a `Signature` as this
+// placeholder and `push_signature` writes it back empty.
+const EMPTY_EMAIL_PLACEHOLDER: &str = "empty-email@git-editor.invalid";
+
+/// Like `Signature::new`, except that `email` may be empty.
+pub fn new_signature(name: &str, email: &str, when: &Time) -> Result<Signature<'static>> {
+    let email = if email.is_empty() {
+        EMPTY_EMAIL_PLACEHOLDER
+    } else {
+        email
+    };
+    Ok(Signature::new(name, email, when)?)
+}
+
+/// `orig`'s committer name and email at `when`, for
+/// `--preserve-committer-identity`.
+pub fn original_committer_at(orig: &Commit, when: &Time) -> Result<Signature<'static>> {
+    let committer = orig.committer();
+    new_signature(
+        committer.name().unwrap_or("Unknown"),
+        committer.email().unwrap_or("unknown@email.com"),
+        when,
+    )
+}
+
+/// Maps every parent of `orig` to its rewritten counterpart. Parent order and
+/// count are kept as-is, so merge commits retain their full topology and only
+/// the commits they point at change.
+pub fn remap_parents<'r>(
+    repo: &'r Repository,
+    orig: &Commit,
+    new_map: &HashMap<Oid, Oid>,
+) -> Result<Vec<Commit<'r>>> {
+    orig.parent_ids()
+        .map(|pid| {
+            let new_pid = *new_map.get(&pid).unwrap_or(&pid);
+            repo.find_commit(new_pid).map_err(|e| e.into())
+        })
+        .collect()
+}
+
+/// How many commits at the start of the oldest-first `orig_oids` come before
+/// the first one `is_edited` picks. Parents are walked before their children,
+/// so none of them descends from an edited commit: each keeps its oid and is
+/// mapped to itself in `new_map` without being read or written again. When no
+/// commit is edited nothing is skipped, so the rewrite still ends at the tip.
+pub fn skip_unchanged_prefix(
+    orig_oids: &[Oid],
+    new_map: &mut HashMap<Oid, Oid>,
+    mut is_edited: impl FnMut(Oid) -> Result<bool>,
+) -> Result<usize> {
+    let mut start = 0;
+    for (i, &oid) in orig_oids.iter().enumerate() {
+        if is_edited(oid)? {
+            start = i;
+            break;
+        }
+    }
+    for &oid in &orig_oids[..start] {
+        new_map.insert(oid, oid);
+    }
+    Ok(start)
+}
+
+/// How many commits the chosen mode would work on, for `--count`: the listed
+/// commits for history, pick and `rewrite --interactive`, the size of the
+/// range given by `--from`/`--to` or `--tail`/`--head`, the commits a plan
+/// edits, and otherwise the commits a full rewrite selects through the
+/// author, path and identity filters. Nothing is simulated or written.
+pub fn count_selected_commits(args: &Args) -> Result<usize> {
+    use crate::rewrite::rewrite_plan::load_plan;
+    use crate::rewrite::rewrite_range::{quick_range, resolve_range_anchors};
+    use crate::utils::commit_history::get_commit_history;
+
+    if args.range {
+        let commits = get_commit_history(args, false)?;
+        if commits.is_empty() {
+            return Ok(0);
+        }
+        let (start_idx, end_idx) = if args.range_from.is_some() || args.range_to.is_some() {
+            let repo = Repository::open(args.repo_path.as_ref().unwrap())?;
+            resolve_range_anchors(
+                &repo,
+                &commits,
+                args.range_from.as_deref(),
+                args.range_to.as_deref(),
+            )?
+        } else {
+            quick_range(commits.len(), args.range_tail, args.range_head)?
+        };
+        return Ok(end_idx - start_idx + 1);
+    }
+    if let Some(plan_path) = &args.apply_plan {
+        let commits = get_commit_history(args, false)?;
+        return Ok(load_plan(plan_path, &commits, args.date_format())?.len());
+    }
+    if args.is_history_mode() || args.pick_specific_commits || args.interactive {
+        return Ok(get_commit_history(args, false)?.len());
+    }
+
+    let repo = Repository::open(args.repo_path.as_ref().unwrap())?;
+    let mut revwalk = repo.revwalk()?;
+    revwalk.push(RewriteTarget::resolve(&repo, args)?.tip(&repo)?)?;
+    let mut selected = 0;
+    for oid in revwalk {
+        if args.is_commit_selected(&repo, &repo.find_commit(oid?)?)? {
+            selected += 1;
+        }
+    }
+    Ok(selected)
+}
+
+/// Writes a copy of `orig` on top of `parents` with the given signatures and,
+/// when `message` is set, a new message. Everything else is copied from the raw
+/// original object, so an `encoding` header and a non-UTF-8 message survive
+/// unchanged where `Repository::commit` would re-encode them. A GPG signature
+/// cannot stay valid once anything changes and is dropped, but a commit that
+/// comes out identical keeps its original oid, signature included.
+pub fn rewrite_commit(
+    repo: &Repository,
+    orig: &Commit,
+    parents: &[Commit],
+    author: &Signature,
+    committer: &Signature,
+    message: Option<&str>,
+) -> Result<Oid> {
+    rewrite_commit_with_tree(
+        repo,
+        orig,
+        orig.tree_id(),
+        parents,
+        author,
+        committer,
+        message,
+    )
+}
+
+/// Like `rewrite_commit`, but points the commit at `tree` instead of its own tree.
+pub fn rewrite_commit_with_tree(
+    repo: &Repository,
+    orig: &Commit,
+    tree: Oid,
+    parents: &[Commit],
+    author: &Signature,
+    committer: &Signature,
+    message: Option<&str>,
+) -> Result<Oid> {
+    let odb = repo.odb()?;
+    let original = odb.read(orig.id())?;
+
+    let signed = build_commit(orig, tree, parents, author, committer, message, true);
+    if signed == original.data() {
+        return Ok(orig.id());
+    }
+
+    let unsigned = build_commit(orig, tree, parents, author, committer, message, false);
+    Ok(odb.write(ObjectType::Commit, &unsigned)?)
+}
+
+fn build_commit(
+    orig: &Commit,
+    tree: Oid,
+    parents: &[Commit],
+    author: &Signature,
+    committer: &Signature,
+    message: Option<&str>,
+    keep_signature: bool,
+) -> Vec<u8> {
+    let mut out = Vec::new();
+
+    for field in header_fields(orig.raw_header_bytes()) {
+        let key = field.split(|&b| b == b' ').next().unwrap_or_default();
+        match key {
+            b"tree" => {
+                out.extend_from_slice(format!("tree {tree}\n").as_bytes());
+                for parent in parents {
+                    out.extend_from_slice(format!("parent {}\n", parent.id()).as_bytes());
+                }
+            }
+            b"parent" => {}
+            b"author" => push_signature(&mut out, "author", author),
+            b"committer" => push_signature(&mut out, "committer", committer),
+            // A new message is always UTF-8
+            b"encoding" if message.is_some() => {}
+            b"gpgsig" | b"gpgsig-sha256" if !keep_signature => {}
+            _ => out.extend_from_slice(field),
+        }
+    }
+
+    out.push(b'\n');
+    match message {
+        Some(message) => out.extend_from_slice(message.as_bytes()),
+        None => out.extend_from_slice(orig.message_raw_bytes()),
+    }
+    out
+}
+
+// Splits a raw commit header into its fields, keeping the continuation lines
+// of multi-line fields such as gpgsig with the field they belong to
+fn header_fields(header: &[u8]) -> Vec<&[u8]> {
+    let mut fields = Vec::new();
+    let mut start = 0;
+    let mut pos = 0;
+
+    while pos < header.len() {
+        let line_end = header[pos..]
+            .iter()
+            .position(|&b| b == b'\n')
+            .map_or(header.len(), |i| pos + i + 1);
+        if pos > start && header[pos] != b' ' {
+            fields.push(&header[start..pos]);
+            start = pos;
+        }
+        pos = line_end;
+    }
+    if start < header.len() {
+        fields.push(&header[start..]);
+    }
+    fields
+}
+
+fn push_signature(out: &mut Vec<u8>, key: &str, signature: &Signature) {
+    let when = signature.when();
+    let offset = when.offset_minutes().abs();
+
+    out.extend_from_slice(key.as_bytes());
+    out.push(b' ');
+    out.extend_from_slice(signature.name_bytes());
+    out.extend_from_slice(b" <");
+    if signature.email_bytes() != EMPTY_EMAIL_PLACEHOLDER.as_bytes() {
+        out.extend_from_slice(signature.email_bytes());
+    }
+    out.extend_from_slice(
+        format!(
+            "> {} {}{:02}{:02}\n",
+            when.seconds(),
+            when.sign(),
+            offset / 60,
+            offset % 60
+        )
+        .as_bytes(),
+    );
+}
+
+/// The notes ref `--rewrite-notes` carries over, git's default one.
+pub const NOTES_REF: &str = "refs/notes/commits";
+
+/// Keeps the target's old tip as a backup ref (see `--list-backups`) unless
+/// `--no-backup` is given, points the target at the rewritten tip, moves other
+/// branches and tags with `--update-refs`, copies notes to the new commits
+/// with `--rewrite-notes`, then records the old -> new oid of every rewritten
+/// commit (oldest first) when `--output-remap` is given. Commits whose content
+/// did not change are listed too, since their parents did.
+///
+/// Every moved reference gets a reflog entry naming `operation`, how many
+/// commits it rewrote and when, so `git reflog` shows what git-editor did.
+pub fn finish_rewrite(
+    repo: &Repository,
+    args: &Args,
+    target: &RewriteTarget,
+    new_tip: Oid,
+    orig_oids: &[Oid],
+    new_map: &HashMap<Oid, Oid>,
+    operation: &str,
+) -> Result<()> {
+    if args.verbose > 0 {
+        log_rewritten_commits(repo, args.verbose, orig_oids, new_map)?;
+    }
+
+    let log_message = reflog_message(operation, orig_oids, new_map);
+    let log_message = log_message.as_str();
+    let backup = if args.no_backup {
+        None
+    } else {
+        Some(create_backup(repo, target, target.tip(repo)?, operation)?)
+    };
+    target.update(repo, new_tip, log_message)?;
+    if let Some(backup) = backup.filter(|_| !args.quiet) {
+        println!("{} {}", "Previous tip kept as".green(), backup.cyan());
+    }
+    // The history listed after a rewrite (--show-history) must come from the new tip
+    args.history_cache.invalidate();
+
+    if args.summary {
+        let summary = RewriteSummary::collect(repo, target, orig_oids, new_map)?;
+        *args.rewrite_summary.borrow_mut() = Some(summary);
+    }
+
+    if args.update_refs {
+        let updated = update_other_refs(repo, target, new_map, log_message)?;
+        if updated > 0 && !args.quiet {
+            println!(
+                "{} {updated} other branch/tag reference(s)",
+                "Updated".green()
+            );
+        }
+    }
+
+    if args.rewrite_notes {
+        let copied = rewrite_notes(repo, orig_oids, new_map)?;
+        if copied > 0 && !args.quiet {
+            println!(
+                "{} {copied} note(s) to the rewritten commits",
+                "Copied".green()
+            );
+        }
+    }
+
+    if let Some(remap_path) = &args.output_remap {
+        write_remap(remap_path, orig_oids, new_map)?;
+    }
+    Ok(())
+}
+
+// -v: one `old -> new (fields)` line per rewritten commit, oldest first;
+// -vv: the old and new value of each field too, and the commits kept as is
+fn log_rewritten_commits(
+    repo: &Repository,
+    verbosity: u8,
+    orig_oids: &[Oid],
+    new_map: &HashMap<Oid, Oid>,
+) -> Result<()> {
+    for oid in orig_oids {
+        let short = &oid.to_string()[..8];
+        let Some(&new_oid) = new_map.get(oid) else {
+            println!("{} {}", short.yellow(), "dropped".red());
+            continue;
+        };
+        if new_oid == *oid {
+            if verbosity > 1 {
+                println!("{} {}", short.yellow(), "unchanged".dimmed());
+            }
+            continue;
+        }
+
+        let changes = changed_fields(&repo.find_commit(*oid)?, &repo.find_commit(new_oid)?);
+        let fields: Vec<_> = changes.iter().map(|(field, _, _)| *field).collect();
+        println!(
+            "{} -> {} ({} changed)",
+            short.yellow(),
+            new_oid.to_string()[..8].cyan(),
+            fields.join(", ")
+        );
+        if verbosity > 1 {
+            for (field, old, new) in &changes {
+                print_change(field, old, new);
+            }
+        }
+    }
+    Ok(())
+}
+
+/// The fields that differ between a commit and its rewrite, with their old and
+/// new values. A commit rewritten only because a parent was lists `parents`.
+pub fn changed_fields(orig: &Commit, new: &Commit) -> Vec<(&'static str, String, String)> {
+    let identity = |signature: &Signature| {
+        format!(
+            "{} <{}>",
+            String::from_utf8_lossy(signature.name_bytes()),
+            display_email(&String::from_utf8_lossy(signature.email_bytes()))
+        )
+    };
+    let date = |time: Time| {
+        DateTime::from_timestamp(time.seconds(), 0)
+            .zip(FixedOffset::east_opt(time.offset_minutes() * 60))
+            .map(|(date, offset)| {
+                date.with_timezone(&offset)
+                    .format("%Y-%m-%d %H:%M:%S %z")
+                    .to_string()
+            })
+            .unwrap_or_else(|| time.seconds().to_string())
+    };
+    let summary = |commit: &Commit| {
+        String::from_utf8_lossy(commit.message_raw_bytes())
+            .lines()
+            .next()
+            .unwrap_or_default()
+            .to_string()
+    };
+    let short = |oid: Oid| oid.to_string()[..8].to_string();
+    let parents = |commit: &Commit| {
+        let parents: Vec<_> = commit.parent_ids().map(short).collect();
+        parents.join(" ")
+    };
+
+    let (old_author, new_author) = (orig.author(), new.author());
+    let (old_committer, new_committer) = (orig.committer(), new.committer());
+    let mut changes: Vec<_> = [
+        ("author", identity(&old_author), identity(&new_author)),
+        (
+            "author date",
+            date(old_author.when()),
+            date(new_author.when()),
+        ),
+        (
+            "committer",
+            identity(&old_committer),
+            identity(&new_committer),
+        ),
+        (
+            "commit date",
+            date(old_committer.when()),
+            date(new_committer.when()),
+        ),
+        ("tree", short(orig.tree_id()), short(new.tree_id())),
+    ]
+    .into_iter()
+    .filter(|(_, old, new)| old != new)
+    .collect();
+    // Compared in full, since a reworded body keeps the first line shown
+    if orig.message_raw_bytes() != new.message_raw_bytes() {
+        changes.push(("message", summary(orig), summary(new)));
+    }
+    if changes.is_empty() {
+        changes.push(("parents", parents(orig), parents(new)));
+    }
+    changes
+}
+
+/// The note shown before edits to the commits at `edited`, indices into the
+/// newest-first history, are applied: an oid covers the parent's oid, so every
+/// commit newer than the oldest edited one gets a new one too. None when the
+/// only edit is to the newest commit.
+pub fn chained_rewrite_notice(edited: &[usize]) -> Option<String> {
+    let edited: BTreeSet<usize> = edited.iter().copied().collect();
+    // The oldest edit's newest-first index is how many commits follow it
+    let subsequent = *edited.last()?;
+    if subsequent == 0 {
+        return None;
+    }
+    let subject = if edited.len() == 1 {
+        "this commit"
+    } else {
+        "these commits"
+    };
+    Some(format!(
+        "Editing {subject} will assign new IDs to {subsequent} subsequent commit(s)"
+    ))
+}
+
+/// Reports the ref a rewrite moved to `new_tip`. With `--print-head` only the
+/// full oid is printed, so a script can push it without parsing the line.
+pub fn print_new_tip(args: &Args, target: &RewriteTarget, new_tip: Oid) {
+    if args.print_head {
+        println!("{new_tip}");
+        return;
+    }
+    println!(
+        "{} {} -> {}",
+        "Updated".green(),
+        target.describe().cyan(),
+        new_tip.to_string()[..8].to_string().cyan()
+    );
+}
+
+/// Copies the note of every rewritten commit in `NOTES_REF` to its new oid
+/// and returns how many were copied. The old commits keep theirs. When the
+/// new commit already has a note that does not hold this one, both are kept,
+/// the existing one first, as git's `concatenate` notes rewrite mode does.
+fn rewrite_notes(
+    repo: &Repository,
+    orig_oids: &[Oid],
+    new_map: &HashMap<Oid, Oid>,
+) -> Result<usize> {
+    if repo.find_reference(NOTES_REF).is_err() {
+        return Ok(0);
+    }
+    let committer = repo
+        .signature()
+        .or_else(|_| Signature::now("git-editor", "git-editor@localhost"))?;
+
+    let mut copied = 0;
+    for oid in orig_oids {
+        let Some(&new_oid) = new_map.get(oid).filter(|new_oid| *new_oid != oid) else {
+            continue;
+        };
+        let Ok(note) = repo.find_note(Some(NOTES_REF), *oid) else {
+            continue;
+        };
+        let mut message = String::from_utf8_lossy(note.message_bytes()).into_owned();
+        if let Ok(existing) = repo.find_note(Some(NOTES_REF), new_oid) {
+            let existing = String::from_utf8_lossy(existing.message_bytes());
+            if existing.contains(message.trim_end()) {
+                continue;
+            }
+            message = format!("{}\n\n{message}", existing.trim_end());
+        }
+
+        repo.note(
+            &note.author(),
+            &committer,
+            Some(NOTES_REF),
+            new_oid,
+            &message,
+            true,
+        )?;
+        copied += 1;
+    }
+    Ok(copied)
+}
+
+/// What a rewrite changed, printed as one line by `--summary`: how many
+/// commits got a new oid, and how many of those a new author, author date or
+/// message.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RewriteSummary {
+    pub target: String,
+    pub rewritten: usize,
+    pub authors: usize,
+    pub timestamps: usize,
+    pub messages: usize,
+}
+
+impl RewriteSummary {
+    pub fn collect(
+        repo: &Repository,
+        target: &RewriteTarget,
+        orig_oids: &[Oid],
+        new_map: &HashMap<Oid, Oid>,
+    ) -> Result<Self> {
+        let mut summary = RewriteSummary {
+            target: target.describe(),
+            ..Default::default()
+        };
+        for oid in orig_oids {
+            let Some(&new_oid) = new_map.get(oid).filter(|new_oid| *new_oid != oid) else {
+                continue;
+            };
+            let (orig, new) = (repo.find_commit(*oid)?, repo.find_commit(new_oid)?);
+            let (old_author, new_author) = (orig.author(), new.author());
+
+            summary.rewritten += 1;
+            if old_author.name_bytes() != new_author.name_bytes()
+                || old_author.email_bytes() != new_author.email_bytes()
+            {
+                summary.authors += 1;
+            }
+            if old_author.when() != new_author.when() {
+                summary.timestamps += 1;
+            }
+            if orig.message_raw_bytes() != new.message_raw_bytes() {
+                summary.messages += 1;
+            }
+        }
+        Ok(summary)
+    }
+}
+
+impl std::fmt::Display for RewriteSummary {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Rewrote {} commit(s) ({} authors, {} timestamps, {} messages changed) on {}",
+            self.rewritten, self.authors, self.timestamps, self.messages, self.target
+        )
+    }
+}
+
+// e.g. "git-editor full rewrite: rewrote 3 of 3 commit(s) at 2024-05-01 10:00:00 +0200"
+fn reflog_message(operation: &str, orig_oids: &[Oid], new_map: &HashMap<Oid, Oid>) -> String {
+    let rewritten = orig_oids
+        .iter()
+        .filter(|oid| new_map.get(oid).is_some_and(|new_oid| new_oid != *oid))
+        .count();
+    format!(
+        "git-editor {operation}: rewrote {rewritten} of {} commit(s) at {}",
+        orig_oids.len(),
+        Local::now().format("%Y-%m-%d %H:%M:%S %z")
+    )
+}
+
+fn write_remap(path: &str, orig_oids: &[Oid], new_map: &HashMap<Oid, Oid>) -> Result<()> {
+    let mut content = String::new();
+    for oid in orig_oids {
+        if let Some(new_oid) = new_map.get(oid) {
+            content.push_str(&format!("{oid} {new_oid}\n"));
+        }
+    }
+    std::fs::write(path, content)
+        .map_err(|e| format!("Failed to write oid remap to {path}: {e}"))?;
+    Ok(())
+}
+
+// Moves every local branch and tag that points at a rewritten commit. Annotated
+// tags point at a tag object, so they are re-created with the same name, tagger
+// and message on top of the new commit.
+fn update_other_refs(
+    repo: &Repository,
+    target: &RewriteTarget,
+    new_map: &HashMap<Oid, Oid>,
+    log_message: &str,
+) -> Result<usize> {
+    let target_name = match target {
+        RewriteTarget::Reference(name) => Some(name.as_str()),
+        RewriteTarget::DetachedHead => None,
+    };
+
+    let mut updated = 0;
+    for reference in repo.references()? {
+        let reference = reference?;
+        let (Some(name), Some(oid)) = (reference.name(), reference.target()) else {
+            continue;
+        };
+        if Some(name) == target_name || !(reference.is_branch() || reference.is_tag()) {
+            continue;
+        }
+
+        if let Some(&new_oid) = new_map.get(&oid) {
+            if new_oid != oid {
+                repo.reference(name, new_oid, true, log_message)?;
+                updated += 1;
+            }
+            continue;
+        }
+
+        let (Some(tag_name), Ok(tag)) = (name.strip_prefix("refs/tags/"), repo.find_tag(oid))
+        else {
+            continue;
+        };
+        let Some(&new_oid) = new_map.get(&tag.target_id()) else {
+            continue;
+        };
+        if new_oid == tag.target_id() {
+            continue;
+        }
+
+        let new_target = repo.find_object(new_oid, None)?;
+        let tagger = match tag.tagger() {
+            Some(tagger) => tagger.to_owned(),
+            None => repo.signature()?,
+        };
+        repo.tag(
+            tag_name,
+            &new_target,
+            &tagger,
+            tag.message().unwrap_or_default(),
+            true,
+        )?;
+        updated += 1;
+    }
+
+    Ok(updated)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rewrite::rewrite_specific::apply_edit_options;
+    use crate::utils::types::EditOptions;
+    use tempfile::TempDir;
+
+    // Writes a commit object by hand so it can carry headers Repository::commit never writes
+    fn write_raw_commit(repo: &Repository, extra_headers: &str, message: &[u8]) -> Oid {
+        let tree = repo.treebuilder(None).unwrap().write().unwrap();
+        let mut raw = format!(
+            "tree {tree}\nauthor Test User <test@example.com> 1234567890 +0000\ncommitter Test User <test@example.com> 1234567890 +0000\n{extra_headers}\n"
+        )
+        .into_bytes();
+        raw.extend_from_slice(message);
+        let oid = repo.odb().unwrap().write(ObjectType::Commit, &raw).unwrap();
+        repo.reference("refs/heads/main", oid, true, "test setup")
+            .unwrap();
+        repo.set_head("refs/heads/main").unwrap();
+        oid
+    }
+
+    #[test]
+    fn test_author_rewrite_preserves_encoding_and_raw_message() {
+        let temp_dir = TempDir::new().unwrap();
+        let repo = Repository::init(temp_dir.path()).unwrap();
+        let latin1_message = b"Caf\xe9 na\xefve\n";
+        let oid = write_raw_commit(&repo, "encoding ISO-8859-1\n", latin1_message);
+
+        let args = Args {
+            repo_path: Some(temp_dir.path().to_string_lossy().to_string()),
+            ..Default::default()
+        };
+        let options = EditOptions {
+            author_name: Some("New Author".to_string()),
+            ..Default::default()
+        };
+        apply_edit_options(&repo, &args, &HashMap::from([(oid, &options)]), "test").unwrap();
+
+        let rewritten = repo.head().unwrap().peel_to_commit().unwrap();
+        assert_ne!(rewritten.id(), oid);
+        assert_eq!(rewritten.author().name(), Some("New Author"));
+        assert_eq!(rewritten.message_encoding(), Some("ISO-8859-1"));
+        assert_eq!(rewritten.message_raw_bytes(), latin1_message);
+    }
+
+    #[test]
+    fn test_rewrite_commit_keeps_signature_only_when_unchanged() {
+        let temp_dir = TempDir::new().unwrap();
+        let repo = Repository::init(temp_dir.path()).unwrap();
+        let oid = write_raw_commit(
+            &repo,
+            "gpgsig -----BEGIN PGP SIGNATURE-----\n \n abcdef\n -----END PGP SIGNATURE-----\n",
+            b"Signed commit\n",
+        );
+        let orig = repo.find_commit(oid).unwrap();
+
+        let unchanged =
+            rewrite_commit(&repo, &orig, &[], &orig.author(), &orig.committer(), None).unwrap();
+        assert_eq!(unchanged, oid);
+
+        let author = Signature::new("Other", "other@example.com", &orig.author().when()).unwrap();
+        let changed = rewrite_commit(&repo, &orig, &[], &author, &orig.committer(), None).unwrap();
+        let changed = repo.find_commit(changed).unwrap();
+        assert_ne!(changed.id(), oid);
+        assert!(changed.header_field_bytes("gpgsig").is_err());
+        assert_eq!(changed.message(), Some("Signed commit\n"));
+    }
+
+    #[test]
+    fn test_rewrite_summary_counts_changes() {
+        let temp_dir = TempDir::new().unwrap();
+        let repo = Repository::init(temp_dir.path()).unwrap();
+        let tree = repo
+            .find_tree(repo.index().unwrap().write_tree().unwrap())
+            .unwrap();
+        let mut oids = Vec::new();
+        for (i, message) in ["Root", "Second", "Third", "Fourth"].iter().enumerate() {
+            let sig = Signature::new(
+                "Test User",
+                "test@example.com",
+                &Time::new(1_700_000_000 + i as i64 * 3600, 0),
+            )
+            .unwrap();
+            let parents: Vec<Commit> = oids
+                .last()
+                .map(|&oid| repo.find_commit(oid).unwrap())
+                .into_iter()
+                .collect();
+            oids.push(
+                repo.commit(
+                    Some("HEAD"),
+                    &sig,
+                    &sig,
+                    message,
+                    &tree,
+                    &parents.iter().collect::<Vec<_>>(),
+                )
+                .unwrap(),
+            );
+        }
+
+        let args = Args {
+            repo_path: Some(temp_dir.path().to_string_lossy().to_string()),
+            summary: true,
+            ..Default::default()
+        };
+        let new_author = EditOptions {
+            author_name: Some("New Author".to_string()),
+            ..Default::default()
+        };
+        let new_date_and_message = EditOptions {
+            timestamp: chrono::DateTime::from_timestamp(1_600_000_000, 0).map(|t| t.naive_utc()),
+            message: Some("Third, reworded".to_string()),
+            ..Default::default()
+        };
+        let edits = HashMap::from([(oids[1], &new_author), (oids[2], &new_date_and_message)]);
+        apply_edit_options(&repo, &args, &edits, "test").unwrap();
+
+        // "Fourth" only gets a new oid because its parent did
+        let summary = args.rewrite_summary.borrow().clone().unwrap();
+        assert_eq!(
+            summary,
+            RewriteSummary {
+                target: summary.target.clone(),
+                rewritten: 3,
+                authors: 1,
+                timestamps: 1,
+                messages: 1,
+            }
+        );
+        assert!(summary.target.starts_with("branch '"), "{}", summary.target);
+        assert_eq!(
+            summary.to_string(),
+            format!(
+                "Rewrote 3 commit(s) (1 authors, 1 timestamps, 1 messages changed) on {}",
+                summary.target
+            )
+        );
+    }
+
+    #[test]
+    fn test_changed_fields_names_what_differs() {
+        let temp_dir = TempDir::new().unwrap();
+        let repo = Repository::init(temp_dir.path()).unwrap();
+        let tree = repo
+            .find_tree(repo.index().unwrap().write_tree().unwrap())
+            .unwrap();
+        let sig = |name: &str, secs: i64| {
+            Signature::new(name, "test@example.com", &Time::new(secs, 60)).unwrap()
+        };
+        let commit = |name: &str, secs: i64, message: &str, parents: &[&Commit]| {
+            let oid = repo
+                .commit(
+                    None,
+                    &sig(name, secs),
+                    &sig("Test User", secs),
+                    message,
+                    &tree,
+                    parents,
+                )
+                .unwrap();
+            repo.find_commit(oid).unwrap()
+        };
+
+        let root = commit("Test User", 1_700_000_000, "Root", &[]);
+        let child = commit("Test User", 1_700_003_600, "Child\n\nBody", &[&root]);
+
+        // A new author and a reworded body, with the first line kept
+        let edited = commit("New Author", 1_700_003_600, "Child\n\nNew body", &[&root]);
+        assert_eq!(
+            changed_fields(&child, &edited),
+            [
+                (
+                    "author",
+                    "Test User <test@example.com>".to_string(),
+                    "New Author <test@example.com>".to_string()
+                ),
+                ("message", "Child".to_string(), "Child".to_string()),
+            ]
+        );
+
+        // Moving the root only changes the child's parent
+        let moved_root = commit("Test User", 1_600_000_000, "Root", &[]);
+        let changes = changed_fields(&root, &moved_root);
+        assert_eq!(changes[0].0, "author date");
+        assert_eq!(changes[0].1, "2023-11-14 23:13:20 +0100");
+        assert_eq!(changes[1].0, "commit date");
+        let reparented = commit("Test User", 1_700_003_600, "Child\n\nBody", &[&moved_root]);
+        let changes = changed_fields(&child, &reparented);
+        assert_eq!(changes.len(), 1);
+        assert_eq!(changes[0].0, "parents");
+    }
+
+    #[test]
+    fn test_chained_rewrite_notice_counts_subsequent_commits() {
+        // Newest-first indices 2 and 6 of 10 commits: the earliest edit is
+        // commit #4 counting from the root, so #5 up to HEAD follow it
+        let history_len = 10;
+        let earliest_modified_index = 4;
+        assert_eq!(
+            chained_rewrite_notice(&[6, 2]).unwrap(),
+            format!(
+                "Editing these commits will assign new IDs to {} subsequent commit(s)",
+                history_len - earliest_modified_index
+            )
+        );
+        assert_eq!(
+            chained_rewrite_notice(&[4]).unwrap(),
+            "Editing this commit will assign new IDs to 4 subsequent commit(s)"
+        );
+
+        // An edited commit that follows another edit is counted too
+        assert_eq!(
+            chained_rewrite_notice(&[1, 0]).unwrap(),
+            "Editing these commits will assign new IDs to 1 subsequent commit(s)"
+        );
+
+        // Only HEAD leaves nothing else to renumber
+        assert_eq!(chained_rewrite_notice(&[0]), None);
+        assert_eq!(chained_rewrite_notice(&[]), None);
+    }
+
+    #[test]
+    fn test_rewrite_notes_follow_the_commits() {
+        let temp_dir = TempDir::new().unwrap();
+        let repo = Repository::init(temp_dir.path()).unwrap();
+        let tree = repo
+            .find_tree(repo.index().unwrap().write_tree().unwrap())
+            .unwrap();
+        let sig = Signature::new(
+            "Test User",
+            "test@example.com",
+            &Time::new(1_700_000_000, 0),
+        )
+        .unwrap();
+        let mut oids: Vec<Oid> = Vec::new();
+        for message in ["Root", "Second", "Third"] {
+            let parents: Vec<Commit> = oids
+                .last()
+                .map(|&oid| repo.find_commit(oid).unwrap())
+                .into_iter()
+                .collect();
+            let parents: Vec<&Commit> = parents.iter().collect();
+            oids.push(
+                repo.commit(Some("HEAD"), &sig, &sig, message, &tree, &parents)
+                    .unwrap(),
+            );
+        }
+        let note = |oid: Oid| {
+            repo.find_note(Some(NOTES_REF), oid)
+                .ok()
+                .and_then(|note| note.message().map(str::to_string))
+        };
+        repo.note(&sig, &sig, None, oids[1], "Reviewed-by: Alice", false)
+            .unwrap();
+        repo.note(&sig, &sig, None, oids[2], "Build: green", false)
+            .unwrap();
+
+        let args = Args {
+            repo_path: Some(temp_dir.path().to_string_lossy().to_string()),
+            rewrite_notes: true,
+            ..Default::default()
+        };
+        let options = EditOptions {
+            message: Some("Second, reworded".to_string()),
+            ..Default::default()
+        };
+        apply_edit_options(&repo, &args, &HashMap::from([(oids[1], &options)]), "test").unwrap();
+
+        let head = repo.head().unwrap().peel_to_commit().unwrap();
+        let second = head.parent_id(0).unwrap();
+        assert_ne!(head.id(), oids[2]);
+        assert_eq!(note(head.id()).as_deref(), Some("Build: green"));
+        assert_eq!(note(second).as_deref(), Some("Reviewed-by: Alice"));
+        assert_eq!(note(oids[0]), None);
+        // The old commits keep their notes
+        assert_eq!(note(oids[1]).as_deref(), Some("Reviewed-by: Alice"));
+
+        // A note already on the new commit is kept in front of the copied one
+        let new_map = HashMap::from([(oids[1], head.id())]);
+        assert_eq!(rewrite_notes(&repo, &[oids[1]], &new_map).unwrap(), 1);
+        assert_eq!(
+            note(head.id()).as_deref(),
+            Some("Build: green\n\nReviewed-by: Alice")
+        );
+        // and copying it again changes nothing
+        assert_eq!(rewrite_notes(&repo, &[oids[1]], &new_map).unwrap(), 0);
+    }
+
+    #[test]
+    fn test_commits_before_first_edit_keep_their_oids() {
+        let temp_dir = TempDir::new().unwrap();
+        let repo = Repository::init(temp_dir.path()).unwrap();
+        let tree = repo
+            .find_tree(repo.index().unwrap().write_tree().unwrap())
+            .unwrap();
+        let sig = Signature::new(
+            "Test User",
+            "test@example.com",
+            &Time::new(1_700_000_000, 0),
+        )
+        .unwrap();
+        let mut oids: Vec<Oid> = Vec::new();
+        for message in ["Root", "Second", "Third", "Fourth"] {
+            let parents: Vec<Commit> = oids
+                .last()
+                .map(|&oid| repo.find_commit(oid).unwrap())
+                .into_iter()
+                .collect();
+            let parents: Vec<&Commit> = parents.iter().collect();
+            oids.push(
+                repo.commit(Some("HEAD"), &sig, &sig, message, &tree, &parents)
+                    .unwrap(),
+            );
+        }
+
+        let mut new_map = HashMap::new();
+        let start = skip_unchanged_prefix(&oids, &mut new_map, |oid| Ok(oid == oids[2])).unwrap();
+        assert_eq!(start, 2);
+        assert_eq!(
+            new_map,
+            HashMap::from([(oids[0], oids[0]), (oids[1], oids[1])])
+        );
+        // Nothing edited: the whole walk is still rewritten
+        assert_eq!(
+            skip_unchanged_prefix(&oids, &mut HashMap::new(), |_| Ok(false)).unwrap(),
+            0
+        );
+
+        let remap_path = temp_dir.path().join("remap.txt");
+        let args = Args {
+            repo_path: Some(temp_dir.path().to_string_lossy().to_string()),
+            output_remap: Some(remap_path.to_string_lossy().to_string()),
+            ..Default::default()
+        };
+        let options = EditOptions {
+            message: Some("Third, reworded".to_string()),
+            ..Default::default()
+        };
+        apply_edit_options(&repo, &args, &HashMap::from([(oids[2], &options)]), "test").unwrap();
+
+        let head = repo.head().unwrap().peel_to_commit().unwrap();
+        let third = head.parent(0).unwrap();
+        assert_eq!(third.message(), Some("Third, reworded"));
+        assert_eq!(third.parent_id(0).unwrap(), oids[1]);
+        assert_eq!(third.parent(0).unwrap().parent_id(0).unwrap(), oids[0]);
+        assert_ne!(head.id(), oids[3]);
+
+        // The untouched commits are still listed, mapped to themselves
+        let remap = std::fs::read_to_string(&remap_path).unwrap();
+        let lines: Vec<_> = remap.lines().collect();
+        assert_eq!(lines.len(), 4);
+        assert_eq!(lines[0], format!("{} {}", oids[0], oids[0]));
+        assert_eq!(lines[1], format!("{} {}", oids[1], oids[1]));
+    }
+}
@@ -1,3 +1,9 @@
+pub mod common;
 pub mod rewrite_all;
+pub mod rewrite_content;
+pub mod rewrite_continue;
+pub mod rewrite_date_order;
+pub mod rewrite_plan;
 pub mod rewrite_range;
+pub mod rewrite_round;
 pub mod rewrite_specific;
@@ -1,71 +1,1186 @@
-use crate::utils::types::Result;
-use crate::{args::Args, utils::commit_history::get_commit_history};
+use crate::rewrite::common::{
+    finish_rewrite, new_signature, original_committer_at, prepare_rewrite, print_new_tip,
+    remap_parents, rewrite_commit, skip_unchanged_prefix,
+};
+use crate::rewrite::rewrite_range::edit_commits_interactively;
+use crate::utils::anonymize::AnonymousIdentities;
+use crate::utils::git_ref::RewriteTarget;
+use crate::utils::mailmap::Mailmap;
+use crate::utils::message_transform::add_signoff;
+use crate::utils::types::{Outcome, Result};
+use crate::utils::validator::normalize_email;
+use crate::{
+    args::Args,
+    utils::commit_history::{get_commit_history, print_commit_history},
+};
 use chrono::NaiveDateTime;
 use colored::Colorize;
 use git2::{Repository, Signature, Sort, Time};
 use std::collections::HashMap;
 
-pub fn rewrite_all_commits(args: &Args, timestamps: Vec<NaiveDateTime>) -> Result<()> {
+/// Rewrites every commit with the configured identity, with the identity its
+/// author and committer map to under `--mailmap`, or with its own identity
+/// under `--keep-author`. With `--find-author` only the matching author or
+/// committer is replaced, `--normalize-emails` only tidies the emails of
+/// the commits that need it, and `--anonymize` gives every original email a
+/// placeholder identity. `timestamps` are applied
+/// oldest commit first and are ignored when the original timestamps are kept.
+/// Merge commits keep every parent, remapped in order, so the topology is
+/// unchanged and only signatures differ.
+pub fn rewrite_all_commits(args: &Args, timestamps: Vec<NaiveDateTime>) -> Result<Outcome> {
+    prepare_rewrite(args)?;
+
     let repo = Repository::open(args.repo_path.as_ref().unwrap())?;
-    let head_ref = repo.head()?;
-    let branch_name = head_ref
-        .shorthand()
-        .ok_or("Detached HEAD or invalid branch")?;
-    let full_ref = format!("refs/heads/{branch_name}");
+    let target = RewriteTarget::resolve(&repo, args)?;
 
     let mut revwalk = repo.revwalk()?;
-    revwalk.push_head()?;
+    revwalk.push(target.tip(&repo)?)?;
     revwalk.set_sorting(Sort::TOPOLOGICAL | Sort::TIME)?;
     let mut orig_oids: Vec<_> = revwalk.filter_map(|id| id.ok()).collect();
     orig_oids.reverse();
 
+    let keep_original = args.should_keep_original_timestamps();
+    let mailmap = args
+        .mailmap
+        .as_deref()
+        .map(Mailmap::from_file)
+        .transpose()?;
+    let anonymous = args
+        .anonymize
+        .then(|| AnonymousIdentities::from_commits(&repo, &orig_oids))
+        .transpose()?;
+    let new_identity = |sig: &Signature| {
+        let name = sig.name().unwrap_or("Unknown");
+        let email = sig.email().unwrap_or("unknown@email.com");
+        if let Some(anonymous) = &anonymous {
+            return anonymous.resolve(email);
+        }
+        match &mailmap {
+            Some(mailmap) => mailmap.resolve(name, email),
+            None if args.find_author.is_some() => args.replace_found_author(name, email),
+            None if args.normalize_emails => (
+                name.to_string(),
+                normalize_email(email, args.lowercase_local_part),
+            ),
+            None if args.keep_author => (name.to_string(), email.to_string()),
+            None => (args.name.clone().unwrap(), args.email.clone().unwrap()),
+        }
+    };
     let mut new_map: HashMap<git2::Oid, git2::Oid> = HashMap::new();
+    let start = skip_unchanged_prefix(&orig_oids, &mut new_map, |oid| {
+        args.is_commit_selected(&repo, &repo.find_commit(oid)?)
+    })?;
     let mut last_new_oid = None;
 
-    for (i, &oid) in orig_oids.iter().enumerate() {
+    for (i, &oid) in orig_oids.iter().enumerate().skip(start) {
         let orig = repo.find_commit(oid)?;
-        let tree = orig.tree()?;
 
-        let new_parents: Result<Vec<_>> = orig
-            .parent_ids()
-            .map(|pid| {
-                let new_pid = *new_map.get(&pid).unwrap_or(&pid);
-                repo.find_commit(new_pid).map_err(|e| e.into())
+        let new_parents = remap_parents(&repo, &orig, &new_map)?;
+
+        // Commits filtered out by --only-author/--exclude-author/--find-author/--modified-*/--path keep their signatures entirely
+        let orig_author = orig.author();
+        if !args.is_commit_selected(&repo, &orig)? {
+            let new_oid = rewrite_commit(
+                &repo,
+                &orig,
+                &new_parents,
+                &orig_author,
+                &orig.committer(),
+                None,
+            )?;
+            new_map.insert(oid, new_oid);
+            last_new_oid = Some(new_oid);
+            continue;
+        }
+
+        // KEEP_ORIGINAL leaves each commit's own author/committer time (and offset) intact
+        let (author_time, committer_time) = if keep_original {
+            (orig.author().when(), orig.committer().when())
+        } else {
+            let time = Time::new(timestamps[i].and_utc().timestamp(), 0);
+            (time, time)
+        };
+        let (author_name, author_email) = new_identity(&orig_author);
+        let author_sig = new_signature(&author_name, &author_email, &author_time)?;
+        let committer_sig = if args.preserve_committer_identity {
+            original_committer_at(&orig, &committer_time)?
+        } else {
+            let (committer_name, committer_email) = new_identity(&orig.committer());
+            new_signature(&committer_name, &committer_email, &committer_time)?
+        };
+
+        let message = args.signoff.then(|| {
+            add_signoff(
+                &String::from_utf8_lossy(orig.message_raw_bytes()),
+                &author_name,
+                &author_email,
+            )
+        });
+
+        let new_oid = rewrite_commit(
+            &repo,
+            &orig,
+            &new_parents,
+            &author_sig,
+            &committer_sig,
+            message.as_deref(),
+        )?;
+
+        new_map.insert(oid, new_oid);
+        last_new_oid = Some(new_oid);
+    }
+
+    let Some(new_head) = last_new_oid else {
+        return Ok(Outcome::NothingToDo);
+    };
+    finish_rewrite(
+        &repo,
+        args,
+        &target,
+        new_head,
+        &orig_oids,
+        &new_map,
+        "full rewrite",
+    )?;
+    print_new_tip(args, &target, new_head);
+    if let Some(anonymous) = &anonymous {
+        if let Some(path) = &args.anonymize_map {
+            anonymous.write_mailmap(path)?;
+        }
+        if !args.quiet {
+            let written = match &args.anonymize_map {
+                Some(path) => format!(", map written to {path}"),
+                None => String::new(),
+            };
+            println!(
+                "{}",
+                format!("Anonymized {} identities{written}", anonymous.len()).green()
+            );
+        }
+    }
+    if args.show_history {
+        print_commit_history(args)?;
+    }
+
+    Ok(Outcome::Done)
+}
+
+// Opens the interactive table editor over the entire history with every field editable
+pub fn rewrite_all_interactive(args: &Args) -> Result<Outcome> {
+    let commits = get_commit_history(args, false)?;
+
+    if commits.is_empty() {
+        println!("{}", "No commits found!".red());
+        return Ok(Outcome::NothingToDo);
+    }
+
+    println!(
+        "{} {}",
+        "Commits in history:".bold(),
+        commits.len().to_string().green()
+    );
+
+    edit_commits_interactively(
+        args,
+        &commits,
+        0,
+        commits.len() - 1,
+        (true, true, true, true),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::datetime::generate_timestamps;
+    use std::fs;
+    use tempfile::TempDir;
+
+    fn create_test_repo_with_commits() -> (TempDir, String) {
+        let temp_dir = TempDir::new().unwrap();
+        let repo_path = temp_dir.path().to_str().unwrap().to_string();
+
+        // Initialize git repo
+        let repo = git2::Repository::init(&repo_path).unwrap();
+
+        // Create multiple commits with a non-UTC offset and distinct committer times
+        for i in 1..=3 {
+            let file_path = temp_dir.path().join(format!("test{i}.txt"));
+            fs::write(&file_path, format!("test content {i}")).unwrap();
+
+            let mut index = repo.index().unwrap();
+            index
+                .add_path(std::path::Path::new(&format!("test{i}.txt")))
+                .unwrap();
+            index.write().unwrap();
+
+            let tree_id = index.write_tree().unwrap();
+            let tree = repo.find_tree(tree_id).unwrap();
+
+            let author = git2::Signature::new(
+                "Test User",
+                "test@example.com",
+                &git2::Time::new(1234567890 + i as i64 * 3600, 330),
+            )
+            .unwrap();
+            let committer = git2::Signature::new(
+                "Test User",
+                "test@example.com",
+                &git2::Time::new(1234567890 + i as i64 * 3600 + 60, 330),
+            )
+            .unwrap();
+
+            let parents = if i == 1 {
+                vec![]
+            } else {
+                let head = repo.head().unwrap();
+                let parent_commit = head.peel_to_commit().unwrap();
+                vec![parent_commit]
+            };
+
+            repo.commit(
+                Some("HEAD"),
+                &author,
+                &committer,
+                &format!("Commit {i}"),
+                &tree,
+                &parents.iter().collect::<Vec<_>>(),
+            )
+            .unwrap();
+        }
+
+        (temp_dir, repo_path)
+    }
+
+    fn collect_times(repo_path: &str) -> Vec<(git2::Time, git2::Time)> {
+        let repo = Repository::open(repo_path).unwrap();
+        let mut revwalk = repo.revwalk().unwrap();
+        revwalk.push_head().unwrap();
+        revwalk
+            .map(|oid| {
+                let commit = repo.find_commit(oid.unwrap()).unwrap();
+                let times = (commit.author().when(), commit.committer().when());
+                times
             })
+            .collect()
+    }
+
+    #[test]
+    fn test_rewrite_all_commits_keep_original_timestamps() {
+        let (_temp_dir, repo_path) = create_test_repo_with_commits();
+        let before = collect_times(&repo_path);
+
+        let args = Args {
+            repo_path: Some(repo_path.clone()),
+            email: Some("new@example.com".to_string()),
+            name: Some("New User".to_string()),
+            start: Some("KEEP_ORIGINAL".to_string()),
+            end: Some("KEEP_ORIGINAL".to_string()),
+            ..Default::default()
+        };
+
+        // No timestamps are generated for KEEP_ORIGINAL
+        rewrite_all_commits(&args, Vec::new()).unwrap();
+
+        let after = collect_times(&repo_path);
+        assert_eq!(after.len(), before.len());
+        for ((old_author, old_committer), (new_author, new_committer)) in
+            before.iter().zip(after.iter())
+        {
+            assert_eq!(old_author.seconds(), new_author.seconds());
+            assert_eq!(old_author.offset_minutes(), new_author.offset_minutes());
+            assert_eq!(old_committer.seconds(), new_committer.seconds());
+            assert_eq!(
+                old_committer.offset_minutes(),
+                new_committer.offset_minutes()
+            );
+        }
+
+        let commits = get_commit_history(&args, false).unwrap();
+        assert!(commits
+            .iter()
+            .all(|c| c.author_name == "New User" && c.author_email == "new@example.com"));
+    }
+
+    // root -> side \
+    //      -> main -> merge
+    fn create_test_repo_with_merge() -> (TempDir, String) {
+        let temp_dir = TempDir::new().unwrap();
+        let repo_path = temp_dir.path().to_str().unwrap().to_string();
+        let repo = git2::Repository::init(&repo_path).unwrap();
+
+        let commit_file = |name: &str, time: i64, parents: &[&git2::Commit]| {
+            fs::write(temp_dir.path().join(name), name).unwrap();
+            let mut index = repo.index().unwrap();
+            index.add_path(std::path::Path::new(name)).unwrap();
+            index.write().unwrap();
+            let tree = repo.find_tree(index.write_tree().unwrap()).unwrap();
+            let sig =
+                git2::Signature::new("Test User", "test@example.com", &git2::Time::new(time, 0))
+                    .unwrap();
+            let oid = repo.commit(None, &sig, &sig, name, &tree, parents).unwrap();
+            repo.find_commit(oid).unwrap()
+        };
+
+        let root = commit_file("root.txt", 1234567890, &[]);
+        let side = commit_file("side.txt", 1234571490, &[&root]);
+        let main = commit_file("main.txt", 1234575090, &[&root]);
+        let merge = commit_file("merge.txt", 1234578690, &[&main, &side]);
+        repo.reference("refs/heads/main", merge.id(), true, "test setup")
+            .unwrap();
+        repo.set_head("refs/heads/main").unwrap();
+
+        (temp_dir, repo_path)
+    }
+
+    #[test]
+    fn test_rewrite_all_commits_preserves_merges() {
+        let (_temp_dir, repo_path) = create_test_repo_with_merge();
+        let repo = Repository::open(&repo_path).unwrap();
+        let orig_merge = repo.head().unwrap().peel_to_commit().unwrap();
+        let orig_parents: Vec<_> = orig_merge.parents().collect();
+
+        let mut args = Args {
+            repo_path: Some(repo_path.clone()),
+            email: Some("new@example.com".to_string()),
+            name: Some("New User".to_string()),
+            start: Some("2023-01-01 00:00:00".to_string()),
+            end: Some("2023-01-10 00:00:00".to_string()),
+            ..Default::default()
+        };
+        let timestamps = crate::utils::datetime::generate_timestamps(&mut args).unwrap();
+        assert_eq!(timestamps.len(), 4);
+        rewrite_all_commits(&args, timestamps).unwrap();
+
+        let merge = repo.head().unwrap().peel_to_commit().unwrap();
+        assert_ne!(merge.id(), orig_merge.id());
+        assert_eq!(merge.parent_count(), 2);
+        assert_eq!(merge.tree_id(), orig_merge.tree_id());
+        assert_eq!(merge.author().name(), Some("New User"));
+
+        // Both parents are rewritten, in their original order, onto the same new root
+        let parents: Vec<_> = merge.parents().collect();
+        for (parent, orig_parent) in parents.iter().zip(&orig_parents) {
+            assert_ne!(parent.id(), orig_parent.id());
+            assert_eq!(parent.tree_id(), orig_parent.tree_id());
+            assert_eq!(parent.message(), orig_parent.message());
+            assert_eq!(parent.author().email(), Some("new@example.com"));
+        }
+        let root = parents[0].parent(0).unwrap();
+        assert_eq!(parents[1].parent_id(0).unwrap(), root.id());
+        assert_eq!(root.parent_count(), 0);
+        assert_eq!(root.author().name(), Some("New User"));
+    }
+
+    fn create_test_repo_with_two_authors() -> (TempDir, String) {
+        let temp_dir = TempDir::new().unwrap();
+        let repo_path = temp_dir.path().to_str().unwrap().to_string();
+        let repo = git2::Repository::init(&repo_path).unwrap();
+
+        let authors = [
+            ("Alice", "alice@example.com"),
+            ("Bob", "bob@example.com"),
+            ("Alice", "alice@example.com"),
+            ("Bob", "bob@example.com"),
+        ];
+        for (i, (name, email)) in authors.iter().enumerate() {
+            let file_name = format!("test{i}.txt");
+            fs::write(temp_dir.path().join(&file_name), name).unwrap();
+
+            let mut index = repo.index().unwrap();
+            index.add_path(std::path::Path::new(&file_name)).unwrap();
+            index.write().unwrap();
+            let tree = repo.find_tree(index.write_tree().unwrap()).unwrap();
+
+            let sig = git2::Signature::new(
+                name,
+                email,
+                &git2::Time::new(1234567890 + i as i64 * 3600, 0),
+            )
+            .unwrap();
+            let parents = match repo.head() {
+                Ok(head) => vec![head.peel_to_commit().unwrap()],
+                Err(_) => vec![],
+            };
+            repo.commit(
+                Some("HEAD"),
+                &sig,
+                &sig,
+                &format!("Commit {i}"),
+                &tree,
+                &parents.iter().collect::<Vec<_>>(),
+            )
+            .unwrap();
+        }
+
+        (temp_dir, repo_path)
+    }
+
+    fn full_rewrite_args(repo_path: &str) -> Args {
+        Args {
+            repo_path: Some(repo_path.to_string()),
+            email: Some("new@example.com".to_string()),
+            name: Some("New User".to_string()),
+            start: Some("2023-01-01 00:00:00".to_string()),
+            end: Some("2023-01-10 00:00:00".to_string()),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_rewrite_all_commits_exclude_author() {
+        let (_temp_dir, repo_path) = create_test_repo_with_two_authors();
+        let before = get_commit_history(&full_rewrite_args(&repo_path), false).unwrap();
+
+        let args = Args {
+            exclude_author: vec!["bob@example.com".to_string()],
+            ..full_rewrite_args(&repo_path)
+        };
+        let timestamps = generate_timestamps(&mut full_rewrite_args(&repo_path)).unwrap();
+        rewrite_all_commits(&args, timestamps).unwrap();
+
+        let after = get_commit_history(&args, false).unwrap();
+        for (old, new) in before.iter().zip(after.iter()) {
+            if old.author_name == "Bob" {
+                assert_eq!(new.author_name, "Bob");
+                assert_eq!(new.author_email, "bob@example.com");
+                assert_eq!(new.timestamp, old.timestamp);
+            } else {
+                assert_eq!(new.author_name, "New User");
+                assert_ne!(new.timestamp, old.timestamp);
+            }
+        }
+    }
+
+    #[test]
+    fn test_rewrite_all_commits_only_author() {
+        let (_temp_dir, repo_path) = create_test_repo_with_two_authors();
+
+        let args = Args {
+            only_author: vec!["Bob".to_string()],
+            ..full_rewrite_args(&repo_path)
+        };
+        let timestamps = generate_timestamps(&mut full_rewrite_args(&repo_path)).unwrap();
+        rewrite_all_commits(&args, timestamps).unwrap();
+
+        let after = get_commit_history(&args, false).unwrap();
+        let names: Vec<_> = after.iter().map(|c| c.author_name.as_str()).collect();
+        assert_eq!(names, ["New User", "Alice", "New User", "Alice"]);
+    }
+
+    #[test]
+    fn test_rewrite_all_commits_modified_window() {
+        let (_temp_dir, repo_path) = create_test_repo_with_two_authors();
+        let before = get_commit_history(&full_rewrite_args(&repo_path), false).unwrap();
+
+        // Commits are authored an hour apart from 2009-02-13 23:31:30; the
+        // window starts exactly at the second and ends exactly at the fourth
+        let args = Args {
+            modified_after: Some("2009-02-14 00:31:30".to_string()),
+            modified_before: Some("2009-02-14 02:31:30".to_string()),
+            ..full_rewrite_args(&repo_path)
+        };
+        let timestamps = generate_timestamps(&mut full_rewrite_args(&repo_path)).unwrap();
+        rewrite_all_commits(&args, timestamps).unwrap();
+
+        let after = get_commit_history(&args, false).unwrap();
+        let names: Vec<_> = after.iter().map(|c| c.author_name.as_str()).collect();
+        assert_eq!(names, ["Bob", "New User", "New User", "Alice"]);
+
+        // The commits outside keep their dates; the later one is only re-parented
+        assert_eq!(after[0].author_timestamp, before[0].author_timestamp);
+        assert_ne!(after[0].oid, before[0].oid);
+        assert_eq!(after[3].oid, before[3].oid);
+    }
+
+    #[test]
+    fn test_rewrite_all_commits_find_author() {
+        let (_temp_dir, repo_path) = create_test_repo_with_two_authors();
+        let before = get_commit_history(&full_rewrite_args(&repo_path), false).unwrap();
+
+        let mut args = Args {
+            repo_path: Some(repo_path.clone()),
+            find_author: Some("BOB@example.com".to_string()),
+            replace_name: Some("Robert".to_string()),
+            replace_email: Some("robert@example.com".to_string()),
+            ..Default::default()
+        };
+        args.ensure_all_args_present().unwrap();
+        let timestamps = generate_timestamps(&mut args).unwrap();
+        rewrite_all_commits(&args, timestamps).unwrap();
+
+        let after = get_commit_history(&args, false).unwrap();
+        let identities: Vec<_> = after
+            .iter()
+            .map(|c| (c.author_name.as_str(), c.author_email.as_str()))
             .collect();
+        assert_eq!(
+            identities,
+            [
+                ("Robert", "robert@example.com"),
+                ("Alice", "alice@example.com"),
+                ("Robert", "robert@example.com"),
+                ("Alice", "alice@example.com"),
+            ]
+        );
+        // Timestamps and messages are untouched, and Alice's commits keep their hashes
+        for (old, new) in before.iter().zip(after.iter()) {
+            assert_eq!(new.timestamp, old.timestamp);
+            assert_eq!(new.message, old.message);
+        }
+        assert_eq!(after[3].oid, before[3].oid);
 
-        let timestamp: i64 = timestamps[i].and_utc().timestamp();
-        let sig = Signature::new(
-            args.name.as_ref().unwrap(),
-            args.email.as_ref().unwrap(),
-            &Time::new(timestamp, 0),
-        )?;
+        let repo = Repository::open(&repo_path).unwrap();
+        let head = repo.head().unwrap().peel_to_commit().unwrap();
+        assert_eq!(head.committer().name(), Some("Robert"));
+    }
+
+    #[test]
+    fn test_rewrite_all_commits_normalize_emails() {
+        let (_temp_dir, repo_path) = create_test_repo_with_two_authors();
+        let repo = Repository::open(&repo_path).unwrap();
+        let head = repo.head().unwrap().peel_to_commit().unwrap();
+        let when = Time::new(1234600000, 0);
+        repo.commit(
+            Some("HEAD"),
+            &Signature::new("Alice", "Alice@Example.COM", &when).unwrap(),
+            &Signature::new("Carol", "carol@EXAMPLE.org", &when).unwrap(),
+            "Mixed case",
+            &head.tree().unwrap(),
+            &[&head],
+        )
+        .unwrap();
+
+        let mut args = Args {
+            repo_path: Some(repo_path.clone()),
+            normalize_emails: true,
+            ..Default::default()
+        };
+        args.ensure_all_args_present().unwrap();
+        let before = get_commit_history(&args, false).unwrap();
+
+        // Only the mixed-case commit shows up as changed
+        let timestamps = generate_timestamps(&mut args).unwrap();
+        let simulation =
+            crate::utils::simulation::create_full_rewrite_simulation(&before, &timestamps, &args)
+                .unwrap();
+        assert_eq!(
+            simulation.changes[0].new_email.as_deref(),
+            Some("Alice@example.com")
+        );
+        assert!(simulation.changes[1..].iter().all(|c| !c.has_changes()));
+
+        rewrite_all_commits(&args, timestamps).unwrap();
+        let after = get_commit_history(&args, false).unwrap();
+        assert_eq!(
+            after[1..].iter().map(|c| c.oid).collect::<Vec<_>>(),
+            before[1..].iter().map(|c| c.oid).collect::<Vec<_>>()
+        );
+        assert_eq!(after[0].timestamp, before[0].timestamp);
+        let head = repo.head().unwrap().peel_to_commit().unwrap();
+        assert_eq!(head.author().name(), Some("Alice"));
+        assert_eq!(head.author().email(), Some("Alice@example.com"));
+        assert_eq!(head.committer().name(), Some("Carol"));
+        assert_eq!(head.committer().email(), Some("carol@example.org"));
 
-        let new_oid = repo.commit(
-            None,
+        // The local part is only lowercased on request
+        let mut args = Args {
+            lowercase_local_part: true,
+            ..args
+        };
+        let timestamps = generate_timestamps(&mut args).unwrap();
+        rewrite_all_commits(&args, timestamps).unwrap();
+        let head = repo.head().unwrap().peel_to_commit().unwrap();
+        assert_eq!(head.author().email(), Some("alice@example.com"));
+        assert_eq!(head.parent_id(0).unwrap(), before[1].oid);
+    }
+
+    #[test]
+    fn test_rewrite_all_commits_anonymize() {
+        let (temp_dir, repo_path) = create_test_repo_with_two_authors();
+        let map_path = temp_dir.path().join("identities.mailmap");
+        let mut args = Args {
+            repo_path: Some(repo_path.clone()),
+            anonymize: true,
+            anonymize_map: Some(map_path.to_string_lossy().to_string()),
+            ..Default::default()
+        };
+        args.ensure_all_args_present().unwrap();
+        let before = get_commit_history(&args, false).unwrap();
+
+        let timestamps = generate_timestamps(&mut args).unwrap();
+        let simulation =
+            crate::utils::simulation::create_full_rewrite_simulation(&before, &timestamps, &args)
+                .unwrap();
+        assert_eq!(
+            simulation.changes[3].new_author.as_deref(),
+            Some("Author 1")
+        );
+        assert_eq!(
+            simulation.changes[0].new_author.as_deref(),
+            Some("Author 2")
+        );
+
+        rewrite_all_commits(&args, timestamps).unwrap();
+        let after = get_commit_history(&args, false).unwrap();
+
+        // Oldest first: Alice, Bob, Alice, Bob
+        let identities: Vec<_> = after
+            .iter()
+            .rev()
+            .map(|c| (c.author_name.as_str(), c.author_email.as_str()))
+            .collect();
+        assert_eq!(
+            identities,
+            [
+                ("Author 1", "author1@anon.invalid"),
+                ("Author 2", "author2@anon.invalid"),
+                ("Author 1", "author1@anon.invalid"),
+                ("Author 2", "author2@anon.invalid"),
+            ]
+        );
+        let repo = Repository::open(&repo_path).unwrap();
+        let head = repo.head().unwrap().peel_to_commit().unwrap();
+        assert_eq!(head.committer().email(), Some("author2@anon.invalid"));
+        let timestamps_kept = after
+            .iter()
+            .zip(&before)
+            .all(|(a, b)| a.timestamp == b.timestamp);
+        assert!(timestamps_kept);
+
+        // The map undoes the anonymization as a mailmap
+        let map = std::fs::read_to_string(&map_path).unwrap();
+        assert_eq!(
+            map,
+            "Alice <alice@example.com> Author 1 <author1@anon.invalid>\n\
+             Bob <bob@example.com> Author 2 <author2@anon.invalid>\n"
+        );
+    }
+
+    // Four commits, of which the first and third change files under app/
+    fn create_test_repo_with_subdirectories() -> (TempDir, String) {
+        let temp_dir = TempDir::new().unwrap();
+        let repo_path = temp_dir.path().to_str().unwrap().to_string();
+        let repo = git2::Repository::init(&repo_path).unwrap();
+        fs::create_dir_all(temp_dir.path().join("app")).unwrap();
+        fs::create_dir_all(temp_dir.path().join("docs")).unwrap();
+
+        let files = [
+            "app/main.rs",
+            "docs/guide.md",
+            "app/lib.rs",
+            "docs/guide.md",
+        ];
+        for (i, file) in files.iter().enumerate() {
+            fs::write(temp_dir.path().join(file), format!("version {i}")).unwrap();
+            let mut index = repo.index().unwrap();
+            index.add_path(std::path::Path::new(file)).unwrap();
+            index.write().unwrap();
+            let tree = repo.find_tree(index.write_tree().unwrap()).unwrap();
+
+            let sig = git2::Signature::new(
+                "Test User",
+                "test@example.com",
+                &git2::Time::new(1234567890 + i as i64 * 3600, 0),
+            )
+            .unwrap();
+            let parents: Vec<git2::Commit> = match repo.head() {
+                Ok(head) => vec![head.peel_to_commit().unwrap()],
+                Err(_) => vec![],
+            };
+            repo.commit(
+                Some("HEAD"),
+                &sig,
+                &sig,
+                &format!("Change {file}"),
+                &tree,
+                &parents.iter().collect::<Vec<_>>(),
+            )
+            .unwrap();
+        }
+
+        (temp_dir, repo_path)
+    }
+
+    #[test]
+    fn test_rewrite_all_commits_with_path_filter() {
+        let (_temp_dir, repo_path) = create_test_repo_with_subdirectories();
+        let args = Args {
+            path_filter: Some("app/".to_string()),
+            ..full_rewrite_args(&repo_path)
+        };
+
+        let listed = get_commit_history(&args, false).unwrap();
+        let messages: Vec<_> = listed.iter().map(|c| c.message.as_str()).collect();
+        assert_eq!(messages, ["Change app/lib.rs", "Change app/main.rs"]);
+
+        let before = get_commit_history(&full_rewrite_args(&repo_path), false).unwrap();
+        let timestamps = generate_timestamps(&mut full_rewrite_args(&repo_path)).unwrap();
+        rewrite_all_commits(&args, timestamps).unwrap();
+
+        // Every commit is re-committed, but only those touching app/ change
+        let after = get_commit_history(&full_rewrite_args(&repo_path), false).unwrap();
+        assert_eq!(after.len(), before.len());
+        for (old, new) in before.iter().zip(after.iter()) {
+            assert_eq!(new.message, old.message);
+            if old.message.starts_with("Change app/") {
+                assert_eq!(new.author_name, "New User");
+                assert_ne!(new.timestamp, old.timestamp);
+            } else {
+                assert_eq!(new.author_name, "Test User");
+                assert_eq!(new.timestamp, old.timestamp);
+            }
+        }
+    }
+
+    #[test]
+    fn test_count_selected_commits_with_path_filter() {
+        use crate::rewrite::common::count_selected_commits;
+
+        let (_temp_dir, repo_path) = create_test_repo_with_subdirectories();
+        let count = |args: Args| {
+            count_selected_commits(&Args {
+                count: true,
+                ..args
+            })
+            .unwrap()
+        };
+
+        assert_eq!(count(full_rewrite_args(&repo_path)), 4);
+        assert_eq!(
+            count(Args {
+                path_filter: Some("app/".to_string()),
+                ..full_rewrite_args(&repo_path)
+            }),
+            2
+        );
+        assert_eq!(
+            count(Args {
+                path_filter: Some("docs/guide.md".to_string()),
+                show_history: true,
+                ..full_rewrite_args(&repo_path)
+            }),
+            2
+        );
+        assert_eq!(
+            count(Args {
+                path_filter: Some("missing/".to_string()),
+                ..full_rewrite_args(&repo_path)
+            }),
+            0
+        );
+        assert_eq!(
+            count(Args {
+                range: true,
+                range_tail: Some(3),
+                ..full_rewrite_args(&repo_path)
+            }),
+            3
+        );
+    }
+
+    #[test]
+    fn test_rewrite_all_commits_keep_author() {
+        let (_temp_dir, repo_path) = create_test_repo_with_two_authors();
+        let before = get_commit_history(&full_rewrite_args(&repo_path), false).unwrap();
+
+        let mut args = Args {
+            name: None,
+            email: None,
+            keep_author: true,
+            ..full_rewrite_args(&repo_path)
+        };
+        let timestamps = generate_timestamps(&mut args).unwrap();
+        rewrite_all_commits(&args, timestamps).unwrap();
+
+        let after = get_commit_history(&args, false).unwrap();
+        assert_eq!(after.len(), before.len());
+        for (old, new) in before.iter().zip(after.iter()) {
+            assert_eq!(new.author_name, old.author_name);
+            assert_eq!(new.author_email, old.author_email);
+            assert_ne!(new.timestamp, old.timestamp);
+        }
+
+        let repo = Repository::open(&repo_path).unwrap();
+        let head = repo.head().unwrap().peel_to_commit().unwrap();
+        assert_eq!(head.committer().name(), head.author().name());
+    }
+
+    #[test]
+    fn test_rewrite_all_commits_signoff() {
+        let (_temp_dir, repo_path) = create_test_repo_with_two_authors();
+        // One more commit by Alice that is already signed off
+        let repo = Repository::open(&repo_path).unwrap();
+        let head = repo.head().unwrap().peel_to_commit().unwrap();
+        let sig = Signature::new("Alice", "alice@example.com", &Time::new(1234600000, 0)).unwrap();
+        repo.commit(
+            Some("HEAD"),
             &sig,
             &sig,
-            orig.message().unwrap_or_default(),
-            &tree,
-            &new_parents?.iter().collect::<Vec<_>>(),
-        )?;
+            "Signed\n\nSigned-off-by: Alice <alice@example.com>\n",
+            &head.tree().unwrap(),
+            &[&head],
+        )
+        .unwrap();
 
-        new_map.insert(oid, new_oid);
-        last_new_oid = Some(new_oid);
+        let mut args = Args {
+            name: None,
+            email: None,
+            keep_author: true,
+            signoff: true,
+            ..full_rewrite_args(&repo_path)
+        };
+        let timestamps = generate_timestamps(&mut args).unwrap();
+
+        // The simulation lists the trailer as a message change, except where it exists
+        let before = get_commit_history(&args, false).unwrap();
+        let simulation =
+            crate::utils::simulation::create_full_rewrite_simulation(&before, &timestamps, &args)
+                .unwrap();
+        assert_eq!(simulation.changes[0].new_message, None);
+        assert_eq!(
+            simulation.changes[1].new_message.as_deref(),
+            Some("Commit 3\n\nSigned-off-by: Bob <bob@example.com>\n")
+        );
+        let summary = simulation.changes[1].get_change_summary().join("\n");
+        assert!(
+            summary.contains("Signed-off-by: Bob <bob@example.com>"),
+            "{summary}"
+        );
+
+        rewrite_all_commits(&args, timestamps).unwrap();
+
+        let after = get_commit_history(&args, false).unwrap();
+        assert_eq!(after.len(), 5);
+        for commit in &after {
+            let trailer = format!(
+                "Signed-off-by: {} <{}>",
+                commit.author_name, commit.author_email
+            );
+            assert_eq!(commit.message.matches(&trailer).count(), 1, "{commit:?}");
+            assert_eq!(commit.message.matches("Signed-off-by:").count(), 1);
+        }
+        assert_eq!(
+            after[0].message,
+            "Signed\n\nSigned-off-by: Alice <alice@example.com>\n"
+        );
+    }
+
+    #[test]
+    fn test_rewrite_all_commits_with_mailmap() {
+        let (temp_dir, repo_path) = create_test_repo_with_two_authors();
+        let mailmap_path = temp_dir.path().join("mailmap");
+        fs::write(
+            &mailmap_path,
+            "Alice Smith <alice.smith@example.com> <ALICE@example.com>\n",
+        )
+        .unwrap();
+
+        let args = Args {
+            repo_path: Some(repo_path),
+            mailmap: Some(mailmap_path.to_string_lossy().to_string()),
+            start: Some("KEEP_ORIGINAL".to_string()),
+            end: Some("KEEP_ORIGINAL".to_string()),
+            ..Default::default()
+        };
+        rewrite_all_commits(&args, Vec::new()).unwrap();
+
+        let repo = Repository::open(args.repo_path.as_ref().unwrap()).unwrap();
+        let mut revwalk = repo.revwalk().unwrap();
+        revwalk.push_head().unwrap();
+        for oid in revwalk {
+            let commit = repo.find_commit(oid.unwrap()).unwrap();
+            let (author, committer) = (commit.author(), commit.committer());
+            assert_eq!(author.name(), committer.name());
+            assert_eq!(author.email(), committer.email());
+            match author.name().unwrap() {
+                "Alice Smith" => assert_eq!(author.email(), Some("alice.smith@example.com")),
+                "Bob" => assert_eq!(author.email(), Some("bob@example.com")),
+                other => panic!("unexpected author {other}"),
+            }
+        }
+    }
+
+    #[test]
+    fn test_rewrite_all_commits_refuses_dirty_tree() {
+        let (temp_dir, repo_path) = create_test_repo_with_commits();
+        let repo = git2::Repository::open(&repo_path).unwrap();
+        let head_before = repo.head().unwrap().target().unwrap();
+
+        // Stage a new file without committing it
+        fs::write(temp_dir.path().join("staged.txt"), "work in progress").unwrap();
+        let mut index = repo.index().unwrap();
+        index.add_path(std::path::Path::new("staged.txt")).unwrap();
+        index.write().unwrap();
+
+        let args = full_rewrite_args(&repo_path);
+        let timestamps = generate_timestamps(&mut full_rewrite_args(&repo_path)).unwrap();
+        let err = rewrite_all_commits(&args, timestamps.clone())
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("staged.txt"));
+        assert!(err.contains("--allow-dirty"));
+        assert_eq!(repo.head().unwrap().target().unwrap(), head_before);
+
+        let args = Args {
+            allow_dirty: true,
+            ..full_rewrite_args(&repo_path)
+        };
+        rewrite_all_commits(&args, timestamps).unwrap();
+        assert_ne!(repo.head().unwrap().target().unwrap(), head_before);
     }
 
-    if let Some(new_head) = last_new_oid {
-        repo.reference(&full_ref, new_head, true, "history rewritten")?;
-        println!(
-            "{} '{}' -> {}",
-            "Rewritten branch".green(),
-            branch_name.cyan(),
-            new_head.to_string().cyan()
+    #[test]
+    fn test_rewrite_all_commits_on_named_branch() {
+        let (_temp_dir, repo_path) = create_test_repo_with_commits();
+        let repo = git2::Repository::open(&repo_path).unwrap();
+        let head_before = repo.head().unwrap().target().unwrap();
+        repo.branch("feature", &repo.find_commit(head_before).unwrap(), false)
+            .unwrap();
+
+        let args = Args {
+            rewrite_ref: Some("feature".to_string()),
+            ..full_rewrite_args(&repo_path)
+        };
+        let timestamps = generate_timestamps(&mut full_rewrite_args(&repo_path)).unwrap();
+        rewrite_all_commits(&args, timestamps).unwrap();
+
+        // Only the named branch moves, the checked-out branch stays where it was
+        assert_eq!(repo.head().unwrap().target().unwrap(), head_before);
+        let feature = repo
+            .find_branch("feature", git2::BranchType::Local)
+            .unwrap()
+            .get()
+            .peel_to_commit()
+            .unwrap();
+        assert_ne!(feature.id(), head_before);
+        assert_eq!(feature.author().name(), Some("New User"));
+    }
+
+    #[test]
+    fn test_rewrite_all_commits_in_linked_worktree() {
+        let (temp_dir, repo_path) = create_test_repo_with_commits();
+        let repo = git2::Repository::open(&repo_path).unwrap();
+        let head_before = repo.head().unwrap().target().unwrap();
+        let worktree_path = temp_dir.path().join("linked");
+        repo.worktree("linked", &worktree_path, None).unwrap();
+        let worktree_path = worktree_path.to_str().unwrap().to_string();
+
+        let timestamps = generate_timestamps(&mut full_rewrite_args(&worktree_path)).unwrap();
+        rewrite_all_commits(&full_rewrite_args(&worktree_path), timestamps).unwrap();
+
+        // The worktree's own branch moves, the main checkout is left alone
+        assert_eq!(repo.head().unwrap().target().unwrap(), head_before);
+        let linked = repo
+            .find_branch("linked", git2::BranchType::Local)
+            .unwrap()
+            .get()
+            .peel_to_commit()
+            .unwrap();
+        assert_ne!(linked.id(), head_before);
+        assert_eq!(linked.author().name(), Some("New User"));
+    }
+
+    #[test]
+    fn test_rewrite_all_commits_in_bare_repo() {
+        let (_temp_dir, repo_path) = create_test_repo_with_commits();
+        let bare_dir = TempDir::new().unwrap();
+        let bare = git2::build::RepoBuilder::new()
+            .bare(true)
+            .clone(&repo_path, bare_dir.path())
+            .unwrap();
+        let head_before = bare.head().unwrap().target().unwrap();
+        let bare_path = bare_dir.path().to_str().unwrap().to_string();
+
+        let timestamps = generate_timestamps(&mut full_rewrite_args(&bare_path)).unwrap();
+        rewrite_all_commits(&full_rewrite_args(&bare_path), timestamps).unwrap();
+
+        let new_head = bare.head().unwrap().peel_to_commit().unwrap();
+        assert_ne!(new_head.id(), head_before);
+        assert_eq!(new_head.author().name(), Some("New User"));
+
+        // Bare repositories keep no reflog by default; the rewrite writes one anyway
+        let branch = bare.head().unwrap().name().unwrap().to_string();
+        let reflog = bare.reflog(&branch).unwrap();
+        let latest = reflog.get(0).unwrap();
+        assert_eq!(latest.id_new(), new_head.id());
+        assert!(latest
+            .message()
+            .unwrap()
+            .starts_with("git-editor full rewrite: rewrote 3 of 3 commit(s) at "));
+    }
+
+    #[test]
+    fn test_rewrite_all_commits_on_detached_head() {
+        let (_temp_dir, repo_path) = create_test_repo_with_commits();
+        let repo = git2::Repository::open(&repo_path).unwrap();
+        let branch_name = repo.head().unwrap().name().unwrap().to_string();
+        let head_before = repo.head().unwrap().target().unwrap();
+        repo.set_head_detached(head_before).unwrap();
+
+        let args = Args {
+            rewrite_ref: Some("HEAD".to_string()),
+            ..full_rewrite_args(&repo_path)
+        };
+        let timestamps = generate_timestamps(&mut full_rewrite_args(&repo_path)).unwrap();
+        rewrite_all_commits(&args, timestamps).unwrap();
+
+        assert!(repo.head_detached().unwrap());
+        let new_head = repo.head().unwrap().peel_to_commit().unwrap();
+        assert_ne!(new_head.id(), head_before);
+        assert_eq!(new_head.author().name(), Some("New User"));
+
+        // Exactly one HEAD reflog entry records the rewrite
+        let reflog = repo.reflog("HEAD").unwrap();
+        let entries: Vec<_> = reflog
+            .iter()
+            .filter(|entry| entry.message().is_some_and(|m| m.starts_with("git-editor")))
+            .collect();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].id_old(), head_before);
+        assert_eq!(entries[0].id_new(), new_head.id());
+        assert_eq!(
+            repo.find_reference(&branch_name).unwrap().target(),
+            Some(head_before)
         );
-        if args.show_history {
-            get_commit_history(args, true)?;
+    }
+
+    #[test]
+    fn test_rewrite_all_commits_writes_remap() {
+        let (temp_dir, repo_path) = create_test_repo_with_commits();
+        let before = get_commit_history(&full_rewrite_args(&repo_path), false).unwrap();
+        let remap_path = temp_dir.path().join("remap.txt");
+
+        let args = Args {
+            output_remap: Some(remap_path.to_string_lossy().to_string()),
+            ..full_rewrite_args(&repo_path)
+        };
+        let timestamps = generate_timestamps(&mut full_rewrite_args(&repo_path)).unwrap();
+        rewrite_all_commits(&args, timestamps).unwrap();
+        let after = get_commit_history(&args, false).unwrap();
+
+        // One line per commit, oldest first
+        let remap = fs::read_to_string(&remap_path).unwrap();
+        let expected: Vec<String> = before
+            .iter()
+            .zip(after.iter())
+            .rev()
+            .map(|(old, new)| format!("{} {}", old.oid, new.oid))
+            .collect();
+        assert_eq!(remap.lines().collect::<Vec<_>>(), expected);
+    }
+
+    #[test]
+    fn test_rewrite_all_commits_update_refs() {
+        let (_temp_dir, repo_path) = create_test_repo_with_commits();
+        let repo = git2::Repository::open(&repo_path).unwrap();
+        let middle = repo
+            .head()
+            .unwrap()
+            .peel_to_commit()
+            .unwrap()
+            .parent(0)
+            .unwrap();
+        let middle_id = middle.id();
+        let tagger =
+            git2::Signature::new("Tagger", "tagger@example.com", &git2::Time::new(0, 0)).unwrap();
+        repo.branch("side", &middle, false).unwrap();
+        repo.tag_lightweight("light", middle.as_object(), false)
+            .unwrap();
+        repo.tag(
+            "annotated",
+            middle.as_object(),
+            &tagger,
+            "Release notes",
+            false,
+        )
+        .unwrap();
+
+        let args = Args {
+            update_refs: true,
+            ..full_rewrite_args(&repo_path)
+        };
+        let timestamps = generate_timestamps(&mut full_rewrite_args(&repo_path)).unwrap();
+        rewrite_all_commits(&args, timestamps).unwrap();
+
+        let new_middle = repo
+            .head()
+            .unwrap()
+            .peel_to_commit()
+            .unwrap()
+            .parent(0)
+            .unwrap();
+        assert_ne!(new_middle.id(), middle_id);
+        for name in ["refs/heads/side", "refs/tags/light", "refs/tags/annotated"] {
+            let commit = repo.find_reference(name).unwrap().peel_to_commit().unwrap();
+            assert_eq!(commit.id(), new_middle.id(), "{name} was not moved");
         }
+
+        let tag = repo
+            .find_reference("refs/tags/annotated")
+            .unwrap()
+            .peel_to_tag()
+            .unwrap();
+        assert_eq!(tag.message(), Some("Release notes"));
+        assert_eq!(tag.tagger().unwrap().name(), Some("Tagger"));
     }
 
-    Ok(())
+    #[test]
+    fn test_rewrite_all_commits_leaves_other_refs_by_default() {
+        let (_temp_dir, repo_path) = create_test_repo_with_commits();
+        let repo = git2::Repository::open(&repo_path).unwrap();
+        let middle = repo
+            .head()
+            .unwrap()
+            .peel_to_commit()
+            .unwrap()
+            .parent(0)
+            .unwrap();
+        repo.branch("side", &middle, false).unwrap();
+
+        let args = full_rewrite_args(&repo_path);
+        let timestamps = generate_timestamps(&mut full_rewrite_args(&repo_path)).unwrap();
+        rewrite_all_commits(&args, timestamps).unwrap();
+
+        let side = repo.find_reference("refs/heads/side").unwrap();
+        assert_eq!(side.target(), Some(middle.id()));
+    }
+
+    #[test]
+    fn test_rewrite_all_commits_preserve_committer_identity() {
+        let (_temp_dir, repo_path) = create_test_repo_with_two_authors();
+        // A contributor's patch applied by a maintainer
+        let repo = Repository::open(&repo_path).unwrap();
+        let head = repo.head().unwrap().peel_to_commit().unwrap();
+        let when = git2::Time::new(1234600000, 0);
+        repo.commit(
+            Some("HEAD"),
+            &git2::Signature::new("Contributor", "contrib@example.com", &when).unwrap(),
+            &git2::Signature::new("Maintainer", "maint@example.com", &when).unwrap(),
+            "Applied patch",
+            &head.tree().unwrap(),
+            &[&head],
+        )
+        .unwrap();
+
+        let mut args = Args {
+            preserve_committer_identity: true,
+            ..full_rewrite_args(&repo_path)
+        };
+        let timestamps = generate_timestamps(&mut args).unwrap();
+        rewrite_all_commits(&args, timestamps).unwrap();
+
+        let head = repo.head().unwrap().peel_to_commit().unwrap();
+        assert_eq!(head.author().name(), Some("New User"));
+        assert_eq!(head.committer().name(), Some("Maintainer"));
+        assert_eq!(head.committer().email(), Some("maint@example.com"));
+        assert_eq!(
+            head.committer().when().seconds(),
+            head.author().when().seconds()
+        );
+        // Commits their authors committed themselves keep those committers too
+        let parent = head.parent(0).unwrap();
+        assert_eq!(parent.author().name(), Some("New User"));
+        assert_eq!(parent.committer().name(), Some("Bob"));
+    }
 }
@@ -0,0 +1,492 @@
+use crate::args::Args;
+use crate::rewrite::common::{
+    finish_rewrite, prepare_rewrite, print_new_tip, remap_parents, rewrite_commit_with_tree,
+};
+use crate::utils::commit_history::print_commit_history;
+use crate::utils::git_ref::RewriteTarget;
+use crate::utils::prompt::confirm;
+use crate::utils::types::{Outcome, Result};
+use colored::Colorize;
+use git2::{build::CheckoutBuilder, FileMode, ObjectType, Oid, Repository, Sort, Tree};
+use std::collections::HashMap;
+
+// Same heuristic as git: a NUL byte in the first 8000 bytes marks a blob as binary
+const BINARY_CHECK_LEN: usize = 8000;
+
+/// A literal text replacement applied to file contents.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Replacement {
+    pub pattern: String,
+    pub replacement: String,
+}
+
+/// Parses `pattern=replacement`, splitting at the first `=`.
+pub fn parse_replacement(spec: &str) -> Result<Replacement> {
+    let (pattern, replacement) = spec.split_once('=').ok_or_else(|| {
+        format!("Invalid --replace-in-files '{spec}', expected pattern=replacement")
+    })?;
+    if pattern.is_empty() {
+        return Err(format!("Invalid --replace-in-files '{spec}', the pattern is empty").into());
+    }
+    Ok(Replacement {
+        pattern: pattern.to_string(),
+        replacement: replacement.to_string(),
+    })
+}
+
+/// Rewrites file contents across the history of the rewrite target. Every
+/// text blob containing one of the patterns is replaced, matching bytes so a
+/// file that is not UTF-8 (Latin-1, say) is not passed over; binary files,
+/// symlinks and submodules are never touched. Blobs and trees are cached by
+/// oid, so a file shared by many commits is only rewritten once, and a commit
+/// whose tree and parents are unchanged keeps its original oid.
+pub struct ContentRewriter<'r> {
+    repo: &'r Repository,
+    replacements: Vec<Replacement>,
+    blobs: HashMap<Oid, Oid>,
+    trees: HashMap<Oid, Oid>,
+}
+
+impl<'r> ContentRewriter<'r> {
+    pub fn new(repo: &'r Repository, replacements: Vec<Replacement>) -> Self {
+        Self {
+            repo,
+            replacements,
+            blobs: HashMap::new(),
+            trees: HashMap::new(),
+        }
+    }
+
+    pub fn rewrite_tree(&mut self, tree: &Tree) -> Result<Oid> {
+        if let Some(&new_id) = self.trees.get(&tree.id()) {
+            return Ok(new_id);
+        }
+
+        let mut builder = self.repo.treebuilder(Some(tree))?;
+        let mut changed = false;
+
+        for entry in tree.iter() {
+            let name = entry.name_bytes();
+            let new_id = match (entry.kind(), entry.filemode()) {
+                (Some(ObjectType::Tree), _) => {
+                    let subtree = self.repo.find_tree(entry.id())?;
+                    self.rewrite_tree(&subtree)?
+                }
+                (Some(ObjectType::Blob), mode)
+                    if mode == i32::from(FileMode::Blob)
+                        || mode == i32::from(FileMode::BlobExecutable) =>
+                {
+                    self.rewrite_blob(entry.id())?
+                }
+                // Symlinks and submodule commits are left alone
+                _ => continue,
+            };
+
+            if new_id != entry.id() {
+                builder.insert(name.to_vec(), new_id, entry.filemode())?;
+                changed = true;
+            }
+        }
+
+        let new_id = if changed { builder.write()? } else { tree.id() };
+        self.trees.insert(tree.id(), new_id);
+        Ok(new_id)
+    }
+
+    fn rewrite_blob(&mut self, id: Oid) -> Result<Oid> {
+        if let Some(&new_id) = self.blobs.get(&id) {
+            return Ok(new_id);
+        }
+
+        let blob = self.repo.find_blob(id)?;
+        let content = blob.content();
+        let is_binary = content[..content.len().min(BINARY_CHECK_LEN)].contains(&0);
+
+        let mut new_content = None;
+        if !is_binary {
+            for r in &self.replacements {
+                let current = new_content.as_deref().unwrap_or(content);
+                if let Some(replaced) = replace_bytes(current, &r.pattern, &r.replacement) {
+                    new_content = Some(replaced);
+                }
+            }
+        }
+        let new_id = match new_content {
+            Some(new_content) => self.repo.blob(&new_content)?,
+            None => id,
+        };
+
+        self.blobs.insert(id, new_id);
+        Ok(new_id)
+    }
+
+    /// Number of distinct blobs whose content was replaced so far.
+    pub fn blobs_changed(&self) -> usize {
+        self.blobs.iter().filter(|(old, new)| old != new).count()
+    }
+}
+
+// Replaces every occurrence of `pattern` in `content`, or None when it does
+// not occur
+fn replace_bytes(content: &[u8], pattern: &str, replacement: &str) -> Option<Vec<u8>> {
+    let pattern = pattern.as_bytes();
+    let mut result = Vec::with_capacity(content.len());
+    let mut rest = content;
+    let mut found = false;
+    while let Some(at) = rest.windows(pattern.len()).position(|w| w == pattern) {
+        result.extend_from_slice(&rest[..at]);
+        result.extend_from_slice(replacement.as_bytes());
+        rest = &rest[at + pattern.len()..];
+        found = true;
+    }
+    if !found {
+        return None;
+    }
+    result.extend_from_slice(rest);
+    Some(result)
+}
+
+// The paths whose contents differ between two trees
+fn changed_paths(repo: &Repository, old_tree: Oid, new_tree: Oid) -> Result<Vec<String>> {
+    let old_tree = repo.find_tree(old_tree)?;
+    let new_tree = repo.find_tree(new_tree)?;
+    let diff = repo.diff_tree_to_tree(Some(&old_tree), Some(&new_tree), None)?;
+    Ok(diff
+        .deltas()
+        .filter_map(|delta| delta.new_file().path())
+        .map(|path| path.display().to_string())
+        .collect())
+}
+
+fn print_content_changes(repo: &Repository, changes: &[(Oid, Vec<String>)]) -> Result<()> {
+    println!("\n{}", "Commits whose files change:".bold().yellow());
+    for (oid, paths) in changes {
+        let commit = repo.find_commit(*oid)?;
+        println!(
+            "  {} {}",
+            oid.to_string()[..8].yellow(),
+            commit.summary().unwrap_or_default()
+        );
+        for path in paths {
+            println!("      {}", path.cyan());
+        }
+    }
+    Ok(())
+}
+
+pub fn rewrite_content(args: &Args) -> Result<Outcome> {
+    // A simulation never writes, so it works on any worktree
+    if !args.simulate {
+        prepare_rewrite(args)?;
+    }
+
+    let replacements = args
+        .replace_in_files
+        .iter()
+        .map(|spec| parse_replacement(spec))
+        .collect::<Result<Vec<_>>>()?;
+
+    let repo = Repository::open(args.repo_path.as_ref().unwrap())?;
+    let target = RewriteTarget::resolve(&repo, args)?;
+
+    let mut revwalk = repo.revwalk()?;
+    revwalk.push(target.tip(&repo)?)?;
+    revwalk.set_sorting(Sort::TOPOLOGICAL | Sort::TIME)?;
+    let mut orig_oids: Vec<_> = revwalk.filter_map(|id| id.ok()).collect();
+    orig_oids.reverse();
+
+    let mut rewriter = ContentRewriter::new(&repo, replacements);
+    let mut new_map: HashMap<Oid, Oid> = HashMap::new();
+    // Commits whose tree changes, with the paths that change in them
+    let mut changes = Vec::new();
+    let mut last_new_oid = None;
+
+    for &oid in &orig_oids {
+        let orig = repo.find_commit(oid)?;
+        let new_parents = remap_parents(&repo, &orig, &new_map)?;
+        let new_tree = rewriter.rewrite_tree(&orig.tree()?)?;
+        if new_tree != orig.tree_id() {
+            changes.push((oid, changed_paths(&repo, orig.tree_id(), new_tree)?));
+        }
+
+        // Metadata is carried over untouched; only the tree (and parents) change
+        let new_oid = rewrite_commit_with_tree(
+            &repo,
+            &orig,
+            new_tree,
+            &new_parents,
+            &orig.author(),
+            &orig.committer(),
+            None,
+        )?;
+        new_map.insert(oid, new_oid);
+        last_new_oid = Some(new_oid);
+    }
+
+    let Some(new_head) = last_new_oid else {
+        println!("{}", "No commits found!".red());
+        return Ok(Outcome::NothingToDo);
+    };
+
+    if changes.is_empty() {
+        if !args.quiet {
+            println!(
+                "{}",
+                "No file contains the given pattern(s), nothing to rewrite.".yellow()
+            );
+        }
+        return Ok(Outcome::NothingToDo);
+    }
+
+    // The commits written above are not referenced until the target moves
+    if args.simulate {
+        print_content_changes(&repo, &changes)?;
+        return Ok(Outcome::Done);
+    }
+    if !args.quiet {
+        print_content_changes(&repo, &changes)?;
+    }
+    if !confirm("Rewrite these commits?", args.yes)? {
+        println!("{}", "Operation cancelled.".yellow());
+        return Ok(Outcome::Cancelled);
+    }
+
+    finish_rewrite(
+        &repo,
+        args,
+        &target,
+        new_head,
+        &orig_oids,
+        &new_map,
+        "replace in files",
+    )?;
+
+    // The working tree still holds the old contents; bring it in line with the
+    // new tip unless uncommitted changes were explicitly allowed
+    if target.is_checked_out(&repo)? && !repo.is_bare() {
+        if args.allow_dirty {
+            println!(
+                "{}",
+                "Working tree left as is because of --allow-dirty; run `git reset --hard` to update it."
+                    .yellow()
+            );
+        } else {
+            repo.checkout_head(Some(CheckoutBuilder::new().force()))?;
+        }
+    }
+
+    print_new_tip(args, &target, new_head);
+    if !args.quiet {
+        println!(
+            "{}",
+            format!(
+                "✓ Replaced content in {} file version(s) across {} commit(s)",
+                rewriter.blobs_changed(),
+                changes.len()
+            )
+            .green()
+            .bold()
+        );
+    }
+
+    if args.show_history {
+        print_commit_history(args)?;
+    }
+
+    Ok(Outcome::Done)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use std::path::Path;
+    use tempfile::TempDir;
+
+    // Builds a history where only the middle commit contains the secret:
+    //   1. add clean.txt and logo.bin (binary, contains the secret bytes)
+    //   2. add config/secret.txt containing the secret
+    //   3. remove config/secret.txt again
+    fn create_test_repo_with_secret() -> (TempDir, String) {
+        let temp_dir = TempDir::new().unwrap();
+        let repo_path = temp_dir.path().to_str().unwrap().to_string();
+        let repo = Repository::init(&repo_path).unwrap();
+        let sig =
+            git2::Signature::new("Test User", "test@example.com", &git2::Time::new(0, 0)).unwrap();
+
+        let commit = |message: &str| {
+            let mut index = repo.index().unwrap();
+            index
+                .add_all(["*"].iter(), git2::IndexAddOption::DEFAULT, None)
+                .unwrap();
+            index.update_all(["*"].iter(), None).unwrap();
+            index.write().unwrap();
+            let tree = repo.find_tree(index.write_tree().unwrap()).unwrap();
+            let parents: Vec<git2::Commit> = match repo.head() {
+                Ok(head) => vec![head.peel_to_commit().unwrap()],
+                Err(_) => vec![],
+            };
+            repo.commit(
+                Some("HEAD"),
+                &sig,
+                &sig,
+                message,
+                &tree,
+                &parents.iter().collect::<Vec<_>>(),
+            )
+            .unwrap();
+        };
+
+        fs::write(temp_dir.path().join("clean.txt"), "nothing to see").unwrap();
+        fs::write(temp_dir.path().join("logo.bin"), b"\0\x01hunter2\x02").unwrap();
+        commit("Add clean file");
+
+        fs::create_dir(temp_dir.path().join("config")).unwrap();
+        fs::write(
+            temp_dir.path().join("config/secret.txt"),
+            "password=hunter2\nuser=admin\n",
+        )
+        .unwrap();
+        commit("Add secret");
+
+        fs::remove_file(temp_dir.path().join("config/secret.txt")).unwrap();
+        commit("Remove secret");
+
+        (temp_dir, repo_path)
+    }
+
+    fn history(repo: &Repository) -> Vec<Oid> {
+        let mut revwalk = repo.revwalk().unwrap();
+        revwalk.push_head().unwrap();
+        revwalk
+            .set_sorting(Sort::TOPOLOGICAL | Sort::REVERSE)
+            .unwrap();
+        revwalk.map(|oid| oid.unwrap()).collect()
+    }
+
+    #[test]
+    fn test_parse_replacement() {
+        assert_eq!(
+            parse_replacement("hunter2=***").unwrap(),
+            Replacement {
+                pattern: "hunter2".to_string(),
+                replacement: "***".to_string(),
+            }
+        );
+        assert_eq!(parse_replacement("a=b=c").unwrap().replacement, "b=c");
+        assert_eq!(parse_replacement("secret=").unwrap().replacement, "");
+        assert!(parse_replacement("no-separator").is_err());
+        assert!(parse_replacement("=value").is_err());
+    }
+
+    #[test]
+    fn test_rewrite_content_changes_only_matching_trees() {
+        let (temp_dir, repo_path) = create_test_repo_with_secret();
+        let repo = Repository::open(&repo_path).unwrap();
+        let before = history(&repo);
+        let trees_before: Vec<Oid> = before
+            .iter()
+            .map(|oid| repo.find_commit(*oid).unwrap().tree_id())
+            .collect();
+
+        let args = Args {
+            repo_path: Some(repo_path.clone()),
+            replace_in_files: vec!["hunter2=REDACTED".to_string()],
+            yes: true,
+            ..Default::default()
+        };
+        rewrite_content(&args).unwrap();
+
+        let after = history(&repo);
+        assert_eq!(after.len(), 3);
+
+        // The first commit has no text match (the binary file is skipped) and keeps its oid
+        assert_eq!(after[0], before[0]);
+
+        // The second commit's tree changes, but only along the path to the secret
+        let old_tree = repo.find_tree(trees_before[1]).unwrap();
+        let new_tree = repo.find_commit(after[1]).unwrap().tree().unwrap();
+        assert_ne!(new_tree.id(), old_tree.id());
+        for name in ["clean.txt", "logo.bin"] {
+            assert_eq!(
+                new_tree.get_name(name).unwrap().id(),
+                old_tree.get_name(name).unwrap().id()
+            );
+        }
+        let secret = new_tree
+            .get_path(Path::new("config/secret.txt"))
+            .unwrap()
+            .to_object(&repo)
+            .unwrap();
+        assert_eq!(
+            secret.as_blob().unwrap().content(),
+            b"password=REDACTED\nuser=admin\n"
+        );
+
+        // The third commit no longer has the file: same tree, new oid from the new parent
+        let third = repo.find_commit(after[2]).unwrap();
+        assert_eq!(third.tree_id(), trees_before[2]);
+        assert_ne!(after[2], before[2]);
+        assert_eq!(third.parent_id(0).unwrap(), after[1]);
+        assert_eq!(third.message(), Some("Remove secret"));
+
+        // The checked-out working tree follows the new tip
+        assert_eq!(
+            fs::read(temp_dir.path().join("logo.bin")).unwrap(),
+            b"\0\x01hunter2\x02"
+        );
+        assert!(repo.statuses(None).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_rewrite_content_without_match_leaves_history_untouched() {
+        let (_temp_dir, repo_path) = create_test_repo_with_secret();
+        let repo = Repository::open(&repo_path).unwrap();
+        let before = history(&repo);
+
+        let args = Args {
+            repo_path: Some(repo_path.clone()),
+            replace_in_files: vec!["not-present=x".to_string()],
+            ..Default::default()
+        };
+        rewrite_content(&args).unwrap();
+
+        assert_eq!(history(&repo), before);
+    }
+
+    #[test]
+    fn test_rewrite_content_needs_confirmation_and_simulates() {
+        let (_temp_dir, repo_path) = create_test_repo_with_secret();
+        let repo = Repository::open(&repo_path).unwrap();
+        let before = history(&repo);
+        let args = |simulate: bool| Args {
+            repo_path: Some(repo_path.clone()),
+            replace_in_files: vec!["hunter2=REDACTED".to_string()],
+            simulate,
+            ..Default::default()
+        };
+
+        // Tests have no terminal to confirm on
+        let err = rewrite_content(&args(false)).unwrap_err();
+        assert!(err.to_string().contains("pass --yes"), "{err}");
+        assert_eq!(history(&repo), before);
+
+        assert_eq!(rewrite_content(&args(true)).unwrap(), Outcome::Done);
+        assert_eq!(history(&repo), before);
+    }
+
+    #[test]
+    fn test_replace_bytes_covers_non_utf8_text() {
+        // "café hunter2" in Latin-1
+        let latin1 = b"caf\xe9 hunter2\n";
+        assert_eq!(
+            replace_bytes(latin1, "hunter2", "***").unwrap(),
+            b"caf\xe9 ***\n"
+        );
+        assert_eq!(
+            replace_bytes(b"a-a-a", "a", "bb").unwrap(),
+            b"bb-bb-bb".to_vec()
+        );
+        assert_eq!(replace_bytes(b"clean", "hunter2", "***"), None);
+    }
+}
@@ -0,0 +1,242 @@
+use crate::args::Args;
+use crate::rewrite::common::prepare_rewrite;
+use crate::rewrite::rewrite_specific::resume_edit_options;
+use crate::utils::commit_history::print_commit_history;
+use crate::utils::git_ref::RewriteTarget;
+use crate::utils::prompt::ask_yes_no;
+use crate::utils::rewrite_state::RewriteState;
+use crate::utils::types::{EditOptions, Outcome, Result};
+use colored::Colorize;
+use git2::{Oid, Repository};
+use std::collections::HashMap;
+use std::io::IsTerminal;
+
+/// Resumes the rewrite recorded in `.git/git-editor-state.json` (`--continue`).
+/// The ref and committer handling of the interrupted run replace the ones on
+/// the command line, since they decide which commits it wrote; the commits it
+/// already wrote are reused and the rest are written as before. It asks for no
+/// confirmation: the edits were confirmed when the interrupted run started,
+/// and asking `--continue` is itself the decision to finish them.
+pub fn continue_rewrite(args: &mut Args) -> Result<Outcome> {
+    let repo = Repository::open(args.repo_path.as_ref().unwrap())?;
+    let Some(state) = RewriteState::load(&repo)? else {
+        return Err("No interrupted rewrite to continue".into());
+    };
+    args.rewrite_ref = state.rewrite_ref.clone();
+    args.preserve_committer_identity = state.preserve_committer_identity;
+    prepare_rewrite(args)?;
+
+    let target = RewriteTarget::resolve(&repo, args)?;
+    let tip = target.tip(&repo)?;
+    let original_tip = state.original_tip()?;
+    let written = state.written()?;
+    if tip != original_tip {
+        // Interrupted after the ref moved, while cleaning up
+        if written.get(&original_tip) == Some(&tip) {
+            RewriteState::discard(&repo)?;
+            if !args.quiet {
+                println!(
+                    "{}",
+                    format!("The {} had already finished.", state.operation).yellow()
+                );
+            }
+            return Ok(Outcome::NothingToDo);
+        }
+        return Err(format!(
+            "{} has moved since the {} was interrupted; delete {} to discard it",
+            target.describe(),
+            state.operation,
+            RewriteState::path(&repo).display()
+        )
+        .into());
+    }
+
+    if !args.quiet {
+        println!("{} {}", "Resuming".cyan(), state.describe().cyan());
+    }
+    let edits = state.edits()?;
+    let edits: HashMap<Oid, &EditOptions> = edits.iter().map(|(oid, o)| (*oid, o)).collect();
+    resume_edit_options(&repo, args, &edits, &state.operation, &written)?;
+
+    if !args.quiet {
+        println!(
+            "\n{}",
+            format!("✓ Finished the interrupted {}", state.operation)
+                .green()
+                .bold()
+        );
+    }
+
+    if args.show_history {
+        print_commit_history(args)?;
+    }
+
+    Ok(Outcome::Done)
+}
+
+/// Checks for a rewrite left unfinished before a pick or `--apply-plan`
+/// rewrite starts, and asks whether to resume it (its outcome is returned) or
+/// discard it. Without a terminal to ask on, or with `--yes`, the state is
+/// left alone with a warning.
+pub fn offer_to_resume(args: &mut Args) -> Result<Option<Outcome>> {
+    let repo = Repository::open(args.repo_path.as_ref().unwrap())?;
+    let Some(state) = RewriteState::load(&repo)? else {
+        return Ok(None);
+    };
+
+    if args.yes || !std::io::stdin().is_terminal() {
+        warn_interrupted(&state);
+        return Ok(None);
+    }
+
+    println!(
+        "{} {}",
+        "Found an interrupted".yellow(),
+        state.describe().yellow()
+    );
+    if ask_yes_no("Resume it now? Answering no discards it")? {
+        return continue_rewrite(args).map(Some);
+    }
+    RewriteState::discard(&repo)?;
+    println!("{}", "Discarded the interrupted rewrite.".yellow());
+    Ok(None)
+}
+
+/// Before the other rewrites, which record no progress of their own and so
+/// cannot resume one, a leftover state is only pointed out: once the branch
+/// moves it can no longer be continued.
+pub fn warn_if_interrupted(args: &Args) -> Result<()> {
+    let repo = Repository::open(args.repo_path.as_ref().unwrap())?;
+    if let Some(state) = RewriteState::load(&repo)? {
+        warn_interrupted(&state);
+    }
+    Ok(())
+}
+
+fn warn_interrupted(state: &RewriteState) {
+    eprintln!(
+        "{} An interrupted {} was found; run with --continue to resume it",
+        "Warning:".yellow().bold(),
+        state.describe()
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rewrite::rewrite_specific::apply_edit_options;
+    use git2::{Signature, Sort, Time};
+    use tempfile::TempDir;
+
+    fn create_test_repo(count: i64) -> (TempDir, String) {
+        let temp_dir = TempDir::new().unwrap();
+        let repo_path = temp_dir.path().to_str().unwrap().to_string();
+        let repo = Repository::init(&repo_path).unwrap();
+        let tree = repo
+            .find_tree(repo.index().unwrap().write_tree().unwrap())
+            .unwrap();
+
+        for i in 1..=count {
+            let sig = Signature::new(
+                "Test User",
+                "test@example.com",
+                &Time::new(1_700_000_000 + i * 3600, 0),
+            )
+            .unwrap();
+            let parents: Vec<git2::Commit> = match repo.head() {
+                Ok(head) => vec![head.peel_to_commit().unwrap()],
+                Err(_) => vec![],
+            };
+            repo.commit(
+                Some("HEAD"),
+                &sig,
+                &sig,
+                &format!("Commit {i}"),
+                &tree,
+                &parents.iter().collect::<Vec<_>>(),
+            )
+            .unwrap();
+        }
+        drop(tree);
+
+        (temp_dir, repo_path)
+    }
+
+    // Commits from the root up
+    fn history(repo: &Repository, tip: Oid) -> Vec<Oid> {
+        let mut revwalk = repo.revwalk().unwrap();
+        revwalk.push(tip).unwrap();
+        revwalk
+            .set_sorting(Sort::TOPOLOGICAL | Sort::REVERSE)
+            .unwrap();
+        revwalk.map(|oid| oid.unwrap()).collect()
+    }
+
+    #[test]
+    fn test_continue_interrupted_rewrite() {
+        let (_temp_dir, repo_path) = create_test_repo(4);
+        let repo = Repository::open(&repo_path).unwrap();
+        let original_tip = repo.head().unwrap().peel_to_commit().unwrap().id();
+        let original = history(&repo, original_tip);
+        let mut args = Args {
+            repo_path: Some(repo_path.clone()),
+            ..Default::default()
+        };
+
+        // The uninterrupted rewrite, for comparison
+        let options = EditOptions {
+            author_name: Some("New Name".to_string()),
+            ..Default::default()
+        };
+        let edits = HashMap::from([(original[1], &options)]);
+        apply_edit_options(&repo, &args, &edits, "pick edit").unwrap();
+        let expected_tip = repo.head().unwrap().peel_to_commit().unwrap().id();
+        let expected = history(&repo, expected_tip);
+        assert!(RewriteState::load(&repo).unwrap().is_none());
+
+        // Interrupted after writing the edited commit: the branch is still at
+        // the old tip and the state file records the one commit written
+        let branch = repo.head().unwrap().name().unwrap().to_string();
+        repo.reference(&branch, original_tip, true, "reset")
+            .unwrap();
+        let mut state = RewriteState::new("pick edit", &args, original_tip, 4, &edits);
+        state.record(original[1], expected[1]);
+        state.save(&repo).unwrap();
+
+        assert_eq!(continue_rewrite(&mut args).unwrap(), Outcome::Done);
+        assert_eq!(
+            repo.head().unwrap().peel_to_commit().unwrap().id(),
+            expected_tip
+        );
+        assert_eq!(
+            repo.find_commit(expected[1]).unwrap().author().name(),
+            Some("New Name")
+        );
+        assert!(RewriteState::load(&repo).unwrap().is_none());
+
+        // Nothing left to continue
+        assert!(continue_rewrite(&mut args)
+            .unwrap_err()
+            .to_string()
+            .contains("No interrupted rewrite"));
+
+        // Interrupted after the branch moved: only the state file is left
+        state.record(original_tip, expected_tip);
+        state.save(&repo).unwrap();
+        assert_eq!(continue_rewrite(&mut args).unwrap(), Outcome::NothingToDo);
+        assert!(RewriteState::load(&repo).unwrap().is_none());
+
+        // A branch that moved elsewhere is not rewritten from a stale state
+        let mut stale = RewriteState::new("pick edit", &args, original_tip, 4, &edits);
+        stale.record(original[1], expected[1]);
+        stale.save(&repo).unwrap();
+        assert!(continue_rewrite(&mut args)
+            .unwrap_err()
+            .to_string()
+            .contains("has moved since the pick edit was interrupted"));
+        assert_eq!(
+            repo.head().unwrap().peel_to_commit().unwrap().id(),
+            expected_tip
+        );
+    }
+}
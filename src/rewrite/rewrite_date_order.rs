@@ -0,0 +1,247 @@
+use crate::args::Args;
+use crate::rewrite::common::{
+    finish_rewrite, new_signature, prepare_rewrite, print_new_tip, remap_parents, rewrite_commit,
+};
+use crate::utils::commit_history::print_commit_history;
+use crate::utils::git_ref::RewriteTarget;
+use crate::utils::prompt::confirm;
+use crate::utils::types::{Outcome, Result};
+use colored::Colorize;
+use git2::{Oid, Repository, Sort, Time};
+use std::collections::HashMap;
+
+// How far past its latest parent an out-of-order commit is moved
+pub const DATE_ORDER_NUDGE_SECS: i64 = 60;
+
+/// Moves the author date of every commit authored before one of its parents
+/// to just after that parent. Commits are visited oldest first, so a nudge
+/// carries over to descendants that would otherwise fall behind it. Names,
+/// emails, messages, trees and timezone offsets are kept; the committer date
+/// only moves when it would otherwise precede the new author date.
+pub fn fix_author_date_order(args: &Args) -> Result<Outcome> {
+    prepare_rewrite(args)?;
+
+    let repo = Repository::open(args.repo_path.as_ref().unwrap())?;
+    let target = RewriteTarget::resolve(&repo, args)?;
+
+    let mut revwalk = repo.revwalk()?;
+    revwalk.push(target.tip(&repo)?)?;
+    revwalk.set_sorting(Sort::TOPOLOGICAL | Sort::TIME)?;
+    let mut orig_oids: Vec<_> = revwalk.filter_map(|id| id.ok()).collect();
+    orig_oids.reverse();
+
+    // Author dates as rewritten, keyed by the original oid
+    let mut author_secs: HashMap<Oid, i64> = HashMap::new();
+    let mut new_map: HashMap<Oid, Oid> = HashMap::new();
+    let mut commits_fixed = 0;
+    let mut last_new_oid = None;
+
+    for &oid in &orig_oids {
+        let orig = repo.find_commit(oid)?;
+        let new_parents = remap_parents(&repo, &orig, &new_map)?;
+
+        let author = orig.author();
+        let committer = orig.committer();
+        let own_secs = author.when().seconds();
+        let latest_parent = orig
+            .parent_ids()
+            .filter_map(|pid| author_secs.get(&pid).copied())
+            .max();
+
+        let new_oid = match latest_parent {
+            Some(parent_secs) if own_secs < parent_secs => {
+                let secs = parent_secs + DATE_ORDER_NUDGE_SECS;
+                let author_sig = new_signature(
+                    author.name().unwrap_or("Unknown"),
+                    author.email().unwrap_or("unknown@email.com"),
+                    &Time::new(secs, author.when().offset_minutes()),
+                )?;
+                let committer_sig = if committer.when().seconds() < secs {
+                    new_signature(
+                        committer.name().unwrap_or("Unknown"),
+                        committer.email().unwrap_or("unknown@email.com"),
+                        &Time::new(secs, committer.when().offset_minutes()),
+                    )?
+                } else {
+                    committer.to_owned()
+                };
+
+                author_secs.insert(oid, secs);
+                commits_fixed += 1;
+                rewrite_commit(
+                    &repo,
+                    &orig,
+                    &new_parents,
+                    &author_sig,
+                    &committer_sig,
+                    None,
+                )?
+            }
+            _ => {
+                author_secs.insert(oid, own_secs);
+                rewrite_commit(&repo, &orig, &new_parents, &author, &committer, None)?
+            }
+        };
+
+        new_map.insert(oid, new_oid);
+        last_new_oid = Some(new_oid);
+    }
+
+    let Some(new_head) = last_new_oid.filter(|_| commits_fixed > 0) else {
+        if !args.quiet {
+            println!("{}", "No commit is authored before its parents.".yellow());
+        }
+        return Ok(Outcome::NothingToDo);
+    };
+
+    if !confirm("Move these author dates?", args.yes)? {
+        println!("{}", "Operation cancelled.".yellow());
+        return Ok(Outcome::Cancelled);
+    }
+
+    finish_rewrite(
+        &repo,
+        args,
+        &target,
+        new_head,
+        &orig_oids,
+        &new_map,
+        "author date order fix",
+    )?;
+
+    print_new_tip(args, &target, new_head);
+    if !args.quiet {
+        println!(
+            "{}",
+            format!("✓ Moved the author date of {commits_fixed} commit(s) forward")
+                .green()
+                .bold()
+        );
+    }
+
+    if args.show_history {
+        print_commit_history(args)?;
+    }
+
+    Ok(Outcome::Done)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::commit_history::find_author_date_violations;
+    use git2::Signature;
+    use tempfile::TempDir;
+
+    // Four commits on one line; "Backdated" is authored a day before its
+    // parent, and "After backdated" only an hour after it, so fixing the
+    // former pushes the latter out of order as well
+    fn create_out_of_order_repo() -> (TempDir, String) {
+        let temp_dir = TempDir::new().unwrap();
+        let repo_path = temp_dir.path().to_str().unwrap().to_string();
+        let repo = Repository::init(&repo_path).unwrap();
+        let tree = repo
+            .find_tree(repo.index().unwrap().write_tree().unwrap())
+            .unwrap();
+
+        let commits = [
+            ("Root", 1_700_000_000),
+            ("Second", 1_700_100_000),
+            ("Backdated", 1_700_013_600),
+            ("After backdated", 1_700_017_200),
+        ];
+        for (message, time) in commits {
+            let author =
+                Signature::new("Author", "author@example.com", &Time::new(time, 120)).unwrap();
+            let committer =
+                Signature::new("Committer", "committer@example.com", &Time::new(time, 0)).unwrap();
+            let parents: Vec<git2::Commit> = match repo.head() {
+                Ok(head) => vec![head.peel_to_commit().unwrap()],
+                Err(_) => vec![],
+            };
+            repo.commit(
+                Some("HEAD"),
+                &author,
+                &committer,
+                message,
+                &tree,
+                &parents.iter().collect::<Vec<_>>(),
+            )
+            .unwrap();
+        }
+        drop(tree);
+
+        (temp_dir, repo_path)
+    }
+
+    fn history(repo: &Repository) -> Vec<git2::Commit<'_>> {
+        let mut revwalk = repo.revwalk().unwrap();
+        revwalk.push_head().unwrap();
+        revwalk.set_sorting(Sort::TOPOLOGICAL).unwrap();
+        revwalk
+            .map(|oid| repo.find_commit(oid.unwrap()).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn test_find_author_date_violations() {
+        let (_temp_dir, repo_path) = create_out_of_order_repo();
+        let args = Args {
+            repo_path: Some(repo_path.clone()),
+            ..Default::default()
+        };
+
+        let repo = Repository::open(&repo_path).unwrap();
+        let commits = history(&repo);
+        let violations = find_author_date_violations(&args).unwrap();
+        assert_eq!(violations.len(), 1);
+        assert_eq!(violations[0].oid, commits[1].id());
+        assert_eq!(violations[0].parent_oid, commits[2].id());
+    }
+
+    #[test]
+    fn test_fix_author_date_order() {
+        let (_temp_dir, repo_path) = create_out_of_order_repo();
+        let args = Args {
+            repo_path: Some(repo_path.clone()),
+            author_date_order: true,
+            fix: true,
+            yes: true,
+            ..Default::default()
+        };
+
+        assert_eq!(fix_author_date_order(&args).unwrap(), Outcome::Done);
+        assert!(find_author_date_violations(&args).unwrap().is_empty());
+
+        let repo = Repository::open(&repo_path).unwrap();
+        let commits = history(&repo);
+        let messages: Vec<_> = commits.iter().map(|c| c.message().unwrap()).collect();
+        assert_eq!(messages, ["After backdated", "Backdated", "Second", "Root"]);
+
+        // Both later commits now sit one nudge apart after "Second"
+        let second = commits[2].author().when().seconds();
+        assert_eq!(
+            commits[1].author().when().seconds(),
+            second + DATE_ORDER_NUDGE_SECS
+        );
+        assert_eq!(
+            commits[0].author().when().seconds(),
+            second + 2 * DATE_ORDER_NUDGE_SECS
+        );
+
+        // Identities and offsets are untouched; committer dates follow along
+        for commit in &commits[..2] {
+            assert_eq!(commit.author().name(), Some("Author"));
+            assert_eq!(commit.author().when().offset_minutes(), 120);
+            assert_eq!(commit.committer().name(), Some("Committer"));
+            assert_eq!(
+                commit.committer().when().seconds(),
+                commit.author().when().seconds()
+            );
+        }
+        assert_eq!(commits[3].author().when().seconds(), 1_700_000_000);
+
+        // A second run has nothing left to repair
+        assert_eq!(fix_author_date_order(&args).unwrap(), Outcome::NothingToDo);
+    }
+}
@@ -0,0 +1,654 @@
+use crate::rewrite::common::prepare_rewrite;
+use crate::rewrite::rewrite_specific::apply_edit_options;
+use crate::utils::datetime::format_hint;
+use crate::utils::message_transform::{same_message, sign_off_edit};
+use crate::utils::prompt::confirm;
+use crate::utils::simulation::{SimulationChange, SimulationResult, SimulationStats};
+use crate::utils::types::{CommitInfo, EditOptions};
+use crate::utils::types::{Outcome, Result};
+use crate::utils::validator::validate_email;
+use crate::{
+    args::Args,
+    utils::commit_history::{get_commit_history, print_commit_history},
+};
+use chrono::NaiveDateTime;
+use colored::Colorize;
+use git2::{Oid, Repository};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// One commit of a rewrite plan. Every field except `oid` is optional; fields
+/// that are left out keep the commit's current value, but an entry has to
+/// set at least one. Unknown fields are rejected, so a misspelt one is not
+/// silently dropped.
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct PlanEntry {
+    pub oid: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub new_author: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub new_email: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub new_timestamp: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub new_message: Option<String>,
+}
+
+// A plan is either a bare list of entries or an object with a `changes` list,
+// which is what `simulate --format json` prints
+#[derive(Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct PlanChanges {
+    changes: Vec<PlanEntry>,
+}
+
+pub fn parse_plan(content: &str) -> Result<Vec<PlanEntry>> {
+    let invalid = |e: serde_json::Error| format!("Invalid plan file: {e}");
+    let value: serde_json::Value = serde_json::from_str(content).map_err(invalid)?;
+    // Picking the shape first keeps the error about the entry at fault, where
+    // an untagged enum would only say that neither shape matched
+    let entries = if value.is_array() {
+        serde_json::from_value(value).map_err(invalid)?
+    } else {
+        serde_json::from_value::<PlanChanges>(value)
+            .map_err(invalid)?
+            .changes
+    };
+    Ok(entries)
+}
+
+/// Reads the plan at `path` and checks every entry against `commits` before
+/// anything is written. All problems are reported together, so a stale plan
+/// never leaves the history half rewritten. Timestamps are read with
+/// `date_format` (`--date-format`), or else as ISO 8601.
+pub fn load_plan(
+    path: &str,
+    commits: &[CommitInfo],
+    date_format: &str,
+) -> Result<Vec<(Oid, EditOptions)>> {
+    let content = std::fs::read_to_string(path)
+        .map_err(|e| format!("Failed to read plan file {path}: {e}"))?;
+    resolve_plan(&parse_plan(&content)?, commits, date_format)
+}
+
+fn resolve_plan(
+    entries: &[PlanEntry],
+    commits: &[CommitInfo],
+    date_format: &str,
+) -> Result<Vec<(Oid, EditOptions)>> {
+    let mut planned: Vec<(Oid, EditOptions)> = Vec::new();
+    let mut errors = Vec::new();
+    // The first entry to set each field of a commit, and the value it set
+    let mut set_by: HashMap<(Oid, &str), (String, String)> = HashMap::new();
+
+    for (i, entry) in entries.iter().enumerate() {
+        let label = format!("entry {} ({})", i + 1, entry.oid);
+        let (oid, options) = match resolve_entry(entry, commits, date_format) {
+            Ok(resolved) => resolved,
+            Err(e) => {
+                errors.push(format!("{label}: {e}"));
+                continue;
+            }
+        };
+
+        // A commit may be listed again for other fields or the same values,
+        // but never with a different value for a field it already has
+        let mut conflicting = false;
+        for (field, value) in planned_fields(&options) {
+            match set_by.get(&(oid, field)) {
+                Some((first, previous)) if *previous != value => {
+                    errors.push(format!(
+                        "{label}: {field} '{value}' conflicts with '{previous}' from {first}"
+                    ));
+                    conflicting = true;
+                }
+                Some(_) => {}
+                None => {
+                    set_by.insert((oid, field), (label.clone(), value));
+                }
+            }
+        }
+        if conflicting {
+            continue;
+        }
+
+        match planned.iter_mut().find(|(seen, _)| *seen == oid) {
+            Some((_, existing)) => {
+                existing.author_name = existing.author_name.take().or(options.author_name);
+                existing.author_email = existing.author_email.take().or(options.author_email);
+                existing.timestamp = existing.timestamp.or(options.timestamp);
+                existing.message = existing.message.take().or(options.message);
+            }
+            None => planned.push((oid, options)),
+        }
+    }
+
+    if !errors.is_empty() {
+        return Err(format!("Rewrite plan is invalid:\n  {}", errors.join("\n  ")).into());
+    }
+    if planned.is_empty() {
+        return Err("Rewrite plan contains no changes".into());
+    }
+    Ok(planned)
+}
+
+// The fields an entry sets, named as in the plan file
+fn planned_fields(options: &EditOptions) -> Vec<(&'static str, String)> {
+    [
+        ("new_author", options.author_name.clone()),
+        ("new_email", options.author_email.clone()),
+        (
+            "new_timestamp",
+            options
+                .timestamp
+                .map(|t| t.format("%Y-%m-%d %H:%M:%S").to_string()),
+        ),
+        ("new_message", options.message.clone()),
+    ]
+    .into_iter()
+    .filter_map(|(field, value)| Some((field, value?)))
+    .collect()
+}
+
+fn resolve_entry(
+    entry: &PlanEntry,
+    commits: &[CommitInfo],
+    date_format: &str,
+) -> Result<(Oid, EditOptions)> {
+    let prefix = entry.oid.trim().to_lowercase();
+    if prefix.len() < 4 || !prefix.chars().all(|c| c.is_ascii_hexdigit()) {
+        return Err("oid must be at least 4 hexadecimal characters".into());
+    }
+
+    let matches: Vec<&CommitInfo> = commits
+        .iter()
+        .filter(|c| c.oid.to_string().starts_with(&prefix))
+        .collect();
+    let commit = match matches.as_slice() {
+        [commit] => *commit,
+        [] => return Err("commit is not reachable from HEAD (stale plan?)".into()),
+        _ => return Err("oid prefix is ambiguous".into()),
+    };
+
+    if entry.new_author.is_none()
+        && entry.new_email.is_none()
+        && entry.new_timestamp.is_none()
+        && entry.new_message.is_none()
+    {
+        return Err("sets none of new_author, new_email, new_timestamp or new_message".into());
+    }
+
+    if let Some(email) = &entry.new_email {
+        validate_email(email)?;
+    }
+    if entry
+        .new_author
+        .as_deref()
+        .is_some_and(|a| a.trim().is_empty())
+    {
+        return Err("new_author must not be empty".into());
+    }
+    if entry
+        .new_message
+        .as_deref()
+        .is_some_and(|m| m.trim().is_empty())
+    {
+        return Err("new_message must not be empty".into());
+    }
+    let timestamp = entry
+        .new_timestamp
+        .as_deref()
+        .map(|value| parse_plan_timestamp(value, date_format))
+        .transpose()?;
+
+    Ok((
+        commit.oid,
+        EditOptions {
+            author_name: entry.new_author.clone(),
+            author_email: entry.new_email.clone(),
+            timestamp,
+            message: entry.new_message.clone(),
+        },
+    ))
+}
+
+// Accepts the --date-format used everywhere else in the CLI as well as ISO 8601
+fn parse_plan_timestamp(value: &str, date_format: &str) -> Result<NaiveDateTime> {
+    NaiveDateTime::parse_from_str(value, date_format)
+        .or_else(|_| NaiveDateTime::parse_from_str(value, "%Y-%m-%dT%H:%M:%S"))
+        .map_err(|_| {
+            format!(
+                "Invalid new_timestamp '{value}', expected {}",
+                format_hint(date_format)
+            )
+            .into()
+        })
+}
+
+pub fn create_plan_simulation(
+    commits: &[CommitInfo],
+    plan: &[(Oid, EditOptions)],
+) -> Result<SimulationResult> {
+    let planned: HashMap<Oid, &EditOptions> = plan.iter().map(|(oid, o)| (*oid, o)).collect();
+
+    let changes: Vec<SimulationChange> = commits
+        .iter()
+        .map(|commit| {
+            let options = planned.get(&commit.oid);
+            SimulationChange {
+                commit_oid: commit.oid,
+                short_hash: commit.short_hash.clone(),
+                original_author: commit.author_name.clone(),
+                original_email: commit.author_email.clone(),
+                original_timestamp: commit.timestamp,
+                original_message: commit.message.clone(),
+                new_author: options.and_then(|o| o.author_name.clone()),
+                new_email: options.and_then(|o| o.author_email.clone()),
+                new_timestamp: options.and_then(|o| o.timestamp),
+                new_message: options.and_then(|o| o.message.clone()),
+            }
+        })
+        .collect();
+
+    let mut stats = SimulationStats::new(commits);
+    stats.update_from_changes(&changes);
+
+    Ok(SimulationResult {
+        changes,
+        stats,
+        operation_mode: "Rewrite Plan".to_string(),
+    })
+}
+
+/// The simulated changes as a plan `--apply-plan` reads back, for
+/// `simulate --format json`. Only fields that differ from the commit are
+/// listed, with timestamps in ISO 8601 so any `--date-format` accepts them,
+/// and commits left as they are have no entry.
+pub fn simulation_plan(result: &SimulationResult) -> Result<String> {
+    let changes = result
+        .changes
+        .iter()
+        .map(|change| PlanEntry {
+            oid: change.commit_oid.to_string(),
+            new_author: change
+                .new_author
+                .clone()
+                .filter(|author| *author != change.original_author),
+            new_email: change
+                .new_email
+                .clone()
+                .filter(|email| *email != change.original_email),
+            new_timestamp: change
+                .new_timestamp
+                .filter(|timestamp| *timestamp != change.original_timestamp)
+                .map(|timestamp| timestamp.format("%Y-%m-%dT%H:%M:%S").to_string()),
+            new_message: change
+                .new_message
+                .clone()
+                .filter(|message| !same_message(message, &change.original_message)),
+        })
+        .filter(|entry| {
+            entry.new_author.is_some()
+                || entry.new_email.is_some()
+                || entry.new_timestamp.is_some()
+                || entry.new_message.is_some()
+        })
+        .collect();
+    Ok(serde_json::to_string_pretty(&PlanChanges { changes })?)
+}
+
+/// Applies `--signoff` to every commit the plan rewrites.
+pub fn sign_off_plan(plan: &mut [(Oid, EditOptions)], commits: &[CommitInfo]) {
+    for (oid, options) in plan.iter_mut() {
+        if let Some(commit) = commits.iter().find(|commit| commit.oid == *oid) {
+            sign_off_edit(options, commit);
+        }
+    }
+}
+
+pub fn apply_plan(args: &Args) -> Result<Outcome> {
+    prepare_rewrite(args)?;
+
+    let plan_path = args.apply_plan.as_ref().unwrap();
+    let commits = get_commit_history(args, false)?;
+    let mut plan = load_plan(plan_path, &commits, args.date_format())?;
+    if args.signoff {
+        sign_off_plan(&mut plan, &commits);
+    }
+
+    if !args.quiet {
+        print_plan(&plan);
+    }
+    if !confirm("Apply this plan?", args.yes)? {
+        println!("{}", "Operation cancelled.".yellow());
+        return Ok(Outcome::Cancelled);
+    }
+
+    let repo = Repository::open(args.repo_path.as_ref().unwrap())?;
+    let edits: HashMap<Oid, &EditOptions> = plan.iter().map(|(oid, o)| (*oid, o)).collect();
+    apply_edit_options(&repo, args, &edits, "apply plan")?;
+
+    if !args.quiet {
+        println!(
+            "\n{}",
+            format!("✓ Applied rewrite plan to {} commit(s)", plan.len())
+                .green()
+                .bold()
+        );
+    }
+
+    if args.show_history {
+        print_commit_history(args)?;
+    }
+
+    Ok(Outcome::Done)
+}
+
+fn print_plan(plan: &[(Oid, EditOptions)]) {
+    println!("\n{}", "Planned changes:".bold().green());
+    for (oid, options) in plan {
+        let short = &oid.to_string()[..8];
+        let mut fields = Vec::new();
+        if options.author_name.is_some() {
+            fields.push("author");
+        }
+        if options.author_email.is_some() {
+            fields.push("email");
+        }
+        if options.timestamp.is_some() {
+            fields.push("timestamp");
+        }
+        if options.message.is_some() {
+            fields.push("message");
+        }
+        println!("  {} {}", short.yellow(), fields.join(", "));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::datetime::DEFAULT_DATE_FORMAT;
+    use std::fs;
+    use tempfile::TempDir;
+
+    fn create_test_repo_with_commits() -> (TempDir, String) {
+        let temp_dir = TempDir::new().unwrap();
+        let repo_path = temp_dir.path().to_str().unwrap().to_string();
+        let repo = Repository::init(&repo_path).unwrap();
+
+        for i in 1..=3 {
+            let file_path = temp_dir.path().join(format!("test{i}.txt"));
+            fs::write(&file_path, format!("content {i}")).unwrap();
+
+            let mut index = repo.index().unwrap();
+            index
+                .add_path(std::path::Path::new(&format!("test{i}.txt")))
+                .unwrap();
+            index.write().unwrap();
+
+            let tree_id = index.write_tree().unwrap();
+            let tree = repo.find_tree(tree_id).unwrap();
+            let sig = git2::Signature::new(
+                "Test User",
+                "test@example.com",
+                &git2::Time::new(1234567890 + i * 3600, 0),
+            )
+            .unwrap();
+
+            let parents: Vec<git2::Commit> = match repo.head() {
+                Ok(head) => vec![head.peel_to_commit().unwrap()],
+                Err(_) => vec![],
+            };
+            let parent_refs: Vec<&git2::Commit> = parents.iter().collect();
+            repo.commit(
+                Some("HEAD"),
+                &sig,
+                &sig,
+                &format!("Commit {i}"),
+                &tree,
+                &parent_refs,
+            )
+            .unwrap();
+        }
+
+        (temp_dir, repo_path)
+    }
+
+    fn plan_args(repo_path: &str, plan_path: &str) -> Args {
+        Args {
+            repo_path: Some(repo_path.to_string()),
+            apply_plan: Some(plan_path.to_string()),
+            yes: true,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_parse_plan_accepts_list_and_changes_object() {
+        let list = r#"[{"oid": "abcd1234", "new_message": "msg"}]"#;
+        let object = r#"{"changes": [{"oid": "abcd1234", "new_author": "A"}]}"#;
+
+        assert_eq!(
+            parse_plan(list).unwrap()[0].new_message.as_deref(),
+            Some("msg")
+        );
+        assert_eq!(
+            parse_plan(object).unwrap()[0].new_author.as_deref(),
+            Some("A")
+        );
+        assert!(parse_plan(r#"{"oid": "abcd"}"#).is_err());
+
+        // A misspelt field is named rather than ignored
+        for plan in [
+            r#"[{"oid": "abcd1234", "new_autor": "A"}]"#,
+            r#"{"changes": [{"oid": "abcd1234", "new_autor": "A"}]}"#,
+        ] {
+            let err = parse_plan(plan).unwrap_err().to_string();
+            assert!(err.contains("unknown field `new_autor`"), "{err}");
+        }
+    }
+
+    #[test]
+    fn test_resolve_plan_rejects_entries_without_changes() {
+        let (_temp_dir, repo_path) = create_test_repo_with_commits();
+        let args = plan_args(&repo_path, "");
+        let commits = get_commit_history(&args, false).unwrap();
+        let head = commits[0].oid.to_string();
+
+        let entries = parse_plan(&format!(
+            r#"[{{"oid": "{head}", "new_author": "A"}}, {{"oid": "{head}"}}]"#
+        ))
+        .unwrap();
+        let err = resolve_plan(&entries, &commits, DEFAULT_DATE_FORMAT)
+            .err()
+            .unwrap()
+            .to_string();
+        assert!(
+            err.contains("entry 2") && err.contains("sets none of"),
+            "{err}"
+        );
+    }
+
+    #[test]
+    fn test_resolve_plan_reports_every_conflicting_field() {
+        let (_temp_dir, repo_path) = create_test_repo_with_commits();
+        let args = plan_args(&repo_path, "");
+        let commits = get_commit_history(&args, false).unwrap();
+        let head = commits[0].oid.to_string();
+        let middle = commits[1].oid.to_string();
+
+        // Other fields and repeated values of the same commit are merged
+        let entries = parse_plan(&format!(
+            r#"[{{"oid": "{head}", "new_author": "A"}},
+                {{"oid": "{head}", "new_message": "M", "new_author": "A"}},
+                {{"oid": "{middle}", "new_timestamp": "2023-05-01 12:00:00"}},
+                {{"oid": "{middle}", "new_timestamp": "2023-05-01T12:00:00"}}]"#
+        ))
+        .unwrap();
+        let planned = resolve_plan(&entries, &commits, DEFAULT_DATE_FORMAT).unwrap();
+        assert_eq!(planned.len(), 2);
+        assert_eq!(planned[0].1.author_name.as_deref(), Some("A"));
+        assert_eq!(planned[0].1.message.as_deref(), Some("M"));
+
+        let entries = parse_plan(&format!(
+            r#"[{{"oid": "{head}", "new_author": "A", "new_email": "a@example.com"}},
+                {{"oid": "{middle}", "new_message": "First"}},
+                {{"oid": "{}", "new_author": "B", "new_email": "b@example.com"}},
+                {{"oid": "{middle}", "new_message": "Second"}}]"#,
+            &head[..8]
+        ))
+        .unwrap();
+        let err = resolve_plan(&entries, &commits, DEFAULT_DATE_FORMAT)
+            .err()
+            .unwrap()
+            .to_string();
+        assert!(
+            err.contains("entry 3")
+                && err.contains("new_author 'B' conflicts with 'A' from entry 1"),
+            "{err}"
+        );
+        assert!(
+            err.contains("new_email 'b@example.com' conflicts with 'a@example.com'"),
+            "{err}"
+        );
+        assert!(
+            err.contains("entry 4")
+                && err.contains("new_message 'Second' conflicts with 'First' from entry 2"),
+            "{err}"
+        );
+    }
+
+    #[test]
+    fn test_plan_timestamps_use_date_format() {
+        let (_temp_dir, repo_path) = create_test_repo_with_commits();
+        let args = plan_args(&repo_path, "");
+        let commits = get_commit_history(&args, false).unwrap();
+        let head = commits[0].oid.to_string();
+        let expected =
+            NaiveDateTime::parse_from_str("2023-05-01 12:00:00", DEFAULT_DATE_FORMAT).unwrap();
+
+        for value in ["01/05/2023 12:00", "2023-05-01T12:00:00"] {
+            let entries = parse_plan(&format!(
+                r#"[{{"oid": "{head}", "new_timestamp": "{value}"}}]"#
+            ))
+            .unwrap();
+            let planned = resolve_plan(&entries, &commits, "%d/%m/%Y %H:%M").unwrap();
+            assert_eq!(planned[0].1.timestamp, Some(expected), "{value}");
+        }
+
+        let entries = parse_plan(&format!(
+            r#"[{{"oid": "{head}", "new_timestamp": "2023-05-01 12:00:00"}}]"#
+        ))
+        .unwrap();
+        let err = resolve_plan(&entries, &commits, "%d/%m/%Y %H:%M")
+            .err()
+            .unwrap()
+            .to_string();
+        assert!(err.contains("expected %d/%m/%Y %H:%M"), "{err}");
+    }
+
+    #[test]
+    fn test_simulation_plan_reads_back_as_the_same_plan() {
+        let (_temp_dir, repo_path) = create_test_repo_with_commits();
+        let args = plan_args(&repo_path, "");
+        let commits = get_commit_history(&args, false).unwrap();
+        let head = commits[0].oid.to_string();
+        let middle = commits[1].oid.to_string();
+
+        // Values equal to the commit's own are not changes
+        let entries = parse_plan(&format!(
+            r#"[{{"oid": "{head}", "new_author": "Test User", "new_message": "Commit 3"}},
+                {{"oid": "{middle}", "new_author": "A", "new_timestamp": "2023-05-01 12:00:00"}}]"#
+        ))
+        .unwrap();
+        let plan = resolve_plan(&entries, &commits, DEFAULT_DATE_FORMAT).unwrap();
+        let simulation = create_plan_simulation(&commits, &plan).unwrap();
+
+        let exported = simulation_plan(&simulation).unwrap();
+        let value: serde_json::Value = serde_json::from_str(&exported).unwrap();
+        assert_eq!(
+            value,
+            serde_json::json!({"changes": [{
+                "oid": middle,
+                "new_author": "A",
+                "new_timestamp": "2023-05-01T12:00:00",
+            }]})
+        );
+
+        let reread = resolve_plan(&parse_plan(&exported).unwrap(), &commits, "%d/%m/%Y").unwrap();
+        assert_eq!(reread, vec![plan[1].clone()]);
+    }
+
+    #[test]
+    fn test_apply_plan_rewrites_listed_commits() {
+        let (temp_dir, repo_path) = create_test_repo_with_commits();
+        let args = plan_args(&repo_path, "");
+        let commits = get_commit_history(&args, false).unwrap();
+        let middle = commits[1].oid.to_string();
+
+        let plan_path = temp_dir.path().join("plan.json");
+        fs::write(
+            &plan_path,
+            format!(
+                r#"[{{"oid": "{}", "new_author": "Planned", "new_email": "planned@example.com",
+                     "new_timestamp": "2023-05-01 12:00:00", "new_message": "Planned message"}}]"#,
+                &middle[..10]
+            ),
+        )
+        .unwrap();
+
+        // Tests have no terminal to confirm on
+        let unconfirmed = Args {
+            yes: false,
+            ..plan_args(&repo_path, plan_path.to_str().unwrap())
+        };
+        let err = apply_plan(&unconfirmed).unwrap_err().to_string();
+        assert!(err.contains("pass --yes"), "{err}");
+        assert_eq!(
+            get_commit_history(&args, false).unwrap()[1].oid,
+            commits[1].oid
+        );
+
+        let args = plan_args(&repo_path, plan_path.to_str().unwrap());
+        apply_plan(&args).unwrap();
+
+        let rewritten = get_commit_history(&args, false).unwrap();
+        assert_eq!(rewritten.len(), 3);
+        assert_eq!(rewritten[1].author_name, "Planned");
+        assert_eq!(rewritten[1].author_email, "planned@example.com");
+        assert_eq!(rewritten[1].message, "Planned message");
+        assert_eq!(
+            rewritten[1].timestamp,
+            NaiveDateTime::parse_from_str("2023-05-01 12:00:00", "%Y-%m-%d %H:%M:%S").unwrap()
+        );
+        assert_eq!(rewritten[0].author_name, "Test User");
+        assert_eq!(rewritten[0].message, "Commit 3");
+        assert_eq!(rewritten[2].oid, commits[2].oid);
+    }
+
+    #[test]
+    fn test_apply_plan_with_stale_oid_leaves_history_untouched() {
+        let (temp_dir, repo_path) = create_test_repo_with_commits();
+        let repo = Repository::open(&repo_path).unwrap();
+        let head_before = repo.head().unwrap().target().unwrap();
+
+        let plan_path = temp_dir.path().join("plan.json");
+        fs::write(
+            &plan_path,
+            format!(
+                r#"[{{"oid": "{head_before}", "new_message": "Changed"}},
+                    {{"oid": "deadbeefdeadbeef", "new_message": "Missing"}}]"#
+            ),
+        )
+        .unwrap();
+
+        let args = plan_args(&repo_path, plan_path.to_str().unwrap());
+        let err = apply_plan(&args).unwrap_err().to_string();
+        assert!(err.contains("deadbeefdeadbeef"));
+
+        let head_after = repo.head().unwrap().target().unwrap();
+        assert_eq!(head_before, head_after);
+    }
+}
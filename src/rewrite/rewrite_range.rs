@@ -1,17 +1,39 @@
-use crate::utils::types::CommitInfo;
-use crate::utils::types::Result;
-use crate::{args::Args, utils::commit_history::get_commit_history};
+use crate::rewrite::common::{
+    chained_rewrite_notice, finish_rewrite, new_signature, original_committer_at, prepare_rewrite,
+    print_new_tip, rewrite_commit_with_tree, skip_unchanged_prefix,
+};
+use crate::utils::change_summary::print_change;
+use crate::utils::datetime::{format_hint, DEFAULT_DATE_FORMAT};
+use crate::utils::editor::edit_message;
+use crate::utils::git_ref::RewriteTarget;
+use crate::utils::message_transform::{add_signoff, same_message, MessageTransforms};
+use crate::utils::prompt::confirm;
+use crate::utils::simulation::{create_range_simulation, print_simulation_result};
+use crate::utils::theme::Theme;
+use crate::utils::types::{display_email, CommitInfo, EditOptions};
+use crate::utils::types::{Outcome, Result};
+use crate::utils::validator::{check_email_domain, validate_email};
+use crate::{
+    args::Args,
+    utils::commit_history::{get_commit_history, print_commit_history},
+};
 use chrono::NaiveDateTime;
-use colored::Colorize;
+use colored::{ColoredString, Colorize};
 use crossterm::{
     cursor,
-    event::{self, Event, KeyCode, KeyEvent, KeyEventKind},
+    event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers},
     terminal::{self, Clear, ClearType},
     ExecutableCommand,
 };
-use git2::{Repository, Signature, Sort, Time};
-use std::collections::HashMap;
-use std::io::{self, Write};
+use git2::{Repository, Sort, Time};
+use signal_hook::consts::{SIGINT, SIGTERM};
+use signal_hook::SigId;
+use std::collections::{HashMap, HashSet};
+use std::io::{self, BufRead, IsTerminal, Write};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
 
 #[derive(Debug, Clone)]
 struct CommitEdit {
@@ -20,9 +42,31 @@ struct CommitEdit {
     author_name: String,
     author_email: String,
     timestamp: NaiveDateTime,
+    committer_timestamp: NaiveDateTime,
     message: String,
     is_modified: bool,
     modifications: ModificationFlags,
+    action: RowAction,
+}
+
+// What happens to a row's commit besides its cell edits
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+enum RowAction {
+    #[default]
+    Keep,
+    // Left out of the rewritten history; its children move onto its parent
+    Drop,
+    // Folded into the commit below it, appending its message
+    Squash,
+    // Folded into the commit below it, discarding its message
+    Fixup,
+}
+
+impl RowAction {
+    // Squash and fixup both fold the commit into the one below it
+    fn is_folded(self) -> bool {
+        matches!(self, RowAction::Squash | RowAction::Fixup)
+    }
 }
 
 #[derive(Debug, Clone, Default)]
@@ -30,9 +74,66 @@ struct ModificationFlags {
     author_name_changed: bool,
     author_email_changed: bool,
     timestamp_changed: bool,
+    committer_timestamp_changed: bool,
     message_changed: bool,
 }
 
+impl ModificationFlags {
+    fn any(&self) -> bool {
+        self.author_name_changed
+            || self.author_email_changed
+            || self.timestamp_changed
+            || self.committer_timestamp_changed
+            || self.message_changed
+    }
+}
+
+impl CommitEdit {
+    // Only the fields that were actually changed
+    fn edit_options(&self) -> EditOptions {
+        let flags = &self.modifications;
+        EditOptions {
+            author_name: flags.author_name_changed.then(|| self.author_name.clone()),
+            author_email: flags
+                .author_email_changed
+                .then(|| self.author_email.clone()),
+            timestamp: flags.timestamp_changed.then_some(self.timestamp),
+            message: flags.message_changed.then(|| self.message.clone()),
+        }
+    }
+}
+
+// The value of one editable cell, as recorded in the undo history
+#[derive(Debug, Clone, PartialEq)]
+enum CellValue {
+    Text(String),
+    Timestamp(NaiveDateTime),
+}
+
+#[derive(Debug, Clone)]
+struct CellChange {
+    row: usize,
+    column: TableColumn,
+    old_value: CellValue,
+    new_value: CellValue,
+}
+
+// One undoable step in the table: a cell edit, a row moved to a neighbouring
+// position, or a change of a row's drop/squash/fixup mark
+#[derive(Debug, Clone)]
+enum TableChange {
+    Cell(CellChange),
+    Move {
+        from: usize,
+        to: usize,
+    },
+    Action {
+        row: usize,
+        old: RowAction,
+        new: RowAction,
+    },
+}
+
 #[derive(Debug, Clone, Copy, PartialEq)]
 enum TableColumn {
     Index = 0,
@@ -40,16 +141,126 @@ enum TableColumn {
     AuthorName = 2,
     AuthorEmail = 3,
     Timestamp = 4,
-    Message = 5,
+    CommitterTimestamp = 5,
+    Message = 6,
+}
+
+pub const SIGNAL_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+// Raw mode needs the terminal settings of stdin; without a terminal there is
+// nothing to save or restore, so refuse before any of it is touched
+pub fn ensure_terminal() -> Result<()> {
+    if !io::stdin().is_terminal() {
+        return Err("Cannot enter interactive mode: stdin is not a terminal".into());
+    }
+    Ok(())
+}
+
+// Records SIGINT/SIGTERM while the table editor (or the pick list) runs; the
+// handlers are removed on drop
+pub struct SignalGuard {
+    ids: Vec<SigId>,
+    received: Arc<AtomicUsize>,
+}
+
+impl SignalGuard {
+    pub fn install() -> Result<Self> {
+        let received = Arc::new(AtomicUsize::new(0));
+        let mut ids = Vec::new();
+        for signal in [SIGINT, SIGTERM] {
+            ids.push(signal_hook::flag::register_usize(
+                signal,
+                Arc::clone(&received),
+                signal as usize,
+            )?);
+        }
+        Ok(Self { ids, received })
+    }
+
+    pub fn received(&self) -> Option<i32> {
+        match self.received.load(Ordering::SeqCst) {
+            0 => None,
+            signal => Some(signal as i32),
+        }
+    }
+}
+
+impl Drop for SignalGuard {
+    fn drop(&mut self) {
+        for id in self.ids.drain(..) {
+            signal_hook::low_level::unregister(id);
+        }
+    }
+}
+
+// Widths of the table columns that follow the content on screen and the
+// terminal. The index, hash and timestamp columns always keep their width.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct ColumnWidths {
+    author_name: usize,
+    author_email: usize,
+    message: usize,
+}
+
+impl ColumnWidths {
+    // Index (4), hash (8) and timestamp (19) plus the five separating spaces
+    const FIXED: usize = 4 + 8 + 19 + 5;
+    // Wide enough for the full-size columns; used when the size is unknown
+    const DEFAULT_TERMINAL_WIDTH: usize = Self::FIXED + 15 + 20 + 40;
+
+    // Sizes the author columns to their widest visible value (`name_width`,
+    // `email_width`), each capped at a share of the terminal so the message
+    // keeps room, and hands every column left over to the message
+    fn fit(terminal_width: usize, name_width: usize, email_width: usize) -> ColumnWidths {
+        let flexible = terminal_width.saturating_sub(Self::FIXED);
+        let author_name = name_width.clamp(6, (flexible / 4).max(6));
+        let author_email = email_width.clamp(8, (flexible / 3).max(8));
+        let message = flexible.saturating_sub(author_name + author_email).max(10);
+        ColumnWidths {
+            author_name,
+            author_email,
+            message,
+        }
+    }
+}
+
+// Columns `text` takes up on a terminal, so wide characters count twice
+fn display_width(text: &str) -> usize {
+    UnicodeWidthStr::width(text)
+}
+
+// Pads `text` with spaces to `width` terminal columns; `{:<width$}` counts
+// characters instead, which misaligns the table around wide characters
+fn pad_to_width(text: &str, width: usize) -> String {
+    format!(
+        "{text}{}",
+        " ".repeat(width.saturating_sub(display_width(text)))
+    )
 }
 
 struct InteractiveTable {
     commits: Vec<CommitEdit>,
     current_row: usize,
     current_col: TableColumn,
+    scroll_offset: usize,
     editing: bool,
     edit_buffer: String,
     editable_fields: (bool, bool, bool, bool), // (author_name, author_email, timestamp, message)
+    undo_stack: Vec<TableChange>,
+    redo_stack: Vec<TableChange>,
+    // Shown under the table until the next key press
+    notice: Option<String>,
+    // Timestamps are typed and pre-filled in this format (--date-format)
+    date_format: String,
+    // Typed emails must use one of these domains when any are set
+    allowed_email_domains: Vec<String>,
+    // --allow-empty-email: an email cell may be saved empty
+    allow_empty_email: bool,
+    // --committer-column: the Timestamp column holds the author date and the
+    // committer date gets a column of its own
+    committer_column: bool,
+    // Column colors from the config file
+    theme: Theme,
 }
 
 impl InteractiveTable {
@@ -68,9 +279,11 @@ impl InteractiveTable {
                 author_name: commit.author_name.clone(),
                 author_email: commit.author_email.clone(),
                 timestamp: commit.timestamp,
+                committer_timestamp: commit.timestamp,
                 message: commit.message.clone(), // Keep full message, truncate only for display
                 is_modified: false,
                 modifications: ModificationFlags::default(),
+                action: RowAction::Keep,
             });
         }
 
@@ -95,9 +308,36 @@ impl InteractiveTable {
             commits: commit_edits,
             current_row: 0,
             current_col: starting_col,
+            scroll_offset: 0,
             editing: false,
             edit_buffer: String::new(),
             editable_fields,
+            undo_stack: Vec::new(),
+            redo_stack: Vec::new(),
+            notice: None,
+            date_format: DEFAULT_DATE_FORMAT.to_string(),
+            allowed_email_domains: Vec::new(),
+            allow_empty_email: false,
+            committer_column: false,
+            theme: Theme::default(),
+        }
+    }
+
+    // Splits the dates for --committer-column: every Timestamp cell starts
+    // from the author date instead of the committer date
+    fn show_committer_column(&mut self) {
+        self.committer_column = true;
+        for commit in &mut self.commits {
+            commit.timestamp = commit.original.author_timestamp;
+        }
+    }
+
+    // The value the Timestamp column of `commit` started from
+    fn original_timestamp(&self, commit: &CommitEdit) -> NaiveDateTime {
+        if self.committer_column {
+            commit.original.author_timestamp
+        } else {
+            commit.original.timestamp
         }
     }
 
@@ -106,6 +346,13 @@ impl InteractiveTable {
         let _ = io::stdout().execute(Clear(ClearType::All));
         let _ = io::stdout().execute(cursor::MoveTo(0, 0));
 
+        // Measured on every draw, so a resized terminal or a longer edit gets a fitting layout
+        let terminal_width = Self::terminal_width();
+        let visible_rows = Self::visible_row_count();
+        let widths = self.column_widths(terminal_width, visible_rows);
+        let name_w = widths.author_name;
+        let email_w = widths.author_email;
+
         println!(
             "{}",
             "Interactive Commit Editor - Range Mode".bold().green()
@@ -130,34 +377,81 @@ impl InteractiveTable {
         println!("{}", editable_info.cyan());
         println!(
             "{}",
-            "Use Arrow Keys to navigate, Enter to edit, Esc to save & exit, Ctrl+C to cancel"
-                .yellow()
+            self.truncate_text(
+                "Use Arrow Keys to navigate, Shift+Up/Down to move a commit, d/s/f to drop, squash or fix it up, Enter to edit, Esc to save & exit, Ctrl+C to cancel",
+                terminal_width
+            )
+            .yellow()
         );
         println!();
 
         // Print header
+        let header = |text: &str, width: usize| {
+            pad_to_width(&self.truncate_text(text, width), width)
+                .bold()
+                .white()
+        };
+        // The committer column, when shown, follows the timestamp with its own separator
+        let committer_cell = |text: ColoredString| {
+            if self.committer_column {
+                format!(" {text}")
+            } else {
+                String::new()
+            }
+        };
         println!(
-            "{:<4} {:<8} {:<15} {:<20} {:<19} {}",
-            "#".bold().white(),
-            "HASH".bold().white(),
-            "AUTHOR NAME".bold().white(),
-            "AUTHOR EMAIL".bold().white(),
-            "TIMESTAMP".bold().white(),
+            "{} {} {} {} {}{} {}",
+            header("#", 4),
+            header("HASH", 8),
+            header("AUTHOR NAME", name_w),
+            header("AUTHOR EMAIL", email_w),
+            header(
+                if self.committer_column {
+                    "AUTHOR DATE"
+                } else {
+                    "TIMESTAMP"
+                },
+                19
+            ),
+            committer_cell(header("COMMIT DATE", 19)),
             "MESSAGE".bold().white()
         );
 
-        // Draw rows
-        for (row_idx, commit) in self.commits.iter().enumerate() {
+        // Draw only the rows inside the viewport so large histories fit on screen
+        let viewport_end = (self.scroll_offset + visible_rows).min(self.commits.len());
+
+        for (row_idx, commit) in self
+            .commits
+            .iter()
+            .enumerate()
+            .skip(self.scroll_offset)
+            .take(visible_rows)
+        {
             let is_current_row = row_idx == self.current_row;
 
             // Prepare content
             let index_str = format!("{}", commit.index + 1);
             let hash_str = self.truncate_text(&commit.original.short_hash, 8);
-            let author_name_str = self.truncate_text(&commit.author_name, 15);
-            let author_email_str = self.truncate_text(&commit.author_email, 20);
+            // Edited cells leave a column for their `*` marker
+            let mods = &commit.modifications;
+            let author_name_str = self.truncate_text(
+                &commit.author_name,
+                name_w - usize::from(mods.author_name_changed),
+            );
+            let author_email_str = self.truncate_text(
+                display_email(&commit.author_email),
+                email_w - usize::from(mods.author_email_changed),
+            );
             let timestamp_str = commit.timestamp.format("%Y-%m-%d %H:%M:%S").to_string();
+            let committer_str = commit
+                .committer_timestamp
+                .format("%Y-%m-%d %H:%M:%S")
+                .to_string();
             let first_line_message = commit.message.lines().next().unwrap_or("");
-            let message_str = self.truncate_text(first_line_message, 40);
+            let message_str = self.truncate_text(
+                first_line_message,
+                widths.message - usize::from(mods.message_changed),
+            );
 
             // Add modification indicators and current cell brackets
             let is_current_cell_index =
@@ -170,32 +464,49 @@ impl InteractiveTable {
                 is_current_row && matches!(self.current_col, TableColumn::AuthorEmail);
             let is_current_cell_timestamp =
                 is_current_row && matches!(self.current_col, TableColumn::Timestamp);
+            let is_current_cell_committer =
+                is_current_row && matches!(self.current_col, TableColumn::CommitterTimestamp);
             let is_current_cell_message =
                 is_current_row && matches!(self.current_col, TableColumn::Message);
 
-            let index_final = index_str; // Index is never editable, so no brackets
-            let hash_final = hash_str; // Hash is never editable, so no brackets
+            // Index is never editable; it carries the squash/fixup mark, and a
+            // moved commit is marked like an edited cell
+            let index_final = match commit.action {
+                RowAction::Squash => format!("s{index_str}"),
+                RowAction::Fixup => format!("f{index_str}"),
+                _ if self.is_moved(row_idx) => format!("~{index_str}"),
+                _ => index_str,
+            };
+            let index_final = pad_to_width(&index_final, 4);
+            let hash_final = pad_to_width(&hash_str, 8); // Hash is never editable, so no brackets
 
             let author_name_with_mod = if commit.modifications.author_name_changed {
                 format!("*{author_name_str}")
             } else {
                 author_name_str
             };
-            let author_name_final = author_name_with_mod;
+            let author_name_final = pad_to_width(&author_name_with_mod, name_w);
 
             let author_email_with_mod = if commit.modifications.author_email_changed {
                 format!("*{author_email_str}")
             } else {
                 author_email_str
             };
-            let author_email_final = author_email_with_mod;
+            let author_email_final = pad_to_width(&author_email_with_mod, email_w);
 
             let timestamp_with_mod = if commit.modifications.timestamp_changed {
                 format!("*{timestamp_str}")
             } else {
                 timestamp_str
             };
-            let timestamp_final = timestamp_with_mod;
+            let timestamp_final = pad_to_width(&timestamp_with_mod, 19);
+
+            let committer_with_mod = if commit.modifications.committer_timestamp_changed {
+                format!("*{committer_str}")
+            } else {
+                committer_str
+            };
+            let committer_final = pad_to_width(&committer_with_mod, 19);
 
             let message_with_mod = if commit.modifications.message_changed {
                 format!("*{message_str}")
@@ -204,79 +515,158 @@ impl InteractiveTable {
             };
             let message_final = message_with_mod;
 
+            // A dropped row stays in place, struck through
+            let strike = |text: ColoredString| {
+                if commit.action == RowAction::Drop {
+                    text.strikethrough()
+                } else {
+                    text
+                }
+            };
+
             // Apply formatting and colors
             if is_current_row {
                 if self.editing {
                     println!(
-                        "{:<4} {:<8} {:<15} {:<20} {:<19} {}",
-                        index_final.black().on_yellow(),
-                        hash_final.black().on_yellow(),
-                        author_name_final.black().on_yellow(),
-                        author_email_final.black().on_yellow(),
-                        timestamp_final.black().on_yellow(),
-                        message_final.black().on_yellow()
+                        "{} {} {} {} {}{} {}",
+                        strike(index_final.black().on_yellow()),
+                        strike(hash_final.black().on_yellow()),
+                        strike(author_name_final.black().on_yellow()),
+                        strike(author_email_final.black().on_yellow()),
+                        strike(timestamp_final.black().on_yellow()),
+                        committer_cell(strike(committer_final.black().on_yellow())),
+                        strike(message_final.black().on_yellow())
                     );
                 } else {
                     // Current row, not editing - highlight current cell with special background
-                    let index_styled = if is_current_cell_index {
+                    let index_styled = strike(if is_current_cell_index {
                         index_final.white().on_blue()
                     } else {
                         index_final.white().on_bright_black()
-                    };
-                    let hash_styled = if is_current_cell_hash {
+                    });
+                    let hash_styled = strike(if is_current_cell_hash {
                         hash_final.white().on_blue()
                     } else {
                         hash_final.yellow().on_bright_black()
-                    };
+                    });
                     let author_name_styled =
-                        if is_current_cell_author_name && self.editable_fields.0 {
+                        strike(if is_current_cell_author_name && self.editable_fields.0 {
                             author_name_final.white().on_blue()
                         } else {
-                            author_name_final.cyan().on_bright_black()
-                        };
+                            author_name_final.color(self.theme.author).on_bright_black()
+                        });
                     let author_email_styled =
-                        if is_current_cell_author_email && self.editable_fields.1 {
+                        strike(if is_current_cell_author_email && self.editable_fields.1 {
                             author_email_final.white().on_blue()
                         } else {
-                            author_email_final.blue().on_bright_black()
-                        };
-                    let timestamp_styled = if is_current_cell_timestamp && self.editable_fields.2 {
-                        timestamp_final.white().on_blue()
-                    } else {
-                        timestamp_final.magenta().on_bright_black()
-                    };
-                    let message_styled = if is_current_cell_message && self.editable_fields.3 {
-                        message_final.white().on_blue()
-                    } else {
-                        message_final.green().on_bright_black()
-                    };
+                            author_email_final.color(self.theme.email).on_bright_black()
+                        });
+                    let timestamp_styled =
+                        strike(if is_current_cell_timestamp && self.editable_fields.2 {
+                            timestamp_final.white().on_blue()
+                        } else {
+                            timestamp_final
+                                .color(self.theme.timestamp)
+                                .on_bright_black()
+                        });
+                    let committer_styled = committer_cell(strike(
+                        if is_current_cell_committer && self.editable_fields.2 {
+                            committer_final.white().on_blue()
+                        } else {
+                            committer_final
+                                .color(self.theme.timestamp)
+                                .on_bright_black()
+                        },
+                    ));
+                    let message_styled =
+                        strike(if is_current_cell_message && self.editable_fields.3 {
+                            message_final.white().on_blue()
+                        } else {
+                            message_final.color(self.theme.message).on_bright_black()
+                        });
 
                     println!(
-                        "{index_styled:<4} {hash_styled:<8} {author_name_styled:<15} {author_email_styled:<20} {timestamp_styled:<19} {message_styled}"
+                        "{index_styled} {hash_styled} {author_name_styled} {author_email_styled} {timestamp_styled}{committer_styled} {message_styled}"
                     );
                 }
+            } else if commit.action == RowAction::Drop {
+                println!(
+                    "{} {} {} {} {}{} {}",
+                    strike(index_final.bright_black()),
+                    strike(hash_final.bright_black()),
+                    strike(author_name_final.bright_black()),
+                    strike(author_email_final.bright_black()),
+                    strike(timestamp_final.bright_black()),
+                    committer_cell(strike(committer_final.bright_black())),
+                    strike(message_final.bright_black())
+                );
             } else {
+                let theme = &self.theme;
+                let committer_styled = if self.committer_column {
+                    format!(
+                        " {}",
+                        theme.cell(
+                            &committer_final,
+                            theme.timestamp,
+                            mods.committer_timestamp_changed
+                        )
+                    )
+                } else {
+                    String::new()
+                };
                 println!(
-                    "{:<4} {:<8} {:<15} {:<20} {:<19} {}",
+                    "{} {} {} {} {}{} {}",
                     index_final.white(),
                     hash_final.yellow(),
-                    author_name_final.cyan(),
-                    author_email_final.blue(),
-                    timestamp_final.magenta(),
-                    message_final.green()
+                    theme.cell(&author_name_final, theme.author, mods.author_name_changed),
+                    theme.cell(&author_email_final, theme.email, mods.author_email_changed),
+                    theme.cell(&timestamp_final, theme.timestamp, mods.timestamp_changed),
+                    committer_styled,
+                    theme.cell(&message_final, theme.message, mods.message_changed)
                 );
             }
         }
 
+        if self.commits.len() > visible_rows {
+            println!(
+                "{}",
+                format!(
+                    "Rows {}-{} of {} (PgUp/PgDn to scroll, Home/End to jump)",
+                    self.scroll_offset + 1,
+                    viewport_end,
+                    self.commits.len()
+                )
+                .dimmed()
+            );
+        }
+
+        if let Some(notice) = &self.notice {
+            println!("{}", notice.red());
+        }
+
         println!();
 
         if self.editing {
             println!("{}: {}", "Editing".bold().yellow(), self.edit_buffer);
+            if matches!(
+                self.current_col,
+                TableColumn::Timestamp | TableColumn::CommitterTimestamp
+            ) {
+                // Live feedback so a bad timestamp shows up before Enter is pressed
+                match parse_timestamp_input(&self.edit_buffer, &self.date_format) {
+                    Ok(_) => println!("{}", "✓ valid timestamp".green()),
+                    Err(e) => println!("{}", format!("✗ {e}").red()),
+                }
+            }
             println!("{}", "Press Enter to save, Esc to cancel edit".italic());
         } else {
             println!(
                 "{}",
-                "Navigation: ←→↑↓  Edit: Enter  Save & Exit: Esc  Cancel: Ctrl+C".italic()
+                self.truncate_text(
+                    "Navigation: ←→↑↓  Move: Shift+↑↓ or K/J  Drop/Squash/Fixup: d/s/f  Edit: Enter  Reset cell: r  Undo/Redo: Ctrl+Z/Ctrl+Y  Save & Exit: Esc  Cancel: Ctrl+C",
+                    terminal_width
+                )
+                .italic()
             );
             println!(
                 "{}",
@@ -285,14 +675,172 @@ impl InteractiveTable {
         }
     }
 
+    // Fits `text` into `max_width` terminal columns, cutting between characters
+    // and counting wide ones twice, with an ellipsis marking the cut
     fn truncate_text(&self, text: &str, max_width: usize) -> String {
-        if text.len() > max_width {
-            format!("{}…", &text[..max_width.saturating_sub(1)])
+        if display_width(text) <= max_width {
+            return text.to_string();
+        }
+
+        let mut kept = String::new();
+        let mut width = 0;
+        for ch in text.chars() {
+            let ch_width = ch.width().unwrap_or(0);
+            if width + ch_width > max_width.saturating_sub(1) {
+                break;
+            }
+            kept.push(ch);
+            width += ch_width;
+        }
+        format!("{kept}…")
+    }
+
+    // Column widths for the rows currently on screen; a `*` edit marker takes a column too
+    fn column_widths(&self, terminal_width: usize, visible_rows: usize) -> ColumnWidths {
+        let visible = self
+            .commits
+            .iter()
+            .skip(self.scroll_offset)
+            .take(visible_rows);
+        let (mut name_width, mut email_width) =
+            (display_width("AUTHOR NAME"), display_width("AUTHOR EMAIL"));
+        for commit in visible {
+            let mods = &commit.modifications;
+            name_width = name_width
+                .max(display_width(&commit.author_name) + usize::from(mods.author_name_changed));
+            email_width = email_width.max(
+                display_width(display_email(&commit.author_email))
+                    + usize::from(mods.author_email_changed),
+            );
+        }
+        // The committer column takes a fixed 19 columns plus its separator
+        let terminal_width =
+            terminal_width.saturating_sub(if self.committer_column { 20 } else { 0 });
+        ColumnWidths::fit(terminal_width, name_width, email_width)
+    }
+
+    fn terminal_width() -> usize {
+        terminal::size()
+            .map(|(width, _)| width as usize)
+            .unwrap_or(ColumnWidths::DEFAULT_TERMINAL_WIDTH)
+    }
+
+    // Number of table rows that fit in the terminal besides the header and footer lines
+    fn visible_row_count() -> usize {
+        // Includes the timestamp hint shown while editing
+        const CHROME_LINES: usize = 12;
+        const MIN_VISIBLE_ROWS: usize = 5;
+
+        terminal::size()
+            .map(|(_, height)| (height as usize).saturating_sub(CHROME_LINES))
+            .unwrap_or(usize::MAX)
+            .max(MIN_VISIBLE_ROWS)
+    }
+
+    // Adjusts the scroll offset so the current row lies within a viewport of the
+    // given height, and so a viewport that grew (e.g. after a resize) is filled
+    fn scroll_to_current_row(&mut self, visible_rows: usize) {
+        if self.current_row < self.scroll_offset {
+            self.scroll_offset = self.current_row;
+        } else if self.current_row >= self.scroll_offset + visible_rows {
+            self.scroll_offset = self.current_row + 1 - visible_rows;
+        }
+        self.scroll_offset = self
+            .scroll_offset
+            .min(self.commits.len().saturating_sub(visible_rows));
+    }
+
+    // Swaps the current row with the one above (newer) or below (older) it.
+    // Replaying a commit onto a different parent needs exactly one parent on
+    // both sides of the swap, so merge and root commits stay where they are.
+    fn move_current_row(&mut self, up: bool) {
+        let from = self.current_row;
+        let to = if up {
+            match from.checked_sub(1) {
+                Some(to) => to,
+                None => return,
+            }
+        } else if from + 1 < self.commits.len() {
+            from + 1
         } else {
-            text.to_string()
+            return;
+        };
+
+        if [from, to]
+            .iter()
+            .any(|&row| self.commits[row].original.parent_count != 1)
+        {
+            self.notice = Some("Merge commits and the root commit cannot be moved".to_string());
+            return;
+        }
+        // The row that ends up at the bottom would have nothing left to fold into
+        let bottom = self.commits.len() - 1;
+        if (to == bottom && self.commits[from].action.is_folded())
+            || (from == bottom && self.commits[to].action.is_folded())
+        {
+            self.notice =
+                Some("A squashed commit cannot be moved to the bottom of the range".to_string());
+            return;
         }
+
+        self.swap_rows(from, to);
+        self.undo_stack.push(TableChange::Move { from, to });
+        self.redo_stack.clear();
+    }
+
+    // Marks the current row to be dropped, squashed or fixed up, or takes the
+    // mark back when it is pressed again. A merge has no single parent to hand
+    // its children to or to fold into, and the root has none at all. The
+    // bottom row's parent is outside the range, so it cannot be folded either.
+    fn toggle_current_row_action(&mut self, action: RowAction) {
+        let row = self.current_row;
+        let parent_count = self.commits[row].original.parent_count;
+        let refusal = match action {
+            RowAction::Drop if parent_count > 1 => Some("Merge commits cannot be dropped"),
+            RowAction::Squash | RowAction::Fixup if parent_count != 1 => {
+                Some("Merge commits and the root commit cannot be squashed")
+            }
+            RowAction::Squash | RowAction::Fixup if row + 1 == self.commits.len() => {
+                Some("The oldest commit of the range has no commit below it to squash into")
+            }
+            _ => None,
+        };
+        if let Some(refusal) = refusal {
+            self.notice = Some(refusal.to_string());
+            return;
+        }
+
+        let old = self.commits[row].action;
+        let new = if old == action {
+            RowAction::Keep
+        } else {
+            action
+        };
+        self.set_action(row, new);
+        self.undo_stack.push(TableChange::Action { row, old, new });
+        self.redo_stack.clear();
+    }
+
+    fn set_action(&mut self, row: usize, action: RowAction) {
+        self.commits[row].action = action;
+        self.current_row = row;
+    }
+
+    fn swap_rows(&mut self, from: usize, to: usize) {
+        self.commits.swap(from, to);
+        self.current_row = to;
+    }
+
+    fn is_moved(&self, row: usize) -> bool {
+        is_moved(&self.commits, row)
     }
 
+    fn is_reordered(&self) -> bool {
+        (0..self.commits.len()).any(|row| self.is_moved(row))
+    }
+
+    // Each key keeps its own arm, so the bounds checks stay inside them
+    #[allow(clippy::collapsible_match)]
     fn handle_navigation_key_input(&mut self, key: KeyCode) -> Result<bool> {
         match key {
             KeyCode::Up => {
@@ -331,10 +879,51 @@ impl InteractiveTable {
                     self.current_row += 1;
                 }
             }
+            KeyCode::Char('K') => {
+                // Shift+Up (vim-style), for terminals that do not report Shift with arrows
+                self.move_current_row(true);
+            }
+            KeyCode::Char('J') => {
+                // Shift+Down (vim-style)
+                self.move_current_row(false);
+            }
+            KeyCode::PageUp => {
+                let page = Self::visible_row_count();
+                self.current_row = self.current_row.saturating_sub(page);
+            }
+            KeyCode::PageDown => {
+                let page = Self::visible_row_count();
+                self.current_row =
+                    (self.current_row.saturating_add(page)).min(self.commits.len() - 1);
+            }
+            KeyCode::Home => {
+                self.current_row = 0;
+            }
+            KeyCode::End => {
+                self.current_row = self.commits.len() - 1;
+            }
+            KeyCode::Enter
+                if self.current_col == TableColumn::Message
+                    && self.is_column_editable(&TableColumn::Message) =>
+            {
+                self.edit_message_externally();
+            }
             KeyCode::Enter => {
                 self.start_editing();
                 return Ok(true);
             }
+            KeyCode::Char('r') => {
+                self.reset_current_cell();
+            }
+            KeyCode::Char('d') => {
+                self.toggle_current_row_action(RowAction::Drop);
+            }
+            KeyCode::Char('s') => {
+                self.toggle_current_row_action(RowAction::Squash);
+            }
+            KeyCode::Char('f') => {
+                self.toggle_current_row_action(RowAction::Fixup);
+            }
             KeyCode::Esc => {
                 return Ok(false); // Exit and save
             }
@@ -343,12 +932,22 @@ impl InteractiveTable {
         Ok(true)
     }
 
+    // Ctrl+Z undoes the last cell change and Ctrl+Y redoes it
+    fn handle_control_key_input(&mut self, key: KeyCode) {
+        match key {
+            KeyCode::Char('z') => self.undo(),
+            KeyCode::Char('y') => self.redo(),
+            _ => {}
+        }
+    }
+
     fn is_column_editable(&self, col: &TableColumn) -> bool {
         match col {
             TableColumn::Index | TableColumn::Hash => false,
             TableColumn::AuthorName => self.editable_fields.0,
             TableColumn::AuthorEmail => self.editable_fields.1,
             TableColumn::Timestamp => self.editable_fields.2,
+            TableColumn::CommitterTimestamp => self.committer_column && self.editable_fields.2,
             TableColumn::Message => self.editable_fields.3,
         }
     }
@@ -360,6 +959,7 @@ impl InteractiveTable {
             TableColumn::AuthorName,
             TableColumn::AuthorEmail,
             TableColumn::Timestamp,
+            TableColumn::CommitterTimestamp,
             TableColumn::Message,
         ];
 
@@ -385,6 +985,7 @@ impl InteractiveTable {
             TableColumn::AuthorName,
             TableColumn::AuthorEmail,
             TableColumn::Timestamp,
+            TableColumn::CommitterTimestamp,
             TableColumn::Message,
         ];
 
@@ -407,6 +1008,174 @@ impl InteractiveTable {
         }
     }
 
+    // Restores the current cell to the commit's original value
+    fn reset_current_cell(&mut self) {
+        if !self.is_column_editable(&self.current_col) {
+            return;
+        }
+
+        let commit = &self.commits[self.current_row];
+        let original = &commit.original;
+        let value = match self.current_col {
+            TableColumn::AuthorName => CellValue::Text(original.author_name.clone()),
+            TableColumn::AuthorEmail => CellValue::Text(original.author_email.clone()),
+            TableColumn::Timestamp => CellValue::Timestamp(self.original_timestamp(commit)),
+            TableColumn::CommitterTimestamp => CellValue::Timestamp(original.timestamp),
+            TableColumn::Message => CellValue::Text(original.message.clone()),
+            _ => return,
+        };
+        self.record_change(self.current_row, self.current_col, value);
+    }
+
+    // Pre-fills the Timestamp column for --shift-anchor. The rows are listed
+    // newest first, so the oldest commit of the range is the last row. The
+    // shifted values are the starting state, so they are not undoable.
+    fn shift_timestamps(&mut self, anchor: NaiveDateTime) {
+        let originals: Vec<_> = self
+            .commits
+            .iter()
+            .rev()
+            .map(|commit| self.original_timestamp(commit))
+            .collect();
+        let shifted = shift_range_timestamps(&originals, anchor);
+
+        let last_row = self.commits.len() - 1;
+        for (i, timestamp) in shifted.into_iter().enumerate() {
+            self.set_cell(
+                last_row - i,
+                TableColumn::Timestamp,
+                CellValue::Timestamp(timestamp),
+            );
+        }
+    }
+
+    // Pre-fills the Message column with the --message-* transforms, which
+    // manual edits in the table then build on
+    fn transform_messages(&mut self, transforms: &MessageTransforms) {
+        for row in 0..self.commits.len() {
+            let message = transforms.apply(&self.commits[row].message);
+            self.set_cell(row, TableColumn::Message, CellValue::Text(message));
+        }
+    }
+
+    fn cell_value(&self, row: usize, column: TableColumn) -> Option<CellValue> {
+        let commit = &self.commits[row];
+        match column {
+            TableColumn::AuthorName => Some(CellValue::Text(commit.author_name.clone())),
+            TableColumn::AuthorEmail => Some(CellValue::Text(commit.author_email.clone())),
+            TableColumn::Timestamp => Some(CellValue::Timestamp(commit.timestamp)),
+            TableColumn::CommitterTimestamp => {
+                Some(CellValue::Timestamp(commit.committer_timestamp))
+            }
+            TableColumn::Message => Some(CellValue::Text(commit.message.clone())),
+            TableColumn::Index | TableColumn::Hash => None,
+        }
+    }
+
+    // Writes a cell and recomputes the commit's modification flags against its original
+    fn set_cell(&mut self, row: usize, column: TableColumn, value: CellValue) {
+        let original_timestamp = self.original_timestamp(&self.commits[row]);
+        let commit = &mut self.commits[row];
+        match (column, value) {
+            (TableColumn::AuthorName, CellValue::Text(name)) => {
+                commit.modifications.author_name_changed = commit.original.author_name != name;
+                commit.author_name = name;
+            }
+            (TableColumn::AuthorEmail, CellValue::Text(email)) => {
+                commit.modifications.author_email_changed = commit.original.author_email != email;
+                commit.author_email = email;
+            }
+            (TableColumn::Timestamp, CellValue::Timestamp(timestamp)) => {
+                commit.modifications.timestamp_changed = original_timestamp != timestamp;
+                commit.timestamp = timestamp;
+            }
+            (TableColumn::CommitterTimestamp, CellValue::Timestamp(timestamp)) => {
+                commit.modifications.committer_timestamp_changed =
+                    commit.original.timestamp != timestamp;
+                commit.committer_timestamp = timestamp;
+            }
+            (TableColumn::Message, CellValue::Text(message)) => {
+                commit.modifications.message_changed =
+                    !same_message(&commit.original.message, &message);
+                commit.message = message;
+            }
+            _ => return,
+        }
+        commit.is_modified = commit.modifications.any();
+    }
+
+    // Applies a cell edit and records it for undo; a new edit discards the redo history
+    fn record_change(&mut self, row: usize, column: TableColumn, new_value: CellValue) {
+        let Some(old_value) = self.cell_value(row, column) else {
+            return;
+        };
+        let unchanged = match (&old_value, &new_value) {
+            (CellValue::Text(old), CellValue::Text(new)) if column == TableColumn::Message => {
+                same_message(old, new)
+            }
+            _ => old_value == new_value,
+        };
+        if unchanged {
+            return;
+        }
+
+        self.set_cell(row, column, new_value.clone());
+        self.undo_stack.push(TableChange::Cell(CellChange {
+            row,
+            column,
+            old_value,
+            new_value,
+        }));
+        self.redo_stack.clear();
+    }
+
+    fn undo(&mut self) {
+        if let Some(change) = self.undo_stack.pop() {
+            match &change {
+                TableChange::Cell(cell) => {
+                    self.set_cell(cell.row, cell.column, cell.old_value.clone());
+                    self.current_row = cell.row;
+                    self.current_col = cell.column;
+                }
+                TableChange::Move { from, to } => self.swap_rows(*to, *from),
+                TableChange::Action { row, old, .. } => self.set_action(*row, *old),
+            }
+            self.redo_stack.push(change);
+        }
+    }
+
+    fn redo(&mut self) {
+        if let Some(change) = self.redo_stack.pop() {
+            match &change {
+                TableChange::Cell(cell) => {
+                    self.set_cell(cell.row, cell.column, cell.new_value.clone());
+                    self.current_row = cell.row;
+                    self.current_col = cell.column;
+                }
+                TableChange::Move { from, to } => self.swap_rows(*from, *to),
+                TableChange::Action { row, new, .. } => self.set_action(*row, *new),
+            }
+            self.undo_stack.push(change);
+        }
+    }
+
+    // Commit bodies are edited in $GIT_EDITOR/$EDITOR. A cancelled edit leaves
+    // the cell alone; an editor that cannot be started falls back to the
+    // inline editor.
+    fn edit_message_externally(&mut self) {
+        let _ = terminal::disable_raw_mode();
+        let current = self.commits[self.current_row].message.clone();
+        match edit_message(&current) {
+            Ok(Some(message)) => self.record_change(
+                self.current_row,
+                TableColumn::Message,
+                CellValue::Text(message),
+            ),
+            Ok(None) => {}
+            Err(_) => self.start_editing(),
+        }
+    }
+
     fn start_editing(&mut self) {
         if !self.is_column_editable(&self.current_col) {
             return; // This column is not editable
@@ -420,7 +1189,11 @@ impl InteractiveTable {
             TableColumn::AuthorEmail => self.commits[self.current_row].author_email.clone(),
             TableColumn::Timestamp => self.commits[self.current_row]
                 .timestamp
-                .format("%Y-%m-%d %H:%M:%S")
+                .format(&self.date_format)
+                .to_string(),
+            TableColumn::CommitterTimestamp => self.commits[self.current_row]
+                .committer_timestamp
+                .format(&self.date_format)
                 .to_string(),
             TableColumn::Message => {
                 // Use the full original message when editing, not the truncated display version
@@ -465,77 +1238,178 @@ impl InteractiveTable {
     }
 
     fn save_current_edit(&mut self) -> Result<()> {
-        let commit = &mut self.commits[self.current_row];
-
-        match self.current_col {
+        let value = match self.current_col {
             TableColumn::AuthorName => {
                 if self.edit_buffer.trim().is_empty() {
                     return Err("Author name cannot be empty".into());
                 }
-                if commit.author_name != self.edit_buffer {
-                    commit.author_name = self.edit_buffer.clone();
-                    commit.modifications.author_name_changed =
-                        commit.original.author_name != commit.author_name;
-                    commit.is_modified = true;
-                }
+                CellValue::Text(self.edit_buffer.clone())
             }
             TableColumn::AuthorEmail => {
                 if self.edit_buffer.trim().is_empty() {
-                    return Err("Author email cannot be empty".into());
-                }
-                if !self.edit_buffer.contains('@') {
-                    return Err("Invalid email format".into());
-                }
-                if commit.author_email != self.edit_buffer {
-                    commit.author_email = self.edit_buffer.clone();
-                    commit.modifications.author_email_changed =
-                        commit.original.author_email != commit.author_email;
-                    commit.is_modified = true;
+                    if !self.allow_empty_email {
+                        return Err(
+                            "Author email cannot be empty (pass --allow-empty-email to keep it empty)"
+                                .into(),
+                        );
+                    }
+                    self.record_change(
+                        self.current_row,
+                        self.current_col,
+                        CellValue::Text(String::new()),
+                    );
+                    return Ok(());
                 }
+                validate_email(&self.edit_buffer)?;
+                check_email_domain(&self.edit_buffer, &self.allowed_email_domains)?;
+                CellValue::Text(self.edit_buffer.clone())
             }
-            TableColumn::Timestamp => {
-                let new_timestamp =
-                    NaiveDateTime::parse_from_str(&self.edit_buffer, "%Y-%m-%d %H:%M:%S")
-                        .map_err(|_| "Invalid timestamp format (use YYYY-MM-DD HH:MM:SS)")?;
-
-                if commit.timestamp != new_timestamp {
-                    commit.timestamp = new_timestamp;
-                    commit.modifications.timestamp_changed =
-                        commit.original.timestamp != commit.timestamp;
-                    commit.is_modified = true;
-                }
+            TableColumn::Timestamp | TableColumn::CommitterTimestamp => {
+                CellValue::Timestamp(parse_timestamp_input(&self.edit_buffer, &self.date_format)?)
             }
             TableColumn::Message => {
                 if self.edit_buffer.trim().is_empty() {
                     return Err("Commit message cannot be empty".into());
                 }
-                if commit.message != self.edit_buffer {
-                    commit.message = self.edit_buffer.clone();
-                    commit.modifications.message_changed =
-                        commit.original.message != commit.message;
-                    commit.is_modified = true;
-                }
+                CellValue::Text(self.edit_buffer.clone())
             }
-            _ => {}
-        }
+            _ => return Ok(()),
+        };
+
+        self.record_change(self.current_row, self.current_col, value);
         Ok(())
     }
 
+    // Applies `<index>:<field>=<value>` lines, where index is the commit number
+    // shown in the table and field one of name, email, timestamp, committer
+    // (with --committer-column) or message.
+    // Every value goes through the same checks as a cell typed in the editor;
+    // blank lines and lines starting with # are skipped. Lines giving one
+    // field of a commit different values are all reported before anything is
+    // applied. Returns how many edits were applied.
+    fn apply_batch_edits(&mut self, reader: impl BufRead) -> Result<usize> {
+        let mut lines = Vec::new();
+        for (line_no, line) in reader.lines().enumerate() {
+            let line = line?;
+            let trimmed = line.trim();
+            if !trimmed.is_empty() && !trimmed.starts_with('#') {
+                lines.push((line_no + 1, trimmed.to_string()));
+            }
+        }
+
+        let conflicts = batch_conflicts(&lines);
+        if !conflicts.is_empty() {
+            return Err(format!("Conflicting batch edits:\n  {}", conflicts.join("\n  ")).into());
+        }
+
+        for (line_no, line) in &lines {
+            self.apply_batch_line(line)
+                .map_err(|e| format!("Batch line {line_no}: {e}"))?;
+        }
+        Ok(lines.len())
+    }
+
+    fn apply_batch_line(&mut self, line: &str) -> Result<()> {
+        let (target, value) = line
+            .split_once('=')
+            .ok_or("expected <index>:<field>=<value>")?;
+        let (index, field) = target
+            .split_once(':')
+            .ok_or("expected <index>:<field>=<value>")?;
+
+        let index: usize = index
+            .trim()
+            .parse()
+            .map_err(|_| format!("'{}' is not a commit number", index.trim()))?;
+        let row = self
+            .commits
+            .iter()
+            .position(|commit| commit.index + 1 == index)
+            .ok_or_else(|| format!("commit {index} is not in the selected range"))?;
+        let column = match field.trim() {
+            "name" => TableColumn::AuthorName,
+            "email" => TableColumn::AuthorEmail,
+            "timestamp" => TableColumn::Timestamp,
+            "committer" => TableColumn::CommitterTimestamp,
+            "message" => TableColumn::Message,
+            other => {
+                return Err(format!(
+                "unknown field '{other}' (expected name, email, timestamp, committer or message)"
+            )
+                .into())
+            }
+        };
+        if !self.is_column_editable(&column) {
+            return Err(format!("{} is not editable in this mode", field.trim()).into());
+        }
+
+        self.current_row = row;
+        self.current_col = column;
+        self.edit_buffer = value.to_string();
+        let saved = self.save_current_edit();
+        self.edit_buffer.clear();
+        saved
+    }
+
     fn run(&mut self) -> Result<bool> {
+        ensure_terminal()?;
+
+        // Raw mode turns Ctrl+C into a key event, but a SIGINT or SIGTERM sent
+        // from elsewhere would still end the process with the terminal mangled
+        let signals = SignalGuard::install()?;
+
         let result = loop {
             // Disable raw mode for drawing the table
             let _ = terminal::disable_raw_mode();
+            self.scroll_to_current_row(Self::visible_row_count());
             self.draw_table();
 
             // Enable raw mode only for reading input
             terminal::enable_raw_mode()?;
 
+            // Poll instead of blocking so a pending signal is noticed promptly
+            while !event::poll(SIGNAL_POLL_INTERVAL)? {
+                if let Some(signal) = signals.received() {
+                    self.restore_terminal();
+                    std::process::exit(128 + signal);
+                }
+            }
+
+            // A resize (SIGWINCH on Unix, a console event on Windows) only
+            // needs the redraw at the top of the loop, which re-measures the
+            // terminal and scrolls the current row back into view
+            let event = event::read()?;
+            if let Event::Resize(..) = event {
+                continue;
+            }
+
             if let Event::Key(KeyEvent {
                 code,
+                modifiers,
                 kind: KeyEventKind::Press,
                 ..
-            }) = event::read()?
+            }) = event
             {
+                self.notice = None;
+
+                if modifiers.contains(KeyModifiers::SHIFT)
+                    && !self.editing
+                    && matches!(code, KeyCode::Up | KeyCode::Down)
+                {
+                    self.move_current_row(code == KeyCode::Up);
+                    continue;
+                }
+
+                if modifiers.contains(KeyModifiers::CONTROL) {
+                    if code == KeyCode::Char('c') {
+                        break Ok(false);
+                    }
+                    if !self.editing {
+                        self.handle_control_key_input(code);
+                    }
+                    continue;
+                }
+
                 let should_continue = if self.editing {
                     match self.handle_edit_key_input(code) {
                         Ok(cont) => cont,
@@ -564,36 +1438,129 @@ impl InteractiveTable {
         let _ = io::stdout().execute(cursor::MoveTo(0, 0));
     }
 
+    // --signoff: every edited row that is kept gets a trailer for its author
+    fn sign_off_modified(&mut self) {
+        for row in 0..self.commits.len() {
+            let commit = &self.commits[row];
+            if commit.action != RowAction::Keep || !commit.is_modified {
+                continue;
+            }
+            let message = add_signoff(&commit.message, &commit.author_name, &commit.author_email);
+            self.set_cell(row, TableColumn::Message, CellValue::Text(message));
+        }
+    }
+
+    // Edits to a dropped or squashed commit are discarded with it, apart from
+    // the message a squash carries over
     fn get_modified_commits(&self) -> Vec<&CommitEdit> {
-        self.commits.iter().filter(|c| c.is_modified).collect()
+        self.commits
+            .iter()
+            .filter(|c| c.is_modified && c.action == RowAction::Keep)
+            .collect()
     }
-}
 
-pub fn parse_range_input(input: &str, total_commits: usize) -> Result<(usize, usize)> {
-    let trimmed_input = input.trim();
+    fn has_row_actions(&self) -> bool {
+        self.commits.iter().any(|c| c.action != RowAction::Keep)
+    }
 
-    // Check if user entered '*' to select all commits
-    if trimmed_input == "*" {
-        if total_commits == 0 {
-            return Err("No commits available to select".into());
+    // History indices of the rows that are edited, moved, dropped or folded
+    fn edited_indices(&self) -> Vec<usize> {
+        (0..self.commits.len())
+            .filter(|&row| {
+                let commit = &self.commits[row];
+                commit.is_modified || commit.action != RowAction::Keep || self.is_moved(row)
+            })
+            .map(|row| self.commits[row].index)
+            .collect()
+    }
+}
+
+// Pairs of `--batch` lines that set the same field of the same commit to
+// different values, described for the user. Lines that do not parse are left
+// for `apply_batch_line` to report.
+fn batch_conflicts(lines: &[(usize, String)]) -> Vec<String> {
+    let mut first_set: HashMap<(usize, &str), (usize, &str)> = HashMap::new();
+    let mut conflicts = Vec::new();
+
+    for (line_no, line) in lines {
+        let Some((target, value)) = line.split_once('=') else {
+            continue;
+        };
+        let Some((index, field)) = target.split_once(':') else {
+            continue;
+        };
+        let Ok(index) = index.trim().parse::<usize>() else {
+            continue;
+        };
+
+        let field = field.trim();
+        match first_set.get(&(index, field)) {
+            Some(&(first_line, first_value)) if first_value != value => {
+                conflicts.push(format!(
+                    "lines {first_line} and {line_no} set the {field} of commit {index} to '{first_value}' and '{value}'"
+                ));
+            }
+            Some(_) => {}
+            None => {
+                first_set.insert((index, field), (*line_no, value));
+            }
         }
-        return Ok((1, total_commits)); // Return 1-based indexing for all commits
     }
+    conflicts
+}
+
+/// Parses a timestamp typed into the table editor in `format` (see
+/// `--date-format`). Shared by the live hint and the save path so both accept
+/// exactly the same input.
+pub fn parse_timestamp_input(input: &str, format: &str) -> Result<NaiveDateTime> {
+    NaiveDateTime::parse_from_str(input.trim(), format)
+        .map_err(|_| format!("Invalid timestamp format (use {})", format_hint(format)).into())
+}
+
+const RANGE_FORMATS: &str = "Use 'start-end' (e.g. '5-11'), 'start-' (through the last commit), '-end' (from the first commit), a single number, or '*' for all commits";
+
+/// Parses the range typed at the prompt into 1-based inclusive positions.
+/// Either end may be left open, and a single number selects one commit.
+pub fn parse_range_input(input: &str, total_commits: usize) -> Result<(usize, usize)> {
+    let trimmed_input = input.trim();
 
-    let parts: Vec<&str> = trimmed_input.split('-').collect();
+    if total_commits == 0 {
+        return Err("No commits available to select".into());
+    }
 
-    if parts.len() != 2 {
-        return Err("Invalid range format. Use format like '5-11' or '*' for all commits".into());
+    // Check if user entered '*' to select all commits
+    if trimmed_input == "*" {
+        return Ok((1, total_commits)); // Return 1-based indexing for all commits
     }
 
-    let start = parts[0]
-        .trim()
-        .parse::<usize>()
-        .map_err(|_| "Invalid start number in range")?;
-    let end = parts[1]
-        .trim()
-        .parse::<usize>()
-        .map_err(|_| "Invalid end number in range")?;
+    let parse = |part: &str, position: &str| -> Result<usize> {
+        part.parse::<usize>()
+            .map_err(|_| format!("Invalid {position} number in range. {RANGE_FORMATS}").into())
+    };
+
+    let (start, end) = match trimmed_input.split_once('-') {
+        None => {
+            let position = parse(trimmed_input, "commit")?;
+            (position, position)
+        }
+        Some((start, end)) => {
+            let (start, end) = (start.trim(), end.trim());
+            if (start.is_empty() && end.is_empty()) || end.contains('-') {
+                return Err(format!("Invalid range format. {RANGE_FORMATS}").into());
+            }
+            let start = if start.is_empty() {
+                1
+            } else {
+                parse(start, "start")?
+            };
+            let end = if end.is_empty() {
+                total_commits
+            } else {
+                parse(end, "end")?
+            };
+            (start, end)
+        }
+    };
 
     if start < 1 {
         return Err("Start position must be 1 or greater".into());
@@ -603,10 +1570,47 @@ pub fn parse_range_input(input: &str, total_commits: usize) -> Result<(usize, us
         return Err("End position must be greater than or equal to start position".into());
     }
 
+    if end > total_commits {
+        return Err(format!("Range out of bounds. Available commits: 1-{total_commits}").into());
+    }
+
     Ok((start, end))
 }
 
-pub fn select_commit_range(commits: &[CommitInfo]) -> Result<(usize, usize)> {
+// Beyond this many rows the table editor is impractical to navigate
+const LARGE_RANGE_THRESHOLD: usize = 500;
+
+// Number of commits between two inclusive indices, in either order
+fn range_size(start_idx: usize, end_idx: usize) -> usize {
+    start_idx.abs_diff(end_idx) + 1
+}
+
+fn print_range_size(start_idx: usize, end_idx: usize, total_commits: usize) {
+    let size = range_size(start_idx, end_idx);
+    println!(
+        "{} {} of {} commits",
+        "Range covers".bold(),
+        size.to_string().green(),
+        total_commits
+    );
+    if size > LARGE_RANGE_THRESHOLD {
+        println!(
+            "{} {}",
+            "Warning:".yellow().bold(),
+            format!(
+                "{size} commits is more than {LARGE_RANGE_THRESHOLD}; the table editor will be unwieldy, consider a smaller range or --limit"
+            )
+            .yellow()
+        );
+    }
+}
+
+pub fn select_commit_range(commits: &[CommitInfo], theme: &Theme) -> Result<(usize, usize)> {
+    println!(
+        "\n{} {}",
+        "Total commits:".bold(),
+        commits.len().to_string().green()
+    );
     println!("\n{}", "Commit History:".bold().green());
     println!("{}", "-".repeat(80).cyan());
 
@@ -615,20 +1619,16 @@ pub fn select_commit_range(commits: &[CommitInfo]) -> Result<(usize, usize)> {
             "{:3}. {} {} {} {}",
             i + 1,
             commit.short_hash.yellow().bold(),
-            commit
-                .timestamp
-                .format("%Y-%m-%d %H:%M:%S")
-                .to_string()
-                .blue(),
-            commit.author_name.magenta(),
-            commit.message.lines().next().unwrap_or("").white()
+            theme.timestamp(&commit.timestamp.format("%Y-%m-%d %H:%M:%S").to_string()),
+            theme.author(&commit.author_name),
+            theme.message(commit.message.lines().next().unwrap_or(""))
         );
     }
 
     println!("{}", "-".repeat(80).cyan());
     println!(
         "\n{}",
-        "Enter range in format 'start-end' (e.g., '5-11') or '*' for all commits:"
+        "Enter range as 'start-end' (e.g., '5-11'), 'start-', '-end', a single number, or '*' for all commits:"
             .bold()
             .green()
     );
@@ -640,18 +1640,90 @@ pub fn select_commit_range(commits: &[CommitInfo]) -> Result<(usize, usize)> {
 
     let (start, end) = parse_range_input(&input, commits.len())?;
 
-    if start > commits.len() || end > commits.len() {
+    print_range_size(start - 1, end - 1, commits.len());
+    Ok((start - 1, end - 1)) // Convert to 0-based indexing
+}
+
+/// Resolves `--tail N` (the N newest commits) or `--head N` (the N oldest) to
+/// indices into `total_commits` commits ordered newest first.
+pub fn quick_range(
+    total_commits: usize,
+    tail: Option<usize>,
+    head: Option<usize>,
+) -> Result<(usize, usize)> {
+    let (flag, n) = match (tail, head) {
+        (Some(n), None) => ("--tail", n),
+        (None, Some(n)) => ("--head", n),
+        _ => return Err("Use either --tail or --head, not both".into()),
+    };
+    if n == 0 {
+        return Err(format!("{flag} must be greater than 0").into());
+    }
+    if n > total_commits {
         return Err(format!(
-            "Range out of bounds. Available commits: 1-{}",
-            commits.len()
+            "{flag} {n} is out of bounds: the history has {total_commits} commit(s)"
         )
         .into());
     }
 
-    Ok((start - 1, end - 1)) // Convert to 0-based indexing
+    if flag == "--tail" {
+        Ok((0, n - 1))
+    } else {
+        Ok((total_commits - n, total_commits - 1))
+    }
+}
+
+/// Resolves `--from`/`--to` to indices into `commits` (newest first), so the
+/// range can be given by commit instead of at the prompt. A missing `--from`
+/// means the oldest loaded commit, a missing `--to` the newest.
+pub fn resolve_range_anchors(
+    repo: &Repository,
+    commits: &[CommitInfo],
+    from: Option<&str>,
+    to: Option<&str>,
+) -> Result<(usize, usize)> {
+    let find = |spec: &str, flag: &str| -> Result<(usize, git2::Oid)> {
+        let oid = repo
+            .revparse_single(spec)
+            .and_then(|object| object.peel_to_commit())
+            .map_err(|_| format!("{flag} {spec} does not name a commit"))?
+            .id();
+        let index = commits.iter().position(|c| c.oid == oid).ok_or_else(|| {
+            format!("{flag} {spec} is not in the loaded history (check --ref and --limit)")
+        })?;
+        Ok((index, oid))
+    };
+
+    let (end_idx, from_oid) = match from {
+        Some(spec) => find(spec, "--from")?,
+        None => (commits.len() - 1, commits[commits.len() - 1].oid),
+    };
+    let (start_idx, to_oid) = match to {
+        Some(spec) => find(spec, "--to")?,
+        None => (0, commits[0].oid),
+    };
+
+    if from_oid != to_oid && !repo.graph_descendant_of(to_oid, from_oid)? {
+        return Err(format!(
+            "--from {} is not an ancestor of --to {}",
+            &from_oid.to_string()[..8],
+            &to_oid.to_string()[..8]
+        )
+        .into());
+    }
+    if start_idx > end_idx {
+        return Err("--from must be older than --to".into());
+    }
+
+    Ok((start_idx, end_idx))
 }
 
-pub fn show_range_details(commits: &[CommitInfo], start_idx: usize, end_idx: usize) -> Result<()> {
+pub fn show_range_details(
+    commits: &[CommitInfo],
+    start_idx: usize,
+    end_idx: usize,
+    theme: &Theme,
+) -> Result<()> {
     let total_selected = end_idx - start_idx + 1;
     let is_all_commits = total_selected == commits.len();
 
@@ -670,23 +1742,20 @@ pub fn show_range_details(commits: &[CommitInfo], start_idx: usize, end_idx: usi
             &commit.oid.to_string()[..8]
         );
         println!(
-            "{}: {}",
+            "{}: {} <{}>",
             "Author".bold(),
-            format!("{} <{}>", commit.author_name, commit.author_email).magenta()
+            theme.author(&commit.author_name),
+            theme.email(display_email(&commit.author_email))
         );
         println!(
             "{}: {}",
             "Date".bold(),
-            commit
-                .timestamp
-                .format("%Y-%m-%d %H:%M:%S")
-                .to_string()
-                .blue()
+            theme.timestamp(&commit.timestamp.format("%Y-%m-%d %H:%M:%S").to_string())
         );
         println!(
             "{}: {}",
             "Message".bold(),
-            commit.message.lines().next().unwrap_or("").white()
+            theme.message(commit.message.lines().next().unwrap_or(""))
         );
     }
 
@@ -709,6 +1778,45 @@ pub fn show_range_details(commits: &[CommitInfo], start_idx: usize, end_idx: usi
     Ok(())
 }
 
+// One line on what the editor is about to cover, e.g. "3 of 40 commits, 2 authors, 1 merge"
+fn preflight_summary(commits: &[CommitInfo], start_idx: usize, end_idx: usize) -> String {
+    let selected = &commits[start_idx..=end_idx];
+    let authors: HashSet<&str> = selected.iter().map(|c| c.author_email.as_str()).collect();
+    let merges = selected.iter().filter(|c| c.parent_count > 1).count();
+    let plural = |n: usize, word: &str| format!("{n} {word}{}", if n == 1 { "" } else { "s" });
+
+    format!(
+        "{} of {} commits, {}, {}",
+        selected.len(),
+        commits.len(),
+        plural(authors.len(), "author"),
+        plural(merges, "merge")
+    )
+}
+
+/// Waits for Enter before the editor switches the terminal to raw mode, so
+/// the range can still be backed out of with `q` while the shell is intact.
+fn confirm_editor_launch(commits: &[CommitInfo], start_idx: usize, end_idx: usize) -> Result<bool> {
+    println!(
+        "\n{} {}",
+        "About to edit:".bold(),
+        preflight_summary(commits, start_idx, end_idx).green()
+    );
+    print!(
+        "{} ",
+        "Press Enter to open the editor, or 'q' to cancel:".bold()
+    );
+    io::stdout().flush()?;
+
+    let mut input = String::new();
+    io::stdin().read_line(&mut input)?;
+
+    Ok(!matches!(
+        input.trim().to_lowercase().as_str(),
+        "q" | "quit" | "n" | "no"
+    ))
+}
+
 pub fn get_range_edit_info(args: &Args) -> Result<(String, String, NaiveDateTime, NaiveDateTime)> {
     println!("\n{}", "Range Edit Configuration:".bold().green());
 
@@ -734,29 +1842,31 @@ pub fn get_range_edit_info(args: &Args) -> Result<(String, String, NaiveDateTime
         input.trim().to_string()
     };
 
+    let format = args.date_format();
+    let hint = format_hint(format);
+
     // Get start timestamp
     let start_timestamp = if let Some(start) = &args.start {
-        NaiveDateTime::parse_from_str(start, "%Y-%m-%d %H:%M:%S")
+        NaiveDateTime::parse_from_str(start, format)
             .map_err(|_| "Invalid start timestamp format")?
     } else {
-        print!("{} ", "Start timestamp (YYYY-MM-DD HH:MM:SS):".bold());
+        print!("{} ", format!("Start timestamp ({hint}):").bold());
         io::stdout().flush()?;
         let mut input = String::new();
         io::stdin().read_line(&mut input)?;
-        NaiveDateTime::parse_from_str(input.trim(), "%Y-%m-%d %H:%M:%S")
+        NaiveDateTime::parse_from_str(input.trim(), format)
             .map_err(|_| "Invalid start timestamp format")?
     };
 
     // Get end timestamp
     let end_timestamp = if let Some(end) = &args.end {
-        NaiveDateTime::parse_from_str(end, "%Y-%m-%d %H:%M:%S")
-            .map_err(|_| "Invalid end timestamp format")?
+        NaiveDateTime::parse_from_str(end, format).map_err(|_| "Invalid end timestamp format")?
     } else {
-        print!("{} ", "End timestamp (YYYY-MM-DD HH:MM:SS):".bold());
+        print!("{} ", format!("End timestamp ({hint}):").bold());
         io::stdout().flush()?;
         let mut input = String::new();
         io::stdin().read_line(&mut input)?;
-        NaiveDateTime::parse_from_str(input.trim(), "%Y-%m-%d %H:%M:%S")
+        NaiveDateTime::parse_from_str(input.trim(), format)
             .map_err(|_| "Invalid end timestamp format")?
     };
 
@@ -788,43 +1898,178 @@ pub fn generate_range_timestamps(
         .collect()
 }
 
-pub fn rewrite_range_commits(args: &Args) -> Result<()> {
+/// Moves `timestamps` (oldest commit first) so the first one lands on `anchor`,
+/// keeping every commit's original offset from it.
+pub fn shift_range_timestamps(
+    timestamps: &[NaiveDateTime],
+    anchor: NaiveDateTime,
+) -> Vec<NaiveDateTime> {
+    let Some(&first) = timestamps.first() else {
+        return vec![];
+    };
+
+    timestamps
+        .iter()
+        .map(|&timestamp| anchor + timestamp.signed_duration_since(first))
+        .collect()
+}
+
+pub fn rewrite_range_commits(args: &Args) -> Result<Outcome> {
     let commits = get_commit_history(args, false)?;
 
     if commits.is_empty() {
         println!("{}", "No commits found!".red());
-        return Ok(());
+        return Ok(Outcome::NothingToDo);
     }
 
-    let (start_idx, end_idx) = select_commit_range(&commits)?;
+    let (start_idx, end_idx) = if args.range_from.is_some() || args.range_to.is_some() {
+        let repo = Repository::open(args.repo_path.as_ref().unwrap())?;
+        let (start_idx, end_idx) = resolve_range_anchors(
+            &repo,
+            &commits,
+            args.range_from.as_deref(),
+            args.range_to.as_deref(),
+        )?;
+        if !args.quiet {
+            print_range_size(start_idx, end_idx, commits.len());
+        }
+        (start_idx, end_idx)
+    } else if args.range_tail.is_some() || args.range_head.is_some() {
+        let (start_idx, end_idx) = quick_range(commits.len(), args.range_tail, args.range_head)?;
+        if !args.quiet {
+            print_range_size(start_idx, end_idx, commits.len());
+        }
+        (start_idx, end_idx)
+    } else {
+        select_commit_range(&commits, &args.theme)?
+    };
 
     // Show range details for user feedback
-    show_range_details(&commits, start_idx, end_idx)?;
+    if !args.quiet {
+        show_range_details(&commits, start_idx, end_idx, &args.theme)?;
+    }
+    // A batch run has its edits on stdin and no editor to launch
+    if !args.batch && !args.yes && !confirm_editor_launch(&commits, start_idx, end_idx)? {
+        println!("{}", "Operation cancelled.".yellow());
+        return Ok(Outcome::Cancelled);
+    }
 
     // Get editable fields based on command line flags
     let editable_fields = args.get_editable_fields();
 
+    edit_commits_interactively(args, &commits, start_idx, end_idx, editable_fields)
+}
+
+// Opens the interactive table over commits[start_idx..=end_idx], then confirms and applies the edits
+pub fn edit_commits_interactively(
+    args: &Args,
+    commits: &[CommitInfo],
+    start_idx: usize,
+    end_idx: usize,
+    editable_fields: (bool, bool, bool, bool),
+) -> Result<Outcome> {
+    // Check before the editor opens so no edits are lost to a refusal afterwards.
+    // A simulation never writes, so it works on any worktree.
+    if !args.simulate {
+        prepare_rewrite(args)?;
+    }
+
     // Launch interactive table editor
-    let mut table = InteractiveTable::new(commits.clone(), start_idx, end_idx, editable_fields);
-    let should_save = table.run()?;
+    let mut table = InteractiveTable::new(commits.to_vec(), start_idx, end_idx, editable_fields);
+    table.date_format = args.date_format().to_string();
+    table.allowed_email_domains = args.allowed_email_domains.clone();
+    table.allow_empty_email = args.allow_empty_email;
+    table.theme = args.theme;
+    if args.committer_column {
+        table.show_committer_column();
+    }
+    if let Some(anchor) = &args.shift_anchor {
+        table.shift_timestamps(parse_timestamp_input(anchor, args.date_format())?);
+    }
+    let transforms = MessageTransforms::from_args(args)?;
+    if !transforms.is_empty() {
+        table.transform_messages(&transforms);
+    }
+    let should_save = if args.batch {
+        let applied = table.apply_batch_edits(io::stdin().lock())?;
+        if !args.quiet {
+            println!("{}", format!("Read {applied} edit(s) from stdin").cyan());
+        }
+        true
+    } else {
+        table.run()?
+    };
 
     if !should_save {
         println!("{}", "Operation cancelled.".yellow());
-        return Ok(());
+        return Ok(Outcome::Cancelled);
     }
 
+    if args.signoff {
+        table.sign_off_modified();
+    }
     let modified_commits = table.get_modified_commits();
+    let reordered = table.is_reordered();
 
-    if modified_commits.is_empty() {
+    if modified_commits.is_empty() && !reordered && !table.has_row_actions() {
         println!("{}", "No changes made.".yellow());
-        return Ok(());
+        return Ok(Outcome::NothingToDo);
+    }
+
+    if args.simulate {
+        let edits: HashMap<git2::Oid, EditOptions> = modified_commits
+            .iter()
+            .map(|commit_edit| (commit_edit.original.oid, commit_edit.edit_options()))
+            .collect();
+        let result = create_range_simulation(commits, (start_idx, end_idx), &edits)?;
+        print_simulation_result(&result, args.show_diff, args.preview_rows);
+        if reordered {
+            print_new_order(&table.commits);
+        }
+        print_row_actions(&table.commits);
+        return Ok(Outcome::Done);
     }
 
     // Show summary of changes
+    if !args.quiet {
+        print_change_summary(args, &table, &modified_commits, reordered);
+    }
+    if !confirm("Apply these changes?", args.yes)? {
+        println!("{}", "Operation cancelled.".yellow());
+        return Ok(Outcome::Cancelled);
+    }
+
+    // Apply changes
+    apply_interactive_range_changes(args, commits, &table.commits)?;
+
+    if !args.quiet {
+        println!("\n{}", "✓ Commit range successfully edited!".green().bold());
+    }
+
+    if args.show_history {
+        print_commit_history(args)?;
+    }
+
+    Ok(Outcome::Done)
+}
+
+// Lists what the confirmation applies: the new order, the row actions and
+// every field changed, followed by the chained rewrite notice
+fn print_change_summary(
+    args: &Args,
+    table: &InteractiveTable,
+    modified_commits: &[&CommitEdit],
+    reordered: bool,
+) {
     println!("\n{}", "Summary of Changes:".bold().green());
     println!("{}", "=".repeat(80).cyan());
 
-    for commit_edit in &modified_commits {
+    if reordered {
+        print_new_order(&table.commits);
+    }
+    print_row_actions(&table.commits);
+
+    for commit_edit in modified_commits {
         println!(
             "\n{}: {} ({})",
             format!("Commit {}", commit_edit.index + 1).bold(),
@@ -832,179 +2077,487 @@ pub fn rewrite_range_commits(args: &Args) -> Result<()> {
             &commit_edit.original.oid.to_string()[..8]
         );
 
-        if commit_edit.modifications.author_name_changed {
+        if commit_edit.original.parent_count > 1 {
             println!(
-                "  {}: {} -> {}",
-                "Author Name".bold(),
-                commit_edit.original.author_name.red(),
-                commit_edit.author_name.green()
+                "  {}",
+                format!(
+                    "Merge commit: all {} parents are preserved, only metadata changes",
+                    commit_edit.original.parent_count
+                )
+                .bright_black()
+            );
+        }
+        if commit_edit.original.parent_count == 0 {
+            println!(
+                "  {}",
+                "Root commit: it stays the root and every later commit is re-attached to it"
+                    .bright_black()
+            );
+        }
+
+        if commit_edit.modifications.author_name_changed {
+            print_change(
+                "Author Name",
+                &commit_edit.original.author_name,
+                &commit_edit.author_name,
             );
         }
 
         if commit_edit.modifications.author_email_changed {
-            println!(
-                "  {}: {} -> {}",
-                "Author Email".bold(),
-                commit_edit.original.author_email.red(),
-                commit_edit.author_email.green()
+            print_change(
+                "Author Email",
+                display_email(&commit_edit.original.author_email),
+                display_email(&commit_edit.author_email),
             );
         }
 
         if commit_edit.modifications.timestamp_changed {
-            println!(
-                "  {}: {} -> {}",
-                "Timestamp".bold(),
-                commit_edit
-                    .original
+            let (label, original) = if args.committer_column {
+                ("Author Date", commit_edit.original.author_timestamp)
+            } else {
+                ("Timestamp", commit_edit.original.timestamp)
+            };
+            print_change(
+                label,
+                &original.format("%Y-%m-%d %H:%M:%S").to_string(),
+                &commit_edit
                     .timestamp
                     .format("%Y-%m-%d %H:%M:%S")
-                    .to_string()
-                    .red(),
-                commit_edit
+                    .to_string(),
+            );
+        }
+
+        if commit_edit.modifications.committer_timestamp_changed {
+            print_change(
+                "Committer Date",
+                &commit_edit
+                    .original
                     .timestamp
                     .format("%Y-%m-%d %H:%M:%S")
-                    .to_string()
-                    .green()
+                    .to_string(),
+                &commit_edit
+                    .committer_timestamp
+                    .format("%Y-%m-%d %H:%M:%S")
+                    .to_string(),
             );
         }
 
         if commit_edit.modifications.message_changed {
-            let original_first_line = commit_edit.original.message.lines().next().unwrap_or("");
-            let new_first_line = commit_edit.message.lines().next().unwrap_or("");
-            println!(
-                "  {}: {} -> {}",
-                "Message".bold(),
-                original_first_line.red(),
-                new_first_line.green()
+            print_change(
+                "Message",
+                commit_edit.original.message.lines().next().unwrap_or(""),
+                commit_edit.message.lines().next().unwrap_or(""),
             );
         }
     }
 
-    print!("\n{} (y/n): ", "Apply these changes?".bold());
-    io::stdout().flush()?;
+    if let Some(notice) = chained_rewrite_notice(&table.edited_indices()) {
+        println!("\n{}", notice.yellow());
+    }
+}
 
-    let mut confirm = String::new();
-    io::stdin().read_line(&mut confirm)?;
+// Rows start out in their original order, so a row whose commit came from
+// another position has been moved
+fn is_moved(commits: &[CommitEdit], row: usize) -> bool {
+    let first = commits.iter().map(|commit| commit.index).min();
+    first.is_some_and(|first| commits[row].index != first + row)
+}
 
-    if confirm.trim().to_lowercase() != "y" {
-        println!("{}", "Operation cancelled.".yellow());
-        return Ok(());
+// Lists the rows newest first as they will be written, marking the moved ones
+fn print_new_order(commits: &[CommitEdit]) {
+    println!("\n{}", "New order (newest first):".bold());
+    for (row, commit) in commits.iter().enumerate() {
+        let line = format!(
+            "  {} {}",
+            commit.original.short_hash,
+            commit.original.message.lines().next().unwrap_or("")
+        );
+        if is_moved(commits, row) {
+            println!(
+                "{}",
+                format!("{line} (moved from #{})", commit.index + 1).cyan()
+            );
+        } else {
+            println!("{}", line.bright_black());
+        }
     }
+}
 
-    // Apply changes
-    apply_interactive_range_changes(args, &commits, &table.commits)?;
+// Lists the dropped, squashed and fixed-up rows. Dropping a commit takes its
+// changes out of every later commit, which is replayed without them; one that
+// builds on those changes stops the rewrite with a conflict, so say so before
+// asking for confirmation.
+fn print_row_actions(commits: &[CommitEdit]) {
+    let sections = [
+        (
+            RowAction::Drop,
+            "Dropped commits:",
+            "Later commits are replayed without these changes; if they build on them the content may conflict and nothing is applied",
+        ),
+        (
+            RowAction::Squash,
+            "Squashed into the commit below (messages combined):",
+            "The combined commit keeps the older commit's author and date",
+        ),
+        (
+            RowAction::Fixup,
+            "Fixed up into the commit below (messages discarded):",
+            "The combined commit keeps the older commit's author, date and message",
+        ),
+    ];
+
+    for (action, heading, note) in sections {
+        let marked: Vec<_> = commits.iter().filter(|c| c.action == action).collect();
+        if marked.is_empty() {
+            continue;
+        }
+        println!("\n{}", heading.bold());
+        for commit in marked {
+            let first_line = commit.original.message.lines().next().unwrap_or("");
+            if action == RowAction::Drop {
+                println!(
+                    "  {} {}",
+                    commit.original.short_hash.red().strikethrough(),
+                    first_line.red().strikethrough()
+                );
+            } else {
+                println!("  {} {}", commit.original.short_hash.yellow(), first_line);
+            }
+        }
+        println!("  {}", note.yellow());
+    }
+}
 
-    println!("\n{}", "✓ Commit range successfully edited!".green().bold());
+// The edited rows oldest first, in their original order and in the order the
+// user left them. Both are equal unless rows were moved.
+fn range_orders(edited_commits: &[CommitEdit]) -> (Vec<git2::Oid>, Vec<git2::Oid>) {
+    let mut original: Vec<_> = edited_commits.iter().collect();
+    original.sort_by_key(|commit| std::cmp::Reverse(commit.index));
+    let original = original.iter().map(|commit| commit.original.oid).collect();
+    let new = edited_commits
+        .iter()
+        .rev()
+        .map(|commit| commit.original.oid)
+        .collect();
+    (original, new)
+}
 
-    if args.show_history {
-        get_commit_history(args, true)?;
-    }
+// Rewrites `onto` with the tree of the commit squashed into it. The result
+// keeps the parents, author, committer and (for a fixup) the message of
+// `onto`; a squash appends the squashed commit's message.
+fn squash_commit(
+    repo: &Repository,
+    onto: &git2::Commit,
+    tree: git2::Oid,
+    squashed: &CommitEdit,
+) -> Result<git2::Oid> {
+    let combined = match squashed.action {
+        RowAction::Squash => Some(format!(
+            "{}\n\n{}",
+            String::from_utf8_lossy(onto.message_raw_bytes()).trim_end(),
+            squashed.message
+        )),
+        _ => None,
+    };
+    let parents: Vec<_> = onto.parents().collect();
+    rewrite_commit_with_tree(
+        repo,
+        onto,
+        tree,
+        &parents,
+        &onto.author(),
+        &onto.committer(),
+        combined.as_deref(),
+    )
+}
+
+// The commit a child of `oid` points at after the rewrite; None once `oid`
+// was a dropped root
+fn rewritten_parent(
+    parent_map: &HashMap<git2::Oid, Option<git2::Oid>>,
+    oid: git2::Oid,
+) -> Option<git2::Oid> {
+    parent_map.get(&oid).copied().unwrap_or(Some(oid))
+}
 
+// Moving commits only makes sense along a single line of history: every
+// commit of the range, oldest first, has one parent and that parent is the
+// commit before it
+fn ensure_linear_range(repo: &Repository, oldest_first: &[git2::Oid]) -> Result<()> {
+    for (i, &oid) in oldest_first.iter().enumerate() {
+        let commit = repo.find_commit(oid)?;
+        let follows_previous = i == 0 || commit.parent_id(0).ok() == Some(oldest_first[i - 1]);
+        if commit.parent_count() != 1 || !follows_previous {
+            return Err(format!(
+                "Cannot reorder commits: {} is a merge, the root, or not on the same line of history as the rest of the range",
+                &oid.to_string()[..8]
+            )
+            .into());
+        }
+    }
     Ok(())
 }
 
+// Replays the change `orig` made to its first parent on top of `onto` (no
+// parent once the commits below it were dropped), the way a rebase would, and
+// returns the resulting tree. While `onto` has the same tree as the original
+// parent, the original tree is kept as-is. A merge is replayed against its
+// first parent only.
+fn replay_tree(
+    repo: &Repository,
+    orig: &git2::Commit,
+    onto: Option<&git2::Commit>,
+) -> Result<git2::Oid> {
+    if orig.parent_count() == 0 {
+        return Ok(orig.tree_id());
+    }
+    let base = orig.parent(0)?.tree()?;
+    let onto_tree = match onto {
+        Some(onto) => onto.tree()?,
+        None => repo.find_tree(repo.treebuilder(None)?.write()?)?,
+    };
+    if onto_tree.id() == base.id() {
+        return Ok(orig.tree_id());
+    }
+
+    let mut index = repo.merge_trees(&base, &onto_tree, &orig.tree()?, None)?;
+    if index.has_conflicts() {
+        return Err(format!(
+            "Cannot replay commit {}: its changes conflict with the commits moved or dropped before it",
+            &orig.id().to_string()[..8]
+        )
+        .into());
+    }
+    Ok(index.write_tree_to(repo)?)
+}
+
 fn apply_interactive_range_changes(
     args: &Args,
     _original_commits: &[CommitInfo],
     edited_commits: &[CommitEdit],
 ) -> Result<()> {
+    // The bottom row's parent is outside the range, so there is nothing of the
+    // range to fold it into
+    if let Some(oldest) = edited_commits.last().filter(|c| c.action.is_folded()) {
+        return Err(format!(
+            "Cannot squash {}: it is the oldest commit of the range",
+            oldest.original.short_hash
+        )
+        .into());
+    }
     let repo = Repository::open(args.repo_path.as_ref().unwrap())?;
-    let head_ref = repo.head()?;
-    let branch_name = head_ref
-        .shorthand()
-        .ok_or("Detached HEAD or invalid branch")?;
-    let full_ref = format!("refs/heads/{branch_name}");
+    let target = RewriteTarget::resolve(&repo, args)?;
 
     let mut revwalk = repo.revwalk()?;
-    revwalk.push_head()?;
+    revwalk.push(target.tip(&repo)?)?;
     revwalk.set_sorting(Sort::TOPOLOGICAL | Sort::TIME)?;
     let mut orig_oids: Vec<_> = revwalk.filter_map(|id| id.ok()).collect();
     orig_oids.reverse();
 
-    // Create a map for quick lookup of edited commits
-    let mut edit_map: HashMap<usize, &CommitEdit> = HashMap::new();
+    // Create a map for quick lookup of edited commits. Edits are keyed by oid
+    // because the edited window may be a --limit'ed slice of the history.
+    let mut edit_map: HashMap<git2::Oid, &CommitEdit> = HashMap::new();
+    let mut dropped = HashSet::new();
+    let mut squashed: HashMap<git2::Oid, &CommitEdit> = HashMap::new();
     for commit_edit in edited_commits {
-        if commit_edit.is_modified {
-            edit_map.insert(commit_edit.index, commit_edit);
+        match commit_edit.action {
+            RowAction::Drop => {
+                dropped.insert(commit_edit.original.oid);
+            }
+            RowAction::Squash | RowAction::Fixup => {
+                squashed.insert(commit_edit.original.oid, commit_edit);
+            }
+            RowAction::Keep if commit_edit.is_modified => {
+                edit_map.insert(commit_edit.original.oid, commit_edit);
+            }
+            RowAction::Keep => {}
+        }
+    }
+
+    // Moved rows take the slots of the range in their new order. Every commit
+    // of the range after the first moved one is replayed onto its new parent,
+    // and whatever followed the range is re-attached to its new newest commit.
+    let (range_oids, new_order) = range_orders(edited_commits);
+    let reordered = range_oids != new_order;
+    if reordered {
+        ensure_linear_range(&repo, &range_oids)?;
+    }
+    let mut apply_order = orig_oids.clone();
+    if reordered {
+        let mut moved = new_order.iter();
+        for slot in apply_order.iter_mut() {
+            if range_oids.contains(slot) {
+                *slot = *moved.next().unwrap();
+            }
         }
     }
+    let range_base = match range_oids.first() {
+        Some(&oldest) if reordered => Some(repo.find_commit(oldest)?.parent_id(0)?),
+        _ => None,
+    };
+    let range_top = range_oids.last().copied();
 
     let mut new_map: HashMap<git2::Oid, git2::Oid> = HashMap::new();
+    // What a child of each written commit points at instead. It differs from
+    // new_map for the range's original newest commit once rows moved, and for
+    // a dropped commit, which hands its children to its own parent (or leaves
+    // them parentless when it was the root).
+    let mut parent_map: HashMap<git2::Oid, Option<git2::Oid>> = HashMap::new();
+    // Rewritten oid of the last range commit written while replaying moved rows
+    let mut previous_in_range = None;
     let mut last_new_oid = None;
-
-    for (commit_idx, &oid) in orig_oids.iter().enumerate() {
+    let start = skip_unchanged_prefix(&apply_order, &mut new_map, |oid| {
+        Ok(edit_map.contains_key(&oid)
+            || dropped.contains(&oid)
+            || squashed.contains_key(&oid)
+            || (reordered && range_oids.contains(&oid)))
+    })?;
+
+    for &oid in &apply_order[start..] {
         let orig = repo.find_commit(oid)?;
-        let tree = orig.tree()?;
-
-        let new_parents: Result<Vec<_>> = orig
-            .parent_ids()
-            .map(|pid| {
-                let new_pid = *new_map.get(&pid).unwrap_or(&pid);
-                repo.find_commit(new_pid).map_err(|e| e.into())
-            })
-            .collect();
+        let in_moved_range = reordered && range_oids.contains(&oid);
+
+        // The walk is oldest first, so a root commit is written before anything
+        // that points at it: it stays parentless and its descendants pick up the
+        // rewritten root through parent_map
+        let new_parent_ids: Vec<git2::Oid> = if in_moved_range {
+            previous_in_range
+                .or_else(|| range_base.and_then(|base| rewritten_parent(&parent_map, base)))
+                .into_iter()
+                .collect()
+        } else {
+            let mut ids = Vec::new();
+            for pid in orig.parent_ids() {
+                // Two parents can collapse into one when the commits between them are dropped
+                if let Some(new_pid) = rewritten_parent(&parent_map, pid) {
+                    if !ids.contains(&new_pid) {
+                        ids.push(new_pid);
+                    }
+                }
+            }
+            ids
+        };
 
-        let new_oid = if let Some(commit_edit) = edit_map.get(&commit_idx) {
-            // This commit has been edited - apply changes
-            let author_sig = Signature::new(
-                &commit_edit.author_name,
-                &commit_edit.author_email,
-                &Time::new(commit_edit.timestamp.and_utc().timestamp(), 0),
-            )?;
+        if dropped.contains(&oid) {
+            let replacement = new_parent_ids.first().copied();
+            parent_map.insert(oid, replacement);
+            if in_moved_range {
+                parent_map.insert(range_top.unwrap(), replacement);
+            }
+            if let Some(replacement) = replacement {
+                new_map.insert(oid, replacement);
+            }
+            last_new_oid = replacement;
+            continue;
+        }
 
-            let committer_sig = Signature::new(
+        let new_parents = new_parent_ids
+            .iter()
+            .map(|&id| repo.find_commit(id))
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+        let tree = replay_tree(&repo, &orig, new_parents.first())?;
+
+        let new_oid = if let Some(commit_edit) = squashed.get(&oid) {
+            let Some(onto) = new_parents.first() else {
+                return Err(format!(
+                    "Cannot squash {}: there is no commit below it left to squash into",
+                    &oid.to_string()[..8]
+                )
+                .into());
+            };
+            let new_oid = squash_commit(&repo, onto, tree, commit_edit)?;
+            // Whatever stood for the commit squashed into now stands for both
+            for value in new_map.values_mut() {
+                if *value == onto.id() {
+                    *value = new_oid;
+                }
+            }
+            new_oid
+        } else if let Some(commit_edit) = edit_map.get(&oid) {
+            // This commit has been edited - apply changes. With --committer-column
+            // each date only changes when its own cell was edited.
+            let edited_time = |time: NaiveDateTime| Time::new(time.and_utc().timestamp(), 0);
+            let flags = &commit_edit.modifications;
+            let (author_time, committer_time) = if args.committer_column {
+                (
+                    if flags.timestamp_changed {
+                        edited_time(commit_edit.timestamp)
+                    } else {
+                        orig.author().when()
+                    },
+                    if flags.committer_timestamp_changed {
+                        edited_time(commit_edit.committer_timestamp)
+                    } else {
+                        orig.committer().when()
+                    },
+                )
+            } else {
+                let time = edited_time(commit_edit.timestamp);
+                (time, time)
+            };
+            let author_sig = new_signature(
                 &commit_edit.author_name,
                 &commit_edit.author_email,
-                &Time::new(commit_edit.timestamp.and_utc().timestamp(), 0),
+                &author_time,
             )?;
 
-            // Use the edited message or keep the original if not changed
-            let message = if commit_edit.modifications.message_changed {
-                &commit_edit.message
+            let committer_sig = if args.preserve_committer_identity {
+                original_committer_at(&orig, &committer_time)?
             } else {
-                orig.message().unwrap_or_default()
+                new_signature(
+                    &commit_edit.author_name,
+                    &commit_edit.author_email,
+                    &committer_time,
+                )?
             };
 
-            repo.commit(
-                None,
+            // Use the edited message or keep the original (raw) one if not changed
+            let message = commit_edit
+                .modifications
+                .message_changed
+                .then_some(commit_edit.message.as_str());
+
+            rewrite_commit_with_tree(
+                &repo,
+                &orig,
+                tree,
+                &new_parents,
                 &author_sig,
                 &committer_sig,
                 message,
-                &tree,
-                &new_parents?.iter().collect::<Vec<_>>(),
             )?
         } else {
             // Keep other commits as-is but update parent references
             let author = orig.author();
             let committer = orig.committer();
 
-            repo.commit(
-                None,
-                &author,
-                &committer,
-                orig.message().unwrap_or_default(),
-                &tree,
-                &new_parents?.iter().collect::<Vec<_>>(),
-            )?
+            rewrite_commit_with_tree(&repo, &orig, tree, &new_parents, &author, &committer, None)?
         };
 
         new_map.insert(oid, new_oid);
+        parent_map.insert(oid, Some(new_oid));
+        if in_moved_range {
+            previous_in_range = Some(new_oid);
+            parent_map.insert(range_top.unwrap(), Some(new_oid));
+        }
         last_new_oid = Some(new_oid);
     }
 
+    if last_new_oid.is_none() && !orig_oids.is_empty() {
+        return Err("Cannot drop every commit of the history".into());
+    }
+
     if let Some(new_head) = last_new_oid {
-        repo.reference(
-            &full_ref,
+        finish_rewrite(
+            &repo,
+            args,
+            &target,
             new_head,
-            true,
-            "edited commit range interactively",
+            &orig_oids,
+            &new_map,
+            "range edit",
         )?;
-        println!(
-            "{} '{}' -> {}",
-            "Updated branch".green(),
-            branch_name.cyan(),
-            new_head.to_string()[..8].to_string().cyan()
-        );
+        print_new_tip(args, &target, new_head);
     }
 
     Ok(())
@@ -1066,16 +2619,58 @@ mod tests {
         (temp_dir, repo_path)
     }
 
-    #[test]
-    fn test_parse_range_input_valid() {
-        let result = parse_range_input("5-11", 20);
-        assert!(result.is_ok());
-        let (start, end) = result.unwrap();
-        assert_eq!(start, 5);
-        assert_eq!(end, 11);
-    }
-
-    #[test]
+    fn create_test_repo_with_merge() -> (TempDir, String) {
+        let temp_dir = TempDir::new().unwrap();
+        let repo_path = temp_dir.path().to_str().unwrap().to_string();
+        let repo = git2::Repository::init(&repo_path).unwrap();
+
+        // root -> side \
+        //      -> main -> merge
+        let commit_file = |name: &str, message: &str, time: i64, parents: &[&git2::Commit]| {
+            fs::write(temp_dir.path().join(name), message).unwrap();
+            let mut index = repo.index().unwrap();
+            index.add_path(std::path::Path::new(name)).unwrap();
+            index.write().unwrap();
+            let tree = repo.find_tree(index.write_tree().unwrap()).unwrap();
+            let sig =
+                git2::Signature::new("Test User", "test@example.com", &git2::Time::new(time, 0))
+                    .unwrap();
+            repo.commit(None, &sig, &sig, message, &tree, parents)
+                .unwrap()
+        };
+
+        let root = repo
+            .find_commit(commit_file("root.txt", "Root commit", 1234567890, &[]))
+            .unwrap();
+        let side = repo
+            .find_commit(commit_file("side.txt", "Side commit", 1234571490, &[&root]))
+            .unwrap();
+        let main = repo
+            .find_commit(commit_file("main.txt", "Main commit", 1234575090, &[&root]))
+            .unwrap();
+        let merge = commit_file(
+            "merge.txt",
+            "Merge side into main",
+            1234578690,
+            &[&main, &side],
+        );
+        repo.reference("refs/heads/main", merge, true, "test setup")
+            .unwrap();
+        repo.set_head("refs/heads/main").unwrap();
+
+        (temp_dir, repo_path)
+    }
+
+    #[test]
+    fn test_parse_range_input_valid() {
+        let result = parse_range_input("5-11", 20);
+        assert!(result.is_ok());
+        let (start, end) = result.unwrap();
+        assert_eq!(start, 5);
+        assert_eq!(end, 11);
+    }
+
+    #[test]
     fn test_parse_range_input_with_spaces() {
         let result = parse_range_input(" 3 - 8 ", 20);
         assert!(result.is_ok());
@@ -1099,7 +2694,7 @@ mod tests {
 
     #[test]
     fn test_parse_range_input_invalid_format() {
-        let result = parse_range_input("5", 20);
+        let result = parse_range_input("-", 20);
         assert!(result.is_err());
 
         let result = parse_range_input("5-11-15", 20);
@@ -1116,6 +2711,53 @@ mod tests {
 
         let result = parse_range_input("0-5", 20);
         assert!(result.is_err());
+
+        let result = parse_range_input("5-21", 20);
+        assert!(result.is_err());
+
+        let result = parse_range_input("21-", 20);
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_parse_range_input_open_ended() {
+        assert_eq!(parse_range_input("5-", 20).unwrap(), (5, 20));
+        assert_eq!(parse_range_input("-8", 20).unwrap(), (1, 8));
+        assert_eq!(parse_range_input(" 7 ", 20).unwrap(), (7, 7));
+        assert_eq!(parse_range_input("20-", 20).unwrap(), (20, 20));
+
+        let err = parse_range_input("x", 20).unwrap_err();
+        assert!(err.to_string().contains("'start-'"), "{err}");
+    }
+
+    #[test]
+    fn test_parse_timestamp_input() {
+        let expected =
+            NaiveDateTime::parse_from_str("2023-05-01 12:30:00", "%Y-%m-%d %H:%M:%S").unwrap();
+        assert_eq!(
+            parse_timestamp_input("2023-05-01 12:30:00", DEFAULT_DATE_FORMAT).unwrap(),
+            expected
+        );
+        assert_eq!(
+            parse_timestamp_input(" 2023-05-01 12:30:00 ", DEFAULT_DATE_FORMAT).unwrap(),
+            expected
+        );
+        assert!(parse_timestamp_input("2023-05-01", DEFAULT_DATE_FORMAT).is_err());
+        assert!(parse_timestamp_input("2023-13-01 12:30:00", DEFAULT_DATE_FORMAT).is_err());
+        assert!(parse_timestamp_input("", DEFAULT_DATE_FORMAT).is_err());
+
+        // --date-format swaps the format for both the hint and the save path
+        let expected = parse_timestamp_input("2023-05-01 12:30:00", DEFAULT_DATE_FORMAT).unwrap();
+        assert_eq!(
+            parse_timestamp_input("2023-05-01T12:30:00", "%Y-%m-%dT%H:%M:%S").unwrap(),
+            expected
+        );
+        assert_eq!(
+            parse_timestamp_input("01/05/2023 12:30", "%d/%m/%Y %H:%M").unwrap(),
+            expected
+        );
+        let err = parse_timestamp_input("2023-05-01 12:30:00", "%d/%m/%Y %H:%M").unwrap_err();
+        assert!(err.to_string().contains("%d/%m/%Y %H:%M"), "{err}");
     }
 
     #[test]
@@ -1137,6 +2779,129 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_shift_range_timestamps_preserves_gaps() {
+        let parse = |s: &str| NaiveDateTime::parse_from_str(s, "%Y-%m-%d %H:%M:%S").unwrap();
+        let original = [
+            parse("2023-01-01 10:00:00"),
+            parse("2023-01-01 10:00:07"),
+            parse("2023-01-03 18:30:00"),
+            // Out of order commits keep their negative offset too
+            parse("2023-01-02 09:15:59"),
+        ];
+        let anchor = parse("2024-06-15 08:00:00");
+
+        let shifted = shift_range_timestamps(&original, anchor);
+
+        assert_eq!(shifted[0], anchor);
+        assert_eq!(
+            shifted,
+            vec![
+                parse("2024-06-15 08:00:00"),
+                parse("2024-06-15 08:00:07"),
+                parse("2024-06-17 16:30:00"),
+                parse("2024-06-16 07:15:59"),
+            ]
+        );
+        for i in 1..original.len() {
+            assert_eq!(shifted[i] - shifted[i - 1], original[i] - original[i - 1]);
+        }
+
+        assert!(shift_range_timestamps(&[], anchor).is_empty());
+    }
+
+    #[test]
+    fn test_shift_timestamps_prefills_table() {
+        let (_temp_dir, repo_path) = create_test_repo_with_commits();
+        let args = Args {
+            repo_path: Some(repo_path),
+            ..Default::default()
+        };
+        let commits = get_commit_history(&args, false).unwrap();
+        let mut table = InteractiveTable::new(commits.clone(), 1, 3, (true, true, true, true));
+        let anchor =
+            NaiveDateTime::parse_from_str("2030-01-01 00:00:00", "%Y-%m-%d %H:%M:%S").unwrap();
+
+        table.shift_timestamps(anchor);
+
+        // The last row is the oldest commit of the range and lands on the anchor
+        assert_eq!(table.commits[2].timestamp, anchor);
+        assert_eq!(
+            table.commits[0].timestamp - table.commits[2].timestamp,
+            commits[1].timestamp - commits[3].timestamp
+        );
+        assert_eq!(table.get_modified_commits().len(), 3);
+        assert!(table.undo_stack.is_empty());
+    }
+
+    #[test]
+    fn test_transform_messages_prefills_table() {
+        let (_temp_dir, repo_path) = create_test_repo_with_commits();
+        let args = Args {
+            repo_path: Some(repo_path),
+            message_prepend: Some("ABC-1: ".to_string()),
+            ..Default::default()
+        };
+        let commits = get_commit_history(&args, false).unwrap();
+        let mut table = InteractiveTable::new(commits.clone(), 0, 1, (true, true, true, true));
+
+        table.transform_messages(&MessageTransforms::from_args(&args).unwrap());
+
+        assert_eq!(
+            table.commits[0].message,
+            format!("ABC-1: {}", commits[0].message)
+        );
+        assert!(table.commits[1].modifications.message_changed);
+        assert_eq!(table.get_modified_commits().len(), 2);
+    }
+
+    #[test]
+    fn test_range_size() {
+        assert_eq!(range_size(0, 0), 1);
+        assert_eq!(range_size(4, 10), 7);
+        assert_eq!(range_size(10, 4), 7);
+
+        // Sizes as selected at the prompt (1-based, inclusive)
+        let (start, end) = parse_range_input("5-11", 20).unwrap();
+        assert_eq!(range_size(start - 1, end - 1), 7);
+        let (start, end) = parse_range_input("*", 600).unwrap();
+        assert_eq!(range_size(start - 1, end - 1), 600);
+        assert!(range_size(start - 1, end - 1) > LARGE_RANGE_THRESHOLD);
+    }
+
+    #[test]
+    fn test_quick_range() {
+        // Commits are ordered newest first
+        assert_eq!(quick_range(10, Some(3), None).unwrap(), (0, 2));
+        assert_eq!(quick_range(10, None, Some(3)).unwrap(), (7, 9));
+        assert_eq!(quick_range(10, Some(10), None).unwrap(), (0, 9));
+        assert_eq!(quick_range(10, None, Some(10)).unwrap(), (0, 9));
+
+        let err = quick_range(10, Some(11), None).unwrap_err();
+        assert!(err.to_string().contains("out of bounds"), "{err}");
+        assert!(quick_range(10, None, Some(0)).is_err());
+        assert!(quick_range(10, Some(1), Some(1)).is_err());
+    }
+
+    #[test]
+    fn test_preflight_summary() {
+        let (_temp_dir, repo_path) = create_test_repo_with_merge();
+        let args = Args {
+            repo_path: Some(repo_path),
+            ..Default::default()
+        };
+        let commits = get_commit_history(&args, false).unwrap();
+
+        assert_eq!(
+            preflight_summary(&commits, 0, 3),
+            "4 of 4 commits, 1 author, 1 merge"
+        );
+        assert_eq!(
+            preflight_summary(&commits, 1, 2),
+            "2 of 4 commits, 1 author, 0 merges"
+        );
+    }
+
     #[test]
     fn test_generate_range_timestamps_edge_cases() {
         let start =
@@ -1172,7 +2937,7 @@ mod tests {
             edit_author: false,
             edit_time: false,
             docs: false,
-            _temp_dir: None,
+            ..Default::default()
         };
 
         // Test that get_commit_history returns commits for this repo
@@ -1192,4 +2957,1040 @@ mod tests {
         let timestamps = generate_range_timestamps(start_time, end_time, 3);
         assert_eq!(timestamps.len(), 3);
     }
+
+    #[test]
+    fn test_resolve_range_anchors() {
+        let (_temp_dir, repo_path) = create_test_repo_with_commits();
+        let repo = Repository::open(&repo_path).unwrap();
+        let args = Args {
+            repo_path: Some(repo_path),
+            ..Default::default()
+        };
+        let commits = get_commit_history(&args, false).unwrap();
+        let hash = |i: usize| commits[i].oid.to_string();
+
+        let range = resolve_range_anchors(&repo, &commits, Some(&hash(3)[..7]), Some(&hash(1)));
+        assert_eq!(range.unwrap(), (1, 3));
+        let range = resolve_range_anchors(&repo, &commits, Some(&hash(2)), None);
+        assert_eq!(range.unwrap(), (0, 2));
+        let range = resolve_range_anchors(&repo, &commits, None, Some(&hash(2)));
+        assert_eq!(range.unwrap(), (2, 4));
+        let range = resolve_range_anchors(&repo, &commits, Some(&hash(2)), Some(&hash(2)));
+        assert_eq!(range.unwrap(), (2, 2));
+
+        let reversed = resolve_range_anchors(&repo, &commits, Some(&hash(1)), Some(&hash(3)));
+        assert!(reversed
+            .unwrap_err()
+            .to_string()
+            .contains("not an ancestor"));
+        assert!(resolve_range_anchors(&repo, &commits, Some("deadbeef"), None).is_err());
+    }
+
+    #[test]
+    fn test_apply_range_changes_with_limited_history() {
+        let (_temp_dir, repo_path) = create_test_repo_with_commits();
+        let args = Args {
+            repo_path: Some(repo_path.clone()),
+            range: true,
+            limit: Some(2),
+            ..Default::default()
+        };
+
+        let full_history = get_commit_history(
+            &Args {
+                repo_path: Some(repo_path.clone()),
+                ..Default::default()
+            },
+            false,
+        )
+        .unwrap();
+        let commits = get_commit_history(&args, false).unwrap();
+        assert_eq!(commits.len(), 2);
+
+        // Edit the older of the two listed commits ("Commit 4")
+        let mut table = InteractiveTable::new(commits.clone(), 1, 1, (true, true, true, true));
+        table.commits[0].message = "Edited commit 4".to_string();
+        table.commits[0].modifications.message_changed = true;
+        table.commits[0].is_modified = true;
+
+        apply_interactive_range_changes(&args, &commits, &table.commits).unwrap();
+
+        let rewritten = get_commit_history(
+            &Args {
+                repo_path: Some(repo_path),
+                ..Default::default()
+            },
+            false,
+        )
+        .unwrap();
+        assert_eq!(rewritten.len(), 5);
+        assert_eq!(rewritten[0].message, "Commit 5");
+        assert_eq!(rewritten[1].message, "Edited commit 4");
+
+        // Commits outside the limited window keep their original oids
+        for i in 2..5 {
+            assert_eq!(rewritten[i].oid, full_history[i].oid);
+        }
+    }
+
+    #[test]
+    fn test_apply_range_changes_to_root_commit() {
+        let (_temp_dir, repo_path) = create_test_repo_with_commits();
+        let args = Args {
+            repo_path: Some(repo_path.clone()),
+            range: true,
+            ..Default::default()
+        };
+
+        let commits = get_commit_history(&args, false).unwrap();
+        let root_idx = commits.len() - 1;
+        assert_eq!(commits[root_idx].parent_count, 0);
+
+        // Edit only the very first commit of the repository
+        let mut table = InteractiveTable::new(
+            commits.clone(),
+            root_idx,
+            root_idx,
+            (true, true, true, true),
+        );
+        table.commits[0].message = "Edited root".to_string();
+        table.commits[0].modifications.message_changed = true;
+        table.commits[0].is_modified = true;
+
+        apply_interactive_range_changes(&args, &commits, &table.commits).unwrap();
+
+        let repo = Repository::open(&repo_path).unwrap();
+        let mut revwalk = repo.revwalk().unwrap();
+        revwalk.push_head().unwrap();
+        let chain: Vec<git2::Commit> = revwalk
+            .map(|oid| repo.find_commit(oid.unwrap()).unwrap())
+            .collect();
+        assert_eq!(chain.len(), commits.len());
+
+        let new_root = chain.last().unwrap();
+        assert_eq!(new_root.parent_count(), 0);
+        assert_eq!(new_root.message(), Some("Edited root"));
+        assert_ne!(new_root.id(), commits[root_idx].oid);
+
+        // Every later commit is rewritten onto the new root, one parent each
+        for (i, pair) in chain.windows(2).enumerate() {
+            assert_eq!(pair[0].parent_count(), 1);
+            assert_eq!(pair[0].parent_id(0).unwrap(), pair[1].id());
+            assert_eq!(pair[0].message(), Some(commits[i].message.as_str()));
+            assert_ne!(pair[0].id(), commits[i].oid);
+        }
+    }
+
+    #[test]
+    fn test_apply_range_changes_reorders_commits() {
+        let (_temp_dir, repo_path) = create_test_repo_with_commits();
+        let args = Args {
+            repo_path: Some(repo_path.clone()),
+            range: true,
+            ..Default::default()
+        };
+        let commits = get_commit_history(&args, false).unwrap();
+        let repo = Repository::open(&repo_path).unwrap();
+        let original_head_tree = repo.head().unwrap().peel_to_tree().unwrap().id();
+
+        // Rows are Commit 5, 4, 3; moving Commit 4 up makes it the newest
+        let mut table = InteractiveTable::new(commits.clone(), 0, 2, (true, true, true, true));
+        table.current_row = 1;
+        table
+            .handle_navigation_key_input(KeyCode::Char('K'))
+            .unwrap();
+        assert_eq!(table.current_row, 0);
+        assert!(table.is_reordered());
+        assert!(table.get_modified_commits().is_empty());
+
+        apply_interactive_range_changes(&args, &commits, &table.commits).unwrap();
+
+        let rewritten = get_commit_history(&args, false).unwrap();
+        let messages: Vec<_> = rewritten.iter().map(|c| c.message.as_str()).collect();
+        assert_eq!(
+            messages,
+            ["Commit 4", "Commit 5", "Commit 3", "Commit 2", "Commit 1"]
+        );
+
+        // Each moved commit carries only its own change on top of its new parent
+        let files = |oid: git2::Oid| -> Vec<String> {
+            let tree = repo.find_commit(oid).unwrap().tree().unwrap();
+            tree.iter()
+                .map(|entry| entry.name().unwrap().to_string())
+                .collect()
+        };
+        assert_eq!(
+            files(rewritten[1].oid),
+            ["test1.txt", "test2.txt", "test3.txt", "test5.txt"]
+        );
+        assert_eq!(
+            repo.head().unwrap().peel_to_tree().unwrap().id(),
+            original_head_tree
+        );
+
+        // Commits below the range are untouched
+        for i in 2..5 {
+            assert_eq!(rewritten[i].oid, commits[i].oid);
+        }
+    }
+
+    fn tree_files(repo: &Repository, oid: git2::Oid) -> Vec<String> {
+        let tree = repo.find_commit(oid).unwrap().tree().unwrap();
+        tree.iter()
+            .map(|entry| entry.name().unwrap().to_string())
+            .collect()
+    }
+
+    #[test]
+    fn test_apply_range_changes_drops_middle_commit() {
+        let (_temp_dir, repo_path) = create_test_repo_with_commits();
+        let args = Args {
+            repo_path: Some(repo_path.clone()),
+            range: true,
+            ..Default::default()
+        };
+        let commits = get_commit_history(&args, false).unwrap();
+        let repo = Repository::open(&repo_path).unwrap();
+
+        // Rows are Commit 5 down to Commit 1; drop Commit 3
+        let mut table = InteractiveTable::new(commits.clone(), 0, 4, (true, true, true, true));
+        table.current_row = 2;
+        table
+            .handle_navigation_key_input(KeyCode::Char('d'))
+            .unwrap();
+        assert_eq!(
+            table
+                .commits
+                .iter()
+                .filter(|c| c.action == RowAction::Drop)
+                .count(),
+            1
+        );
+
+        apply_interactive_range_changes(&args, &commits, &table.commits).unwrap();
+
+        let rewritten = get_commit_history(&args, false).unwrap();
+        let messages: Vec<_> = rewritten.iter().map(|c| c.message.as_str()).collect();
+        assert_eq!(messages, ["Commit 5", "Commit 4", "Commit 2", "Commit 1"]);
+
+        // Commit 4 now sits on Commit 2, and neither it nor Commit 5 keeps test3.txt
+        let commit_4 = repo.find_commit(rewritten[1].oid).unwrap();
+        assert_eq!(commit_4.parent_id(0).unwrap(), commits[3].oid);
+        assert_eq!(
+            tree_files(&repo, rewritten[0].oid),
+            ["test1.txt", "test2.txt", "test4.txt", "test5.txt"]
+        );
+        assert_eq!(rewritten[2].oid, commits[3].oid);
+        assert_eq!(rewritten[3].oid, commits[4].oid);
+    }
+
+    #[test]
+    fn test_apply_range_changes_drops_root_and_consecutive_commits() {
+        let (_temp_dir, repo_path) = create_test_repo_with_commits();
+        let args = Args {
+            repo_path: Some(repo_path.clone()),
+            range: true,
+            ..Default::default()
+        };
+        let commits = get_commit_history(&args, false).unwrap();
+        let repo = Repository::open(&repo_path).unwrap();
+
+        // Drop Commit 1 (the root) and Commit 2 right after it
+        let mut table = InteractiveTable::new(commits.clone(), 0, 4, (true, true, true, true));
+        for row in [3, 4] {
+            table.current_row = row;
+            table.toggle_current_row_action(RowAction::Drop);
+        }
+
+        apply_interactive_range_changes(&args, &commits, &table.commits).unwrap();
+
+        let rewritten = get_commit_history(&args, false).unwrap();
+        let messages: Vec<_> = rewritten.iter().map(|c| c.message.as_str()).collect();
+        assert_eq!(messages, ["Commit 5", "Commit 4", "Commit 3"]);
+
+        // Commit 3 becomes the new root, holding only its own file
+        let new_root = repo.find_commit(rewritten[2].oid).unwrap();
+        assert_eq!(new_root.parent_count(), 0);
+        assert_eq!(tree_files(&repo, new_root.id()), ["test3.txt"]);
+        assert_eq!(
+            tree_files(&repo, rewritten[0].oid),
+            ["test3.txt", "test4.txt", "test5.txt"]
+        );
+
+        // Dropping everything that is left is refused
+        let mut table = InteractiveTable::new(rewritten.clone(), 0, 2, (true, true, true, true));
+        for row in 0..3 {
+            table.current_row = row;
+            table.toggle_current_row_action(RowAction::Drop);
+        }
+        assert!(apply_interactive_range_changes(&args, &rewritten, &table.commits).is_err());
+    }
+
+    #[test]
+    fn test_drop_undo_and_conflicting_replay() {
+        let (temp_dir, repo_path) = create_test_repo_with_commits();
+        let args = Args {
+            repo_path: Some(repo_path.clone()),
+            range: true,
+            ..Default::default()
+        };
+
+        // A sixth commit rewrites test5.txt, so it builds on Commit 5
+        let repo = Repository::open(&repo_path).unwrap();
+        fs::write(temp_dir.path().join("test5.txt"), "changed content 5").unwrap();
+        let mut index = repo.index().unwrap();
+        index.add_path(std::path::Path::new("test5.txt")).unwrap();
+        index.write().unwrap();
+        let tree = repo.find_tree(index.write_tree().unwrap()).unwrap();
+        let head = repo.head().unwrap().peel_to_commit().unwrap();
+        let sig = git2::Signature::now("Test User", "test@example.com").unwrap();
+        repo.commit(Some("HEAD"), &sig, &sig, "Commit 6", &tree, &[&head])
+            .unwrap();
+        let before = repo.head().unwrap().target().unwrap();
+
+        let commits = get_commit_history(&args, false).unwrap();
+        let mut table = InteractiveTable::new(commits.clone(), 0, 5, (true, true, true, true));
+        table.current_row = 1;
+        table.toggle_current_row_action(RowAction::Drop);
+        table.handle_control_key_input(KeyCode::Char('z'));
+        assert!(!table.has_row_actions());
+        table.handle_control_key_input(KeyCode::Char('y'));
+        assert_eq!(
+            table
+                .commits
+                .iter()
+                .filter(|c| c.action == RowAction::Drop)
+                .count(),
+            1
+        );
+
+        // Dropping Commit 5 leaves Commit 6 nothing to apply its change to
+        let err = apply_interactive_range_changes(&args, &commits, &table.commits).unwrap_err();
+        assert!(err.to_string().contains("conflict"));
+        assert_eq!(repo.head().unwrap().target().unwrap(), before);
+    }
+
+    #[test]
+    fn test_apply_range_changes_squashes_two_commits() {
+        let (_temp_dir, repo_path) = create_test_repo_with_commits();
+        let args = Args {
+            repo_path: Some(repo_path.clone()),
+            range: true,
+            ..Default::default()
+        };
+        let commits = get_commit_history(&args, false).unwrap();
+        let repo = Repository::open(&repo_path).unwrap();
+        let original_head_tree = repo.head().unwrap().peel_to_tree().unwrap().id();
+
+        // Squash Commit 5 into Commit 4
+        let mut table = InteractiveTable::new(commits.clone(), 0, 1, (true, true, true, true));
+        table
+            .handle_navigation_key_input(KeyCode::Char('s'))
+            .unwrap();
+        assert_eq!(table.commits[0].action, RowAction::Squash);
+
+        apply_interactive_range_changes(&args, &commits, &table.commits).unwrap();
+
+        let rewritten = get_commit_history(&args, false).unwrap();
+        assert_eq!(rewritten.len(), 4);
+        let combined = repo.find_commit(rewritten[0].oid).unwrap();
+        assert_eq!(combined.message(), Some("Commit 4\n\nCommit 5"));
+        assert_eq!(combined.tree_id(), original_head_tree);
+        assert_eq!(combined.parent_id(0).unwrap(), commits[2].oid);
+
+        // Author and date come from the older commit
+        assert_eq!(rewritten[0].timestamp, commits[1].timestamp);
+        assert_eq!(rewritten[0].author_name, commits[1].author_name);
+    }
+
+    #[test]
+    fn test_fixup_and_refused_squash_of_root() {
+        let (_temp_dir, repo_path) = create_test_repo_with_commits();
+        let args = Args {
+            repo_path: Some(repo_path.clone()),
+            range: true,
+            ..Default::default()
+        };
+        let commits = get_commit_history(&args, false).unwrap();
+
+        let mut table = InteractiveTable::new(commits.clone(), 0, 4, (true, true, true, true));
+        table.current_row = 4;
+        table.toggle_current_row_action(RowAction::Squash);
+        assert!(!table.has_row_actions());
+        assert!(table.notice.is_some());
+
+        // Fixing up Commit 3 and then Commit 4 folds both into Commit 2
+        for row in [2, 1] {
+            table.current_row = row;
+            table.toggle_current_row_action(RowAction::Fixup);
+        }
+        apply_interactive_range_changes(&args, &commits, &table.commits).unwrap();
+
+        let rewritten = get_commit_history(&args, false).unwrap();
+        let messages: Vec<_> = rewritten.iter().map(|c| c.message.as_str()).collect();
+        assert_eq!(messages, ["Commit 5", "Commit 2", "Commit 1"]);
+    }
+
+    #[test]
+    fn test_squash_refused_on_oldest_row_of_range() {
+        let (_temp_dir, repo_path) = create_test_repo_with_commits();
+        let args = Args {
+            repo_path: Some(repo_path.clone()),
+            range: true,
+            ..Default::default()
+        };
+        let commits = get_commit_history(&args, false).unwrap();
+
+        // Commit 3 is the bottom row; its parent is outside the table
+        let mut table = InteractiveTable::new(commits.clone(), 0, 2, (true, true, true, true));
+        for action in [RowAction::Squash, RowAction::Fixup] {
+            table.current_row = 2;
+            table.toggle_current_row_action(action);
+            assert!(!table.has_row_actions());
+            assert!(table.notice.take().is_some());
+        }
+
+        // Nor can a squashed row be moved down to the bottom
+        table.current_row = 1;
+        table.toggle_current_row_action(RowAction::Squash);
+        table.move_current_row(false);
+        assert!(!table.is_reordered());
+        assert!(table.notice.is_some());
+
+        // The apply step refuses it too, before anything is written
+        table.commits[1].action = RowAction::Keep;
+        table.commits[2].action = RowAction::Fixup;
+        let err = apply_interactive_range_changes(&args, &commits, &table.commits).unwrap_err();
+        assert!(
+            err.to_string().contains("oldest commit of the range"),
+            "{err}"
+        );
+        let after = get_commit_history(&args, false).unwrap();
+        assert_eq!(
+            after.iter().map(|c| c.oid).collect::<Vec<_>>(),
+            commits.iter().map(|c| c.oid).collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn test_move_rows_refuses_root_and_undoes() {
+        let (_temp_dir, repo_path) = create_test_repo_with_commits();
+        let args = Args {
+            repo_path: Some(repo_path),
+            ..Default::default()
+        };
+        let commits = get_commit_history(&args, false).unwrap();
+        let mut table = InteractiveTable::new(commits, 0, 4, (true, true, true, true));
+
+        // The root is the last row and cannot take part in a swap
+        table.current_row = 3;
+        table.move_current_row(false);
+        assert!(!table.is_reordered());
+        assert!(table.notice.is_some());
+
+        table.move_current_row(true);
+        assert!(table.is_reordered());
+        assert!(table.is_moved(2) && table.is_moved(3));
+        assert_eq!(table.commits[2].original.message, "Commit 2");
+
+        table.handle_control_key_input(KeyCode::Char('z'));
+        assert!(!table.is_reordered());
+        assert_eq!(table.current_row, 3);
+
+        table.handle_control_key_input(KeyCode::Char('y'));
+        assert_eq!(table.commits[2].original.message, "Commit 2");
+        assert_eq!(table.current_row, 2);
+    }
+
+    #[test]
+    fn test_apply_range_changes_remaps_both_merge_parents() {
+        let (_temp_dir, repo_path) = create_test_repo_with_merge();
+        let args = Args {
+            repo_path: Some(repo_path.clone()),
+            range: true,
+            ..Default::default()
+        };
+
+        let commits = get_commit_history(&args, false).unwrap();
+        let messages: Vec<_> = commits.iter().map(|c| c.message.as_str()).collect();
+        assert_eq!(
+            messages,
+            [
+                "Merge side into main",
+                "Main commit",
+                "Side commit",
+                "Root commit"
+            ]
+        );
+
+        // Edit the merge itself and the commit on the merged-in side branch
+        let mut table = InteractiveTable::new(commits.clone(), 0, 2, (true, true, true, true));
+        table.commits[0].author_name = "Merger".to_string();
+        table.commits[0].modifications.author_name_changed = true;
+        table.commits[0].is_modified = true;
+        table.commits[2].author_name = "Side Author".to_string();
+        table.commits[2].modifications.author_name_changed = true;
+        table.commits[2].is_modified = true;
+
+        apply_interactive_range_changes(&args, &commits, &table.commits).unwrap();
+
+        let repo = Repository::open(&repo_path).unwrap();
+        let new_head = repo.head().unwrap().peel_to_commit().unwrap();
+        assert_eq!(new_head.author().name(), Some("Merger"));
+        assert_eq!(new_head.parent_count(), 2);
+
+        let first_parent = new_head.parent(0).unwrap();
+        let second_parent = new_head.parent(1).unwrap();
+        // The untouched main-line parent keeps its oid, the edited side parent is remapped
+        assert_eq!(first_parent.id(), commits[1].oid);
+        assert_ne!(second_parent.id(), commits[2].oid);
+        assert_eq!(second_parent.summary(), Some("Side commit"));
+        assert_eq!(second_parent.author().name(), Some("Side Author"));
+        assert_eq!(second_parent.parent_id(0).unwrap(), commits[3].oid);
+    }
+
+    #[test]
+    fn test_scroll_to_current_row_keeps_row_visible() {
+        let (_temp_dir, repo_path) = create_test_repo_with_commits();
+        let args = Args {
+            repo_path: Some(repo_path),
+            ..Default::default()
+        };
+        let commits = get_commit_history(&args, false).unwrap();
+        let mut table = InteractiveTable::new(commits, 0, 4, (true, true, true, true));
+
+        // Moving below a 2-row viewport scrolls it down
+        table.current_row = 3;
+        table.scroll_to_current_row(2);
+        assert_eq!(table.scroll_offset, 2);
+
+        // Moving back above it scrolls up again
+        table.current_row = 1;
+        table.scroll_to_current_row(2);
+        assert_eq!(table.scroll_offset, 1);
+
+        // Rows already in view leave the offset alone
+        table.current_row = 2;
+        table.scroll_to_current_row(2);
+        assert_eq!(table.scroll_offset, 1);
+
+        // Growing the viewport (a taller terminal) scrolls back to fill it
+        table.current_row = 4;
+        table.scroll_to_current_row(2);
+        assert_eq!(table.scroll_offset, 3);
+        table.scroll_to_current_row(4);
+        assert_eq!(table.scroll_offset, 1);
+        table.scroll_to_current_row(10);
+        assert_eq!(table.scroll_offset, 0);
+    }
+
+    #[test]
+    fn test_column_widths_fit_content_and_terminal() {
+        // Short authors leave the rest of the row to the message
+        let fitted = ColumnWidths::fit(ColumnWidths::DEFAULT_TERMINAL_WIDTH, 11, 16);
+        assert_eq!((fitted.author_name, fitted.author_email), (11, 16));
+        assert_eq!(
+            fitted.message,
+            ColumnWidths::DEFAULT_TERMINAL_WIDTH - ColumnWidths::FIXED - 27
+        );
+
+        // Long authors are capped at a share of the terminal
+        let capped = ColumnWidths::fit(120, 60, 60);
+        assert_eq!((capped.author_name, capped.author_email), (21, 28));
+        assert_eq!(ColumnWidths::FIXED + 21 + 28 + capped.message, 120);
+
+        // A wider terminal lets a long author through
+        let wide = ColumnWidths::fit(200, 30, 40);
+        assert_eq!((wide.author_name, wide.author_email), (30, 40));
+        assert_eq!(wide.message, 200 - ColumnWidths::FIXED - 70);
+
+        // Tiny terminals bottom out at minimum widths instead of zero
+        let tiny = ColumnWidths::fit(10, 11, 12);
+        assert_eq!(
+            (tiny.author_name, tiny.author_email, tiny.message),
+            (6, 8, 10)
+        );
+    }
+
+    #[test]
+    fn test_column_widths_follow_visible_rows() {
+        let (_temp_dir, repo_path) = create_test_repo_with_commits();
+        let args = Args {
+            repo_path: Some(repo_path),
+            ..Default::default()
+        };
+        let commits = get_commit_history(&args, false).unwrap();
+        let mut table = InteractiveTable::new(commits, 0, 2, (true, true, true, true));
+
+        // The headers are the widest values in a "Test User" repository
+        let widths = table.column_widths(120, 10);
+        assert_eq!((widths.author_name, widths.author_email), (11, 16));
+
+        // An edited row widens its column, marker included
+        table.commits[1].author_name = "Someone With A Long Name".to_string();
+        table.commits[1].modifications.author_name_changed = true;
+        assert_eq!(table.column_widths(120, 10).author_name, 21);
+        assert_eq!(table.column_widths(200, 10).author_name, 25);
+
+        // Rows scrolled out of view no longer count
+        table.scroll_offset = 2;
+        assert_eq!(table.column_widths(200, 10).author_name, 11);
+    }
+
+    #[test]
+    fn test_truncate_text_counts_characters() {
+        let (_temp_dir, repo_path) = create_test_repo_with_commits();
+        let args = Args {
+            repo_path: Some(repo_path),
+            ..Default::default()
+        };
+        let commits = get_commit_history(&args, false).unwrap();
+        let table = InteractiveTable::new(commits, 0, 0, (true, true, true, true));
+
+        assert_eq!(table.truncate_text("Zoë Müller", 6), "Zoë M…");
+        assert_eq!(table.truncate_text("short", 6), "short");
+
+        // Wide characters take two columns each
+        assert_eq!(table.truncate_text("山田太郎", 8), "山田太郎");
+        assert_eq!(table.truncate_text("山田太郎", 6), "山田…");
+        assert_eq!(display_width(&pad_to_width("山田", 6)), 6);
+    }
+
+    #[test]
+    fn test_reset_current_cell_restores_original() {
+        let (_temp_dir, repo_path) = create_test_repo_with_commits();
+        let args = Args {
+            repo_path: Some(repo_path),
+            ..Default::default()
+        };
+        let commits = get_commit_history(&args, false).unwrap();
+        let mut table = InteractiveTable::new(commits, 0, 4, (true, true, true, true));
+        table.current_row = 1;
+
+        table.current_col = TableColumn::AuthorName;
+        table.edit_buffer = "Someone Else".to_string();
+        table.save_current_edit().unwrap();
+        table.current_col = TableColumn::Message;
+        table.edit_buffer = "Reworded".to_string();
+        table.save_current_edit().unwrap();
+        assert_eq!(table.get_modified_commits().len(), 1);
+
+        // Resetting one of two changed fields keeps the commit modified
+        table
+            .handle_navigation_key_input(KeyCode::Char('r'))
+            .unwrap();
+        let commit = &table.commits[1];
+        assert_eq!(commit.message, commit.original.message);
+        assert!(!commit.modifications.message_changed);
+        assert!(commit.is_modified);
+
+        // Resetting the last one drops it from the modified commits
+        table.current_col = TableColumn::AuthorName;
+        table
+            .handle_navigation_key_input(KeyCode::Char('r'))
+            .unwrap();
+        let commit = &table.commits[1];
+        assert_eq!(commit.author_name, commit.original.author_name);
+        assert!(!commit.is_modified);
+        assert!(table.get_modified_commits().is_empty());
+    }
+
+    #[test]
+    fn test_save_current_edit_rejects_invalid_email() {
+        let (_temp_dir, repo_path) = create_test_repo_with_commits();
+        let args = Args {
+            repo_path: Some(repo_path),
+            ..Default::default()
+        };
+        let commits = get_commit_history(&args, false).unwrap();
+        let mut table = InteractiveTable::new(commits, 0, 4, (true, true, true, true));
+        table.current_col = TableColumn::AuthorEmail;
+
+        for invalid in ["@", "a@", "@example.com", "user@localhost"] {
+            table.edit_buffer = invalid.to_string();
+            assert!(table.save_current_edit().is_err(), "{invalid} was accepted");
+        }
+        assert!(table.get_modified_commits().is_empty());
+
+        table.edit_buffer = "new@example.com".to_string();
+        table.save_current_edit().unwrap();
+        assert_eq!(table.commits[0].author_email, "new@example.com");
+
+        // --allowed-email-domain turns away other domains with their name
+        table.allowed_email_domains = vec!["corp.com".to_string()];
+        table.edit_buffer = "dev@gmail.com".to_string();
+        let err = table.save_current_edit().unwrap_err();
+        assert!(err.to_string().contains("'gmail.com'"), "{err}");
+        assert_eq!(table.commits[0].author_email, "new@example.com");
+
+        table.edit_buffer = "dev@corp.com".to_string();
+        table.save_current_edit().unwrap();
+        assert_eq!(table.commits[0].author_email, "dev@corp.com");
+    }
+
+    #[test]
+    fn test_empty_author_email_round_trip() {
+        let (_temp_dir, repo_path) = create_test_repo_with_commits();
+        let repo = git2::Repository::open(&repo_path).unwrap();
+
+        // libgit2 cannot build a signature without an email, so write `Name <>` raw
+        let head = repo.head().unwrap().peel_to_commit().unwrap();
+        let raw = format!(
+            "tree {}\nparent {}\nauthor No Email <> 1234600000 +0000\ncommitter No Email <> 1234600000 +0000\n\nCommit 6\n",
+            head.tree_id(),
+            head.id()
+        );
+        let oid = repo
+            .odb()
+            .unwrap()
+            .write(git2::ObjectType::Commit, raw.as_bytes())
+            .unwrap();
+        repo.head()
+            .unwrap()
+            .set_target(oid, "commit without email")
+            .unwrap();
+
+        let args = Args {
+            repo_path: Some(repo_path),
+            range: true,
+            batch: true,
+            yes: true,
+            ..Default::default()
+        };
+        let commits = get_commit_history(&args, false).unwrap();
+        assert_eq!(commits[0].author_email, "");
+        assert_eq!(display_email(&commits[0].author_email), "(none)");
+
+        // Without --allow-empty-email an empty cell cannot be saved
+        let mut table = InteractiveTable::new(commits.clone(), 0, 1, (true, true, true, true));
+        let err = table.apply_batch_edits("1:email=".as_bytes()).unwrap_err();
+        assert!(err.to_string().contains("--allow-empty-email"), "{err}");
+
+        // Editing another field keeps the email empty
+        let mut table = InteractiveTable::new(commits.clone(), 0, 1, (true, true, true, true));
+        table.allow_empty_email = true;
+        table
+            .apply_batch_edits("1:name=Renamed\n1:email=\n2:email=".as_bytes())
+            .unwrap();
+        apply_interactive_range_changes(&args, &commits, &table.commits).unwrap();
+
+        let rewritten = get_commit_history(&args, false).unwrap();
+        assert_eq!(rewritten[0].author_name, "Renamed");
+        assert_eq!(rewritten[0].author_email, "");
+        assert_eq!(rewritten[1].author_email, "");
+        let tip = repo.head().unwrap().peel_to_commit().unwrap();
+        assert_eq!(tip.committer().email(), Some(""));
+        assert!(String::from_utf8_lossy(tip.raw_header_bytes()).contains("author Renamed <> "));
+
+        // A real email can still be set on the commit
+        let mut table = InteractiveTable::new(rewritten.clone(), 0, 1, (true, true, true, true));
+        table
+            .apply_batch_edits("1:email=real@example.com".as_bytes())
+            .unwrap();
+        apply_interactive_range_changes(&args, &rewritten, &table.commits).unwrap();
+        let fixed = get_commit_history(&args, false).unwrap();
+        assert_eq!(fixed[0].author_email, "real@example.com");
+        assert_eq!(fixed[1].author_email, "");
+    }
+
+    #[test]
+    fn test_apply_batch_edits_from_reader() {
+        let (_temp_dir, repo_path) = create_test_repo_with_commits();
+        let args = Args {
+            repo_path: Some(repo_path.clone()),
+            range: true,
+            batch: true,
+            yes: true,
+            ..Default::default()
+        };
+        let commits = get_commit_history(&args, false).unwrap();
+
+        let script = "\
+# newest three commits
+1:message=Batch message
+
+2:email=batch@example.com
+3:timestamp=2024-02-03 04:05:06
+3:name=Batch Author
+";
+        let mut table = InteractiveTable::new(commits.clone(), 0, 2, (true, true, true, true));
+        assert_eq!(table.apply_batch_edits(script.as_bytes()).unwrap(), 4);
+        assert_eq!(table.get_modified_commits().len(), 3);
+
+        apply_interactive_range_changes(&args, &commits, &table.commits).unwrap();
+        let rewritten = get_commit_history(&args, false).unwrap();
+        assert_eq!(rewritten[0].message, "Batch message");
+        assert_eq!(rewritten[1].author_email, "batch@example.com");
+        assert_eq!(rewritten[2].author_name, "Batch Author");
+        assert_eq!(
+            rewritten[2].timestamp,
+            parse_timestamp_input("2024-02-03 04:05:06", DEFAULT_DATE_FORMAT).unwrap()
+        );
+        assert_eq!(rewritten[3].message, "Commit 2");
+
+        // Each bad line is rejected with its line number, like a bad cell in the editor
+        for (script, expected) in [
+            ("1:email=not-an-email", "Batch line 1"),
+            (
+                "\n4:message=Outside",
+                "commit 4 is not in the selected range",
+            ),
+            ("1:hash=abc", "unknown field 'hash'"),
+            ("1 message Missing", "expected <index>:<field>=<value>"),
+            ("1:timestamp=yesterday", "Invalid timestamp format"),
+            ("1:message=   ", "Commit message cannot be empty"),
+        ] {
+            let mut table = InteractiveTable::new(commits.clone(), 0, 2, (true, true, true, true));
+            let err = table.apply_batch_edits(script.as_bytes()).unwrap_err();
+            assert!(err.to_string().contains(expected), "{script}: {err}");
+        }
+
+        // Conflicting values for one field are all reported and nothing is applied
+        let script = "\
+1:message=First
+2:name=Same
+1:message=Second
+2:name=Same
+3:email=a@example.com
+3:email=b@example.com
+";
+        let mut table = InteractiveTable::new(commits.clone(), 0, 2, (true, true, true, true));
+        let err = table
+            .apply_batch_edits(script.as_bytes())
+            .unwrap_err()
+            .to_string();
+        assert!(
+            err.contains("lines 1 and 3 set the message of commit 1 to 'First' and 'Second'"),
+            "{err}"
+        );
+        assert!(
+            err.contains("lines 5 and 6 set the email of commit 3"),
+            "{err}"
+        );
+        assert!(!err.contains("name"), "{err}");
+        assert!(table.get_modified_commits().is_empty());
+
+        // Fields left out by --message/--author/--time stay read-only
+        let mut table = InteractiveTable::new(commits, 0, 2, (false, false, false, true));
+        let err = table
+            .apply_batch_edits("1:name=Someone".as_bytes())
+            .unwrap_err();
+        assert!(err.to_string().contains("not editable"), "{err}");
+    }
+
+    #[test]
+    fn test_whitespace_only_message_edit_is_not_recorded() {
+        let (_temp_dir, repo_path) = create_test_repo_with_commits();
+        let args = Args {
+            repo_path: Some(repo_path),
+            ..Default::default()
+        };
+        let mut commits = get_commit_history(&args, false).unwrap();
+        commits[0].message = "msg\n".to_string();
+        let mut table = InteractiveTable::new(commits, 0, 4, (true, true, true, true));
+        table.current_col = TableColumn::Message;
+
+        for pasted in ["msg\r\n", "msg  \n", "msg\r\n\r\n", "msg"] {
+            table.edit_buffer = pasted.to_string();
+            table.save_current_edit().unwrap();
+            assert!(
+                !table.commits[0].is_modified,
+                "{pasted:?} marked the commit"
+            );
+        }
+        assert!(table.get_modified_commits().is_empty());
+        assert!(table.undo_stack.is_empty());
+
+        table.edit_buffer = "msg edited\r\n".to_string();
+        table.save_current_edit().unwrap();
+        assert!(table.commits[0].modifications.message_changed);
+    }
+
+    #[test]
+    fn test_undo_redo_cell_changes() {
+        let (_temp_dir, repo_path) = create_test_repo_with_commits();
+        let args = Args {
+            repo_path: Some(repo_path),
+            ..Default::default()
+        };
+        let commits = get_commit_history(&args, false).unwrap();
+        let mut table = InteractiveTable::new(commits, 0, 4, (true, true, true, true));
+        let original_name = table.commits[0].author_name.clone();
+        let original_timestamp = table.commits[2].timestamp;
+
+        let edit = |table: &mut InteractiveTable, row, column, value: &str| {
+            table.current_row = row;
+            table.current_col = column;
+            table.edit_buffer = value.to_string();
+            table.save_current_edit().unwrap();
+        };
+        edit(&mut table, 0, TableColumn::AuthorName, "First");
+        edit(&mut table, 0, TableColumn::AuthorName, "Second");
+        edit(&mut table, 2, TableColumn::Timestamp, "2023-05-01 12:00:00");
+        assert_eq!(table.undo_stack.len(), 3);
+        assert_eq!(table.get_modified_commits().len(), 2);
+
+        // Undo walks back one cell change at a time and moves the cursor to it
+        table.handle_control_key_input(KeyCode::Char('z'));
+        assert_eq!(table.commits[2].timestamp, original_timestamp);
+        assert!(!table.commits[2].is_modified);
+        assert_eq!(
+            (table.current_row, table.current_col),
+            (2, TableColumn::Timestamp)
+        );
+
+        table.handle_control_key_input(KeyCode::Char('z'));
+        assert_eq!(table.commits[0].author_name, "First");
+        assert!(table.commits[0].modifications.author_name_changed);
+
+        table.handle_control_key_input(KeyCode::Char('z'));
+        assert_eq!(table.commits[0].author_name, original_name);
+        assert!(table.get_modified_commits().is_empty());
+
+        // Nothing left to undo
+        table.handle_control_key_input(KeyCode::Char('z'));
+        assert!(table.get_modified_commits().is_empty());
+
+        // Redo reapplies in order
+        table.handle_control_key_input(KeyCode::Char('y'));
+        table.handle_control_key_input(KeyCode::Char('y'));
+        assert_eq!(table.commits[0].author_name, "Second");
+        assert_eq!(table.redo_stack.len(), 1);
+
+        // A fresh edit drops what is left to redo
+        edit(&mut table, 1, TableColumn::Message, "Reworded");
+        assert!(table.redo_stack.is_empty());
+        table.handle_control_key_input(KeyCode::Char('y'));
+        assert_eq!(table.commits[2].timestamp, original_timestamp);
+    }
+
+    #[test]
+    fn test_signal_guard_records_signal() {
+        let guard = SignalGuard::install().unwrap();
+        assert_eq!(guard.received(), None);
+
+        signal_hook::low_level::raise(SIGTERM).unwrap();
+        assert_eq!(guard.received(), Some(SIGTERM));
+
+        drop(guard);
+        let guard = SignalGuard::install().unwrap();
+        assert_eq!(guard.received(), None);
+    }
+
+    #[test]
+    fn test_apply_range_changes_preserve_committer_identity() {
+        let (_temp_dir, repo_path) = create_test_repo_with_commits();
+        // A contributor's patch applied by a maintainer
+        let repo = git2::Repository::open(&repo_path).unwrap();
+        let head = repo.head().unwrap().peel_to_commit().unwrap();
+        let when = git2::Time::new(1234600000, 0);
+        repo.commit(
+            Some("HEAD"),
+            &git2::Signature::new("Contributor", "contrib@example.com", &when).unwrap(),
+            &git2::Signature::new("Maintainer", "maint@example.com", &when).unwrap(),
+            "Applied patch",
+            &head.tree().unwrap(),
+            &[&head],
+        )
+        .unwrap();
+
+        // The second run sees the first one's committer as the original
+        for (preserve, author, committer) in [
+            (true, "New Author", "Maintainer"),
+            (false, "Other Author", "Other Author"),
+        ] {
+            let args = Args {
+                repo_path: Some(repo_path.clone()),
+                range: true,
+                preserve_committer_identity: preserve,
+                ..Default::default()
+            };
+            let commits = get_commit_history(&args, false).unwrap();
+            let mut table = InteractiveTable::new(commits.clone(), 0, 0, (true, true, true, true));
+            table.current_col = TableColumn::AuthorName;
+            table.edit_buffer = author.to_string();
+            table.save_current_edit().unwrap();
+
+            apply_interactive_range_changes(&args, &commits, &table.commits).unwrap();
+
+            let head = repo.head().unwrap().peel_to_commit().unwrap();
+            assert_eq!(head.author().name(), Some(author));
+            assert_eq!(head.committer().name(), Some(committer));
+        }
+    }
+
+    #[test]
+    fn test_committer_column_sets_dates_independently() {
+        let (_temp_dir, repo_path) = create_test_repo_with_commits();
+        // A commit whose author and committer dates already differ
+        let repo = git2::Repository::open(&repo_path).unwrap();
+        let head = repo.head().unwrap().peel_to_commit().unwrap();
+        let sig = |time| {
+            git2::Signature::new("Test User", "test@example.com", &git2::Time::new(time, 0))
+                .unwrap()
+        };
+        repo.commit(
+            Some("HEAD"),
+            &sig(1234600000),
+            &sig(1234700000),
+            "Rebased",
+            &head.tree().unwrap(),
+            &[&head],
+        )
+        .unwrap();
+
+        let args = Args {
+            repo_path: Some(repo_path.clone()),
+            range: true,
+            committer_column: true,
+            ..Default::default()
+        };
+        let commits = get_commit_history(&args, false).unwrap();
+
+        // Without the column there is no committer cell to edit
+        let mut table = InteractiveTable::new(commits.clone(), 0, 1, (true, true, true, true));
+        assert!(table
+            .apply_batch_edits("1:committer=2020-01-02 12:30:00\n".as_bytes())
+            .is_err());
+
+        let mut table = InteractiveTable::new(commits.clone(), 0, 1, (true, true, true, true));
+        table.show_committer_column();
+        assert_eq!(table.commits[0].timestamp, commits[0].author_timestamp);
+        assert_eq!(table.commits[0].committer_timestamp, commits[0].timestamp);
+        let batch = "1:timestamp=2020-01-01 10:00:00\n\
+                     1:committer=2020-01-02 12:30:00\n\
+                     2:committer=2020-01-03 08:00:00\n";
+        assert_eq!(table.apply_batch_edits(batch.as_bytes()).unwrap(), 3);
+        assert!(!table.commits[1].modifications.timestamp_changed);
+
+        apply_interactive_range_changes(&args, &commits, &table.commits).unwrap();
+
+        let seconds = |text: &str| {
+            parse_timestamp_input(text, DEFAULT_DATE_FORMAT)
+                .unwrap()
+                .and_utc()
+                .timestamp()
+        };
+        let head = repo.head().unwrap().peel_to_commit().unwrap();
+        assert_eq!(head.message(), Some("Rebased"));
+        assert_eq!(
+            head.author().when().seconds(),
+            seconds("2020-01-01 10:00:00")
+        );
+        assert_eq!(
+            head.committer().when().seconds(),
+            seconds("2020-01-02 12:30:00")
+        );
+
+        // Only the committer date of the second commit was edited
+        let parent = head.parent(0).unwrap();
+        assert_eq!(
+            parent.author().when().seconds(),
+            commits[1].author_timestamp.and_utc().timestamp()
+        );
+        assert_eq!(
+            parent.committer().when().seconds(),
+            seconds("2020-01-03 08:00:00")
+        );
+    }
 }
@@ -0,0 +1,289 @@
+use crate::args::{Args, Granularity};
+use crate::rewrite::common::{
+    finish_rewrite, new_signature, prepare_rewrite, print_new_tip, remap_parents, rewrite_commit,
+};
+use crate::utils::commit_history::print_commit_history;
+use crate::utils::datetime::{round_after, DEFAULT_MIN_GAP_MINUTES};
+use crate::utils::git_ref::RewriteTarget;
+use crate::utils::prompt::confirm;
+use crate::utils::types::{Outcome, Result};
+use colored::Colorize;
+use git2::{Oid, Repository, Signature, Sort, Time};
+use std::collections::HashMap;
+
+/// Rounds the author and committer date of every commit to `--round`'s
+/// granularity. Commits are visited oldest first and each date is kept after
+/// the matching date of its parents, bumping a tie forward by
+/// `--min-gap-minutes`, so the rounded history stays in order. Names, emails,
+/// messages, trees and timezone offsets are kept.
+pub fn round_commit_timestamps(args: &Args) -> Result<Outcome> {
+    let granularity = args.round.unwrap();
+    let min_gap_secs = i64::from(args.min_gap_minutes.unwrap_or(DEFAULT_MIN_GAP_MINUTES)) * 60;
+    prepare_rewrite(args)?;
+
+    let repo = Repository::open(args.repo_path.as_ref().unwrap())?;
+    let target = RewriteTarget::resolve(&repo, args)?;
+
+    let mut revwalk = repo.revwalk()?;
+    revwalk.push(target.tip(&repo)?)?;
+    revwalk.set_sorting(Sort::TOPOLOGICAL | Sort::TIME)?;
+    let mut orig_oids: Vec<_> = revwalk.filter_map(|id| id.ok()).collect();
+    orig_oids.reverse();
+
+    // Rounded (author, committer) dates, keyed by the original oid
+    let mut rounded: HashMap<Oid, (i64, i64)> = HashMap::new();
+    let mut new_map: HashMap<Oid, Oid> = HashMap::new();
+    let mut commits_changed = 0;
+    let mut last_new_oid = None;
+
+    for &oid in &orig_oids {
+        let orig = repo.find_commit(oid)?;
+        let new_parents = remap_parents(&repo, &orig, &new_map)?;
+
+        let parents: Vec<_> = orig
+            .parent_ids()
+            .filter_map(|pid| rounded.get(&pid).copied())
+            .collect();
+        let author = orig.author();
+        let committer = orig.committer();
+        let author_secs = round_after(
+            author.when().seconds(),
+            author.when().offset_minutes(),
+            granularity,
+            parents.iter().map(|&(author, _)| author).max(),
+            min_gap_secs,
+        );
+        // A commit is never committed before it was authored
+        let committer_secs = round_after(
+            committer.when().seconds(),
+            committer.when().offset_minutes(),
+            granularity,
+            parents.iter().map(|&(_, committer)| committer).max(),
+            min_gap_secs,
+        )
+        .max(author_secs);
+        rounded.insert(oid, (author_secs, committer_secs));
+
+        let new_oid = if author_secs == author.when().seconds()
+            && committer_secs == committer.when().seconds()
+        {
+            rewrite_commit(&repo, &orig, &new_parents, &author, &committer, None)?
+        } else {
+            commits_changed += 1;
+            rewrite_commit(
+                &repo,
+                &orig,
+                &new_parents,
+                &with_seconds(&author, author_secs)?,
+                &with_seconds(&committer, committer_secs)?,
+                None,
+            )?
+        };
+
+        new_map.insert(oid, new_oid);
+        last_new_oid = Some(new_oid);
+    }
+
+    let Some(new_head) = last_new_oid.filter(|_| commits_changed > 0) else {
+        if !args.quiet {
+            println!(
+                "{}",
+                format!(
+                    "Every timestamp is already rounded to the {}.",
+                    granularity_name(granularity)
+                )
+                .yellow()
+            );
+        }
+        return Ok(Outcome::NothingToDo);
+    };
+
+    if !args.quiet {
+        println!(
+            "{}",
+            format!(
+                "Rounding changes the dates of {commits_changed} of {} commit(s).",
+                orig_oids.len()
+            )
+            .yellow()
+        );
+    }
+    if !confirm("Rewrite these commits?", args.yes)? {
+        println!("{}", "Operation cancelled.".yellow());
+        return Ok(Outcome::Cancelled);
+    }
+
+    finish_rewrite(
+        &repo,
+        args,
+        &target,
+        new_head,
+        &orig_oids,
+        &new_map,
+        "timestamp rounding",
+    )?;
+
+    print_new_tip(args, &target, new_head);
+    if !args.quiet {
+        println!(
+            "{}",
+            format!(
+                "✓ Rounded the timestamps of {commits_changed} commit(s) to the {}",
+                granularity_name(granularity)
+            )
+            .green()
+            .bold()
+        );
+    }
+
+    if args.show_history {
+        print_commit_history(args)?;
+    }
+
+    Ok(Outcome::Done)
+}
+
+pub fn granularity_name(granularity: Granularity) -> &'static str {
+    match granularity {
+        Granularity::Minute => "minute",
+        Granularity::Hour => "hour",
+        Granularity::Day => "day",
+    }
+}
+
+// The same identity and timezone at another point in time
+fn with_seconds(signature: &Signature, secs: i64) -> Result<Signature<'static>> {
+    new_signature(
+        signature.name().unwrap_or("Unknown"),
+        signature.email().unwrap_or("unknown@email.com"),
+        &Time::new(secs, signature.when().offset_minutes()),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    // Three commits on one line: the second lands in the same minute as the
+    // root, the third is 20:30 later
+    fn create_unrounded_repo() -> (TempDir, String) {
+        let temp_dir = TempDir::new().unwrap();
+        let repo_path = temp_dir.path().to_str().unwrap().to_string();
+        let repo = Repository::init(&repo_path).unwrap();
+        let tree = repo
+            .find_tree(repo.index().unwrap().write_tree().unwrap())
+            .unwrap();
+
+        for (message, time) in [
+            ("Root", 1_700_000_000),
+            ("Same minute", 1_700_000_010),
+            ("Later", 1_700_001_230),
+        ] {
+            let author =
+                Signature::new("Author", "author@example.com", &Time::new(time, 120)).unwrap();
+            let committer = Signature::new(
+                "Committer",
+                "committer@example.com",
+                &Time::new(time + 5, 0),
+            )
+            .unwrap();
+            let parents: Vec<git2::Commit> = match repo.head() {
+                Ok(head) => vec![head.peel_to_commit().unwrap()],
+                Err(_) => vec![],
+            };
+            repo.commit(
+                Some("HEAD"),
+                &author,
+                &committer,
+                message,
+                &tree,
+                &parents.iter().collect::<Vec<_>>(),
+            )
+            .unwrap();
+        }
+        drop(tree);
+
+        (temp_dir, repo_path)
+    }
+
+    fn dates(repo_path: &str) -> Vec<(String, i64, i64, i32)> {
+        let repo = Repository::open(repo_path).unwrap();
+        let mut revwalk = repo.revwalk().unwrap();
+        revwalk.push_head().unwrap();
+        revwalk.set_sorting(Sort::TOPOLOGICAL).unwrap();
+        revwalk
+            .map(|oid| {
+                let commit = repo.find_commit(oid.unwrap()).unwrap();
+                let (author, committer) = (commit.author().when(), commit.committer().when());
+                (
+                    commit.message().unwrap().to_string(),
+                    author.seconds(),
+                    committer.seconds(),
+                    author.offset_minutes(),
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_round_commit_timestamps_to_the_minute() {
+        let (_temp_dir, repo_path) = create_unrounded_repo();
+        let args = |yes: bool| Args {
+            repo_path: Some(repo_path.clone()),
+            round: Some(Granularity::Minute),
+            min_gap_minutes: Some(1),
+            yes,
+            ..Default::default()
+        };
+
+        // Without --yes there is no terminal to confirm on
+        let before = dates(&repo_path);
+        assert!(round_commit_timestamps(&args(false)).is_err());
+        assert_eq!(dates(&repo_path), before);
+
+        let repo = Repository::open(&repo_path).unwrap();
+        let original_tip = repo.head().unwrap().target().unwrap();
+        assert_eq!(round_commit_timestamps(&args(true)).unwrap(), Outcome::Done);
+
+        // The old tip is kept as a backup of the branch
+        let backups = crate::utils::backup::list_backups(&repo).unwrap();
+        assert_eq!(backups.len(), 1);
+        assert_eq!(backups[0].target, original_tip);
+
+        // "Same minute" ties with the root and is bumped the one-minute gap past it
+        let root = 1_699_999_980;
+        assert_eq!(
+            dates(&repo_path),
+            [
+                ("Later".to_string(), 1_700_001_240, 1_700_001_240, 120),
+                ("Same minute".to_string(), root + 60, root + 60, 120),
+                ("Root".to_string(), root, root, 120),
+            ]
+        );
+
+        // A second run finds every date already rounded
+        assert_eq!(
+            round_commit_timestamps(&args(true)).unwrap(),
+            Outcome::NothingToDo
+        );
+    }
+
+    #[test]
+    fn test_round_with_no_backup_keeps_no_backup_ref() {
+        let (_temp_dir, repo_path) = create_unrounded_repo();
+        let args = Args {
+            repo_path: Some(repo_path.clone()),
+            round: Some(Granularity::Minute),
+            no_backup: true,
+            yes: true,
+            ..Default::default()
+        };
+
+        assert_eq!(round_commit_timestamps(&args).unwrap(), Outcome::Done);
+        let repo = Repository::open(&repo_path).unwrap();
+        assert!(crate::utils::backup::list_backups(&repo)
+            .unwrap()
+            .is_empty());
+    }
+}
@@ -1,10 +1,35 @@
-use crate::utils::types::Result;
-use crate::utils::types::{CommitInfo, EditOptions};
-use crate::{args::Args, utils::commit_history::get_commit_history};
+use crate::rewrite::common::{
+    chained_rewrite_notice, finish_rewrite, new_signature, original_committer_at, prepare_rewrite,
+    print_new_tip, remap_parents, rewrite_commit, skip_unchanged_prefix,
+};
+use crate::rewrite::rewrite_range::{ensure_terminal, SignalGuard, SIGNAL_POLL_INTERVAL};
+use crate::utils::change_summary::print_change;
+use crate::utils::datetime::format_hint;
+use crate::utils::editor::edit_message;
+use crate::utils::git_ref::RewriteTarget;
+use crate::utils::message_transform::{same_message, sign_off_edit, MessageTransforms};
+use crate::utils::prompt::confirm;
+use crate::utils::rewrite_state::{RewriteState, SAVE_INTERVAL};
+use crate::utils::simulation::{
+    create_picked_commits_simulation, create_specific_commit_simulation, print_simulation_result,
+};
+use crate::utils::types::{display_email, CommitInfo, EditOptions};
+use crate::utils::types::{Outcome, Result};
+use crate::utils::validator::check_email_domain;
+use crate::{
+    args::Args,
+    utils::commit_history::{get_commit_history, print_commit_history},
+};
 use chrono::NaiveDateTime;
 use colored::Colorize;
-use git2::{Repository, Signature, Sort, Time};
-use std::collections::HashMap;
+use crossterm::{
+    cursor,
+    event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers},
+    terminal::{self, Clear, ClearType},
+    ExecutableCommand,
+};
+use git2::{DiffFormat, Oid, Repository, Sort, Time};
+use std::collections::{BTreeSet, HashMap};
 use std::io::{self, Write};
 
 pub fn select_commit(commits: &[CommitInfo]) -> Result<usize> {
@@ -50,6 +75,177 @@ pub fn select_commit(commits: &[CommitInfo]) -> Result<usize> {
     Ok(selection - 1)
 }
 
+/// Lets the user pick several commits from a list: Up/Down (or k/j) move,
+/// Space toggles the current commit and Enter confirms. Enter with nothing
+/// toggled picks the current commit. Returns the picked indices in history
+/// order, or `None` when the list was closed with Esc, q or Ctrl+C.
+pub fn select_commits(commits: &[CommitInfo]) -> Result<Option<Vec<usize>>> {
+    CommitPicker::new(commits).run()
+}
+
+#[derive(Debug, PartialEq, Eq)]
+enum PickerState {
+    Open,
+    Confirmed(Vec<usize>),
+    Cancelled,
+}
+
+// The multi-select list of `pick --interactive-pick`
+struct CommitPicker<'a> {
+    commits: &'a [CommitInfo],
+    current_row: usize,
+    scroll_offset: usize,
+    selected: BTreeSet<usize>,
+}
+
+impl<'a> CommitPicker<'a> {
+    fn new(commits: &'a [CommitInfo]) -> Self {
+        Self {
+            commits,
+            current_row: 0,
+            scroll_offset: 0,
+            selected: BTreeSet::new(),
+        }
+    }
+
+    fn toggle_current_row(&mut self) {
+        if !self.selected.remove(&self.current_row) {
+            self.selected.insert(self.current_row);
+        }
+    }
+
+    fn handle_key(&mut self, code: KeyCode) -> PickerState {
+        match code {
+            KeyCode::Up | KeyCode::Char('k') => {
+                self.current_row = self.current_row.saturating_sub(1);
+            }
+            KeyCode::Down | KeyCode::Char('j') => {
+                self.current_row = (self.current_row + 1).min(self.commits.len().saturating_sub(1));
+            }
+            KeyCode::Home => self.current_row = 0,
+            KeyCode::End => self.current_row = self.commits.len().saturating_sub(1),
+            KeyCode::Char(' ') => self.toggle_current_row(),
+            KeyCode::Enter => {
+                if self.selected.is_empty() {
+                    return PickerState::Confirmed(vec![self.current_row]);
+                }
+                return PickerState::Confirmed(self.selected.iter().copied().collect());
+            }
+            KeyCode::Esc | KeyCode::Char('q') => return PickerState::Cancelled,
+            _ => {}
+        }
+        PickerState::Open
+    }
+
+    fn visible_row_count() -> usize {
+        // The title, the key help and the selection count
+        const CHROME_LINES: usize = 6;
+        const MIN_VISIBLE_ROWS: usize = 5;
+
+        terminal::size()
+            .map(|(_, height)| (height as usize).saturating_sub(CHROME_LINES))
+            .unwrap_or(usize::MAX)
+            .max(MIN_VISIBLE_ROWS)
+    }
+
+    fn draw(&mut self) {
+        let visible_rows = Self::visible_row_count();
+        if self.current_row < self.scroll_offset {
+            self.scroll_offset = self.current_row;
+        } else if self.current_row >= self.scroll_offset + visible_rows {
+            self.scroll_offset = self.current_row + 1 - visible_rows;
+        }
+
+        let _ = io::stdout().execute(Clear(ClearType::All));
+        let _ = io::stdout().execute(cursor::MoveTo(0, 0));
+        println!("{}", "Pick the commits to edit".bold().green());
+        println!(
+            "{}",
+            "Space: toggle  Enter: confirm  Esc/q: cancel  Up/Down: move".bright_black()
+        );
+        println!("{}", "-".repeat(80).cyan());
+
+        for (i, commit) in self
+            .commits
+            .iter()
+            .enumerate()
+            .skip(self.scroll_offset)
+            .take(visible_rows)
+        {
+            let marker = if self.selected.contains(&i) {
+                "[x]"
+            } else {
+                "[ ]"
+            };
+            let line = format!(
+                "{marker} {:3}. {} {} {} {}",
+                i + 1,
+                commit.short_hash,
+                commit.timestamp.format("%Y-%m-%d %H:%M:%S"),
+                commit.author_name,
+                commit.message.lines().next().unwrap_or("(no message)")
+            );
+            if i == self.current_row {
+                println!("{}", line.white().on_blue());
+            } else if self.selected.contains(&i) {
+                println!("{}", line.green());
+            } else {
+                println!("{line}");
+            }
+        }
+
+        println!("{}", "-".repeat(80).cyan());
+        println!("{} selected", self.selected.len().to_string().yellow());
+    }
+
+    fn run(&mut self) -> Result<Option<Vec<usize>>> {
+        ensure_terminal()?;
+
+        // As in the table editor, a SIGINT or SIGTERM must not leave the
+        // terminal in raw mode
+        let signals = SignalGuard::install()?;
+
+        let result = loop {
+            let _ = terminal::disable_raw_mode();
+            self.draw();
+            terminal::enable_raw_mode()?;
+
+            while !event::poll(SIGNAL_POLL_INTERVAL)? {
+                if let Some(signal) = signals.received() {
+                    Self::restore_terminal();
+                    std::process::exit(128 + signal);
+                }
+            }
+
+            if let Event::Key(KeyEvent {
+                code,
+                modifiers,
+                kind: KeyEventKind::Press,
+                ..
+            }) = event::read()?
+            {
+                if modifiers.contains(KeyModifiers::CONTROL) && code == KeyCode::Char('c') {
+                    break None;
+                }
+                match self.handle_key(code) {
+                    PickerState::Open => {}
+                    PickerState::Confirmed(selected) => break Some(selected),
+                    PickerState::Cancelled => break None,
+                }
+            }
+        };
+
+        Self::restore_terminal();
+        Ok(result)
+    }
+
+    fn restore_terminal() {
+        let _ = terminal::disable_raw_mode();
+        let _ = io::stdout().execute(Clear(ClearType::All));
+        let _ = io::stdout().execute(cursor::MoveTo(0, 0));
+    }
+}
+
 pub fn show_commit_details(commit: &CommitInfo, repo: &Repository) -> Result<()> {
     println!("\n{}", "Selected Commit Details:".bold().green());
     println!("{}", "=".repeat(80).cyan());
@@ -59,7 +255,12 @@ pub fn show_commit_details(commit: &CommitInfo, repo: &Repository) -> Result<()>
     println!(
         "{}: {}",
         "Author".bold(),
-        format!("{} <{}>", commit.author_name, commit.author_email).magenta()
+        format!(
+            "{} <{}>",
+            commit.author_name,
+            display_email(&commit.author_email)
+        )
+        .magenta()
     );
     println!(
         "{}: {}",
@@ -98,8 +299,67 @@ pub fn show_commit_details(commit: &CommitInfo, repo: &Repository) -> Result<()>
     Ok(())
 }
 
-// Get user input for what to change
-pub fn get_edit_options() -> Result<EditOptions> {
+/// Renders the changes `oid` made against its first parent as a unified
+/// diff; a root commit is compared to the empty tree. Binary files show up as
+/// a single "Binary files ... differ" line.
+pub fn commit_patch(repo: &Repository, oid: Oid) -> Result<String> {
+    let commit = repo.find_commit(oid)?;
+    let parent_tree = if commit.parent_count() > 0 {
+        Some(commit.parent(0)?.tree()?)
+    } else {
+        None
+    };
+    let diff = repo.diff_tree_to_tree(parent_tree.as_ref(), Some(&commit.tree()?), None)?;
+
+    let mut patch = String::new();
+    diff.print(DiffFormat::Patch, |_delta, _hunk, line| {
+        if matches!(line.origin(), '+' | '-' | ' ') {
+            patch.push(line.origin());
+        }
+        patch.push_str(&String::from_utf8_lossy(line.content()));
+        true
+    })?;
+    Ok(patch)
+}
+
+pub fn show_commit_patch(commit: &CommitInfo, repo: &Repository) -> Result<()> {
+    let patch = commit_patch(repo, commit.oid)?;
+    println!("\n{}", "Changes:".bold());
+    if patch.is_empty() {
+        println!("{}", "(no file changes)".bright_black());
+    }
+    for line in patch.lines() {
+        let line = if line.starts_with("+++") || line.starts_with("---") {
+            line.bold()
+        } else if line.starts_with('+') {
+            line.green()
+        } else if line.starts_with('-') {
+            line.red()
+        } else if line.starts_with("@@") {
+            line.cyan()
+        } else if line.starts_with("diff --git") || line.starts_with("Binary files") {
+            line.bold()
+        } else {
+            line.normal()
+        };
+        println!("{line}");
+    }
+    println!("{}", "=".repeat(80).cyan());
+    Ok(())
+}
+
+// Opens the commit message in the user's editor; a cancelled edit keeps the message
+fn prompt_message(current_message: &str) -> Result<Option<String>> {
+    let message = edit_message(current_message)?;
+    if message.is_none() {
+        println!("{}", "Message left unchanged.".yellow());
+    }
+    Ok(message)
+}
+
+// Get user input for what to change; timestamps are typed in `date_format`
+pub fn get_edit_options(current_message: &str, date_format: &str) -> Result<EditOptions> {
+    let timestamp_prompt = format!("New timestamp ({}):", format_hint(date_format));
     println!("\n{}", "What would you like to edit?".bold().green());
     println!("1. Author name");
     println!("2. Author email");
@@ -138,26 +398,16 @@ pub fn get_edit_options() -> Result<EditOptions> {
                 options.author_email = Some(email.trim().to_string());
             }
             3 => {
-                print!("{} ", "New timestamp (YYYY-MM-DD HH:MM:SS):".bold());
+                print!("{} ", timestamp_prompt.bold());
                 io::stdout().flush()?;
                 let mut timestamp = String::new();
                 io::stdin().read_line(&mut timestamp)?;
-                let dt = NaiveDateTime::parse_from_str(timestamp.trim(), "%Y-%m-%d %H:%M:%S")
+                let dt = NaiveDateTime::parse_from_str(timestamp.trim(), date_format)
                     .map_err(|_| "Invalid timestamp format")?;
                 options.timestamp = Some(dt);
             }
             4 => {
-                println!("{} ", "New commit message (end with empty line):".bold());
-                let mut message = String::new();
-                loop {
-                    let mut line = String::new();
-                    io::stdin().read_line(&mut line)?;
-                    if line.trim().is_empty() {
-                        break;
-                    }
-                    message.push_str(&line);
-                }
-                options.message = Some(message.trim().to_string());
+                options.message = prompt_message(current_message)?;
             }
             5 => {
                 // Get all inputs
@@ -173,25 +423,15 @@ pub fn get_edit_options() -> Result<EditOptions> {
                 io::stdin().read_line(&mut email)?;
                 options.author_email = Some(email.trim().to_string());
 
-                print!("{} ", "New timestamp (YYYY-MM-DD HH:MM:SS):".bold());
+                print!("{} ", timestamp_prompt.bold());
                 io::stdout().flush()?;
                 let mut timestamp = String::new();
                 io::stdin().read_line(&mut timestamp)?;
-                let dt = NaiveDateTime::parse_from_str(timestamp.trim(), "%Y-%m-%d %H:%M:%S")
+                let dt = NaiveDateTime::parse_from_str(timestamp.trim(), date_format)
                     .map_err(|_| "Invalid timestamp format")?;
                 options.timestamp = Some(dt);
 
-                println!("{} ", "New commit message (end with empty line):".bold());
-                let mut message = String::new();
-                loop {
-                    let mut line = String::new();
-                    io::stdin().read_line(&mut line)?;
-                    if line.trim().is_empty() {
-                        break;
-                    }
-                    message.push_str(&line);
-                }
-                options.message = Some(message.trim().to_string());
+                options.message = prompt_message(current_message)?;
             }
             _ => println!("Invalid option: {selection}"),
         }
@@ -200,185 +440,340 @@ pub fn get_edit_options() -> Result<EditOptions> {
     Ok(options)
 }
 
-pub fn rewrite_specific_commits(args: &Args) -> Result<()> {
+pub fn rewrite_specific_commits(args: &Args) -> Result<Outcome> {
+    // A simulation never writes, so it works on any worktree
+    if !args.simulate {
+        prepare_rewrite(args)?;
+    }
+
     let commits = get_commit_history(args, false)?;
 
     if commits.is_empty() {
         println!("{}", "No commits found!".red());
-        return Ok(());
+        return Ok(Outcome::NothingToDo);
     }
 
-    let selected_index = select_commit(&commits)?;
-    let selected_commit = &commits[selected_index];
+    let selected = if args.interactive_pick {
+        match select_commits(&commits)? {
+            Some(selected) => selected,
+            None => {
+                println!("{}", "Operation cancelled.".yellow());
+                return Ok(Outcome::Cancelled);
+            }
+        }
+    } else {
+        vec![select_commit(&commits)?]
+    };
 
     let repo = Repository::open(args.repo_path.as_ref().unwrap())?;
-    show_commit_details(selected_commit, &repo)?;
+    for &index in &selected {
+        show_commit_details(&commits[index], &repo)?;
+        if args.show_patch {
+            show_commit_patch(&commits[index], &repo)?;
+        }
+    }
 
-    let edit_options = get_edit_options()?;
+    let edit_options = get_edit_options(&commits[selected[0]].message, args.date_format())?;
+    match edit_options.author_email.as_deref() {
+        Some("") if !args.allow_empty_email => {
+            return Err(
+                "Author email cannot be empty (pass --allow-empty-email to keep it empty)".into(),
+            )
+        }
+        Some("") | None => {}
+        Some(email) => check_email_domain(email, &args.allowed_email_domains)?,
+    }
+
+    // Every picked commit gets the same edits; whether the message changes,
+    // the --message-* transforms and --signoff are worked out per commit
+    let transforms = MessageTransforms::from_args(args)?;
+    let edits: Vec<(&CommitInfo, EditOptions)> = selected
+        .iter()
+        .map(|&index| {
+            let commit = &commits[index];
+            let options = edit_options_for(&edit_options, commit, &transforms, args.signoff);
+            (commit, options)
+        })
+        .collect();
+
+    if args.simulate {
+        let result = match edits.as_slice() {
+            [(_, options)] => create_specific_commit_simulation(
+                &commits,
+                selected[0],
+                options.author_name.clone(),
+                options.author_email.clone(),
+                options.timestamp,
+                options.message.clone(),
+            )?,
+            _ => create_picked_commits_simulation(
+                &commits,
+                &edits
+                    .iter()
+                    .map(|(commit, options)| (commit.oid, options.clone()))
+                    .collect(),
+            )?,
+        };
+        print_simulation_result(&result, args.show_diff, args.preview_rows);
+        return Ok(Outcome::Done);
+    }
+
+    let edited: Vec<usize> = selected
+        .iter()
+        .zip(&edits)
+        .filter(|(_, (_, options))| has_edits(options))
+        .map(|(&index, _)| index)
+        .collect();
+    let edits: Vec<(&CommitInfo, &EditOptions)> = edits
+        .iter()
+        .filter(|(_, options)| has_edits(options))
+        .map(|(commit, options)| (*commit, options))
+        .collect();
+    if edits.is_empty() {
+        println!("{}", "No changes made.".yellow());
+        return Ok(Outcome::NothingToDo);
+    }
 
     // Confirm changes
     println!("\n{}", "Planned changes:".bold().yellow());
-    if let Some(ref name) = edit_options.author_name {
-        println!(
-            "  Author name: {} -> {}",
-            selected_commit.author_name.red(),
-            name.green()
-        );
+    for (commit, options) in &edits {
+        if selected.len() > 1 {
+            println!("{}", format!("Commit {}", commit.short_hash).bold());
+        }
+        print_planned_changes(commit, options);
     }
-    if let Some(ref email) = edit_options.author_email {
-        println!(
-            "  Author email: {} -> {}",
-            selected_commit.author_email.red(),
-            email.green()
-        );
+
+    if let Some(notice) = chained_rewrite_notice(&edited) {
+        println!("\n{}", notice.yellow());
     }
-    if let Some(ref timestamp) = edit_options.timestamp {
-        println!(
-            "  Timestamp: {} -> {}",
-            selected_commit
-                .timestamp
-                .format("%Y-%m-%d %H:%M:%S")
-                .to_string()
-                .red(),
-            timestamp.format("%Y-%m-%d %H:%M:%S").to_string().green()
-        );
+    if !confirm("Proceed with changes?", args.yes)? {
+        println!("{}", "Operation cancelled.".yellow());
+        return Ok(Outcome::Cancelled);
     }
-    if let Some(ref message) = edit_options.message {
+
+    // Apply changes
+    apply_commit_changes(&repo, args, &edits)?;
+
+    if edits.len() == 1 {
+        println!("\n{}", "✓ Commit successfully edited!".green().bold());
+    } else {
         println!(
-            "  Message: {} -> {}",
-            selected_commit.message.lines().next().unwrap_or("").red(),
-            message.lines().next().unwrap_or("").green()
+            "\n{}",
+            format!("✓ {} commits successfully edited!", edits.len())
+                .green()
+                .bold()
         );
     }
 
-    print!("\n{} (y/n): ", "Proceed with changes?".bold());
-    io::stdout().flush()?;
-
-    let mut confirm = String::new();
-    io::stdin().read_line(&mut confirm)?;
-
-    if confirm.trim().to_lowercase() != "y" {
-        println!("{}", "Operation cancelled.".yellow());
-        return Ok(());
+    if args.show_history {
+        print_commit_history(args)?;
     }
 
-    // Apply changes
-    apply_commit_changes(&repo, selected_commit, &edit_options)?;
+    Ok(Outcome::Done)
+}
 
-    println!("\n{}", "✓ Commit successfully edited!".green().bold());
+fn has_edits(options: &EditOptions) -> bool {
+    options.author_name.is_some()
+        || options.author_email.is_some()
+        || options.timestamp.is_some()
+        || options.message.is_some()
+}
 
-    if args.show_history {
-        get_commit_history(args, true)?;
+// The edits typed at the prompts as they apply to `commit`: a message that
+// matches the commit's own is no change, the --message-* transforms build on
+// the (edited) message and --signoff signs off any edited commit
+fn edit_options_for(
+    options: &EditOptions,
+    commit: &CommitInfo,
+    transforms: &MessageTransforms,
+    signoff: bool,
+) -> EditOptions {
+    let mut options = options.clone();
+    options.message = options
+        .message
+        .filter(|message| !same_message(message, &commit.message));
+
+    if !transforms.is_empty() {
+        let message = options.message.as_deref().unwrap_or(&commit.message);
+        let message = transforms.apply(message);
+        options.message = Some(message).filter(|m| !same_message(m, &commit.message));
     }
 
-    Ok(())
+    if signoff && has_edits(&options) {
+        sign_off_edit(&mut options, commit);
+    }
+    options
 }
 
-// Apply the changes to the selected commit
+fn print_planned_changes(commit: &CommitInfo, options: &EditOptions) {
+    if commit.parent_count > 1 {
+        println!(
+            "  {}",
+            format!(
+                "Merge commit: all {} parents are preserved, only metadata changes",
+                commit.parent_count
+            )
+            .bright_black()
+        );
+    }
+    if let Some(ref name) = options.author_name {
+        print_change("Author name", &commit.author_name, name);
+    }
+    if let Some(ref email) = options.author_email {
+        print_change(
+            "Author email",
+            display_email(&commit.author_email),
+            display_email(email),
+        );
+    }
+    if let Some(ref timestamp) = options.timestamp {
+        print_change(
+            "Timestamp",
+            &commit.timestamp.format("%Y-%m-%d %H:%M:%S").to_string(),
+            &timestamp.format("%Y-%m-%d %H:%M:%S").to_string(),
+        );
+    }
+    if let Some(ref message) = options.message {
+        print_change(
+            "Message",
+            commit.message.lines().next().unwrap_or(""),
+            message.lines().next().unwrap_or(""),
+        );
+    }
+}
+
+// Apply the changes to the selected commits
 fn apply_commit_changes(
     repo: &Repository,
-    target_commit: &CommitInfo,
-    options: &EditOptions,
+    args: &Args,
+    edits: &[(&CommitInfo, &EditOptions)],
+) -> Result<()> {
+    let edits = edits
+        .iter()
+        .map(|(commit, options)| (commit.oid, *options))
+        .collect();
+    apply_edit_options(repo, args, &edits, "pick edit")
+}
+
+// Rewrites the history of the rewrite target, applying the EditOptions of every commit listed in `edits`
+pub fn apply_edit_options(
+    repo: &Repository,
+    args: &Args,
+    edits: &HashMap<git2::Oid, &EditOptions>,
+    operation: &str,
+) -> Result<()> {
+    resume_edit_options(repo, args, edits, operation, &HashMap::new())
+}
+
+// Like `apply_edit_options`, reusing the commits an interrupted run of the same edits already wrote
+// (`written` maps their original oid to the new one). The progress is kept in the state file until
+// the ref has moved, so `--continue` can pick up where an interruption left off.
+pub fn resume_edit_options(
+    repo: &Repository,
+    args: &Args,
+    edits: &HashMap<git2::Oid, &EditOptions>,
+    operation: &str,
+    written: &HashMap<git2::Oid, git2::Oid>,
 ) -> Result<()> {
-    let head_ref = repo.head()?;
-    let branch_name = head_ref
-        .shorthand()
-        .ok_or("Detached HEAD or invalid branch")?;
-    let full_ref = format!("refs/heads/{branch_name}");
+    let target = RewriteTarget::resolve(repo, args)?;
+    let tip = target.tip(repo)?;
 
     let mut revwalk = repo.revwalk()?;
-    revwalk.push_head()?;
+    revwalk.push(tip)?;
     revwalk.set_sorting(Sort::TOPOLOGICAL | Sort::TIME)?;
     let mut orig_oids: Vec<_> = revwalk.filter_map(|id| id.ok()).collect();
     orig_oids.reverse();
 
+    let mut state = RewriteState::new(operation, args, tip, orig_oids.len(), edits);
+    state.save(repo)?;
+
     let mut new_map: HashMap<git2::Oid, git2::Oid> = HashMap::new();
+    let start =
+        skip_unchanged_prefix(&orig_oids, &mut new_map, |oid| Ok(edits.contains_key(&oid)))?;
     let mut last_new_oid = None;
 
-    for &oid in orig_oids.iter() {
+    for &oid in &orig_oids[start..] {
+        // Written before the interruption, with the same edits and parents
+        if let Some(&new_oid) = written.get(&oid) {
+            if repo.find_commit(new_oid).is_ok() {
+                new_map.insert(oid, new_oid);
+                state.record(oid, new_oid);
+                last_new_oid = Some(new_oid);
+                continue;
+            }
+        }
+
         let orig = repo.find_commit(oid)?;
-        let tree = orig.tree()?;
-
-        let new_parents: Result<Vec<_>> = orig
-            .parent_ids()
-            .map(|pid| {
-                let new_pid = *new_map.get(&pid).unwrap_or(&pid);
-                repo.find_commit(new_pid).map_err(|e| e.into())
-            })
-            .collect();
-
-        let new_oid = if oid == target_commit.oid {
-            // This is the commit we want to edit
+
+        let new_parents = remap_parents(repo, &orig, &new_map)?;
+
+        let new_oid = if let Some(options) = edits.get(&oid) {
+            // This is a commit we want to edit
+            let author = orig.author();
             let author_name = options
                 .author_name
-                .as_ref()
-                .unwrap_or(&target_commit.author_name);
+                .as_deref()
+                .unwrap_or(author.name().unwrap_or("Unknown"));
             let author_email = options
                 .author_email
-                .as_ref()
-                .unwrap_or(&target_commit.author_email);
-            let timestamp = options.timestamp.unwrap_or(target_commit.timestamp);
-            let message = options
-                .message
                 .as_deref()
-                .unwrap_or_else(|| orig.message().unwrap_or_default());
+                .unwrap_or(author.email().unwrap_or("unknown@email.com"));
+            let author_time = options
+                .timestamp
+                .map(|timestamp| Time::new(timestamp.and_utc().timestamp(), 0))
+                .unwrap_or_else(|| author.when());
 
-            let author_sig = Signature::new(
-                author_name,
-                author_email,
-                &Time::new(timestamp.and_utc().timestamp(), 0),
-            )?;
+            let author_sig = new_signature(author_name, author_email, &author_time)?;
 
-            // Keep the original committer unless we're changing the timestamp
+            // Keep the original committer unless we're changing the timestamp;
+            // --preserve-committer-identity keeps its identity even then
             let committer_sig = if options.timestamp.is_some() {
-                author_sig.clone()
+                if args.preserve_committer_identity {
+                    original_committer_at(&orig, &author_time)?
+                } else {
+                    author_sig.clone()
+                }
             } else {
                 let committer = orig.committer();
-                Signature::new(
+                new_signature(
                     committer.name().unwrap_or("Unknown"),
                     committer.email().unwrap_or("unknown@email.com"),
                     &committer.when(),
                 )?
             };
 
-            repo.commit(
-                None,
+            rewrite_commit(
+                repo,
+                &orig,
+                &new_parents,
                 &author_sig,
                 &committer_sig,
-                message,
-                &tree,
-                &new_parents?.iter().collect::<Vec<_>>(),
+                options.message.as_deref(),
             )?
         } else {
             // Keep other commits as-is but update parent references
             let author = orig.author();
             let committer = orig.committer();
 
-            repo.commit(
-                None,
-                &author,
-                &committer,
-                orig.message().unwrap_or_default(),
-                &tree,
-                &new_parents?.iter().collect::<Vec<_>>(),
-            )?
+            rewrite_commit(repo, &orig, &new_parents, &author, &committer, None)?
         };
 
         new_map.insert(oid, new_oid);
+        state.record(oid, new_oid);
+        if state.written.len().is_multiple_of(SAVE_INTERVAL) {
+            state.save(repo)?;
+        }
         last_new_oid = Some(new_oid);
     }
 
     if let Some(new_head) = last_new_oid {
-        repo.reference(&full_ref, new_head, true, "edited specific commit")?;
-        println!(
-            "{} '{}' -> {}",
-            "Updated branch".green(),
-            branch_name.cyan(),
-            new_head.to_string()[..8].to_string().cyan()
-        );
+        finish_rewrite(
+            repo, args, &target, new_head, &orig_oids, &new_map, operation,
+        )?;
+        print_new_tip(args, &target, new_head);
     }
 
-    Ok(())
+    RewriteState::discard(repo)
 }
 
 #[cfg(test)]
@@ -437,6 +832,48 @@ mod tests {
         (temp_dir, repo_path)
     }
 
+    fn create_test_repo_with_merge() -> (TempDir, String) {
+        let temp_dir = TempDir::new().unwrap();
+        let repo_path = temp_dir.path().to_str().unwrap().to_string();
+        let repo = git2::Repository::init(&repo_path).unwrap();
+
+        // root -> side \
+        //      -> main -> merge
+        let commit_file = |name: &str, message: &str, time: i64, parents: &[&git2::Commit]| {
+            fs::write(temp_dir.path().join(name), message).unwrap();
+            let mut index = repo.index().unwrap();
+            index.add_path(std::path::Path::new(name)).unwrap();
+            index.write().unwrap();
+            let tree = repo.find_tree(index.write_tree().unwrap()).unwrap();
+            let sig =
+                git2::Signature::new("Test User", "test@example.com", &git2::Time::new(time, 0))
+                    .unwrap();
+            repo.commit(None, &sig, &sig, message, &tree, parents)
+                .unwrap()
+        };
+
+        let root = repo
+            .find_commit(commit_file("root.txt", "Root commit", 1234567890, &[]))
+            .unwrap();
+        let side = repo
+            .find_commit(commit_file("side.txt", "Side commit", 1234571490, &[&root]))
+            .unwrap();
+        let main = repo
+            .find_commit(commit_file("main.txt", "Main commit", 1234575090, &[&root]))
+            .unwrap();
+        let merge = commit_file(
+            "merge.txt",
+            "Merge side into main",
+            1234578690,
+            &[&main, &side],
+        );
+        repo.reference("refs/heads/main", merge, true, "test setup")
+            .unwrap();
+        repo.set_head("refs/heads/main").unwrap();
+
+        (temp_dir, repo_path)
+    }
+
     #[test]
     fn test_show_commit_details() {
         let (_temp_dir, repo_path) = create_test_repo_with_commits();
@@ -458,7 +895,7 @@ mod tests {
             edit_author: false,
             edit_time: false,
             docs: false,
-            _temp_dir: None,
+            ..Default::default()
         };
 
         let commits = get_commit_history(&args, false).unwrap();
@@ -469,6 +906,45 @@ mod tests {
         assert!(result.is_ok());
     }
 
+    #[test]
+    fn test_commit_patch() {
+        let (temp_dir, repo_path) = create_test_repo_with_commits();
+        let repo = Repository::open(&repo_path).unwrap();
+        let head = repo.head().unwrap().peel_to_commit().unwrap();
+
+        let patch = commit_patch(&repo, head.id()).unwrap();
+        assert!(patch.contains("+++ b/test3.txt"), "{patch}");
+        assert!(patch.contains("\n+test content 3"), "{patch}");
+        assert!(!patch.contains("test1.txt"), "{patch}");
+
+        // The root commit is compared to the empty tree
+        let mut root = head.clone();
+        while root.parent_count() > 0 {
+            root = root.parent(0).unwrap();
+        }
+        let patch = commit_patch(&repo, root.id()).unwrap();
+        assert!(patch.contains("\n+test content 1"), "{patch}");
+
+        fs::write(temp_dir.path().join("image.bin"), [0u8, 159, 146, 150, 0]).unwrap();
+        let mut index = repo.index().unwrap();
+        index.add_path(std::path::Path::new("image.bin")).unwrap();
+        let tree = repo.find_tree(index.write_tree().unwrap()).unwrap();
+        let sig = head.author();
+        let oid = repo
+            .commit(Some("HEAD"), &sig, &sig, "Binary", &tree, &[&head])
+            .unwrap();
+        let patch = commit_patch(&repo, oid).unwrap();
+        assert!(patch.contains("Binary files"), "{patch}");
+        assert!(patch.contains("image.bin"), "{patch}");
+
+        let args = Args {
+            repo_path: Some(repo_path),
+            ..Default::default()
+        };
+        let commits = get_commit_history(&args, false).unwrap();
+        assert!(show_commit_patch(&commits[0], &repo).is_ok());
+    }
+
     #[test]
     fn test_edit_options_default() {
         let options = EditOptions::default();
@@ -505,6 +981,11 @@ mod tests {
             short_hash: "12345678".to_string(),
             timestamp: NaiveDateTime::parse_from_str("2023-01-01 12:00:00", "%Y-%m-%d %H:%M:%S")
                 .unwrap(),
+            author_timestamp: NaiveDateTime::parse_from_str(
+                "2023-01-01 12:00:00",
+                "%Y-%m-%d %H:%M:%S",
+            )
+            .unwrap(),
             author_name: "Test User".to_string(),
             author_email: "test@example.com".to_string(),
             message: "Test commit".to_string(),
@@ -523,6 +1004,59 @@ mod tests {
         assert!(invalid_selection2 < 1 || invalid_selection2 > commits.len());
     }
 
+    #[test]
+    fn test_commit_picker_toggles_several_commits() {
+        let (_temp_dir, repo_path) = create_test_repo_with_commits();
+        let args = Args {
+            repo_path: Some(repo_path.clone()),
+            ..Default::default()
+        };
+        let commits = get_commit_history(&args, false).unwrap();
+
+        let mut picker = CommitPicker::new(&commits);
+        for key in [
+            KeyCode::Char(' '),
+            KeyCode::Down,
+            KeyCode::Char('j'),
+            KeyCode::Char(' '),
+            KeyCode::Down,
+        ] {
+            assert_eq!(picker.handle_key(key), PickerState::Open);
+        }
+        assert_eq!(picker.current_row, 2);
+        assert_eq!(
+            picker.handle_key(KeyCode::Enter),
+            PickerState::Confirmed(vec![0, 2])
+        );
+
+        // Toggling twice unpicks; Enter with nothing picked takes the current row
+        let mut picker = CommitPicker::new(&commits);
+        picker.handle_key(KeyCode::Char(' '));
+        picker.handle_key(KeyCode::Char(' '));
+        picker.handle_key(KeyCode::Down);
+        assert_eq!(
+            picker.handle_key(KeyCode::Enter),
+            PickerState::Confirmed(vec![1])
+        );
+        assert_eq!(picker.handle_key(KeyCode::Esc), PickerState::Cancelled);
+
+        // Both picked commits get the same edit
+        let options = EditOptions {
+            author_name: Some("Picked Author".to_string()),
+            ..Default::default()
+        };
+        let repo = Repository::open(&repo_path).unwrap();
+        apply_commit_changes(
+            &repo,
+            &args,
+            &[(&commits[0], &options), (&commits[2], &options)],
+        )
+        .unwrap();
+        let after = get_commit_history(&args, false).unwrap();
+        let authors: Vec<&str> = after.iter().map(|c| c.author_name.as_str()).collect();
+        assert_eq!(authors, ["Picked Author", "Test User", "Picked Author"]);
+    }
+
     #[test]
     fn test_rewrite_specific_commits_with_empty_commits() {
         let (_temp_dir, repo_path) = create_test_repo_with_commits();
@@ -541,7 +1075,7 @@ mod tests {
             edit_author: false,
             edit_time: false,
             docs: false,
-            _temp_dir: None,
+            ..Default::default()
         };
 
         // Test that the function handles the case where get_commit_history returns commits
@@ -571,7 +1105,7 @@ mod tests {
             edit_author: false,
             edit_time: false,
             docs: false,
-            _temp_dir: None,
+            ..Default::default()
         };
 
         let commits = get_commit_history(&args, false).unwrap();
@@ -613,4 +1147,131 @@ mod tests {
         assert_eq!(author_name, &target_commit.author_name);
         assert_eq!(author_email, &target_commit.author_email);
     }
+
+    #[test]
+    fn test_apply_commit_changes_preserves_merge_parents() {
+        let (_temp_dir, repo_path) = create_test_repo_with_merge();
+        let repo = Repository::open(&repo_path).unwrap();
+        let args = Args {
+            repo_path: Some(repo_path.clone()),
+            ..Default::default()
+        };
+
+        let commits = get_commit_history(&args, false).unwrap();
+        let merge = &commits[0];
+        assert_eq!(merge.parent_count, 2);
+        let orig_parents: Vec<_> = repo.find_commit(merge.oid).unwrap().parent_ids().collect();
+
+        let options = EditOptions {
+            timestamp: Some(
+                NaiveDateTime::parse_from_str("2023-01-01 12:00:00", "%Y-%m-%d %H:%M:%S").unwrap(),
+            ),
+            ..Default::default()
+        };
+        let args = Args {
+            repo_path: Some(repo_path.clone()),
+            rewrite_ref: Some("main".to_string()),
+            ..Default::default()
+        };
+        apply_commit_changes(&repo, &args, &[(merge, &options)]).unwrap();
+
+        let repo = Repository::open(&repo_path).unwrap();
+        let new_head = repo.head().unwrap().peel_to_commit().unwrap();
+        assert_ne!(new_head.id(), merge.oid);
+        assert_eq!(new_head.parent_count(), 2);
+        // Nothing below the merge changed, so both parents keep their oids and order
+        assert_eq!(new_head.parent_ids().collect::<Vec<_>>(), orig_parents);
+        assert_eq!(
+            new_head.tree_id(),
+            repo.find_commit(merge.oid).unwrap().tree_id()
+        );
+    }
+
+    #[test]
+    fn test_history_after_rewrite_lists_new_oids() {
+        let (_temp_dir, repo_path) = create_test_repo_with_commits();
+        let args = Args {
+            repo_path: Some(repo_path.clone()),
+            show_history: true,
+            ..Default::default()
+        };
+
+        // Listing before the rewrite fills the cache on `args`
+        let before = get_commit_history(&args, false).unwrap();
+        let options = EditOptions {
+            message: Some("Edited message".to_string()),
+            ..Default::default()
+        };
+        let repo = Repository::open(&repo_path).unwrap();
+        apply_commit_changes(&repo, &args, &[(&before[before.len() - 1], &options)]).unwrap();
+
+        // The post-rewrite --show-history print, and the list it shows
+        print_commit_history(&args).unwrap();
+        let after = get_commit_history(&args, false).unwrap();
+        let head = Repository::open(&repo_path)
+            .unwrap()
+            .head()
+            .unwrap()
+            .target()
+            .unwrap();
+        assert_eq!(after[0].oid, head);
+        assert_eq!(after.len(), before.len());
+        assert!(after
+            .iter()
+            .all(|new| before.iter().all(|old| old.oid != new.oid)));
+        assert_eq!(after[after.len() - 1].message, "Edited message");
+    }
+
+    #[test]
+    fn test_apply_commit_changes_preserve_committer_identity() {
+        let (_temp_dir, repo_path) = create_test_repo_with_commits();
+        // A contributor's patch applied by a maintainer
+        let repo = Repository::open(&repo_path).unwrap();
+        let head = repo.head().unwrap().peel_to_commit().unwrap();
+        let when = git2::Time::new(1234600000, 0);
+        repo.commit(
+            Some("HEAD"),
+            &git2::Signature::new("Contributor", "contrib@example.com", &when).unwrap(),
+            &git2::Signature::new("Maintainer", "maint@example.com", &when).unwrap(),
+            "Applied patch",
+            &head.tree().unwrap(),
+            &[&head],
+        )
+        .unwrap();
+
+        // The second run sees the first one's committer as the original
+        for (preserve, author, committer) in [
+            (true, "New Author", "Maintainer"),
+            (false, "Other Author", "Other Author"),
+        ] {
+            let options = EditOptions {
+                author_name: Some(author.to_string()),
+                author_email: Some("new@example.com".to_string()),
+                timestamp: Some(
+                    NaiveDateTime::parse_from_str("2023-01-01 12:00:00", "%Y-%m-%d %H:%M:%S")
+                        .unwrap(),
+                ),
+                ..Default::default()
+            };
+            let args = Args {
+                repo_path: Some(repo_path.clone()),
+                preserve_committer_identity: preserve,
+                ..Default::default()
+            };
+            let commits = get_commit_history(&args, false).unwrap();
+            apply_commit_changes(&repo, &args, &[(&commits[0], &options)]).unwrap();
+
+            let head = repo.head().unwrap().peel_to_commit().unwrap();
+            assert_eq!(head.author().name(), Some(author));
+            assert_eq!(head.committer().name(), Some(committer));
+            // The committer date follows the new timestamp either way
+            assert_eq!(
+                head.committer().when().seconds(),
+                head.author().when().seconds()
+            );
+            if preserve {
+                assert_eq!(head.committer().email(), Some("maint@example.com"));
+            }
+        }
+    }
 }
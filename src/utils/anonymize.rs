@@ -0,0 +1,124 @@
+use crate::utils::types::Result;
+use git2::{Oid, Repository};
+use std::collections::HashMap;
+
+/// Domain of the placeholder emails; `.invalid` is reserved and never resolves.
+pub const ANON_DOMAIN: &str = "anon.invalid";
+
+/// Placeholder identities for `--anonymize`. Every original email (compared
+/// case-insensitively) gets its own `Author N <authorN@anon.invalid>`,
+/// numbered in the order the identities first appear, oldest commit first and
+/// the author before the committer. The same history always gets the same
+/// numbering.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct AnonymousIdentities {
+    // Lowercased original email -> position in `originals`
+    numbers: HashMap<String, usize>,
+    // The first name and email seen for every placeholder, in numbering order
+    originals: Vec<(String, String)>,
+}
+
+impl AnonymousIdentities {
+    /// Numbers the authors and committers of `oids`, which are visited in the
+    /// order given.
+    pub fn from_commits(repo: &Repository, oids: &[Oid]) -> Result<AnonymousIdentities> {
+        let mut identities = AnonymousIdentities::default();
+        for &oid in oids {
+            let commit = repo.find_commit(oid)?;
+            for sig in [commit.author(), commit.committer()] {
+                identities.add(
+                    sig.name().unwrap_or("Unknown"),
+                    sig.email().unwrap_or("unknown@email.com"),
+                );
+            }
+        }
+        Ok(identities)
+    }
+
+    fn add(&mut self, name: &str, email: &str) {
+        let key = email.trim().to_lowercase();
+        if !self.numbers.contains_key(&key) {
+            self.numbers.insert(key, self.originals.len());
+            self.originals.push((name.to_string(), email.to_string()));
+        }
+    }
+
+    /// The placeholder for an identity. One that was never numbered, which
+    /// cannot happen for the commits the map was built from, gets the next
+    /// free number rather than a real name.
+    pub fn resolve(&self, email: &str) -> (String, String) {
+        let number = self
+            .numbers
+            .get(&email.trim().to_lowercase())
+            .copied()
+            .unwrap_or(self.originals.len())
+            + 1;
+        placeholder(number)
+    }
+
+    pub fn len(&self) -> usize {
+        self.originals.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.originals.is_empty()
+    }
+
+    /// The mapping as a mailmap that turns the placeholders back into the
+    /// original identities, so `--mailmap` with it undoes the anonymization.
+    pub fn to_mailmap(&self) -> String {
+        self.originals
+            .iter()
+            .enumerate()
+            .map(|(i, (name, email))| {
+                let (anon_name, anon_email) = placeholder(i + 1);
+                format!("{name} <{email}> {anon_name} <{anon_email}>\n")
+            })
+            .collect()
+    }
+
+    pub fn write_mailmap(&self, path: &str) -> Result<()> {
+        std::fs::write(path, self.to_mailmap())
+            .map_err(|e| format!("Failed to write identity map {path}: {e}").into())
+    }
+}
+
+fn placeholder(number: usize) -> (String, String) {
+    (
+        format!("Author {number}"),
+        format!("author{number}@{ANON_DOMAIN}"),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::mailmap::Mailmap;
+
+    #[test]
+    fn test_anonymous_identities_are_numbered_by_first_appearance() {
+        let mut identities = AnonymousIdentities::default();
+        identities.add("Alice", "alice@example.com");
+        identities.add("Bob", "bob@example.com");
+        identities.add("Alice Smith", "Alice@Example.com");
+
+        assert_eq!(identities.len(), 2);
+        assert_eq!(
+            identities.resolve("alice@example.com"),
+            ("Author 1".to_string(), "author1@anon.invalid".to_string())
+        );
+        assert_eq!(
+            identities.resolve(" ALICE@example.com"),
+            identities.resolve("alice@example.com")
+        );
+        assert_eq!(identities.resolve("bob@example.com").0, "Author 2");
+        assert_eq!(identities.resolve("carol@example.com").0, "Author 3");
+
+        // The written map reverses the placeholders
+        let mailmap = Mailmap::parse(&identities.to_mailmap()).unwrap();
+        assert_eq!(
+            mailmap.resolve("Author 2", "author2@anon.invalid"),
+            ("Bob".to_string(), "bob@example.com".to_string())
+        );
+    }
+}
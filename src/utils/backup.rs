@@ -0,0 +1,255 @@
+use crate::utils::git_ref::RewriteTarget;
+use crate::utils::types::Result;
+use chrono::{DateTime, Local, Utc};
+use colored::Colorize;
+use git2::{Oid, Repository};
+use std::collections::HashMap;
+
+/// Backups of a rewritten branch are kept as
+/// `refs/git-editor/backup/<branch>/<unix seconds>`, pointing at the tip the
+/// branch had before the rewrite.
+pub const BACKUP_REF_PREFIX: &str = "refs/git-editor/backup/";
+
+/// Keeps `tip`, where `target` points before a rewrite moves it, as a backup
+/// ref named after the branch and the current time, and returns its name. A
+/// second backup within the same second takes the next free one.
+pub fn create_backup(
+    repo: &Repository,
+    target: &RewriteTarget,
+    tip: Oid,
+    operation: &str,
+) -> Result<String> {
+    let branch = match target {
+        RewriteTarget::Reference(name) => name
+            .strip_prefix("refs/heads/")
+            .or_else(|| name.strip_prefix("refs/"))
+            .unwrap_or(name),
+        RewriteTarget::DetachedHead => "HEAD",
+    };
+
+    let mut created = Utc::now().timestamp();
+    loop {
+        let ref_name = format!("{BACKUP_REF_PREFIX}{branch}/{created}");
+        match repo.find_reference(&ref_name) {
+            Ok(existing) if existing.target() == Some(tip) => return Ok(ref_name),
+            Ok(_) => created += 1,
+            Err(_) => {
+                let message = format!("git-editor: backup before {operation}");
+                repo.reference(&ref_name, tip, false, &message)?;
+                return Ok(ref_name);
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Backup {
+    pub ref_name: String,
+    pub branch: String,
+    // Seconds since the epoch; the target commit's time for a ref not named by time
+    pub created: i64,
+    pub target: Oid,
+}
+
+/// Every backup ref, grouped by branch and newest first within a branch.
+pub fn list_backups(repo: &Repository) -> Result<Vec<Backup>> {
+    let mut backups = Vec::new();
+    for reference in repo.references_glob(&format!("{BACKUP_REF_PREFIX}*"))? {
+        let reference = reference?;
+        let (Some(ref_name), Ok(commit)) = (reference.name(), reference.peel_to_commit()) else {
+            continue;
+        };
+        let rest = &ref_name[BACKUP_REF_PREFIX.len()..];
+        let stamped = rest
+            .rsplit_once('/')
+            .and_then(|(branch, stamp)| Some((branch, stamp.parse::<i64>().ok()?)));
+        let (branch, created) = match stamped {
+            Some((branch, created)) => (branch, created),
+            None => (rest, commit.time().seconds()),
+        };
+
+        backups.push(Backup {
+            ref_name: ref_name.to_string(),
+            branch: branch.to_string(),
+            created,
+            target: commit.id(),
+        });
+    }
+
+    backups.sort_by(|a, b| {
+        a.branch
+            .cmp(&b.branch)
+            .then(b.created.cmp(&a.created))
+            .then(b.ref_name.cmp(&a.ref_name))
+    });
+    Ok(backups)
+}
+
+/// Deletes all but the newest `keep` backups of every branch and returns the
+/// deleted ones.
+pub fn prune_backups(repo: &Repository, keep: usize) -> Result<Vec<Backup>> {
+    let mut seen: HashMap<String, usize> = HashMap::new();
+    let mut pruned = Vec::new();
+
+    for backup in list_backups(repo)? {
+        let count = seen.entry(backup.branch.clone()).or_default();
+        *count += 1;
+        if *count > keep {
+            repo.find_reference(&backup.ref_name)?.delete()?;
+            pruned.push(backup);
+        }
+    }
+    Ok(pruned)
+}
+
+pub fn print_backups(backups: &[Backup]) {
+    if backups.is_empty() {
+        println!("{}", "No backups found.".yellow());
+        return;
+    }
+
+    let branch_width = backups.iter().map(|b| b.branch.len()).max().unwrap_or(0);
+    for backup in backups {
+        println!(
+            "{}  {}  {}  {}",
+            format!("{:<branch_width$}", backup.branch).cyan(),
+            format_created(backup.created).blue(),
+            backup.target.to_string()[..8].yellow(),
+            backup.ref_name.bright_black()
+        );
+    }
+}
+
+fn format_created(created: i64) -> String {
+    DateTime::from_timestamp(created, 0)
+        .map(|time| {
+            time.with_timezone(&Local)
+                .format("%Y-%m-%d %H:%M:%S")
+                .to_string()
+        })
+        .unwrap_or_else(|| created.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use git2::{Signature, Time};
+    use tempfile::TempDir;
+
+    fn create_repo_with_backups() -> (TempDir, Repository, Oid) {
+        let temp_dir = TempDir::new().unwrap();
+        let repo = Repository::init(temp_dir.path()).unwrap();
+        let tree = repo
+            .find_tree(repo.index().unwrap().write_tree().unwrap())
+            .unwrap();
+        let sig = Signature::new(
+            "Test User",
+            "test@example.com",
+            &Time::new(1_700_000_000, 0),
+        )
+        .unwrap();
+        let oid = repo
+            .commit(Some("HEAD"), &sig, &sig, "Initial", &tree, &[])
+            .unwrap();
+        drop(tree);
+
+        for stamp in [1_700_000_100, 1_700_000_300, 1_700_000_200, 1_700_000_500] {
+            repo.reference(
+                &format!("{BACKUP_REF_PREFIX}main/{stamp}"),
+                oid,
+                false,
+                "backup",
+            )
+            .unwrap();
+        }
+        for stamp in [1_700_000_400, 1_700_000_600] {
+            repo.reference(
+                &format!("{BACKUP_REF_PREFIX}feature/login/{stamp}"),
+                oid,
+                false,
+                "backup",
+            )
+            .unwrap();
+        }
+
+        (temp_dir, repo, oid)
+    }
+
+    #[test]
+    fn test_list_backups_groups_by_branch_newest_first() {
+        let (_temp_dir, repo, oid) = create_repo_with_backups();
+
+        let backups = list_backups(&repo).unwrap();
+        let listed: Vec<_> = backups
+            .iter()
+            .map(|b| (b.branch.as_str(), b.created))
+            .collect();
+        assert_eq!(
+            listed,
+            [
+                ("feature/login", 1_700_000_600),
+                ("feature/login", 1_700_000_400),
+                ("main", 1_700_000_500),
+                ("main", 1_700_000_300),
+                ("main", 1_700_000_200),
+                ("main", 1_700_000_100),
+            ]
+        );
+        assert!(backups.iter().all(|b| b.target == oid));
+    }
+
+    #[test]
+    fn test_prune_backups_keeps_newest_per_branch() {
+        let (_temp_dir, repo, _oid) = create_repo_with_backups();
+
+        let pruned = prune_backups(&repo, 2).unwrap();
+        let pruned: Vec<_> = pruned.iter().map(|b| b.created).collect();
+        assert_eq!(pruned, [1_700_000_200, 1_700_000_100]);
+
+        let left = list_backups(&repo).unwrap();
+        assert_eq!(left.len(), 4);
+        for branch in ["main", "feature/login"] {
+            assert_eq!(left.iter().filter(|b| b.branch == branch).count(), 2);
+        }
+        assert_eq!(left[2].created, 1_700_000_500);
+        assert_eq!(left[3].created, 1_700_000_300);
+
+        // Pruning to zero removes every backup and nothing else
+        assert_eq!(prune_backups(&repo, 0).unwrap().len(), 4);
+        assert!(list_backups(&repo).unwrap().is_empty());
+        assert!(repo.head().is_ok());
+    }
+
+    #[test]
+    fn test_create_backup_names_the_branch_and_time() {
+        let (_temp_dir, repo, oid) = create_repo_with_backups();
+        let before = Utc::now().timestamp();
+        let target = RewriteTarget::Reference("refs/heads/feature/login".to_string());
+
+        let first = create_backup(&repo, &target, oid, "test").unwrap();
+        let backup = list_backups(&repo)
+            .unwrap()
+            .into_iter()
+            .find(|b| b.ref_name == first)
+            .unwrap();
+        assert_eq!(backup.branch, "feature/login");
+        assert!(backup.created >= before);
+        assert_eq!(backup.target, oid);
+
+        // The same tip twice is one backup; a detached HEAD is kept as HEAD
+        assert_eq!(create_backup(&repo, &target, oid, "test").unwrap(), first);
+        let detached = create_backup(&repo, &RewriteTarget::DetachedHead, oid, "test").unwrap();
+        assert!(detached.starts_with(&format!("{BACKUP_REF_PREFIX}HEAD/")));
+    }
+
+    #[test]
+    fn test_list_backups_without_timestamp_uses_commit_time() {
+        let (_temp_dir, repo, oid) = create_repo_with_backups();
+        repo.reference(&format!("{BACKUP_REF_PREFIX}manual"), oid, false, "backup")
+            .unwrap();
+
+        let backups = list_backups(&repo).unwrap();
+        let manual = backups.iter().find(|b| b.branch == "manual").unwrap();
+        assert_eq!(manual.created, 1_700_000_000);
+    }
+}
@@ -0,0 +1,175 @@
+use colored::Colorize;
+use crossterm::terminal;
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
+
+// Used when stdout is not a terminal
+const DEFAULT_TERMINAL_WIDTH: usize = 80;
+// Narrower widths would leave almost no room next to the indent
+const MIN_WIDTH: usize = 20;
+const INDENT: &str = "  ";
+const CONTINUATION_INDENT: &str = "      ";
+
+/// The part of a change a piece of text belongs to, which decides its color.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChangePart {
+    Label,
+    Original,
+    Arrow,
+    New,
+}
+
+/// Lays out `label: original -> new` in lines of at most `width` terminal
+/// columns, breaking between words and indenting continuation lines further
+/// than the first. A word wider than a whole line is split where it has to be.
+pub fn layout_change(
+    label: &str,
+    original: &str,
+    new: &str,
+    width: usize,
+) -> Vec<Vec<(ChangePart, String)>> {
+    let width = width.max(MIN_WIDTH);
+    let label = format!("{label}:");
+    let words = std::iter::once((ChangePart::Label, label.as_str()))
+        .chain(
+            original
+                .split_whitespace()
+                .map(|word| (ChangePart::Original, word)),
+        )
+        .chain(std::iter::once((ChangePart::Arrow, "->")))
+        .chain(new.split_whitespace().map(|word| (ChangePart::New, word)));
+
+    let mut lines = vec![vec![(ChangePart::Label, INDENT.to_string())]];
+    let mut line_width = INDENT.len();
+    for (part, word) in words {
+        for piece in split_to_width(word, width - CONTINUATION_INDENT.len()) {
+            let piece_width = UnicodeWidthStr::width(piece.as_str());
+            let at_line_start = lines.last().unwrap().len() == 1;
+            if !at_line_start && line_width + 1 + piece_width > width {
+                lines.push(vec![(part, CONTINUATION_INDENT.to_string())]);
+                line_width = CONTINUATION_INDENT.len();
+            }
+
+            let line = lines.last_mut().unwrap();
+            let text = if line.len() == 1 {
+                piece
+            } else {
+                line_width += 1;
+                format!(" {piece}")
+            };
+            line_width += piece_width;
+            line.push((part, text));
+        }
+    }
+    lines
+}
+
+// Splits `word` into pieces of at most `width` columns
+fn split_to_width(word: &str, width: usize) -> Vec<String> {
+    let mut pieces = vec![String::new()];
+    for c in word.chars() {
+        let current = pieces.last().unwrap();
+        let c_width = c.width().unwrap_or(0);
+        if !current.is_empty() && UnicodeWidthStr::width(current.as_str()) + c_width > width {
+            pieces.push(String::new());
+        }
+        pieces.last_mut().unwrap().push(c);
+    }
+    pieces
+}
+
+/// Prints `label: original -> new` wrapped to the terminal, with the original
+/// value in red and the new one in green.
+pub fn print_change(label: &str, original: &str, new: &str) {
+    let width = terminal::size()
+        .map(|(width, _)| width as usize)
+        .unwrap_or(DEFAULT_TERMINAL_WIDTH);
+
+    for line in layout_change(label, original, new, width) {
+        let rendered: String = line
+            .into_iter()
+            .map(|(part, text)| match part {
+                ChangePart::Label => text.bold().to_string(),
+                ChangePart::Original => text.red().to_string(),
+                ChangePart::Arrow => text,
+                ChangePart::New => text.green().to_string(),
+            })
+            .collect();
+        println!("{rendered}");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn plain(label: &str, original: &str, new: &str, width: usize) -> Vec<String> {
+        layout_change(label, original, new, width)
+            .into_iter()
+            .map(|line| line.into_iter().map(|(_, text)| text).collect())
+            .collect()
+    }
+
+    #[test]
+    fn test_layout_change_fits_on_one_line() {
+        assert_eq!(
+            plain("Author Email", "old@example.com", "new@example.com", 80),
+            ["  Author Email: old@example.com -> new@example.com"]
+        );
+    }
+
+    #[test]
+    fn test_layout_change_wraps_with_indent() {
+        let lines = plain(
+            "Message",
+            "Fix the flaky login test on slow machines",
+            "Fix the flaky login test on slow CI runners",
+            30,
+        );
+        assert_eq!(
+            lines,
+            [
+                "  Message: Fix the flaky login",
+                "      test on slow machines ->",
+                "      Fix the flaky login test",
+                "      on slow CI runners",
+            ]
+        );
+        assert!(lines
+            .iter()
+            .all(|line| UnicodeWidthStr::width(line.as_str()) <= 30));
+
+        // Words keep their part, so colors survive the wrapping
+        let parts: Vec<ChangePart> = layout_change("Message", "a b", "c", 20)
+            .concat()
+            .into_iter()
+            .map(|(part, _)| part)
+            .collect();
+        assert_eq!(
+            parts,
+            [
+                ChangePart::Label,
+                ChangePart::Label,
+                ChangePart::Original,
+                ChangePart::Original,
+                ChangePart::Arrow,
+                ChangePart::New,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_layout_change_splits_overlong_words() {
+        let email = "a.very.long.address.that.never.ends@example.com";
+        let lines = plain("Author Email", email, "me@example.com", 24);
+        assert!(lines
+            .iter()
+            .all(|line| UnicodeWidthStr::width(line.as_str()) <= 24));
+        assert_eq!(lines[0], "  Author Email:");
+        let joined: String = lines[1..]
+            .iter()
+            .map(|line| line.trim_start())
+            .collect::<Vec<_>>()
+            .join("");
+        assert!(joined.starts_with(email), "{lines:?}");
+    }
+}
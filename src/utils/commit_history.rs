@@ -1,31 +1,155 @@
+use crate::utils::git_ref::{history_tip, push_target};
 use crate::utils::types::Result;
-use crate::{args::Args, utils::types::CommitInfo};
+use crate::{
+    args::{Args, OutputFormat},
+    utils::types::CommitInfo,
+};
+use chrono::NaiveDateTime;
 use colored::Colorize;
-use git2::{Repository, Sort};
+use git2::{DiffOptions, Oid, Repository, Sort};
+use serde::Serialize;
+use std::cell::RefCell;
+use std::collections::HashMap;
 
-pub fn get_commit_history(args: &Args, print: bool) -> Result<Vec<CommitInfo>> {
+/// The commits of the last walk, keyed by what decides its result: the
+/// repository, the tip the walk started from, the limit and the path filter. A rewrite moves
+/// the tip, so a walk after it never sees the history from before.
+#[derive(Default)]
+pub struct HistoryCache(RefCell<Option<CachedHistory>>);
+
+impl HistoryCache {
+    /// Forgets the last walk, so the next one reads the repository afresh.
+    pub fn invalidate(&self) {
+        *self.0.borrow_mut() = None;
+    }
+}
+
+struct CachedHistory {
+    repo_path: String,
+    tip: Oid,
+    limit: Option<usize>,
+    path: Option<String>,
+    commits: Vec<CommitInfo>,
+    truncated: bool,
+}
+
+/// Collects the history like `get_commit_history` without printing it, up to
+/// `limit` commits regardless of `--limit`, and without the `--path` filter.
+pub fn collect_commits(args: &Args, limit: Option<usize>) -> Result<Vec<CommitInfo>> {
+    Ok(walk_history(args, limit, None)?.0)
+}
+
+/// Whether `commit` changes `path` (a file, or any file under a directory)
+/// compared to its first parent. A root commit is compared to the empty tree.
+pub fn touches_path(repo: &Repository, commit: &git2::Commit, path: &str) -> Result<bool> {
+    let parent_tree = if commit.parent_count() > 0 {
+        Some(commit.parent(0)?.tree()?)
+    } else {
+        None
+    };
+    let mut options = DiffOptions::new();
+    options.pathspec(path.trim_end_matches('/'));
+    let diff = repo.diff_tree_to_tree(
+        parent_tree.as_ref(),
+        Some(&commit.tree()?),
+        Some(&mut options),
+    )?;
+    Ok(diff.deltas().len() > 0)
+}
+
+// Returns the commits newest first and whether the limit cut the walk short,
+// from the cache on `args` when the same tip was walked with the same limit
+// and path. With a path, only commits touching it are collected and counted.
+fn walk_history(
+    args: &Args,
+    limit: Option<usize>,
+    path: Option<&str>,
+) -> Result<(Vec<CommitInfo>, bool)> {
+    let repo_path = args.repo_path.as_ref().unwrap();
+    let repo = Repository::open(repo_path)?;
+    let tip = history_tip(&repo, args)?;
+
+    if let Some(cached) = args.history_cache.0.borrow().as_ref() {
+        if cached.repo_path == *repo_path
+            && cached.tip == tip
+            && cached.limit == limit
+            && cached.path.as_deref() == path
+        {
+            return Ok((cached.commits.clone(), cached.truncated));
+        }
+    }
+
+    let mut commit_infos = Vec::new();
+    let truncated = walk_from(&repo, tip, limit, path, &mut |commit_info| {
+        commit_infos.push(commit_info);
+        Ok(())
+    })?;
+
+    *args.history_cache.0.borrow_mut() = Some(CachedHistory {
+        repo_path: repo_path.clone(),
+        tip,
+        limit,
+        path: path.map(str::to_string),
+        commits: commit_infos.clone(),
+        truncated,
+    });
+    Ok((commit_infos, truncated))
+}
+
+/// Visits the history like `get_commit_history`, newest first, one commit at
+/// a time. Nothing is kept between commits and the cache is not used, so
+/// memory stays flat however long the history is. Returns whether `limit`
+/// cut the walk short.
+pub fn walk_commits(
+    args: &Args,
+    limit: Option<usize>,
+    path: Option<&str>,
+    mut visit: impl FnMut(CommitInfo) -> Result<()>,
+) -> Result<bool> {
     let repo = Repository::open(args.repo_path.as_ref().unwrap())?;
+    let tip = history_tip(&repo, args)?;
+    walk_from(&repo, tip, limit, path, &mut visit)
+}
 
+// Hands `visit` up to `limit` commits from `tip` (only those touching `path`
+// when given) and returns whether there were more
+fn walk_from(
+    repo: &Repository,
+    tip: Oid,
+    limit: Option<usize>,
+    path: Option<&str>,
+    visit: &mut dyn FnMut(CommitInfo) -> Result<()>,
+) -> Result<bool> {
     let mut revwalk = repo.revwalk()?;
-    revwalk.push_head()?;
+    revwalk.push(tip)?;
     revwalk.set_sorting(Sort::TOPOLOGICAL | Sort::TIME)?;
 
-    // Collect all commits first
-    let mut commits = Vec::new();
-    let mut commit_infos = Vec::new();
+    let max = limit.unwrap_or(usize::MAX);
+    let mut visited = 0;
 
     for oid_result in revwalk {
+        if visited == max {
+            return Ok(true);
+        }
+
         let oid = oid_result?;
         let commit = repo.find_commit(oid)?;
-        let timestamp = commit.time();
-        let datetime = chrono::DateTime::from_timestamp(timestamp.seconds(), 0)
-            .unwrap_or_default()
-            .naive_utc();
+        if let Some(path) = path {
+            if !touches_path(repo, &commit, path)? {
+                continue;
+            }
+        }
+        let naive = |time: git2::Time| {
+            chrono::DateTime::from_timestamp(time.seconds(), 0)
+                .unwrap_or_default()
+                .naive_utc()
+        };
 
-        let commit_info = CommitInfo {
+        visit(CommitInfo {
             oid,
             short_hash: oid.to_string()[..8].to_string(),
-            timestamp: datetime,
+            timestamp: naive(commit.time()),
+            author_timestamp: naive(commit.author().when()),
             author_name: commit.author().name().unwrap_or("Unknown").to_string(),
             author_email: commit
                 .author()
@@ -34,91 +158,448 @@ pub fn get_commit_history(args: &Args, print: bool) -> Result<Vec<CommitInfo>> {
                 .to_string(),
             message: commit.message().unwrap_or("(no message)").to_string(),
             parent_count: commit.parent_count(),
+        })?;
+        visited += 1;
+    }
+
+    Ok(false)
+}
+
+/// Walks the history reachable from the rewrite target (`--ref`, or HEAD), newest first.
+///
+/// With `--limit` only the first N commits of the walk are collected, so the
+/// returned window may stop short of the root commit. Callers that rewrite
+/// history must therefore still walk the full history themselves and match
+/// edits by oid; commits outside the window are re-committed unchanged.
+/// `--path` narrows the list the same way, to the commits touching that path.
+pub fn get_commit_history(args: &Args, print: bool) -> Result<Vec<CommitInfo>> {
+    let (commit_infos, truncated) = walk_history(args, args.limit, args.path_filter.as_deref())?;
+
+    // With `print`, the views that need every commit at once: the JSON list
+    // and the --graph view. print_commit_history streams the others.
+    if print && args.format == OutputFormat::Json {
+        println!("{}", serde_json::to_string_pretty(&commit_infos)?);
+        return Ok(commit_infos);
+    }
+
+    if print && !commit_infos.is_empty() {
+        let tally = tally(&commit_infos);
+        print_summary(args, &tally, &tally.author_stats());
+
+        println!("\n{}", "Detailed Commit History:".bold().green());
+        println!("{}", "-".repeat(60).cyan());
+        let graph = history_graph(
+            &Repository::open(args.repo_path.as_ref().unwrap())?,
+            &commit_infos,
+        )?;
+        for (commit_info, row) in commit_infos.iter().zip(&graph) {
+            print_commit_line(commit_info, Some(row));
+        }
+        println!("{}", "=".repeat(60).cyan());
+
+        if truncated {
+            print_truncated_note(commit_infos.len());
+        }
+    }
+
+    Ok(commit_infos)
+}
+
+/// Prints the history for `-s`, `history` and `--stats-only` without ever
+/// holding all of it, so it works on histories of any size: one walk gathers
+/// the summary, a second prints the commits one by one. `--graph` and the
+/// JSON commit list need every commit at once, so they go through
+/// `get_commit_history`.
+pub fn print_commit_history(args: &Args) -> Result<()> {
+    let json = args.format == OutputFormat::Json;
+    if !args.stats_only && (json || args.graph) {
+        return get_commit_history(args, true).map(|_| ());
+    }
+
+    let path = args.path_filter.as_deref();
+    let mut tally = HistoryTally::default();
+    let truncated = walk_commits(args, args.limit, path, |commit| {
+        tally.add(&commit);
+        Ok(())
+    })?;
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&tally.history_stats())?);
+        return Ok(());
+    }
+    if tally.total_commits == 0 {
+        return Ok(());
+    }
+
+    let authors = tally.author_stats();
+    print_summary(args, &tally, &authors);
+
+    if args.stats_only {
+        print_author_stats(&authors);
+    } else {
+        println!("\n{}", "Detailed Commit History:".bold().green());
+        println!("{}", "-".repeat(60).cyan());
+        walk_commits(args, args.limit, path, |commit| {
+            print_commit_line(&commit, None);
+            Ok(())
+        })?;
+        println!("{}", "=".repeat(60).cyan());
+    }
+
+    if truncated {
+        print_truncated_note(tally.total_commits);
+    }
+    Ok(())
+}
+
+fn print_summary(args: &Args, tally: &HistoryTally, authors: &[AuthorStats]) {
+    let earliest_date = authors.iter().map(|a| a.first).min().unwrap_or_default();
+    let latest_date = authors.iter().map(|a| a.last).max().unwrap_or_default();
+    let date_span = latest_date.signed_duration_since(earliest_date).num_days();
+
+    // Print summary
+    println!("\n{}", "Updated Commit History Summary:".bold().green());
+    println!("{}", "-".repeat(60).cyan());
+    println!(
+        "{}: {}",
+        "Total Commits".bold(),
+        tally.total_commits.to_string().yellow()
+    );
+    println!(
+        "{}: {} days",
+        "Date Span".bold(),
+        date_span.to_string().yellow()
+    );
+    println!(
+        "{}: {} to {}",
+        "Date Range".bold(),
+        earliest_date.format("%Y-%m-%d %H:%M:%S").to_string().blue(),
+        latest_date.format("%Y-%m-%d %H:%M:%S").to_string().blue()
+    );
+    println!(
+        "{}: {}",
+        "Unique Authors".bold(),
+        authors.len().to_string().yellow()
+    );
+    if authors.len() <= 5 && !args.stats_only {
+        println!(
+            "{}: {}",
+            "Authors".bold(),
+            authors
+                .iter()
+                .map(|a| a.name.clone())
+                .collect::<Vec<_>>()
+                .join(", ")
+                .magenta()
+        );
+    }
+    println!("{}", "=".repeat(60).cyan());
+}
+
+fn print_commit_line(commit_info: &CommitInfo, row: Option<&GraphRow>) {
+    if let Some(row) = row {
+        print!("{} ", row.node.cyan());
+    }
+    println!(
+        "{} {} {} {}",
+        commit_info.short_hash.yellow().bold(),
+        commit_info
+            .timestamp
+            .format("%Y-%m-%d %H:%M:%S")
+            .to_string()
+            .blue(),
+        commit_info.author_name.magenta(),
+        commit_info.message.lines().next().unwrap_or("").white()
+    );
+    for connector in row.iter().flat_map(|row| &row.connectors) {
+        println!("{}", connector.cyan());
+    }
+}
+
+fn print_truncated_note(total_commits: usize) {
+    println!(
+        "{}",
+        format!(
+            "Showing the {total_commits} most recent commits (--limit); older history is not listed."
+        )
+        .bright_black()
+    );
+}
+
+/// The `git log --graph` style drawing for one listed commit: `node` goes in
+/// front of the commit, `connectors` are printed on their own lines below it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GraphRow {
+    pub node: String,
+    pub connectors: Vec<String>,
+}
+
+/// Draws the branch structure of `commits`, listed newest first in walk
+/// order. Every open line of history is a column: a commit is a `*` in the
+/// column that expects it, a merge opens a `\` column per further parent and
+/// a `/` closes a column once two lines reach the same parent.
+pub fn history_graph(repo: &Repository, commits: &[CommitInfo]) -> Result<Vec<GraphRow>> {
+    // The commit each column expects next
+    let mut columns: Vec<Oid> = Vec::new();
+    let mut rows = Vec::with_capacity(commits.len());
+
+    for commit in commits {
+        let col = match columns.iter().position(|&oid| oid == commit.oid) {
+            Some(col) => col,
+            None => {
+                columns.push(commit.oid);
+                columns.len() - 1
+            }
         };
+        let node = graph_line(
+            &(0..columns.len())
+                .map(|i| (2 * i, if i == col { '*' } else { '|' }))
+                .collect::<Vec<_>>(),
+        );
 
-        if print {
-            commits.push((oid, commit));
+        let mut connectors = Vec::new();
+        let parents: Vec<Oid> = repo.find_commit(commit.oid)?.parent_ids().collect();
+        match parents.split_first() {
+            None => {
+                columns.remove(col);
+            }
+            Some((&first, rest)) => {
+                columns[col] = first;
+                let opened: Vec<Oid> = rest
+                    .iter()
+                    .copied()
+                    .filter(|parent| !columns.contains(parent))
+                    .collect();
+                if !opened.is_empty() {
+                    let before = columns.len();
+                    let mut marks: Vec<_> = (0..=col).map(|i| (2 * i, '|')).collect();
+                    marks.extend((col + 1..before + opened.len()).map(|i| (2 * i - 1, '\\')));
+                    connectors.push(graph_line(&marks));
+                    columns.splice(col + 1..col + 1, opened);
+                }
+            }
         }
-        commit_infos.push(commit_info);
+
+        // Lines that now expect the same commit merge into the leftmost one
+        while let Some(dup) = (1..columns.len()).find(|&j| columns[..j].contains(&columns[j])) {
+            let mut marks: Vec<_> = (0..dup).map(|i| (2 * i, '|')).collect();
+            marks.extend((dup..columns.len()).map(|i| (2 * i - 1, '/')));
+            connectors.push(graph_line(&marks));
+            columns.remove(dup);
+        }
+
+        rows.push(GraphRow { node, connectors });
     }
+    Ok(rows)
+}
 
-    // If print is true, calculate and display statistics
-    if print {
-        let total_commits = commit_infos.len();
+// A graph line with each character at its position
+fn graph_line(marks: &[(usize, char)]) -> String {
+    let width = marks.iter().map(|&(pos, _)| pos + 1).max().unwrap_or(0);
+    let mut line = vec![' '; width];
+    for &(pos, mark) in marks {
+        line[pos] = mark;
+    }
+    line.into_iter().collect()
+}
 
-        if total_commits > 0 {
-            let timestamps: Vec<_> = commit_infos.iter().map(|c| c.timestamp).collect();
-            let mut sorted_timestamps = timestamps.clone();
-            sorted_timestamps.sort();
+/// Commit count and first/last commit date of one author. In JSON the fields
+/// are named for dashboards: `author`, `email`, `commit_count`, `first_commit`
+/// and `last_commit`.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct AuthorStats {
+    #[serde(rename = "author")]
+    pub name: String,
+    // The email of the author's most recent commit
+    pub email: String,
+    #[serde(rename = "commit_count")]
+    pub commits: usize,
+    #[serde(rename = "first_commit")]
+    pub first: NaiveDateTime,
+    #[serde(rename = "last_commit")]
+    pub last: NaiveDateTime,
+}
 
-            let earliest_date = sorted_timestamps[0];
-            let latest_date = sorted_timestamps[sorted_timestamps.len() - 1];
-            let date_span = latest_date.signed_duration_since(earliest_date).num_days();
+/// Repository-wide totals around the per-author statistics, as printed by
+/// `history --stats-only --format json`. The dates are null for an empty history.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct HistoryStats {
+    pub total_commits: usize,
+    pub total_authors: usize,
+    pub first_commit: Option<NaiveDateTime>,
+    pub last_commit: Option<NaiveDateTime>,
+    pub authors: Vec<AuthorStats>,
+}
 
-            let unique_authors: std::collections::HashSet<String> =
-                commit_infos.iter().map(|c| c.author_name.clone()).collect();
+/// Running totals over a history seen one commit at a time: what the
+/// summary and `--stats-only` print, without keeping the commits themselves.
+#[derive(Debug, Default)]
+pub struct HistoryTally {
+    pub total_commits: usize,
+    authors: HashMap<String, AuthorStats>,
+}
 
-            // Print summary
-            println!("\n{}", "Updated Commit History Summary:".bold().green());
-            println!("{}", "-".repeat(60).cyan());
-            println!(
-                "{}: {}",
-                "Total Commits".bold(),
-                total_commits.to_string().yellow()
-            );
-            println!(
-                "{}: {} days",
-                "Date Span".bold(),
-                date_span.to_string().yellow()
-            );
-            println!(
-                "{}: {} to {}",
-                "Date Range".bold(),
-                earliest_date.format("%Y-%m-%d %H:%M:%S").to_string().blue(),
-                latest_date.format("%Y-%m-%d %H:%M:%S").to_string().blue()
-            );
-            println!(
-                "{}: {}",
-                "Unique Authors".bold(),
-                unique_authors.len().to_string().yellow()
-            );
-            if unique_authors.len() <= 5 {
-                println!(
-                    "{}: {}",
-                    "Authors".bold(),
-                    unique_authors
-                        .iter()
-                        .cloned()
-                        .collect::<Vec<_>>()
-                        .join(", ")
-                        .magenta()
-                );
+impl HistoryTally {
+    pub fn add(&mut self, commit: &CommitInfo) {
+        self.total_commits += 1;
+        match self.authors.get_mut(&commit.author_name) {
+            Some(stats) => {
+                stats.commits += 1;
+                stats.first = stats.first.min(commit.timestamp);
+                stats.last = stats.last.max(commit.timestamp);
             }
-            println!("{}", "=".repeat(60).cyan());
-
-            // Print detailed commit history
-            println!("\n{}", "Detailed Commit History:".bold().green());
-            println!("{}", "-".repeat(60).cyan());
-
-            for commit_info in &commit_infos {
-                println!(
-                    "{} {} {} {}",
-                    commit_info.short_hash.yellow().bold(),
-                    commit_info
-                        .timestamp
-                        .format("%Y-%m-%d %H:%M:%S")
-                        .to_string()
-                        .blue(),
-                    commit_info.author_name.magenta(),
-                    commit_info.message.lines().next().unwrap_or("").white()
+            None => {
+                self.authors.insert(
+                    commit.author_name.clone(),
+                    AuthorStats {
+                        name: commit.author_name.clone(),
+                        email: commit.author_email.clone(),
+                        commits: 1,
+                        first: commit.timestamp,
+                        last: commit.timestamp,
+                    },
                 );
             }
+        }
+    }
+
+    /// The authors seen so far, most commits first (ties by name).
+    pub fn author_stats(&self) -> Vec<AuthorStats> {
+        let mut stats: Vec<AuthorStats> = self.authors.values().cloned().collect();
+        stats.sort_by(|a, b| b.commits.cmp(&a.commits).then_with(|| a.name.cmp(&b.name)));
+        stats
+    }
 
-            println!("{}", "=".repeat(60).cyan());
+    /// `author_stats` together with the totals.
+    pub fn history_stats(&self) -> HistoryStats {
+        let authors = self.author_stats();
+        HistoryStats {
+            total_commits: self.total_commits,
+            total_authors: authors.len(),
+            first_commit: authors.iter().map(|a| a.first).min(),
+            last_commit: authors.iter().map(|a| a.last).max(),
+            authors,
         }
     }
+}
 
-    Ok(commit_infos)
+/// Groups commits by author name, most commits first (ties by name).
+pub fn author_stats(commits: &[CommitInfo]) -> Vec<AuthorStats> {
+    tally(commits).author_stats()
+}
+
+/// `author_stats` together with the totals over the whole list.
+pub fn history_stats(commits: &[CommitInfo]) -> HistoryStats {
+    tally(commits).history_stats()
+}
+
+fn tally(commits: &[CommitInfo]) -> HistoryTally {
+    let mut tally = HistoryTally::default();
+    commits.iter().for_each(|commit| tally.add(commit));
+    tally
+}
+
+fn print_author_stats(authors: &[AuthorStats]) {
+    println!("\n{}", "Authorship Distribution:".bold().green());
+    println!("{}", "-".repeat(60).cyan());
+
+    let name_width = authors.iter().map(|a| a.name.len()).max().unwrap_or(0);
+    for author in authors {
+        println!(
+            "{:<name_width$}  {:>5} commits  {} to {}",
+            author.name.magenta(),
+            author.commits.to_string().yellow(),
+            author.first.format("%Y-%m-%d").to_string().blue(),
+            author.last.format("%Y-%m-%d").to_string().blue()
+        );
+    }
+
+    println!("{}", "=".repeat(60).cyan());
+}
+
+/// A commit authored before one of its parents.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DateOrderViolation {
+    pub oid: git2::Oid,
+    pub author_date: NaiveDateTime,
+    pub parent_oid: git2::Oid,
+    pub parent_date: NaiveDateTime,
+}
+
+/// Finds every commit whose author date is earlier than the author date of
+/// one of its parents, newest first. Only the latest such parent is reported.
+pub fn find_author_date_violations(args: &Args) -> Result<Vec<DateOrderViolation>> {
+    let repo = Repository::open(args.repo_path.as_ref().unwrap())?;
+
+    let mut revwalk = repo.revwalk()?;
+    push_target(&mut revwalk, &repo, args)?;
+    revwalk.set_sorting(Sort::TOPOLOGICAL | Sort::TIME)?;
+
+    let author_date = |commit: &git2::Commit| {
+        chrono::DateTime::from_timestamp(commit.author().when().seconds(), 0)
+            .unwrap_or_default()
+            .naive_utc()
+    };
+
+    let mut violations = Vec::new();
+    for oid in revwalk {
+        let commit = repo.find_commit(oid?)?;
+        let date = author_date(&commit);
+        let latest_parent = commit
+            .parents()
+            .map(|parent| (parent.id(), author_date(&parent)))
+            .max_by_key(|&(_, parent_date)| parent_date);
+
+        if let Some((parent_oid, parent_date)) = latest_parent {
+            if date < parent_date {
+                violations.push(DateOrderViolation {
+                    oid: commit.id(),
+                    author_date: date,
+                    parent_oid,
+                    parent_date,
+                });
+            }
+        }
+    }
+
+    Ok(violations)
+}
+
+pub fn print_date_order_violations(violations: &[DateOrderViolation]) {
+    if violations.is_empty() {
+        println!(
+            "{}",
+            "✓ Every commit is authored after its parents.".green()
+        );
+        return;
+    }
+
+    println!(
+        "\n{}",
+        format!("{} commit(s) authored before a parent:", violations.len())
+            .bold()
+            .yellow()
+    );
+    println!("{}", "-".repeat(80).cyan());
+    for violation in violations {
+        println!(
+            "{} {}  {} {} {}",
+            violation.oid.to_string()[..8].yellow().bold(),
+            violation
+                .author_date
+                .format("%Y-%m-%d %H:%M:%S")
+                .to_string()
+                .red(),
+            "is before parent".bright_black(),
+            violation.parent_oid.to_string()[..8].yellow(),
+            violation
+                .parent_date
+                .format("%Y-%m-%d %H:%M:%S")
+                .to_string()
+                .blue()
+        );
+    }
+    println!("{}", "=".repeat(80).cyan());
 }
 
 #[cfg(test)]
@@ -177,6 +658,56 @@ mod tests {
         (temp_dir, repo_path)
     }
 
+    #[test]
+    fn test_cached_history_matches_fresh_walk() {
+        let (temp_dir, repo_path) = create_test_repo_with_commits();
+        let args = Args {
+            repo_path: Some(repo_path.clone()),
+            ..Default::default()
+        };
+        let fresh = |args: &Args| {
+            let fresh_args = Args {
+                repo_path: args.repo_path.clone(),
+                limit: args.limit,
+                ..Default::default()
+            };
+            serde_json::to_value(get_commit_history(&fresh_args, false).unwrap()).unwrap()
+        };
+
+        // The date-range probe fills the cache that the mode then reads from
+        let probed = collect_commits(&args, None).unwrap();
+        assert!(args.history_cache.0.borrow().is_some());
+        let cached = get_commit_history(&args, false).unwrap();
+        assert_eq!(serde_json::to_value(&probed).unwrap(), fresh(&args));
+        assert_eq!(serde_json::to_value(&cached).unwrap(), fresh(&args));
+
+        // A different limit is walked again rather than cut from the cache
+        let limited = Args {
+            limit: Some(2),
+            ..args
+        };
+        assert_eq!(get_commit_history(&limited, false).unwrap().len(), 2);
+        let args = Args {
+            limit: None,
+            ..limited
+        };
+
+        // A new commit moves the tip, so the cached walk is not reused
+        let repo = Repository::open(&repo_path).unwrap();
+        fs::write(temp_dir.path().join("test4.txt"), "test content 4").unwrap();
+        let mut index = repo.index().unwrap();
+        index.add_path(std::path::Path::new("test4.txt")).unwrap();
+        let tree = repo.find_tree(index.write_tree().unwrap()).unwrap();
+        let head = repo.head().unwrap().peel_to_commit().unwrap();
+        let sig = git2::Signature::now("Test User", "test@example.com").unwrap();
+        repo.commit(Some("HEAD"), &sig, &sig, "Commit 4", &tree, &[&head])
+            .unwrap();
+
+        let after = get_commit_history(&args, false).unwrap();
+        assert_eq!(after.len(), 4);
+        assert_eq!(serde_json::to_value(&after).unwrap(), fresh(&args));
+    }
+
     #[test]
     fn test_get_commit_history_without_print() {
         let (_temp_dir, repo_path) = create_test_repo_with_commits();
@@ -195,7 +726,7 @@ mod tests {
             edit_author: false,
             edit_time: false,
             docs: false,
-            _temp_dir: None,
+            ..Default::default()
         };
 
         let result = get_commit_history(&args, false);
@@ -211,7 +742,7 @@ mod tests {
     }
 
     #[test]
-    fn test_get_commit_history_with_print() {
+    fn test_print_commit_history() {
         let (_temp_dir, repo_path) = create_test_repo_with_commits();
         let args = Args {
             repo_path: Some(repo_path),
@@ -228,14 +759,11 @@ mod tests {
             edit_author: false,
             edit_time: false,
             docs: false,
-            _temp_dir: None,
+            ..Default::default()
         };
 
-        let result = get_commit_history(&args, true);
-        assert!(result.is_ok());
-
-        let commit_infos = result.unwrap();
-        assert_eq!(commit_infos.len(), 3);
+        assert!(print_commit_history(&args).is_ok());
+        assert_eq!(get_commit_history(&args, false).unwrap().len(), 3);
     }
 
     #[test]
@@ -256,7 +784,7 @@ mod tests {
             edit_author: false,
             edit_time: false,
             docs: false,
-            _temp_dir: None,
+            ..Default::default()
         };
 
         let result = get_commit_history(&args, false);
@@ -297,7 +825,7 @@ mod tests {
             edit_author: false,
             edit_time: false,
             docs: false,
-            _temp_dir: None,
+            ..Default::default()
         };
 
         let result = get_commit_history(&args, false);
@@ -322,7 +850,7 @@ mod tests {
             edit_author: false,
             edit_time: false,
             docs: false,
-            _temp_dir: None,
+            ..Default::default()
         };
 
         let result = get_commit_history(&args, false);
@@ -347,7 +875,7 @@ mod tests {
             edit_author: false,
             edit_time: false,
             docs: false,
-            _temp_dir: None,
+            ..Default::default()
         };
 
         let result = get_commit_history(&args, false);
@@ -362,4 +890,230 @@ mod tests {
         assert_eq!(commit_infos[1].parent_count, 1);
         assert_eq!(commit_infos[0].parent_count, 1);
     }
+
+    #[test]
+    fn test_get_commit_history_with_limit() {
+        let (_temp_dir, repo_path) = create_test_repo_with_commits();
+        let args = Args {
+            repo_path: Some(repo_path),
+            limit: Some(2),
+            ..Default::default()
+        };
+
+        assert!(print_commit_history(&args).is_ok());
+        let commit_infos = get_commit_history(&args, false).unwrap();
+
+        // Only the two most recent commits are collected; the root is not reached
+        assert_eq!(commit_infos.len(), 2);
+        assert_eq!(commit_infos[0].message, "Commit 3");
+        assert_eq!(commit_infos[1].message, "Commit 2");
+        assert_eq!(commit_infos[1].parent_count, 1);
+    }
+
+    #[test]
+    fn test_get_commit_history_limit_larger_than_history() {
+        let (_temp_dir, repo_path) = create_test_repo_with_commits();
+        let args = Args {
+            repo_path: Some(repo_path),
+            limit: Some(10),
+            ..Default::default()
+        };
+
+        let commit_infos = get_commit_history(&args, false).unwrap();
+        assert_eq!(commit_infos.len(), 3);
+    }
+
+    #[test]
+    fn test_walk_commits_matches_collected_history() {
+        let (_temp_dir, repo_path) = create_test_repo_with_commits();
+        let args = Args {
+            repo_path: Some(repo_path.clone()),
+            ..Default::default()
+        };
+
+        let mut streamed = Vec::new();
+        let truncated = walk_commits(&args, None, None, |commit| {
+            streamed.push(commit.oid);
+            Ok(())
+        })
+        .unwrap();
+        let collected: Vec<_> = get_commit_history(&args, false)
+            .unwrap()
+            .iter()
+            .map(|commit| commit.oid)
+            .collect();
+        assert_eq!(streamed, collected);
+        assert!(!truncated);
+
+        let mut count = 0;
+        assert!(walk_commits(&args, Some(2), None, |_| {
+            count += 1;
+            Ok(())
+        })
+        .unwrap());
+        assert_eq!(count, 2);
+
+        // An error from the visitor stops the walk
+        let mut seen = 0;
+        let result = walk_commits(&args, None, None, |_| {
+            seen += 1;
+            Err("stop".into())
+        });
+        assert!(result.is_err());
+        assert_eq!(seen, 1);
+
+        // The streamed summary, list and statistics print without collecting
+        for (stats_only, format) in [
+            (false, OutputFormat::Text),
+            (true, OutputFormat::Text),
+            (true, OutputFormat::Json),
+        ] {
+            let args = Args {
+                stats_only,
+                format,
+                limit: Some(2),
+                repo_path: Some(repo_path.clone()),
+                ..Default::default()
+            };
+            assert!(print_commit_history(&args).is_ok());
+        }
+    }
+
+    #[test]
+    fn test_author_stats_two_authors() {
+        let temp_dir = TempDir::new().unwrap();
+        let repo = git2::Repository::init(temp_dir.path()).unwrap();
+
+        let authors = [("Alice", 1000), ("Bob", 2000), ("Alice", 3000)];
+        let mut parent: Option<git2::Oid> = None;
+        for (i, (name, seconds)) in authors.iter().enumerate() {
+            fs::write(temp_dir.path().join(format!("f{i}.txt")), name).unwrap();
+            let mut index = repo.index().unwrap();
+            index
+                .add_path(std::path::Path::new(&format!("f{i}.txt")))
+                .unwrap();
+            index.write().unwrap();
+            let tree = repo.find_tree(index.write_tree().unwrap()).unwrap();
+            let sig = git2::Signature::new(
+                name,
+                &format!("{}@example.com", name.to_lowercase()),
+                &git2::Time::new(*seconds, 0),
+            )
+            .unwrap();
+            let parents: Vec<git2::Commit> = parent
+                .map(|p| repo.find_commit(p).unwrap())
+                .into_iter()
+                .collect();
+            let parent_refs: Vec<&git2::Commit> = parents.iter().collect();
+            parent = Some(
+                repo.commit(Some("HEAD"), &sig, &sig, "msg", &tree, &parent_refs)
+                    .unwrap(),
+            );
+        }
+
+        let args = Args {
+            repo_path: Some(temp_dir.path().to_string_lossy().to_string()),
+            stats_only: true,
+            ..Default::default()
+        };
+        assert!(print_commit_history(&args).is_ok());
+        let commits = get_commit_history(&args, false).unwrap();
+        let stats = author_stats(&commits);
+
+        let at = |s: i64| chrono::DateTime::from_timestamp(s, 0).unwrap().naive_utc();
+        assert_eq!(
+            stats,
+            vec![
+                AuthorStats {
+                    name: "Alice".to_string(),
+                    email: "alice@example.com".to_string(),
+                    commits: 2,
+                    first: at(1000),
+                    last: at(3000),
+                },
+                AuthorStats {
+                    name: "Bob".to_string(),
+                    email: "bob@example.com".to_string(),
+                    commits: 1,
+                    first: at(2000),
+                    last: at(2000),
+                },
+            ]
+        );
+
+        let json = serde_json::to_value(history_stats(&commits)).unwrap();
+        assert_eq!(json["total_commits"], 3);
+        assert_eq!(json["total_authors"], 2);
+        assert_eq!(json["first_commit"], "1970-01-01T00:16:40");
+        assert_eq!(json["last_commit"], "1970-01-01T00:50:00");
+        assert_eq!(
+            json["authors"][0],
+            serde_json::json!({
+                "author": "Alice",
+                "email": "alice@example.com",
+                "commit_count": 2,
+                "first_commit": "1970-01-01T00:16:40",
+                "last_commit": "1970-01-01T00:50:00"
+            })
+        );
+        assert_eq!(json["authors"][1]["commit_count"], 1);
+    }
+
+    #[test]
+    fn test_history_graph_marks_merge() {
+        let temp_dir = TempDir::new().unwrap();
+        let repo_path = temp_dir.path().to_str().unwrap().to_string();
+        let repo = Repository::init(&repo_path).unwrap();
+        let tree = repo
+            .find_tree(repo.index().unwrap().write_tree().unwrap())
+            .unwrap();
+        let commit = |message: &str, time: i64, parents: &[&git2::Commit]| {
+            let sig = git2::Signature::new(
+                "Test User",
+                "test@example.com",
+                &git2::Time::new(1_700_000_000 + time, 0),
+            )
+            .unwrap();
+            let oid = repo
+                .commit(None, &sig, &sig, message, &tree, parents)
+                .unwrap();
+            repo.find_commit(oid).unwrap()
+        };
+
+        // Root, then "Side" and "Main" on two lines, merged into "Merge"
+        let root = commit("Root", 0, &[]);
+        let side = commit("Side", 100, &[&root]);
+        let main = commit("Main", 200, &[&root]);
+        let merge = commit("Merge", 300, &[&main, &side]);
+        repo.reference("refs/heads/master", merge.id(), true, "merge")
+            .unwrap();
+        repo.set_head("refs/heads/master").unwrap();
+
+        let args = Args {
+            repo_path: Some(repo_path),
+            graph: true,
+            ..Default::default()
+        };
+        let commits = get_commit_history(&args, false).unwrap();
+        let messages: Vec<_> = commits.iter().map(|c| c.message.as_str()).collect();
+        assert_eq!(messages, ["Merge", "Main", "Side", "Root"]);
+
+        let graph = history_graph(&repo, &commits).unwrap();
+        let rows: Vec<_> = graph
+            .iter()
+            .map(|row| (row.node.as_str(), row.connectors.clone()))
+            .collect();
+        assert_eq!(
+            rows,
+            [
+                ("*", vec!["|\\".to_string()]),
+                ("* |", vec![]),
+                ("| *", vec!["|/".to_string()]),
+                ("*", vec![]),
+            ]
+        );
+
+        // Printing the graph alongside the history works end to end
+        assert!(print_commit_history(&args).is_ok());
+    }
 }
@@ -0,0 +1,237 @@
+use crate::args::{Args, Distribution};
+use crate::utils::theme::Theme;
+use crate::utils::types::Result;
+use clap::ValueEnum;
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+/// Defaults read from the config file. Each value only applies when the
+/// matching flag was not given on the command line, and a name or email set
+/// here is used instead of the git config lookup.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Config {
+    pub name: Option<String>,
+    pub email: Option<String>,
+    pub begin: Option<String>,
+    pub end: Option<String>,
+    pub distribution: Option<Distribution>,
+    // false turns off the backup ref every rewrite keeps, like --no-backup
+    pub backup: Option<bool>,
+    pub theme: Theme,
+}
+
+// The file as written; values are checked once it has been read
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ConfigFile {
+    name: Option<String>,
+    email: Option<String>,
+    begin: Option<String>,
+    end: Option<String>,
+    distribution: Option<String>,
+    backup: Option<bool>,
+    #[serde(default)]
+    color: BTreeMap<String, String>,
+}
+
+impl Config {
+    /// Loads `path`, or the default location when no path is given. A missing
+    /// default file is not an error; a missing explicit one is.
+    pub fn load(path: Option<&str>) -> Result<Config> {
+        let path = match path {
+            Some(path) => PathBuf::from(path),
+            None => match default_config_path() {
+                Some(path) if path.is_file() => path,
+                _ => return Ok(Config::default()),
+            },
+        };
+
+        let content = std::fs::read_to_string(&path)
+            .map_err(|e| format!("Failed to read config file {}: {e}", path.display()))?;
+        Config::parse(&content).map_err(|e| format!("{}: {e}", path.display()).into())
+    }
+
+    pub fn parse(content: &str) -> Result<Config> {
+        let file: ConfigFile = toml::from_str(content)?;
+
+        let distribution = file
+            .distribution
+            .map(|value| {
+                Distribution::from_str(&value, true)
+                    .map_err(|_| format!("unknown distribution '{value}'"))
+            })
+            .transpose()?;
+        let mut theme = Theme::default();
+        for (element, color) in &file.color {
+            theme
+                .set(element, color)
+                .map_err(|e| format!("color.{element}: {e}"))?;
+        }
+
+        Ok(Config {
+            name: file.name,
+            email: file.email,
+            begin: file.begin,
+            end: file.end,
+            distribution,
+            backup: file.backup,
+            theme,
+        })
+    }
+
+    /// Fills every option that was not given on the command line. The identity
+    /// is left alone with `--mailmap`, which replaces it.
+    pub fn apply(&self, args: &mut Args) {
+        if args.mailmap.is_none()
+            && !args.keep_author
+            && args.find_author.is_none()
+            && !args.normalize_emails
+            && !args.anonymize
+        {
+            if args.name.is_none() {
+                args.name = self.name.clone();
+            }
+            if args.email.is_none() {
+                args.email = self.email.clone();
+            }
+        }
+        if args.start.is_none() {
+            args.start = self.begin.clone();
+        }
+        if args.end.is_none() {
+            args.end = self.end.clone();
+        }
+        if args.distribution.is_none() {
+            args.distribution = self.distribution;
+        }
+        if self.backup == Some(false) {
+            args.no_backup = true;
+        }
+        // There are no color flags, so the theme always comes from here
+        args.theme = self.theme;
+    }
+}
+
+/// `$XDG_CONFIG_HOME/git-editor/config.toml`, falling back to
+/// `~/.config/git-editor/config.toml`.
+pub fn default_config_path() -> Option<PathBuf> {
+    let config_dir = match std::env::var("XDG_CONFIG_HOME") {
+        Ok(dir) if !dir.is_empty() => PathBuf::from(dir),
+        _ => {
+            let home = std::env::var("HOME")
+                .or_else(|_| std::env::var("USERPROFILE"))
+                .ok()?;
+            PathBuf::from(home).join(".config")
+        }
+    };
+    Some(config_dir.join("git-editor").join("config.toml"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_parse_config() {
+        let config = Config::parse(
+            "# git-editor defaults\n\
+             name = \"Jane \\\"JD\\\" Doe\"  # trailing comment\n\
+             email = 'jane#1@example.com'\n\
+             \n\
+             begin = \"2023-01-01 00:00:00\"\n\
+             distribution = \"linear\"\n\
+             backup = false\n\
+             color.author = \"bright yellow\"\n",
+        )
+        .unwrap();
+
+        assert_eq!(config.name.as_deref(), Some("Jane \"JD\" Doe"));
+        assert_eq!(config.email.as_deref(), Some("jane#1@example.com"));
+        assert_eq!(config.begin.as_deref(), Some("2023-01-01 00:00:00"));
+        assert_eq!(config.end, None);
+        assert_eq!(config.distribution, Some(Distribution::Linear));
+        assert_eq!(config.backup, Some(false));
+        assert_eq!(config.theme.author, colored::Color::BrightYellow);
+        assert_eq!(config.theme.email, Theme::default().email);
+
+        // Any TOML layout of the same keys reads the same
+        let tables = Config::parse("[color]\nauthor = \"bright yellow\"\n").unwrap();
+        assert_eq!(tables.theme, config.theme);
+    }
+
+    #[test]
+    fn test_parse_config_errors() {
+        assert!(Config::parse("[section]").is_err());
+        assert!(Config::parse("name").is_err());
+        assert!(Config::parse("name = \"open").is_err());
+        assert!(Config::parse("name = Jane Doe").is_err());
+        assert!(Config::parse("backup = \"yes\"").is_err());
+        assert!(Config::parse("distribution = \"sideways\"").is_err());
+        let err = Config::parse("name = \"ok\"\nbusiness_hours = true")
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("unknown field `business_hours`"), "{err}");
+        let err = Config::parse("color.author = \"mauve\"")
+            .unwrap_err()
+            .to_string();
+        assert_eq!(err, "color.author: unknown color 'mauve'");
+    }
+
+    #[test]
+    fn test_command_line_takes_precedence_over_config() {
+        let config = Config::parse(
+            "name = \"Config Name\"\nemail = \"config@example.com\"\ndistribution = \"gaussian\"\nbackup = false",
+        )
+        .unwrap();
+        let mut args = Args {
+            email: Some("cli@example.com".to_string()),
+            distribution: Some(Distribution::Uniform),
+            ..Default::default()
+        };
+
+        config.apply(&mut args);
+
+        assert_eq!(args.name.as_deref(), Some("Config Name"));
+        assert_eq!(args.email.as_deref(), Some("cli@example.com"));
+        assert_eq!(args.distribution, Some(Distribution::Uniform));
+        assert_eq!(args.start, None);
+        assert!(args.no_backup);
+    }
+
+    #[test]
+    fn test_config_takes_precedence_over_git_config() {
+        let temp_dir = TempDir::new().unwrap();
+        let repo = git2::Repository::init(temp_dir.path()).unwrap();
+        let tree = repo
+            .find_tree(repo.index().unwrap().write_tree().unwrap())
+            .unwrap();
+        let sig = git2::Signature::now("Test User", "test@example.com").unwrap();
+        repo.commit(Some("HEAD"), &sig, &sig, "Initial", &tree, &[])
+            .unwrap();
+        let config_path = temp_dir.path().join("config.toml");
+        std::fs::write(
+            &config_path,
+            "name = \"Config Name\"\nemail = \"config@example.com\"\n\
+             begin = \"2023-01-01 00:00:00\"\nend = \"2023-01-02 00:00:00\"\n",
+        )
+        .unwrap();
+
+        let mut args = Args {
+            repo_path: Some(temp_dir.path().to_string_lossy().to_string()),
+            ..Default::default()
+        };
+        Config::load(config_path.to_str()).unwrap().apply(&mut args);
+
+        // Everything is set, so the git config is never consulted and nothing is prompted
+        args.ensure_all_args_present().unwrap();
+        assert_eq!(args.name.as_deref(), Some("Config Name"));
+        assert_eq!(args.email.as_deref(), Some("config@example.com"));
+    }
+
+    #[test]
+    fn test_load_missing_explicit_file_fails() {
+        assert!(Config::load(Some("/nonexistent/git-editor.toml")).is_err());
+    }
+}
@@ -1,81 +1,355 @@
-use crate::args::Args;
+use crate::args::{Args, Distribution, Granularity};
+use crate::utils::git_clone::is_git_url;
+use crate::utils::git_ref::push_target;
 use crate::utils::types::Result;
-use chrono::{Duration, NaiveDateTime};
-use rand::Rng;
-use uuid::Uuid;
-
-pub fn generate_timestamps(args: &mut Args) -> Result<Vec<NaiveDateTime>> {
-    let start_dt =
-        NaiveDateTime::parse_from_str(args.start.as_ref().unwrap(), "%Y-%m-%d %H:%M:%S")?;
-    let end_dt = NaiveDateTime::parse_from_str(args.end.as_ref().unwrap(), "%Y-%m-%d %H:%M:%S")?;
+use chrono::{Duration, NaiveDateTime, Utc};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::collections::HashMap;
+
+// Smallest gap between two generated timestamps unless --min-gap-minutes says otherwise
+pub const DEFAULT_MIN_GAP_MINUTES: u32 = 180;
+
+/// The strftime format dates given on the command line or typed at a prompt
+/// are parsed with, unless `--date-format` overrides it.
+pub const DEFAULT_DATE_FORMAT: &str = "%Y-%m-%d %H:%M:%S";
+
+/// Parses `input`, ignoring surrounding whitespace, with the strftime `format`.
+pub fn parse_datetime(input: &str, format: &str) -> Result<NaiveDateTime> {
+    NaiveDateTime::parse_from_str(input.trim(), format).map_err(|_| {
+        format!(
+            "Invalid date '{}' (expected {})",
+            input.trim(),
+            format_hint(format)
+        )
+        .into()
+    })
+}
 
-    if start_dt >= end_dt {
-        return Err("Start datetime must be before end datetime".into());
+/// `format` as shown in prompts and errors: spelled out for the default,
+/// the strftime string itself for a custom one.
+pub fn format_hint(format: &str) -> String {
+    if format == DEFAULT_DATE_FORMAT {
+        "YYYY-MM-DD HH:MM:SS".to_string()
+    } else {
+        format.to_string()
     }
+}
 
-    if url::Url::parse(args.repo_path.as_ref().unwrap()).is_ok()
+/// Generates one timestamp per commit, ordered oldest commit first.
+pub fn generate_timestamps(args: &mut Args) -> Result<Vec<NaiveDateTime>> {
+    if is_git_url(args.repo_path.as_ref().unwrap())
         && !std::path::Path::new(args.repo_path.as_ref().unwrap()).exists()
     {
-        let tmp_dir = std::env::temp_dir().join(format!("git_editor_{}", Uuid::new_v4()));
-        std::fs::create_dir_all(&tmp_dir)?;
-
-        let status = std::process::Command::new("git")
-            .args([
-                "clone",
-                args.repo_path.as_ref().unwrap(),
-                &tmp_dir.to_string_lossy(),
-            ])
-            .status()?;
-
-        if !status.success() {
-            return Err("Failed to clone repository".into());
-        }
+        args.clone_remote_repo()?;
+    }
+
+    // KEEP_ORIGINAL is not a date, so hand back each commit's own timestamp instead
+    if args.should_keep_original_timestamps() {
+        return original_timestamps(args);
+    }
 
-        // Update repo_path to point to the cloned repository
-        args.repo_path = Some(tmp_dir.to_string_lossy().to_string());
+    let start_dt = parse_datetime(args.start.as_ref().unwrap(), args.date_format())?;
+    let end_dt = parse_datetime(args.end.as_ref().unwrap(), args.date_format())?;
+
+    if start_dt >= end_dt {
+        return Err("Start datetime must be before end datetime".into());
     }
-    let total_commits = count_commits(args.repo_path.as_ref().unwrap())?;
+
+    let total_commits = count_commits(args)?;
     if total_commits == 0 {
         return Err("No commits found in repository".into());
     }
 
-    let min_span = Duration::hours(3 * (total_commits as i64 - 1));
+    let min_gap = Duration::minutes(args.min_gap_minutes.unwrap_or(DEFAULT_MIN_GAP_MINUTES) as i64);
+    let min_span = min_gap * (total_commits as i32 - 1);
     let total_span = end_dt - start_dt;
 
     if total_span < min_span {
         return Err(format!(
-            "Date range too small for {} commits. Need at least {} hours between start and end dates.",
+            "Date range too small for {} commits. Need at least {} between start and end dates (or lower --min-gap-minutes).",
             total_commits,
-            min_span.num_hours()
+            format_span(min_span)
         ).into());
     }
 
+    // Without --seed every run draws fresh timestamps
+    let mut rng = match args.seed {
+        Some(seed) => StdRng::seed_from_u64(seed),
+        None => StdRng::from_os_rng(),
+    };
     let slack = total_span - min_span;
-    let mut rng = rand::rng();
-    let mut weights: Vec<f64> = (0..(total_commits - 1)).map(|_| rng.random()).collect();
-    let sum: f64 = weights.iter().sum();
+    let timestamps = spread_timestamps(
+        &mut rng,
+        start_dt,
+        total_commits,
+        min_gap,
+        slack,
+        args.distribution.unwrap_or_default(),
+    );
+    if let Some(&latest) = timestamps.last() {
+        check_not_future(args, "Generated timestamp", latest)?;
+    }
+    Ok(timestamps)
+}
 
-    for w in &mut weights {
-        *w = (*w / sum) * slack.num_seconds() as f64;
+/// With `--no-future`, rejects a date later than the current UTC time. Dates
+/// are written to the commits as UTC, and some hosts and CI systems refuse
+/// commits dated ahead of now.
+pub fn check_not_future(args: &Args, what: &str, date: NaiveDateTime) -> Result<()> {
+    if args.no_future && date > Utc::now().naive_utc() {
+        return Err(format!(
+            "{what} {} is in the future (rejected by --no-future)",
+            date.format(args.date_format())
+        )
+        .into());
     }
+    Ok(())
+}
+
+// Places one commit at start_dt and every following commit at least `min_gap`
+// after the previous one, sharing `slack` between the gaps as the distribution
+// dictates. The last commit always lands exactly on start_dt + min span + slack.
+fn spread_timestamps(
+    rng: &mut StdRng,
+    start_dt: NaiveDateTime,
+    total_commits: usize,
+    min_gap: Duration,
+    slack: Duration,
+    distribution: Distribution,
+) -> Vec<NaiveDateTime> {
+    let gaps = total_commits.saturating_sub(1);
+    let slack_secs = slack.num_seconds() as f64;
+    let allocations = match distribution {
+        Distribution::Random => random_allocations(rng, gaps, slack_secs),
+        Distribution::Uniform => uniform_allocations(rng, gaps, slack_secs),
+        Distribution::Linear => linear_allocations(rng, gaps, slack_secs),
+        Distribution::Gaussian => gaussian_allocations(rng, gaps, slack_secs),
+    };
 
     let mut timestamps = Vec::with_capacity(total_commits);
-    let mut current = start_dt;
-    timestamps.push(current);
+    timestamps.push(start_dt);
+
+    // Round the running total rather than each gap so rounding errors don't accumulate
+    let mut allocated = 0.0;
+    for (i, w) in allocations.iter().enumerate() {
+        allocated += w;
+        timestamps.push(
+            start_dt + min_gap * (i as i32 + 1) + Duration::seconds(allocated.round() as i64),
+        );
+    }
+
+    timestamps
+}
 
-    for w in &weights {
-        let secs = w.round() as i64 + 3 * 3600;
-        current += Duration::seconds(secs);
-        timestamps.push(current);
+// "6 hours", or "1 hour 30 minutes" when the span is not a whole number of hours
+fn format_span(span: Duration) -> String {
+    let plural = |n: i64, word: &str| format!("{n} {word}{}", if n == 1 { "" } else { "s" });
+    let (hours, minutes) = (span.num_hours(), span.num_minutes() % 60);
+    match (hours, minutes) {
+        (_, 0) => plural(hours, "hour"),
+        (0, _) => plural(minutes, "minute"),
+        _ => format!("{} {}", plural(hours, "hour"), plural(minutes, "minute")),
     }
+}
 
-    Ok(timestamps)
+// Independent random weights per gap
+fn random_allocations(rng: &mut StdRng, gaps: usize, slack_secs: f64) -> Vec<f64> {
+    let weights: Vec<f64> = (0..gaps).map(|_| rng.random()).collect();
+    let sum: f64 = weights.iter().sum();
+    weights.iter().map(|w| w / sum * slack_secs).collect()
 }
 
-fn count_commits(repo_path: &str) -> Result<usize> {
-    let repo = git2::Repository::open(repo_path)?;
+// Gaps between sorted uniform points, i.e. commits spread uniformly over the range
+fn uniform_allocations(rng: &mut StdRng, gaps: usize, slack_secs: f64) -> Vec<f64> {
+    if gaps == 0 {
+        return Vec::new();
+    }
+    let points: Vec<f64> = (0..gaps - 1).map(|_| rng.random()).collect();
+    spacings(points, slack_secs)
+}
+
+// Equal gaps, like generate_range_timestamps. Takes the generator only to
+// share a signature with the random distributions.
+fn linear_allocations(_rng: &mut StdRng, gaps: usize, slack_secs: f64) -> Vec<f64> {
+    vec![slack_secs / gaps as f64; gaps]
+}
+
+// Points drawn from a normal distribution around the midpoint of the range
+fn gaussian_allocations(rng: &mut StdRng, gaps: usize, slack_secs: f64) -> Vec<f64> {
+    if gaps == 0 {
+        return Vec::new();
+    }
+    let points: Vec<f64> = (0..gaps - 1)
+        .map(|_| {
+            // Box-Muller transform, standard deviation of 1/6 of the range
+            let u1: f64 = 1.0 - rng.random::<f64>();
+            let u2: f64 = rng.random();
+            let z = (-2.0 * u1.ln()).sqrt() * (2.0 * std::f64::consts::PI * u2).cos();
+            (0.5 + z / 6.0).clamp(0.0, 1.0)
+        })
+        .collect();
+    spacings(points, slack_secs)
+}
+
+// Turns interior points in [0, 1] into the gaps between 0, the sorted points and 1
+fn spacings(mut points: Vec<f64>, slack_secs: f64) -> Vec<f64> {
+    points.sort_by(f64::total_cmp);
+    points.push(1.0);
+
+    let mut previous = 0.0;
+    points
+        .into_iter()
+        .map(|point| {
+            let gap = (point - previous) * slack_secs;
+            previous = point;
+            gap
+        })
+        .collect()
+}
+
+// Returns the existing author timestamps, ordered oldest commit first like generate_timestamps
+fn original_timestamps(args: &Args) -> Result<Vec<NaiveDateTime>> {
+    let repo = git2::Repository::open(args.repo_path.as_ref().unwrap())?;
     let mut revwalk = repo.revwalk()?;
-    revwalk.push_head()?;
+    push_target(&mut revwalk, &repo, args)?;
+    revwalk.set_sorting(git2::Sort::TOPOLOGICAL | git2::Sort::TIME)?;
+    let mut oids: Vec<_> = revwalk.filter_map(|id| id.ok()).collect();
+    oids.reverse();
+
+    oids.into_iter()
+        .map(|oid| {
+            let seconds = repo.find_commit(oid)?.author().when().seconds();
+            Ok(chrono::DateTime::from_timestamp(seconds, 0)
+                .unwrap_or_default()
+                .naive_utc())
+        })
+        .collect()
+}
+
+/// A rewritten commit whose new timestamp lies before an ancestor that keeps
+/// its own committer time.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TimeTravel {
+    pub oid: git2::Oid,
+    pub new_time: NaiveDateTime,
+    pub ancestor_time: NaiveDateTime,
+}
+
+/// Compares each commit in `new_times` against the latest committer time of
+/// its ancestors outside the rewrite set. `order` lists the history oldest
+/// commit first, so every parent is visited before its children.
+pub fn find_time_travel(
+    repo: &git2::Repository,
+    order: &[git2::Oid],
+    new_times: &HashMap<git2::Oid, NaiveDateTime>,
+) -> Result<Vec<TimeTravel>> {
+    // Latest committer time among the untouched ancestors of each commit
+    let mut latest_untouched: HashMap<git2::Oid, NaiveDateTime> = HashMap::new();
+    let mut found = Vec::new();
+
+    for &oid in order {
+        let commit = repo.find_commit(oid)?;
+        let mut latest: Option<NaiveDateTime> = None;
+        for parent in commit.parents() {
+            let inherited = latest_untouched.get(&parent.id()).copied();
+            let own = (!new_times.contains_key(&parent.id())).then(|| committer_time(&parent));
+            latest = latest.max(inherited).max(own);
+        }
+
+        if let Some(latest) = latest {
+            if let Some(&new_time) = new_times.get(&oid) {
+                if new_time < latest {
+                    found.push(TimeTravel {
+                        oid,
+                        new_time,
+                        ancestor_time: latest,
+                    });
+                }
+            }
+            latest_untouched.insert(oid, latest);
+        }
+    }
+
+    Ok(found)
+}
+
+/// Warning for a full rewrite whose generated `timestamps` (oldest commit
+/// first) would place a rewritten commit before an ancestor left untouched by
+/// `--only-author`/`--exclude-author`/`--path`.
+pub fn time_travel_warning(args: &Args, timestamps: &[NaiveDateTime]) -> Result<Option<String>> {
+    if timestamps.is_empty() || args.should_keep_original_timestamps() {
+        return Ok(None);
+    }
+
+    let repo = git2::Repository::open(args.repo_path.as_ref().unwrap())?;
+    let mut revwalk = repo.revwalk()?;
+    push_target(&mut revwalk, &repo, args)?;
+    revwalk.set_sorting(git2::Sort::TOPOLOGICAL | git2::Sort::TIME)?;
+    let mut order: Vec<_> = revwalk.filter_map(|id| id.ok()).collect();
+    order.reverse();
+
+    let mut new_times = HashMap::new();
+    for (&oid, &timestamp) in order.iter().zip(timestamps) {
+        let commit = repo.find_commit(oid)?;
+        if args.is_commit_selected(&repo, &commit)? {
+            new_times.insert(oid, timestamp);
+        }
+    }
+
+    let found = find_time_travel(&repo, &order, &new_times)?;
+    let Some(first) = found.first() else {
+        return Ok(None);
+    };
+    Ok(Some(format!(
+        "{} rewritten commit(s) would be dated before an ancestor that keeps its timestamp \
+         (e.g. {} at {} follows an ancestor from {}); consider a later --begin",
+        found.len(),
+        &first.oid.to_string()[..8],
+        first.new_time.format("%Y-%m-%d %H:%M:%S"),
+        first.ancestor_time.format("%Y-%m-%d %H:%M:%S")
+    )))
+}
+
+/// Rounds `secs` to the nearest `granularity` step of the local time at
+/// `offset_minutes`, so a day starts at local midnight. A result that would
+/// not come after `after`, the latest rounded parent, is moved `min_gap_secs`
+/// past it instead; that keeps a rounded history in order without pushing
+/// commits from the same day into later days.
+pub fn round_after(
+    secs: i64,
+    offset_minutes: i32,
+    granularity: Granularity,
+    after: Option<i64>,
+    min_gap_secs: i64,
+) -> i64 {
+    let step = granularity_secs(granularity);
+    let offset = i64::from(offset_minutes) * 60;
+    let rounded = (secs + offset + step / 2).div_euclid(step) * step - offset;
+    match after {
+        Some(after) if rounded <= after => after + min_gap_secs,
+        _ => rounded,
+    }
+}
+
+fn granularity_secs(granularity: Granularity) -> i64 {
+    match granularity {
+        Granularity::Minute => 60,
+        Granularity::Hour => 60 * 60,
+        Granularity::Day => 24 * 60 * 60,
+    }
+}
+
+fn committer_time(commit: &git2::Commit) -> NaiveDateTime {
+    chrono::DateTime::from_timestamp(commit.committer().when().seconds(), 0)
+        .unwrap_or_default()
+        .naive_utc()
+}
+
+fn count_commits(args: &Args) -> Result<usize> {
+    let repo = git2::Repository::open(args.repo_path.as_ref().unwrap())?;
+    let mut revwalk = repo.revwalk()?;
+    push_target(&mut revwalk, &repo, args)?;
     revwalk.set_sorting(git2::Sort::TOPOLOGICAL | git2::Sort::TIME)?;
     Ok(revwalk.count())
 }
@@ -117,10 +391,33 @@ mod tests {
         (temp_dir, repo_path)
     }
 
+    #[test]
+    fn test_generate_timestamps_clones_file_url() {
+        let (_temp_dir, repo_path) = create_test_repo();
+        let url = format!("file://{repo_path}");
+        let mut args = Args {
+            repo_path: Some(url.clone()),
+            start: Some("2023-01-01 00:00:00".to_string()),
+            end: Some("2023-01-02 00:00:00".to_string()),
+            quiet: true,
+            ..Default::default()
+        };
+
+        let timestamps = generate_timestamps(&mut args).unwrap();
+
+        assert_eq!(timestamps.len(), 1);
+        assert_ne!(args.repo_path.as_deref(), Some(url.as_str()));
+        assert!(args._temp_dir.is_some());
+    }
+
     #[test]
     fn test_count_commits() {
         let (_temp_dir, repo_path) = create_test_repo();
-        let count = count_commits(&repo_path).unwrap();
+        let args = Args {
+            repo_path: Some(repo_path),
+            ..Default::default()
+        };
+        let count = count_commits(&args).unwrap();
         assert_eq!(count, 1);
     }
 
@@ -142,7 +439,7 @@ mod tests {
             edit_author: false,
             edit_time: false,
             docs: false,
-            _temp_dir: None,
+            ..Default::default()
         };
 
         let result = generate_timestamps(&mut args);
@@ -167,7 +464,7 @@ mod tests {
             edit_author: false,
             edit_time: false,
             docs: false,
-            _temp_dir: None,
+            ..Default::default()
         };
 
         let result = generate_timestamps(&mut args);
@@ -185,6 +482,50 @@ mod tests {
         assert!(timestamps[0] <= end_dt);
     }
 
+    #[test]
+    fn test_generate_timestamps_custom_date_format() {
+        let (_temp_dir, repo_path) = create_test_repo();
+        let start_dt =
+            NaiveDateTime::parse_from_str("2023-01-01 00:00:00", DEFAULT_DATE_FORMAT).unwrap();
+        let end_dt =
+            NaiveDateTime::parse_from_str("2023-01-10 00:00:00", DEFAULT_DATE_FORMAT).unwrap();
+
+        for (format, start, end) in [
+            (
+                "%Y-%m-%dT%H:%M:%S",
+                "2023-01-01T00:00:00",
+                "2023-01-10T00:00:00",
+            ),
+            ("%d/%m/%Y %H:%M", "01/01/2023 00:00", "10/01/2023 00:00"),
+        ] {
+            let mut args = Args {
+                repo_path: Some(repo_path.clone()),
+                email: Some("test@example.com".to_string()),
+                name: Some("Test User".to_string()),
+                start: Some(start.to_string()),
+                end: Some(end.to_string()),
+                date_format: Some(format.to_string()),
+                ..Default::default()
+            };
+
+            let timestamps = generate_timestamps(&mut args).unwrap();
+            assert!(timestamps[0] >= start_dt && timestamps[0] <= end_dt);
+        }
+
+        // The default format no longer parses once another one is chosen
+        let mut args = Args {
+            repo_path: Some(repo_path),
+            email: Some("test@example.com".to_string()),
+            name: Some("Test User".to_string()),
+            start: Some("2023-01-01 00:00:00".to_string()),
+            end: Some("2023-01-10 00:00:00".to_string()),
+            date_format: Some("%d/%m/%Y %H:%M".to_string()),
+            ..Default::default()
+        };
+        let err = generate_timestamps(&mut args).unwrap_err();
+        assert!(err.to_string().contains("%d/%m/%Y %H:%M"), "{err}");
+    }
+
     #[test]
     fn test_generate_timestamps_preserves_order() {
         let (_temp_dir, repo_path) = create_test_repo();
@@ -203,7 +544,7 @@ mod tests {
             edit_author: false,
             edit_time: false,
             docs: false,
-            _temp_dir: None,
+            ..Default::default()
         };
 
         let result = generate_timestamps(&mut args);
@@ -216,4 +557,328 @@ mod tests {
             assert!(timestamps[i] >= timestamps[i - 1]);
         }
     }
+
+    #[test]
+    fn test_generate_timestamps_cleans_up_cloned_repo() {
+        let (_temp_dir, repo_path) = create_test_repo();
+        let mut args = Args {
+            repo_path: Some(format!("file://{repo_path}")),
+            email: Some("test@example.com".to_string()),
+            name: Some("Test User".to_string()),
+            start: Some("2023-01-01 00:00:00".to_string()),
+            end: Some("2023-01-10 00:00:00".to_string()),
+            show_history: false,
+            pick_specific_commits: false,
+            range: false,
+            simulate: false,
+            show_diff: false,
+            edit_message: false,
+            edit_author: false,
+            edit_time: false,
+            docs: false,
+            ..Default::default()
+        };
+
+        let result = generate_timestamps(&mut args);
+        assert!(result.is_ok());
+
+        // The clone must be tracked by the Args rather than leaked into temp_dir()
+        let clone_path = args._temp_dir.as_ref().unwrap().path().to_path_buf();
+        assert_eq!(
+            args.repo_path.as_deref(),
+            Some(clone_path.to_string_lossy().as_ref())
+        );
+        assert!(clone_path.exists());
+
+        drop(args);
+        assert!(!clone_path.exists());
+    }
+
+    #[test]
+    fn test_generate_timestamps_keep_original() {
+        let (_temp_dir, repo_path) = create_test_repo();
+        let mut args = Args {
+            repo_path: Some(repo_path),
+            email: Some("test@example.com".to_string()),
+            name: Some("Test User".to_string()),
+            start: Some("KEEP_ORIGINAL".to_string()),
+            end: Some("KEEP_ORIGINAL".to_string()),
+            ..Default::default()
+        };
+
+        let timestamps = generate_timestamps(&mut args).unwrap();
+        let original = chrono::DateTime::from_timestamp(1234567890, 0)
+            .unwrap()
+            .naive_utc();
+        assert_eq!(timestamps, vec![original]);
+    }
+
+    #[test]
+    fn test_spread_timestamps_distributions() {
+        let start =
+            NaiveDateTime::parse_from_str("2023-01-01 00:00:00", "%Y-%m-%d %H:%M:%S").unwrap();
+        let end =
+            NaiveDateTime::parse_from_str("2023-01-31 00:00:00", "%Y-%m-%d %H:%M:%S").unwrap();
+        let commits = 25;
+        let slack = (end - start) - Duration::hours(3 * (commits as i64 - 1));
+        let mut rng = StdRng::from_os_rng();
+
+        for distribution in [
+            Distribution::Uniform,
+            Distribution::Random,
+            Distribution::Linear,
+            Distribution::Gaussian,
+        ] {
+            let timestamps = spread_timestamps(
+                &mut rng,
+                start,
+                commits,
+                Duration::hours(3),
+                slack,
+                distribution,
+            );
+            assert_eq!(timestamps.len(), commits, "{distribution:?}");
+            assert_eq!(timestamps[0], start, "{distribution:?}");
+            assert_eq!(timestamps[commits - 1], end, "{distribution:?}");
+            for pair in timestamps.windows(2) {
+                assert!(pair[1] - pair[0] >= Duration::hours(3), "{distribution:?}");
+            }
+        }
+
+        // Linear spacing is exactly even
+        let timestamps = spread_timestamps(
+            &mut rng,
+            start,
+            commits,
+            Duration::hours(3),
+            slack,
+            Distribution::Linear,
+        );
+        let gap = timestamps[1] - timestamps[0];
+        assert!(timestamps.windows(2).all(|pair| pair[1] - pair[0] == gap));
+    }
+
+    #[test]
+    fn test_allocations_sum_to_slack() {
+        let mut rng = StdRng::from_os_rng();
+        for allocate in [
+            random_allocations,
+            uniform_allocations,
+            linear_allocations,
+            gaussian_allocations,
+        ] {
+            for gaps in [1, 2, 10] {
+                let allocations = allocate(&mut rng, gaps, 1000.0);
+                assert_eq!(allocations.len(), gaps);
+                assert!(allocations.iter().all(|w| *w >= 0.0));
+                assert!((allocations.iter().sum::<f64>() - 1000.0).abs() < 1e-6);
+            }
+            assert!(allocate(&mut rng, 0, 1000.0).is_empty());
+        }
+    }
+
+    // Bob's commit is dated 2024 and stays untouched; Alice's two commits on
+    // top of it get timestamps from 2023
+    fn create_repo_with_predated_ancestor() -> (TempDir, String) {
+        let temp_dir = TempDir::new().unwrap();
+        let repo_path = temp_dir.path().to_str().unwrap().to_string();
+        let repo = git2::Repository::init(&repo_path).unwrap();
+        let tree = repo
+            .find_tree(repo.index().unwrap().write_tree().unwrap())
+            .unwrap();
+
+        let commits = [
+            ("Alice", "alice@example.com", 1672531200), // 2023-01-01
+            ("Bob", "bob@example.com", 1717200000),     // 2024-06-01
+            ("Alice", "alice@example.com", 1717286400), // 2024-06-02
+        ];
+        for (name, email, time) in commits {
+            let sig = git2::Signature::new(name, email, &git2::Time::new(time, 0)).unwrap();
+            let parents: Vec<git2::Commit> = match repo.head() {
+                Ok(head) => vec![head.peel_to_commit().unwrap()],
+                Err(_) => vec![],
+            };
+            repo.commit(
+                Some("HEAD"),
+                &sig,
+                &sig,
+                name,
+                &tree,
+                &parents.iter().collect::<Vec<_>>(),
+            )
+            .unwrap();
+        }
+        drop(tree);
+
+        (temp_dir, repo_path)
+    }
+
+    #[test]
+    fn test_generate_timestamps_min_gap_boundary() {
+        // Three commits need two gaps: 6 hours by default, 20 minutes at 10
+        let (_temp_dir, repo_path) = create_repo_with_predated_ancestor();
+        let generate = |end: &str, min_gap_minutes: Option<u32>| {
+            generate_timestamps(&mut Args {
+                repo_path: Some(repo_path.clone()),
+                start: Some("2023-01-01 00:00:00".to_string()),
+                end: Some(end.to_string()),
+                min_gap_minutes,
+                ..Default::default()
+            })
+        };
+
+        let timestamps = generate("2023-01-01 06:00:00", None).unwrap();
+        assert!(timestamps
+            .windows(2)
+            .all(|pair| pair[1] - pair[0] == Duration::hours(3)));
+        let err = generate("2023-01-01 05:59:59", None).unwrap_err();
+        assert!(err.to_string().contains("at least 6 hours"), "{err}");
+
+        let timestamps = generate("2023-01-01 00:20:00", Some(10)).unwrap();
+        assert!(timestamps
+            .windows(2)
+            .all(|pair| pair[1] - pair[0] == Duration::minutes(10)));
+        let err = generate("2023-01-01 00:19:59", Some(10)).unwrap_err();
+        assert!(err.to_string().contains("at least 20 minutes"), "{err}");
+
+        let err = generate("2023-01-01 00:00:01", Some(45)).unwrap_err();
+        assert!(
+            err.to_string().contains("at least 1 hour 30 minutes"),
+            "{err}"
+        );
+    }
+
+    #[test]
+    fn test_generate_timestamps_with_seed_is_deterministic() {
+        let (_temp_dir, repo_path) = create_repo_with_predated_ancestor();
+        let mut args = Args {
+            repo_path: Some(repo_path),
+            start: Some("2023-01-01 00:00:00".to_string()),
+            end: Some("2023-12-31 00:00:00".to_string()),
+            seed: Some(42),
+            ..Default::default()
+        };
+
+        for distribution in [
+            Distribution::Random,
+            Distribution::Uniform,
+            Distribution::Gaussian,
+        ] {
+            args.distribution = Some(distribution);
+            let first = generate_timestamps(&mut args).unwrap();
+            let second = generate_timestamps(&mut args).unwrap();
+            assert_eq!(first.len(), 3, "{distribution:?}");
+            assert_eq!(first, second, "{distribution:?}");
+        }
+
+        // Another seed draws other timestamps
+        args.distribution = Some(Distribution::Random);
+        let first = generate_timestamps(&mut args).unwrap();
+        args.seed = Some(43);
+        assert_ne!(generate_timestamps(&mut args).unwrap(), first);
+    }
+
+    #[test]
+    fn test_generate_timestamps_no_future() {
+        let (_temp_dir, repo_path) = create_repo_with_predated_ancestor();
+        let mut args = Args {
+            repo_path: Some(repo_path),
+            start: Some("2998-01-01 00:00:00".to_string()),
+            end: Some("2999-01-01 00:00:00".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(generate_timestamps(&mut args).unwrap().len(), 3);
+
+        args.no_future = true;
+        let err = generate_timestamps(&mut args).unwrap_err();
+        assert!(
+            err.to_string().starts_with("Generated timestamp 299"),
+            "{err}"
+        );
+        assert!(
+            err.to_string().ends_with("(rejected by --no-future)"),
+            "{err}"
+        );
+    }
+
+    #[test]
+    fn test_round_after_to_the_minute() {
+        // 2023-11-14 22:13:20 UTC
+        let secs = 1_700_000_000;
+        assert_eq!(
+            round_after(secs, 0, Granularity::Minute, None, 60),
+            1_699_999_980
+        );
+        assert_eq!(
+            round_after(secs + 40, 0, Granularity::Minute, None, 60),
+            1_700_000_040
+        );
+        assert_eq!(
+            round_after(secs, 0, Granularity::Hour, None, 60),
+            1_699_999_200
+        );
+
+        // A day is rounded in the commit's own timezone: 00:13 at UTC+2 rounds
+        // down to local midnight, which is 22:00 UTC the day before
+        assert_eq!(
+            round_after(secs, 120, Granularity::Day, None, 60),
+            1_699_999_200
+        );
+        assert_eq!(
+            round_after(secs, 0, Granularity::Day, None, 60),
+            1_700_006_400
+        );
+    }
+
+    #[test]
+    fn test_round_after_breaks_ties_forward() {
+        let parent = round_after(1_700_000_000, 0, Granularity::Minute, None, 60);
+        // Ten seconds later rounds to the same minute, so it moves the gap past it
+        let child = round_after(1_700_000_010, 0, Granularity::Minute, Some(parent), 60);
+        assert_eq!(child, parent + 60);
+        // The next one in that minute follows the bumped child
+        let grandchild = round_after(1_700_000_015, 0, Granularity::Minute, Some(child), 60);
+        assert_eq!(grandchild, child + 60);
+        // A commit already past its parent keeps its own rounding
+        assert_eq!(
+            round_after(1_700_000_400, 0, Granularity::Minute, Some(grandchild), 60),
+            1_700_000_400
+        );
+
+        // Commits from the same day stay on that day, the default gap apart
+        let gap = i64::from(DEFAULT_MIN_GAP_MINUTES) * 60;
+        let parent = round_after(1_700_000_000, 0, Granularity::Day, None, gap);
+        let child = round_after(1_700_003_600, 0, Granularity::Day, Some(parent), gap);
+        assert_eq!(child, parent + 3 * 60 * 60);
+    }
+
+    #[test]
+    fn test_time_travel_warning_with_predated_ancestor() {
+        let (_temp_dir, repo_path) = create_repo_with_predated_ancestor();
+        let mut args = Args {
+            repo_path: Some(repo_path),
+            start: Some("2023-03-01 00:00:00".to_string()),
+            end: Some("2023-03-10 00:00:00".to_string()),
+            only_author: vec!["Alice".to_string()],
+            ..Default::default()
+        };
+        let timestamps = generate_timestamps(&mut args).unwrap();
+
+        let warning = time_travel_warning(&args, &timestamps).unwrap().unwrap();
+        assert!(warning.starts_with("1 rewritten commit(s)"), "{warning}");
+        assert!(warning.contains("2024-06-01 00:00:00"), "{warning}");
+
+        // Moving the range past the untouched ancestor removes the warning
+        args.start = Some("2024-07-01 00:00:00".to_string());
+        args.end = Some("2024-07-10 00:00:00".to_string());
+        let timestamps = generate_timestamps(&mut args).unwrap();
+        assert_eq!(time_travel_warning(&args, &timestamps).unwrap(), None);
+
+        // Rewriting every commit never leaves an ancestor behind
+        args.only_author.clear();
+        args.start = Some("2023-03-01 00:00:00".to_string());
+        args.end = Some("2023-03-10 00:00:00".to_string());
+        let timestamps = generate_timestamps(&mut args).unwrap();
+        assert_eq!(time_travel_warning(&args, &timestamps).unwrap(), None);
+    }
 }
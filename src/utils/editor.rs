@@ -0,0 +1,133 @@
+use crate::utils::types::Result;
+use std::io::Write;
+use std::process::Command;
+
+const EDITOR_HELP: &str = "\n\
+# Please enter the commit message. Lines starting with '#' will be ignored,\n\
+# and an empty message cancels the edit.\n";
+
+/// The editor git itself would use: `$GIT_EDITOR`, then `$EDITOR`, then `vi`.
+fn editor_command() -> String {
+    ["GIT_EDITOR", "EDITOR"]
+        .iter()
+        .filter_map(|var| std::env::var(var).ok())
+        .find(|editor| !editor.trim().is_empty())
+        .unwrap_or_else(|| "vi".to_string())
+}
+
+/// Opens `message` in the user's editor and returns the edited message with
+/// comment lines stripped. Returns `None` when the edit is cancelled: the
+/// editor exited non-zero, the message was left empty, or it is unchanged.
+pub fn edit_message(message: &str) -> Result<Option<String>> {
+    edit_message_with(&editor_command(), message)
+}
+
+fn edit_message_with(editor: &str, message: &str) -> Result<Option<String>> {
+    let mut file = tempfile::Builder::new()
+        .prefix("git-editor-")
+        .suffix(".COMMIT_EDITMSG")
+        .tempfile()?;
+    write!(file, "{}\n{EDITOR_HELP}", message.trim_end())?;
+    file.flush()?;
+
+    // The editor may carry its own arguments, e.g. `code --wait`
+    let mut parts = editor.split_whitespace();
+    let program = parts.next().ok_or("No editor configured")?;
+    let status = Command::new(program)
+        .args(parts)
+        .arg(file.path())
+        .status()
+        .map_err(|e| format!("Failed to launch editor '{editor}': {e}"))?;
+    if !status.success() {
+        return Ok(None);
+    }
+
+    let edited = clean_message(&std::fs::read_to_string(file.path())?);
+    if edited.is_empty() || edited == clean_message(message) {
+        return Ok(None);
+    }
+    Ok(Some(edited))
+}
+
+/// Applies git's default message cleanup: drops `#` comment lines and
+/// trailing whitespace, collapses runs of blank lines and trims blank lines
+/// at both ends.
+fn clean_message(text: &str) -> String {
+    let mut lines: Vec<&str> = Vec::new();
+    for line in text.lines() {
+        if line.starts_with('#') {
+            continue;
+        }
+        let line = line.trim_end();
+        if line.is_empty() && lines.last().is_none_or(|last| last.is_empty()) {
+            continue;
+        }
+        lines.push(line);
+    }
+    while lines.last().is_some_and(|last| last.is_empty()) {
+        lines.pop();
+    }
+    lines.join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_clean_message() {
+        assert_eq!(
+            clean_message("\n\nSubject  \n\n\n\nBody line\n# a comment\n#another\n\n"),
+            "Subject\n\nBody line"
+        );
+        assert_eq!(clean_message("# only comments\n\n"), "");
+        assert_eq!(
+            clean_message("Keep # inline hashes"),
+            "Keep # inline hashes"
+        );
+    }
+
+    // Run through `sh` rather than executed directly, so no test races on
+    // executing a file that was just written
+    #[cfg(unix)]
+    fn fake_editor(dir: &tempfile::TempDir, script: &str) -> String {
+        let path = dir.path().join("editor.sh");
+        std::fs::write(&path, script).unwrap();
+        format!("sh {}", path.display())
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_edit_message_with_editor() {
+        let dir = tempfile::TempDir::new().unwrap();
+
+        // The file starts with the current message followed by the help comments
+        let editor = fake_editor(
+            &dir,
+            "grep -q '^Old subject$' \"$1\" && grep -q '^# Please' \"$1\" || exit 3\n\
+             printf 'New subject\\n\\nNew body\\n# ignored\\n' > \"$1\"",
+        );
+        assert_eq!(
+            edit_message_with(&editor, "Old subject\n").unwrap(),
+            Some("New subject\n\nNew body".to_string())
+        );
+
+        // Saving the message untouched changes nothing
+        let editor = fake_editor(&dir, "true");
+        assert_eq!(edit_message_with(&editor, "Old subject\n").unwrap(), None);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_edit_message_cancelled() {
+        let dir = tempfile::TempDir::new().unwrap();
+
+        let editor = fake_editor(&dir, "printf 'New subject\\n' > \"$1\"; exit 1");
+        assert_eq!(edit_message_with(&editor, "Old subject").unwrap(), None);
+
+        let editor = fake_editor(&dir, "printf '# nothing left\\n\\n' > \"$1\"");
+        assert_eq!(edit_message_with(&editor, "Old subject").unwrap(), None);
+
+        assert!(edit_message_with("/nonexistent/editor", "Old subject").is_err());
+    }
+}
@@ -1,13 +1,18 @@
 use crate::utils::types::Result;
 use colored::Colorize;
-use git2::Repository;
+use git2::build::RepoBuilder;
+use git2::{Cred, CredentialType, ErrorCode, FetchOptions, RemoteCallbacks};
+use std::cell::RefCell;
+use std::path::{Path, PathBuf};
 use tempfile::TempDir;
 use url::Url;
 
-/// Checks if a string is a valid Git URL
+/// Checks if a string is a Git URL that should be cloned rather than opened as
+/// a local path. This is the one place URLs are detected, so the validator and
+/// every clone path agree on what counts as remote.
 pub fn is_git_url(input: &str) -> bool {
     if let Ok(url) = Url::parse(input) {
-        matches!(url.scheme(), "http" | "https" | "git" | "ssh")
+        matches!(url.scheme(), "http" | "https" | "git" | "ssh" | "file")
     } else {
         // Check for SSH format like git@github.com:user/repo.git
         input.contains('@') && input.contains(':') && !input.contains(' ')
@@ -23,28 +28,196 @@ pub fn normalize_git_url(url: &str) -> String {
     }
 }
 
-/// Clones a Git repository to a temporary directory and returns the path
-pub fn clone_repository(git_url: &str) -> Result<TempDir> {
-    println!("{}", "🔄 Cloning repository...".cyan());
-    println!("{} {}", "Repository:".bold(), git_url.yellow());
+// Environment variable holding the HTTPS password or token for --clone-user
+pub const GIT_TOKEN_ENV: &str = "GIT_TOKEN";
 
+// Private keys tried after the SSH agent, in this order, under ~/.ssh
+const DEFAULT_KEY_FILES: [&str; 3] = ["id_ed25519", "id_ecdsa", "id_rsa"];
+
+/// One way of authenticating a clone.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AuthMethod {
+    SshAgent,
+    SshKey(PathBuf),
+    Token,
+}
+
+impl AuthMethod {
+    fn describe(&self) -> String {
+        match self {
+            AuthMethod::SshAgent => "SSH agent".to_string(),
+            AuthMethod::SshKey(path) => format!("SSH key {}", path.display()),
+            AuthMethod::Token => format!("username and {GIT_TOKEN_ENV}"),
+        }
+    }
+}
+
+/// Hands out the next credential each time the remote asks for one: the SSH
+/// agent, then the default key files, then `--clone-user` with `GIT_TOKEN`.
+/// Every method is tried at most once, so a rejected clone ends instead of
+/// asking forever.
+#[derive(Debug, Default)]
+pub struct CloneAuth {
+    key_files: Vec<PathBuf>,
+    has_token: bool,
+    tried: Vec<AuthMethod>,
+}
+
+impl CloneAuth {
+    pub fn new(key_files: Vec<PathBuf>, has_token: bool) -> Self {
+        CloneAuth {
+            key_files,
+            has_token,
+            tried: Vec::new(),
+        }
+    }
+
+    // The default key files found in ~/.ssh, and whether GIT_TOKEN is set
+    fn from_environment() -> Self {
+        let ssh_dir = std::env::var("HOME")
+            .or_else(|_| std::env::var("USERPROFILE"))
+            .map(|home| PathBuf::from(home).join(".ssh"));
+        let key_files = match ssh_dir {
+            Ok(dir) => DEFAULT_KEY_FILES
+                .iter()
+                .map(|name| dir.join(name))
+                .filter(|path| path.is_file())
+                .collect(),
+            Err(_) => Vec::new(),
+        };
+        let has_token = std::env::var(GIT_TOKEN_ENV).is_ok_and(|token| !token.is_empty());
+        CloneAuth::new(key_files, has_token)
+    }
+
+    pub fn next_method(&mut self, allowed: CredentialType) -> Option<AuthMethod> {
+        let mut candidates = Vec::new();
+        if allowed.contains(CredentialType::SSH_KEY) {
+            candidates.push(AuthMethod::SshAgent);
+            candidates.extend(self.key_files.iter().cloned().map(AuthMethod::SshKey));
+        }
+        if allowed.contains(CredentialType::USER_PASS_PLAINTEXT) && self.has_token {
+            candidates.push(AuthMethod::Token);
+        }
+
+        let method = candidates
+            .into_iter()
+            .find(|method| !self.tried.contains(method))?;
+        self.tried.push(method.clone());
+        Some(method)
+    }
+
+    pub fn tried(&self) -> &[AuthMethod] {
+        &self.tried
+    }
+}
+
+// What went wrong and how to fix it, for a clone the remote did not let in
+fn auth_help(git_url: &str, error: &git2::Error, tried: &[AuthMethod]) -> String {
+    let tried = if tried.is_empty() {
+        "no credentials were available".to_string()
+    } else {
+        format!(
+            "tried {}",
+            tried
+                .iter()
+                .map(AuthMethod::describe)
+                .collect::<Vec<_>>()
+                .join(", ")
+        )
+    };
+    format!(
+        "Failed to clone repository '{git_url}': authentication failed ({tried}): {error}\n\
+         For SSH URLs, load your key into ssh-agent or put it at ~/.ssh/id_ed25519, ~/.ssh/id_ecdsa or ~/.ssh/id_rsa.\n\
+         For HTTPS URLs, set {GIT_TOKEN_ENV} to a password or access token and pass --clone-user <USER>."
+    )
+}
+
+/// Clones a Git repository to a temporary directory and returns the path.
+/// Private repositories are authenticated as described on [`CloneAuth`].
+pub fn clone_repository(git_url: &str, clone_user: Option<&str>, quiet: bool) -> Result<TempDir> {
     // Create a temporary directory
     let temp_dir =
         TempDir::new().map_err(|e| format!("Failed to create temporary directory: {e}"))?;
 
-    let repo_path = temp_dir.path();
+    clone_into(git_url, temp_dir.path(), clone_user, quiet)?;
+    Ok(temp_dir)
+}
+
+/// Clones a Git repository into `repo_path`, which must not exist yet or be an
+/// empty directory, and leaves it there once the program exits.
+pub fn clone_into(
+    git_url: &str,
+    repo_path: &Path,
+    clone_user: Option<&str>,
+    quiet: bool,
+) -> Result<()> {
+    let is_empty_dir = repo_path
+        .read_dir()
+        .is_ok_and(|mut entries| entries.next().is_none());
+    if repo_path.exists() && !is_empty_dir {
+        return Err(format!(
+            "Cannot clone into {}: it already exists and is not an empty directory",
+            repo_path.display()
+        )
+        .into());
+    }
+
+    if !quiet {
+        println!("{}", "🔄 Cloning repository...".cyan());
+        println!("{} {}", "Repository:".bold(), git_url.yellow());
+    }
+
+    let auth = RefCell::new(CloneAuth::from_environment());
+    let mut callbacks = RemoteCallbacks::new();
+    callbacks.credentials(|_url, username_from_url, allowed| {
+        let user = username_from_url.or(clone_user).unwrap_or("git");
+        // SSH asks for the username on its own before any key
+        if allowed == CredentialType::USERNAME {
+            return Cred::username(user);
+        }
+
+        let Some(method) = auth.borrow_mut().next_method(allowed) else {
+            return Err(git2::Error::from_str("no more credentials to try"));
+        };
+        if !quiet {
+            println!(
+                "{}",
+                format!("🔑 Trying {}...", method.describe()).bright_black()
+            );
+        }
+        match method {
+            AuthMethod::SshAgent => Cred::ssh_key_from_agent(user),
+            AuthMethod::SshKey(path) => Cred::ssh_key(user, None, &path, None),
+            AuthMethod::Token => {
+                let token = std::env::var(GIT_TOKEN_ENV).unwrap_or_default();
+                Cred::userpass_plaintext(clone_user.unwrap_or(user), &token)
+            }
+        }
+    });
+    let mut fetch_options = FetchOptions::new();
+    fetch_options.remote_callbacks(callbacks);
 
     // Clone the repository
-    let _repo = Repository::clone(git_url, repo_path)
-        .map_err(|e| format!("Failed to clone repository '{git_url}': {e}"))?;
+    let result = RepoBuilder::new()
+        .fetch_options(fetch_options)
+        .clone(git_url, repo_path);
+    if let Err(e) = result {
+        let auth = auth.borrow();
+        if e.code() == ErrorCode::Auth || !auth.tried().is_empty() {
+            return Err(auth_help(git_url, &e, auth.tried()).into());
+        }
+        return Err(format!("Failed to clone repository '{git_url}': {e}").into());
+    }
 
-    println!(
-        "{} {}",
-        "✓ Successfully cloned to:".green(),
-        repo_path.display().to_string().cyan()
-    );
+    if !quiet {
+        println!(
+            "{} {}",
+            "✓ Successfully cloned to:".green(),
+            repo_path.display().to_string().cyan()
+        );
+    }
 
-    Ok(temp_dir)
+    Ok(())
 }
 
 /// Gets repository name from Git URL for display purposes
@@ -85,6 +258,7 @@ pub fn get_repo_name_from_url(git_url: &str) -> String {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use git2::Repository;
 
     #[test]
     fn test_is_git_url() {
@@ -93,11 +267,80 @@ mod tests {
         assert!(is_git_url("http://gitlab.com/user/repo"));
         assert!(is_git_url("git://github.com/user/repo.git"));
         assert!(is_git_url("git@github.com:user/repo.git"));
+        assert!(is_git_url("ssh://git@github.com/user/repo.git"));
+        assert!(is_git_url("file:///local/path"));
 
         assert!(!is_git_url("./local/path"));
         assert!(!is_git_url("/absolute/path"));
         assert!(!is_git_url("not-a-url"));
-        assert!(!is_git_url("file:///local/path"));
+        assert!(!is_git_url("mailto:user@example.com"));
+    }
+
+    #[test]
+    fn test_clone_file_url() {
+        let source = tempfile::TempDir::new().unwrap();
+        let repo = Repository::init(source.path()).unwrap();
+        let tree = repo
+            .find_tree(repo.index().unwrap().write_tree().unwrap())
+            .unwrap();
+        let sig = git2::Signature::now("Test User", "test@example.com").unwrap();
+        let oid = repo
+            .commit(Some("HEAD"), &sig, &sig, "Initial", &tree, &[])
+            .unwrap();
+
+        let url = format!("file://{}", source.path().display());
+        assert!(is_git_url(&url));
+        let clone = clone_repository(&url, Some("nobody"), true).unwrap();
+
+        let cloned = Repository::open(clone.path()).unwrap();
+        assert_eq!(cloned.head().unwrap().target(), Some(oid));
+    }
+
+    #[test]
+    fn test_clone_auth_order() {
+        let keys = vec![
+            PathBuf::from("/keys/id_ed25519"),
+            PathBuf::from("/keys/id_rsa"),
+        ];
+        let ssh = CredentialType::SSH_KEY;
+        let https = CredentialType::USER_PASS_PLAINTEXT;
+
+        let mut auth = CloneAuth::new(keys.clone(), true);
+        assert_eq!(auth.next_method(ssh), Some(AuthMethod::SshAgent));
+        assert_eq!(
+            auth.next_method(ssh),
+            Some(AuthMethod::SshKey(keys[0].clone()))
+        );
+        assert_eq!(
+            auth.next_method(ssh),
+            Some(AuthMethod::SshKey(keys[1].clone()))
+        );
+        assert_eq!(auth.next_method(ssh), None);
+        assert_eq!(auth.next_method(https), Some(AuthMethod::Token));
+        assert_eq!(auth.next_method(https), None);
+        assert_eq!(auth.tried().len(), 4);
+
+        // Without GIT_TOKEN an HTTPS remote gets nothing to try
+        let mut auth = CloneAuth::new(keys, false);
+        assert_eq!(auth.next_method(https), None);
+    }
+
+    #[test]
+    fn test_auth_help_lists_tried_methods() {
+        let error = git2::Error::from_str("authentication required");
+        let help = auth_help(
+            "https://example.com/private.git",
+            &error,
+            &[AuthMethod::SshAgent, AuthMethod::Token],
+        );
+        assert!(
+            help.contains("tried SSH agent, username and GIT_TOKEN"),
+            "{help}"
+        );
+        assert!(help.contains("--clone-user"), "{help}");
+
+        let help = auth_help("https://example.com/private.git", &error, &[]);
+        assert!(help.contains("no credentials were available"), "{help}");
     }
 
     #[test]
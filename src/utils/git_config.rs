@@ -1,61 +1,211 @@
-use std::path::PathBuf;
+use colored::Colorize;
+use std::path::{Path, PathBuf};
 use std::process::Command;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+// Set once spawning `git` has failed, so later lookups skip straight to the
+// config files instead of trying to start it again
+static GIT_MISSING: AtomicBool = AtomicBool::new(false);
+
+// How deep `include.path` directives are followed, as in git itself; this
+// also stops a file that includes itself
+const MAX_INCLUDE_DEPTH: usize = 10;
+
+// Attempts to get git configuration values for user name and email. First tries the git command, then falls back to reading ~/.gitconfig directly. `includeIf "gitdir:"` sections are matched against the repository at `repo_path`.
+pub fn get_git_user_name(repo_path: &str) -> Option<String> {
+    git_user_value(
+        "git",
+        &GIT_MISSING,
+        repo_path,
+        "name",
+        &get_gitconfig_paths(),
+    )
+}
 
-// Attempts to get git configuration values for user name and email. First tries the git command, then falls back to reading ~/.gitconfig directly.
-pub fn get_git_user_name() -> Option<String> {
-    // Try git command first
-    if let Ok(output) = Command::new("git")
-        .args(["config", "--global", "user.name"])
-        .output()
-    {
-        if output.status.success() {
-            let name = String::from_utf8_lossy(&output.stdout).trim().to_string();
-            if !name.is_empty() {
-                return Some(name);
+// Attempts to get git configuration values for user email. First tries the git command, then falls back to reading ~/.gitconfig directly. `includeIf "gitdir:"` sections are matched against the repository at `repo_path`.
+pub fn get_git_user_email(repo_path: &str) -> Option<String> {
+    git_user_value(
+        "git",
+        &GIT_MISSING,
+        repo_path,
+        "email",
+        &get_gitconfig_paths(),
+    )
+}
+
+// Looks up `user.<key>` with `program`, falling back to parsing `gitconfig_paths`. When the program cannot be started, `missing` is set and a one-time note is printed.
+fn git_user_value(
+    program: &str,
+    missing: &AtomicBool,
+    repo_path: &str,
+    key: &str,
+    gitconfig_paths: &[PathBuf],
+) -> Option<String> {
+    if !missing.load(Ordering::Relaxed) {
+        // Run inside the repository so git evaluates its includeIf conditions too
+        match Command::new(program)
+            .args([
+                "-C",
+                repo_path,
+                "config",
+                "--global",
+                &format!("user.{key}"),
+            ])
+            .output()
+        {
+            Ok(output) if output.status.success() => {
+                let value = String::from_utf8_lossy(&output.stdout).trim().to_string();
+                if !value.is_empty() {
+                    return Some(value);
+                }
+            }
+            Ok(_) => {}
+            Err(_) => {
+                if !missing.swap(true, Ordering::Relaxed) {
+                    eprintln!(
+                        "{} `{program}` could not be run, reading the git config files directly",
+                        "Info:".cyan().bold()
+                    );
+                }
             }
         }
     }
 
     // Fallback to reading ~/.gitconfig file
-    read_gitconfig_value("user", "name")
+    let gitdir = repo_gitdir(repo_path);
+    read_gitconfig_value(gitconfig_paths, "user", key, gitdir.as_deref())
 }
 
-// Attempts to get git configuration values for user email. First tries the git command, then falls back to reading ~/.gitconfig directly.
-pub fn get_git_user_email() -> Option<String> {
-    // Try git command first
-    if let Ok(output) = Command::new("git")
-        .args(["config", "--global", "user.email"])
-        .output()
-    {
-        if output.status.success() {
-            let email = String::from_utf8_lossy(&output.stdout).trim().to_string();
-            if !email.is_empty() {
-                return Some(email);
-            }
-        }
+// Reads a specific value from the git config file directly. This is used as a fallback when the git command is not available. Handles cross-platform git config locations.
+fn read_gitconfig_value(
+    gitconfig_paths: &[PathBuf],
+    section: &str,
+    key: &str,
+    gitdir: Option<&str>,
+) -> Option<String> {
+    // Try each possible gitconfig path
+    gitconfig_paths
+        .iter()
+        .find_map(|gitconfig_path| read_config_file(gitconfig_path, section, key, gitdir, 0))
+}
+
+// Reads one config file and the files it includes, `depth` levels below the one git loaded itself.
+fn read_config_file(
+    path: &Path,
+    section: &str,
+    key: &str,
+    gitdir: Option<&str>,
+    depth: usize,
+) -> Option<String> {
+    if depth > MAX_INCLUDE_DEPTH {
+        return None;
     }
+    let content = std::fs::read_to_string(path).ok()?;
+    let dir = path.parent().unwrap_or(Path::new(""));
+
+    parse_config(
+        &content,
+        section,
+        key,
+        &mut |include_section, include_path| {
+            if !include_applies(include_section, dir, gitdir) {
+                return None;
+            }
+            let included = expand_path(include_path, dir)?;
+            read_config_file(&included, section, key, gitdir, depth + 1)
+        },
+    )
+}
 
-    // Fallback to reading ~/.gitconfig file
-    read_gitconfig_value("user", "email")
+// The `.git` directory of the repository at `repo_path`, which `gitdir:` conditions are matched against.
+fn repo_gitdir(repo_path: &str) -> Option<String> {
+    let repo = git2::Repository::open(repo_path).ok()?;
+    let gitdir = repo.path().canonicalize().ok()?;
+    Some(
+        gitdir
+            .to_string_lossy()
+            .replace('\\', "/")
+            .trim_end_matches('/')
+            .to_string(),
+    )
 }
 
-// Reads a specific value from the git config file directly. This is used as a fallback when the git command is not available. Handles cross-platform git config locations.
-fn read_gitconfig_value(section: &str, key: &str) -> Option<String> {
-    use std::fs;
+// Whether the entries of `section` are included: always for `[include]`, and for `[includeIf "gitdir:..."]` when the repository matches. Other conditions such as `onbranch:` are not supported and never apply.
+fn include_applies(section: &Section, config_dir: &Path, gitdir: Option<&str>) -> bool {
+    let condition = match &section.subsection {
+        None if section.name.eq_ignore_ascii_case("include") => return true,
+        Some(condition) if section.name.eq_ignore_ascii_case("includeIf") => condition,
+        _ => return false,
+    };
+    let (pattern, ignore_case) = if let Some(pattern) = condition.strip_prefix("gitdir:") {
+        (pattern, false)
+    } else if let Some(pattern) = condition.strip_prefix("gitdir/i:") {
+        (pattern, true)
+    } else {
+        return false;
+    };
+    let (Some(gitdir), Some(pattern)) = (gitdir, gitdir_pattern(pattern, config_dir)) else {
+        return false;
+    };
 
-    // Get the appropriate git config path for the current OS
-    let gitconfig_paths = get_gitconfig_paths();
+    if ignore_case {
+        glob_match(
+            pattern.to_lowercase().as_bytes(),
+            gitdir.to_lowercase().as_bytes(),
+        )
+    } else {
+        glob_match(pattern.as_bytes(), gitdir.as_bytes())
+    }
+}
 
-    // Try each possible gitconfig path
-    for gitconfig_path in gitconfig_paths {
-        if let Ok(content) = fs::read_to_string(&gitconfig_path) {
-            if let Some(value) = parse_gitconfig(&content, section, key) {
-                return Some(value);
-            }
-        }
+// Turns a `gitdir:` pattern into an absolute glob the way git does: `~/` and `./` are expanded, a pattern that isn't absolute may match at any depth, and a trailing `/` matches everything below it.
+fn gitdir_pattern(pattern: &str, config_dir: &Path) -> Option<String> {
+    let mut pattern = if pattern.starts_with("~/") || pattern.starts_with("./") {
+        expand_path(pattern, config_dir)?
+            .to_string_lossy()
+            .replace('\\', "/")
+    } else {
+        pattern.replace('\\', "/")
+    };
+    if !pattern.starts_with('/')
+        && !Path::new(&pattern).is_absolute()
+        && !pattern.starts_with("**/")
+    {
+        pattern = format!("**/{pattern}");
+    }
+    if pattern.ends_with('/') {
+        pattern.push_str("**");
+    }
+    Some(pattern)
+}
+
+// Resolves `~/` against the home directory and any other relative path against the directory of the config file that names it.
+fn expand_path(path: &str, config_dir: &Path) -> Option<PathBuf> {
+    if let Some(rest) = path.strip_prefix("~/") {
+        return Some(PathBuf::from(format!(
+            "{}/{rest}",
+            get_home_dir()?.display()
+        )));
     }
+    let path = path.strip_prefix("./").unwrap_or(path);
+    Some(if Path::new(path).is_absolute() {
+        PathBuf::from(path)
+    } else {
+        config_dir.join(path)
+    })
+}
 
-    None
+// Matches `text` against a glob where `*` and `?` stay within one path component and `**` crosses any number of them.
+fn glob_match(pattern: &[u8], text: &[u8]) -> bool {
+    match pattern {
+        [] => text.is_empty(),
+        [b'*', b'*', rest @ ..] => (0..=text.len()).any(|i| glob_match(rest, &text[i..])),
+        [b'*', rest @ ..] => (0..=text.len())
+            .take_while(|&i| i == 0 || text[i - 1] != b'/')
+            .any(|i| glob_match(rest, &text[i..])),
+        [b'?', rest @ ..] => matches!(text, [c, tail @ ..] if *c != b'/' && glob_match(rest, tail)),
+        [p, rest @ ..] => matches!(text, [c, tail @ ..] if c == p && glob_match(rest, tail)),
+    }
 }
 
 // Returns the possible git config file paths for the current operating system. Returns them in order of precedence (user config first, then system config).
@@ -77,6 +227,11 @@ fn get_gitconfig_paths() -> Vec<PathBuf> {
 
 // Gets the user-level git config path for the current OS.
 fn get_user_gitconfig_path() -> Option<PathBuf> {
+    Some(get_home_dir()?.join(".gitconfig"))
+}
+
+// Gets the home directory for the current OS.
+fn get_home_dir() -> Option<PathBuf> {
     // Try different environment variables for home directory
     let home_dir = if let Ok(home) = std::env::var("HOME") {
         // Unix-like systems (Linux, macOS)
@@ -95,7 +250,7 @@ fn get_user_gitconfig_path() -> Option<PathBuf> {
         return None;
     };
 
-    Some(home_dir.join(".gitconfig"))
+    Some(home_dir)
 }
 
 // Gets the system-level git config path for the current OS.
@@ -121,9 +276,67 @@ fn get_system_gitconfig_path() -> Option<PathBuf> {
     }
 }
 
-// Simple parser for .gitconfig files to extract specific values. Handles basic INI-style format with [section] and key = value pairs.
-fn parse_gitconfig(content: &str, target_section: &str, target_key: &str) -> Option<String> {
-    let mut in_target_section = false;
+// A config section header: `[remote "origin"]`, or the older `[remote.origin]`, has the subsection `origin`.
+#[derive(Debug, Default, PartialEq, Eq)]
+struct Section {
+    name: String,
+    subsection: Option<String>,
+}
+
+impl Section {
+    fn parse(header: &str) -> Self {
+        let header = header.trim();
+        if let Some((name, rest)) = header.split_once(char::is_whitespace) {
+            if let Some(quoted) = rest
+                .trim()
+                .strip_prefix('"')
+                .and_then(|r| r.strip_suffix('"'))
+            {
+                // Only `\"` and `\\` are escaped inside the quotes
+                let mut subsection = String::new();
+                let mut chars = quoted.chars();
+                while let Some(c) = chars.next() {
+                    subsection.extend(if c == '\\' { chars.next() } else { Some(c) });
+                }
+                return Self {
+                    name: name.to_string(),
+                    subsection: Some(subsection),
+                };
+            }
+        }
+        match header.split_once('.') {
+            // The old dotted form is case-insensitive, so git lowercases it
+            Some((name, subsection)) => Self {
+                name: name.to_string(),
+                subsection: Some(subsection.to_lowercase()),
+            },
+            None => Self {
+                name: header.to_string(),
+                subsection: None,
+            },
+        }
+    }
+
+    // Whether this is `target`, written `section` or `section.subsection`;
+    // the name is case-insensitive, the subsection is not
+    fn is(&self, target: &str) -> bool {
+        let (name, subsection) = match target.split_once('.') {
+            Some((name, subsection)) => (name, Some(subsection)),
+            None => (target, None),
+        };
+        self.name.eq_ignore_ascii_case(name) && self.subsection.as_deref() == subsection
+    }
+}
+
+// Simple parser for .gitconfig files to extract specific values. Handles basic INI-style format with [section] and key = value pairs, where `target_section` is `section` or `section.subsection`. Each `path` entry is handed to `include` along with its section. As in git, a later value (including one from an included file) overrides an earlier one.
+fn parse_config(
+    content: &str,
+    target_section: &str,
+    target_key: &str,
+    include: &mut dyn FnMut(&Section, &str) -> Option<String>,
+) -> Option<String> {
+    let mut section = Section::default();
+    let mut found = None;
 
     for line in content.lines() {
         let line = line.trim();
@@ -135,38 +348,46 @@ fn parse_gitconfig(content: &str, target_section: &str, target_key: &str) -> Opt
 
         // Check for section headers
         if line.starts_with('[') && line.ends_with(']') {
-            let section = &line[1..line.len() - 1];
-            in_target_section = section.trim().eq_ignore_ascii_case(target_section);
+            section = Section::parse(&line[1..line.len() - 1]);
             continue;
         }
 
-        // If we're in the target section, look for the key
-        if in_target_section {
-            if let Some(eq_pos) = line.find('=') {
-                let key = line[..eq_pos].trim();
-                if key.eq_ignore_ascii_case(target_key) {
-                    let value = line[eq_pos + 1..].trim();
-                    // Remove quotes if present
-                    let value = if (value.starts_with('"') && value.ends_with('"'))
-                        || (value.starts_with('\'') && value.ends_with('\''))
-                    {
-                        &value[1..value.len() - 1]
-                    } else {
-                        value
-                    };
-                    return Some(value.to_string());
-                }
+        let Some(eq_pos) = line.find('=') else {
+            continue;
+        };
+        let key = line[..eq_pos].trim();
+        let value = line[eq_pos + 1..].trim();
+        // Remove quotes if present
+        let value = if value.len() >= 2
+            && ((value.starts_with('"') && value.ends_with('"'))
+                || (value.starts_with('\'') && value.ends_with('\'')))
+        {
+            &value[1..value.len() - 1]
+        } else {
+            value
+        };
+
+        if section.is(target_section) && key.eq_ignore_ascii_case(target_key) {
+            found = Some(value.to_string());
+        } else if key.eq_ignore_ascii_case("path") {
+            if let Some(included) = include(&section, value) {
+                found = Some(included);
             }
         }
     }
 
-    None
+    found
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    // A single file, without following its includes
+    fn parse_gitconfig(content: &str, section: &str, key: &str) -> Option<String> {
+        parse_config(content, section, key, &mut |_, _| None)
+    }
+
     #[test]
     fn test_parse_gitconfig_basic() {
         let config = r#"
@@ -271,8 +492,184 @@ mod tests {
     #[test]
     fn test_get_git_user_functions_exist() {
         // These functions should not panic and should return Option values
-        let _name = get_git_user_name();
-        let _email = get_git_user_email();
+        let _name = get_git_user_name(".");
+        let _email = get_git_user_email(".");
+    }
+
+    #[test]
+    fn test_missing_git_binary_falls_back_to_the_config_file() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let gitconfig = temp_dir.path().join(".gitconfig");
+        std::fs::write(
+            &gitconfig,
+            "[user]\n    name = Jane Doe\n    email = jane@example.com\n",
+        )
+        .unwrap();
+        let paths = [temp_dir.path().join("missing"), gitconfig];
+        let missing = AtomicBool::new(false);
+
+        assert_eq!(
+            git_user_value("git-editor-no-such-git", &missing, ".", "name", &paths),
+            Some("Jane Doe".to_string())
+        );
+        assert!(missing.load(Ordering::Relaxed));
+
+        // Once the binary is known to be missing the file is read straight away
+        assert_eq!(
+            git_user_value("git-editor-no-such-git", &missing, ".", "email", &paths),
+            Some("jane@example.com".to_string())
+        );
+        assert_eq!(
+            git_user_value(
+                "git-editor-no-such-git",
+                &missing,
+                ".",
+                "signingkey",
+                &paths
+            ),
+            None
+        );
+    }
+
+    #[test]
+    fn test_parse_gitconfig_subsections() {
+        let config = r#"
+[user "work"]
+    name = Work Name
+[user]
+    name = Plain Name
+[remote "origin"]
+    url = git@example.com:me/repo.git
+[remote "Up\"stream"]
+    url = https://example.com/upstream.git
+[credential.HTTPS://example.com]
+    username = jane
+"#;
+
+        assert_eq!(
+            parse_gitconfig(config, "user", "name"),
+            Some("Plain Name".to_string())
+        );
+        assert_eq!(
+            parse_gitconfig(config, "user.work", "name"),
+            Some("Work Name".to_string())
+        );
+        assert_eq!(
+            parse_gitconfig(config, "USER.work", "name"),
+            Some("Work Name".to_string())
+        );
+        // Quoted subsections are case-sensitive
+        assert_eq!(parse_gitconfig(config, "user.Work", "name"), None);
+        assert_eq!(
+            parse_gitconfig(config, "remote.origin", "url"),
+            Some("git@example.com:me/repo.git".to_string())
+        );
+        assert_eq!(
+            parse_gitconfig(config, "remote.Up\"stream", "url"),
+            Some("https://example.com/upstream.git".to_string())
+        );
+        assert_eq!(parse_gitconfig(config, "remote", "url"), None);
+        // The old dotted form is lowercased
+        assert_eq!(
+            parse_gitconfig(config, "credential.https://example.com", "username"),
+            Some("jane".to_string())
+        );
+    }
+
+    #[test]
+    fn test_include_path_is_followed() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let base = temp_dir.path().join(".gitconfig");
+        std::fs::create_dir(temp_dir.path().join("conf")).unwrap();
+        std::fs::write(
+            &base,
+            "[core]\n    editor = vim\n[include]\n    path = conf/user.inc\n",
+        )
+        .unwrap();
+        // Relative to the file that names it, and nested one level further
+        std::fs::write(
+            temp_dir.path().join("conf/user.inc"),
+            "[user]\n    name = Jane Doe\n[include]\n    path = email.inc\n",
+        )
+        .unwrap();
+        std::fs::write(
+            temp_dir.path().join("conf/email.inc"),
+            "[user]\n    email = jane@example.com\n",
+        )
+        .unwrap();
+
+        let paths = [base];
+        assert_eq!(
+            read_gitconfig_value(&paths, "user", "name", None),
+            Some("Jane Doe".to_string())
+        );
+        assert_eq!(
+            read_gitconfig_value(&paths, "user", "email", None),
+            Some("jane@example.com".to_string())
+        );
+        // Without following includes the base file has no user
+        let content = std::fs::read_to_string(&paths[0]).unwrap();
+        assert_eq!(parse_gitconfig(&content, "user", "name"), None);
+    }
+
+    #[test]
+    fn test_include_cycle_stops() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let base = temp_dir.path().join(".gitconfig");
+        std::fs::write(&base, "[include]\n    path = .gitconfig\n").unwrap();
+
+        assert_eq!(read_gitconfig_value(&[base], "user", "name", None), None);
+    }
+
+    #[test]
+    fn test_include_if_gitdir_matches_the_repository() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let base = temp_dir.path().join(".gitconfig");
+        std::fs::write(
+            &base,
+            "[user]\n    name = Personal\n\
+             [includeIf \"gitdir:work/\"]\n    path = work.inc\n\
+             [includeIf \"gitdir/i:./CLIENT/\"]\n    path = client.inc\n\
+             [includeIf \"onbranch:main\"]\n    path = work.inc\n",
+        )
+        .unwrap();
+        std::fs::write(
+            temp_dir.path().join("work.inc"),
+            "[user]\n    name = Work\n",
+        )
+        .unwrap();
+        std::fs::write(
+            temp_dir.path().join("client.inc"),
+            "[user]\n    name = Client\n",
+        )
+        .unwrap();
+
+        let work_repo = temp_dir.path().join("src/work/project");
+        let client_repo = temp_dir.path().join("client/project");
+        let other_repo = temp_dir.path().join("src/other");
+        for repo in [&work_repo, &client_repo, &other_repo] {
+            git2::Repository::init(repo).unwrap();
+        }
+
+        let paths = [base];
+        let name = |repo: &Path| {
+            let gitdir = repo_gitdir(repo.to_str().unwrap());
+            assert!(gitdir.as_deref().unwrap().ends_with("/.git"));
+            read_gitconfig_value(&paths, "user", "name", gitdir.as_deref())
+        };
+        // The included file overrides the name set before it
+        assert_eq!(name(&work_repo), Some("Work".to_string()));
+        assert_eq!(name(&client_repo), Some("Client".to_string()));
+        assert_eq!(name(&other_repo), Some("Personal".to_string()));
+    }
+
+    #[test]
+    fn test_glob_match() {
+        assert!(glob_match(b"**/work/**", b"/home/jane/work/project/.git"));
+        assert!(glob_match(b"/home/*/work/**", b"/home/jane/work/.git"));
+        assert!(!glob_match(b"/home/*/.git", b"/home/jane/work/.git"));
+        assert!(glob_match(b"/home/jan?/.git", b"/home/jane/.git"));
+        assert!(!glob_match(b"**/work/**", b"/home/jane/workshop/.git"));
     }
 
     #[test]
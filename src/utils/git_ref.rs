@@ -0,0 +1,348 @@
+use crate::args::Args;
+use crate::utils::types::Result;
+use git2::{ErrorCode, Oid, Repository, Revwalk, Signature};
+
+/// The reference a rewrite reads its history from and moves once it is done.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RewriteTarget {
+    /// A named reference such as `refs/heads/main`, updated in place
+    Reference(String),
+    /// A detached HEAD, which is moved directly to the new tip
+    DetachedHead,
+}
+
+impl RewriteTarget {
+    /// Resolves `--ref`, falling back to whatever HEAD points at.
+    pub fn resolve(repo: &Repository, args: &Args) -> Result<RewriteTarget> {
+        let name = args.rewrite_ref.as_deref().unwrap_or("HEAD");
+
+        if name == "HEAD" {
+            if head_is_unborn(repo) {
+                // A bare repository's HEAD may name a branch that was never pushed
+                if has_references(repo) {
+                    return Err(
+                        "HEAD points at a branch without commits; pick a branch with --ref".into(),
+                    );
+                }
+                let branch = unborn_branch(repo).unwrap_or_else(|| "HEAD".to_string());
+                return Err(
+                    format!("Branch '{branch}' has no commits yet; nothing to rewrite").into(),
+                );
+            }
+            if repo.head_detached()? {
+                return Ok(RewriteTarget::DetachedHead);
+            }
+            let head = repo.head()?;
+            let full_name = head.name().ok_or("HEAD points at an invalid reference")?;
+            return Ok(RewriteTarget::Reference(full_name.to_string()));
+        }
+
+        let reference = repo
+            .resolve_reference_from_short_name(name)
+            .map_err(|_| format!("Reference not found: {name}"))?;
+        if reference.is_tag() {
+            return Err(format!("--ref must name a branch, not the tag '{name}'").into());
+        }
+        if reference.is_remote() {
+            return Err(format!(
+                "--ref must name a local branch, not the remote-tracking branch '{name}'; use --rev to read it"
+            )
+            .into());
+        }
+        let full_name = reference
+            .name()
+            .ok_or_else(|| format!("Invalid reference name: {name}"))?;
+        Ok(RewriteTarget::Reference(full_name.to_string()))
+    }
+
+    /// The commit the target currently points at.
+    pub fn tip(&self, repo: &Repository) -> Result<Oid> {
+        let reference = match self {
+            RewriteTarget::Reference(name) => repo.find_reference(name)?,
+            RewriteTarget::DetachedHead => repo.head()?,
+        };
+        Ok(reference.peel_to_commit()?.id())
+    }
+
+    /// Points the target at `new_tip` and records `log_message` in its reflog.
+    pub fn update(&self, repo: &Repository, new_tip: Oid, log_message: &str) -> Result<()> {
+        // A detached HEAD is a direct reference itself, so it is moved the same way
+        let name = match self {
+            RewriteTarget::Reference(name) => name.as_str(),
+            RewriteTarget::DetachedHead => "HEAD",
+        };
+        repo.reference(name, new_tip, true, log_message)?;
+        ensure_reflog_entry(repo, name, new_tip, log_message)
+    }
+
+    /// Whether rewriting this target moves the commit checked out in the working tree.
+    pub fn is_checked_out(&self, repo: &Repository) -> Result<bool> {
+        Ok(match self {
+            RewriteTarget::DetachedHead => true,
+            RewriteTarget::Reference(name) => {
+                !repo.head_detached()? && repo.head()?.name() == Some(name.as_str())
+            }
+        })
+    }
+
+    pub fn describe(&self) -> String {
+        match self {
+            RewriteTarget::Reference(name) => match name.strip_prefix("refs/heads/") {
+                Some(branch) => format!("branch '{branch}'"),
+                None => format!("ref '{name}'"),
+            },
+            RewriteTarget::DetachedHead => "detached HEAD".to_string(),
+        }
+    }
+}
+
+// Repositories with core.logAllRefUpdates off, as bare ones are by default,
+// skip the reflog on a reference update; the entry is then written by hand
+fn ensure_reflog_entry(repo: &Repository, name: &str, new_tip: Oid, message: &str) -> Result<()> {
+    let mut reflog = repo.reflog(name)?;
+    if let Some(latest) = reflog.get(0) {
+        if latest.id_new() == new_tip && latest.message() == Some(message) {
+            return Ok(());
+        }
+    }
+
+    let committer = repo
+        .signature()
+        .or_else(|_| Signature::now("git-editor", "git-editor@localhost"))?;
+    reflog.append(new_tip, &committer, Some(message))?;
+    reflog.write()?;
+    Ok(())
+}
+
+/// Whether HEAD points at a branch without any commits, as in a freshly
+/// initialised repository.
+pub fn head_is_unborn(repo: &Repository) -> bool {
+    matches!(repo.head(), Err(e) if e.code() == ErrorCode::UnbornBranch)
+}
+
+/// The branch HEAD points at when it has no commits yet and the repository
+/// has no other references either, as in a freshly initialised repository.
+pub fn unborn_branch(repo: &Repository) -> Option<String> {
+    if !head_is_unborn(repo) || has_references(repo) {
+        return None;
+    }
+    let head = repo.find_reference("HEAD").ok()?;
+    let target = head.symbolic_target()?;
+    Some(
+        target
+            .strip_prefix("refs/heads/")
+            .unwrap_or(target)
+            .to_string(),
+    )
+}
+
+/// Whether the repository has any reference at all, e.g. branches that HEAD
+/// does not point at.
+pub fn has_references(repo: &Repository) -> bool {
+    repo.references()
+        .is_ok_and(|mut references| references.next().is_some())
+}
+
+/// The commit history is read from: `--rev` when given, which may be any
+/// revision such as a tag or a remote-tracking branch, and otherwise the tip
+/// of the rewrite target.
+pub fn history_tip(repo: &Repository, args: &Args) -> Result<Oid> {
+    match args.rev.as_deref() {
+        Some(rev) => {
+            let object = repo
+                .revparse_single(rev)
+                .map_err(|_| format!("Revision not found: {rev}"))?;
+            let commit = object
+                .peel_to_commit()
+                .map_err(|_| format!("Revision does not point at a commit: {rev}"))?;
+            Ok(commit.id())
+        }
+        None => RewriteTarget::resolve(repo, args)?.tip(repo),
+    }
+}
+
+/// Starts `revwalk` from the commit the history is read from.
+pub fn push_target(revwalk: &mut Revwalk, repo: &Repository, args: &Args) -> Result<()> {
+    revwalk.push(history_tip(repo, args)?)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    fn create_test_repo() -> (TempDir, Repository) {
+        let temp_dir = TempDir::new().unwrap();
+        let repo = Repository::init(temp_dir.path()).unwrap();
+
+        fs::write(temp_dir.path().join("test.txt"), "content").unwrap();
+        let mut index = repo.index().unwrap();
+        index.add_path(std::path::Path::new("test.txt")).unwrap();
+        index.write().unwrap();
+        let tree = repo.find_tree(index.write_tree().unwrap()).unwrap();
+        let sig =
+            git2::Signature::new("Test User", "test@example.com", &git2::Time::new(0, 0)).unwrap();
+        repo.commit(Some("HEAD"), &sig, &sig, "Initial", &tree, &[])
+            .unwrap();
+        drop(tree);
+
+        (temp_dir, repo)
+    }
+
+    fn args_with_ref(temp_dir: &TempDir, rewrite_ref: Option<&str>) -> Args {
+        Args {
+            repo_path: Some(temp_dir.path().to_string_lossy().to_string()),
+            rewrite_ref: rewrite_ref.map(str::to_string),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_resolve_target() {
+        let (temp_dir, repo) = create_test_repo();
+        let head = repo.head().unwrap().target().unwrap();
+        let head_name = repo.head().unwrap().name().unwrap().to_string();
+        repo.branch("feature", &repo.find_commit(head).unwrap(), false)
+            .unwrap();
+        repo.tag_lightweight("v1", repo.find_commit(head).unwrap().as_object(), false)
+            .unwrap();
+
+        let target = RewriteTarget::resolve(&repo, &args_with_ref(&temp_dir, None)).unwrap();
+        assert_eq!(target, RewriteTarget::Reference(head_name));
+        assert!(target.is_checked_out(&repo).unwrap());
+
+        let target =
+            RewriteTarget::resolve(&repo, &args_with_ref(&temp_dir, Some("feature"))).unwrap();
+        assert_eq!(
+            target,
+            RewriteTarget::Reference("refs/heads/feature".to_string())
+        );
+        assert!(!target.is_checked_out(&repo).unwrap());
+        assert_eq!(target.tip(&repo).unwrap(), head);
+
+        assert!(RewriteTarget::resolve(&repo, &args_with_ref(&temp_dir, Some("v1"))).is_err());
+        assert!(RewriteTarget::resolve(&repo, &args_with_ref(&temp_dir, Some("missing"))).is_err());
+
+        repo.set_head_detached(head).unwrap();
+        let target = RewriteTarget::resolve(&repo, &args_with_ref(&temp_dir, None)).unwrap();
+        assert_eq!(target, RewriteTarget::DetachedHead);
+    }
+
+    #[test]
+    fn test_history_tip_resolves_any_revision() {
+        let (temp_dir, repo) = create_test_repo();
+        let first = repo.head().unwrap().peel_to_commit().unwrap();
+        let sig = first.author();
+        let second = repo
+            .commit(
+                Some("HEAD"),
+                &sig,
+                &sig,
+                "Second",
+                &first.tree().unwrap(),
+                &[&first],
+            )
+            .unwrap();
+
+        // A tag and a remote-tracking branch left behind at the first commit
+        repo.tag("v1", first.as_object(), &sig, "Release", false)
+            .unwrap();
+        repo.reference("refs/remotes/origin/main", first.id(), false, "fetch")
+            .unwrap();
+
+        let tip = |rev: Option<&str>| {
+            let args = Args {
+                rev: rev.map(str::to_string),
+                ..args_with_ref(&temp_dir, None)
+            };
+            history_tip(&repo, &args)
+        };
+        assert_eq!(tip(None).unwrap(), second);
+        assert_eq!(tip(Some("v1")).unwrap(), first.id());
+        assert_eq!(tip(Some("origin/main")).unwrap(), first.id());
+        assert_eq!(tip(Some("HEAD~1")).unwrap(), first.id());
+        let err = tip(Some("origin/missing")).unwrap_err().to_string();
+        assert_eq!(err, "Revision not found: origin/missing");
+
+        let args = Args {
+            rev: Some("origin/main".to_string()),
+            ..args_with_ref(&temp_dir, None)
+        };
+        let commits = crate::utils::commit_history::collect_commits(&args, None).unwrap();
+        assert_eq!(commits.len(), 1);
+        assert_eq!(commits[0].oid, first.id());
+
+        // A remote-tracking branch cannot be rewritten in place
+        let err = RewriteTarget::resolve(&repo, &args_with_ref(&temp_dir, Some("origin/main")))
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("--rev"), "{err}");
+    }
+
+    #[test]
+    fn test_resolve_target_in_empty_repo() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut options = git2::RepositoryInitOptions::new();
+        options.initial_head("main");
+        let repo = Repository::init_opts(temp_dir.path(), &options).unwrap();
+
+        // Staged files leave the branch unborn
+        fs::write(temp_dir.path().join("staged.txt"), "staged").unwrap();
+        let mut index = repo.index().unwrap();
+        index.add_path(std::path::Path::new("staged.txt")).unwrap();
+        index.write().unwrap();
+        assert!(head_is_unborn(&repo));
+        assert_eq!(unborn_branch(&repo).as_deref(), Some("main"));
+
+        let err = RewriteTarget::resolve(&repo, &args_with_ref(&temp_dir, None))
+            .unwrap_err()
+            .to_string();
+        assert_eq!(err, "Branch 'main' has no commits yet; nothing to rewrite");
+
+        // Naming the unborn branch is the same as reading HEAD
+        for requested in [None, Some("HEAD"), Some("main"), Some("refs/heads/main")] {
+            let args = args_with_ref(&temp_dir, requested);
+            assert_eq!(args.unborn_branch().as_deref(), Some("main"));
+        }
+        let args = Args {
+            rev: Some("HEAD".to_string()),
+            ..args_with_ref(&temp_dir, None)
+        };
+        assert!(args.has_no_commits());
+        assert!(!args_with_ref(&temp_dir, Some("other")).has_no_commits());
+    }
+
+    #[test]
+    fn test_resolve_target_in_bare_repo() {
+        let (_source_dir, source) = create_test_repo();
+        let tip = source.head().unwrap().peel_to_commit().unwrap().id();
+
+        let temp_dir = TempDir::new().unwrap();
+        let repo = Repository::init_bare(temp_dir.path()).unwrap();
+        let mut remote = repo
+            .remote_anonymous(source.path().to_str().unwrap())
+            .unwrap();
+        remote
+            .fetch(&["+refs/heads/*:refs/heads/*"], None, None)
+            .unwrap();
+        drop(remote);
+
+        let branch = source.head().unwrap().name().unwrap().to_string();
+        repo.set_head(&branch).unwrap();
+        let target = RewriteTarget::resolve(&repo, &args_with_ref(&temp_dir, None)).unwrap();
+        assert_eq!(target, RewriteTarget::Reference(branch.clone()));
+        assert_eq!(target.tip(&repo).unwrap(), tip);
+
+        // With HEAD on a branch that does not exist, --ref is needed
+        repo.set_head("refs/heads/missing").unwrap();
+        let err = RewriteTarget::resolve(&repo, &args_with_ref(&temp_dir, None))
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("--ref"), "{err}");
+        let short = branch.trim_start_matches("refs/heads/");
+        let target = RewriteTarget::resolve(&repo, &args_with_ref(&temp_dir, Some(short))).unwrap();
+        assert_eq!(target.tip(&repo).unwrap(), tip);
+    }
+}
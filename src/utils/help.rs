@@ -9,30 +9,55 @@ pub fn print_help() {
     println!("{}", "A powerful Rust-based command-line utility designed to safely rewrite Git commit metadata.".white());
     println!();
     println!("{}", "USAGE:".yellow().bold());
-    println!("    {} [OPTIONS]", "git-editor".cyan());
+    println!("    {} [OPTIONS] [COMMAND]", "git-editor".cyan());
     println!();
     println!("{}", "OPERATION MODES:".yellow().bold());
     println!("  {} Full History Rewrite (default)", "•".green());
+    println!("    Command: rewrite");
     println!("    Requires: --email, --name, --begin, --end");
-    println!("    Example: git-editor --email user@example.com --name \"User\" --begin \"2023-01-01 00:00:00\" --end \"2023-01-07 23:59:59\"");
+    println!("    Example: git-editor rewrite --email user@example.com --name \"User\" --begin \"2023-01-01 00:00:00\" --end \"2023-01-07 23:59:59\"");
     println!();
     println!("  {} Show History", "•".green());
-    println!("    Flag: -s, --show-history");
-    println!("    Example: git-editor -s");
+    println!("    Command: history");
+    println!("    Example: git-editor history");
     println!();
     println!("  {} Pick Specific Commits", "•".green());
-    println!("    Flag: -p, --pick-specific-commits");
-    println!("    Example: git-editor -p");
+    println!("    Command: pick [--simulate|--dry-run] [--show-diff]");
+    println!("    Example: git-editor pick");
     println!();
     println!("  {} Range Editing", "•".green());
-    println!("    Flag: -x, --range");
-    println!("    Example: git-editor -x");
+    println!(
+        "    Command: range [--message] [--author] [--time] [--simulate|--dry-run] [--show-diff]"
+    );
+    println!("    Example: git-editor range --time");
+    println!();
+    println!("  {} Interactive Full Edit", "•".green());
+    println!("    Command: rewrite --interactive");
+    println!("    Example: git-editor rewrite -i");
+    println!();
+    println!("  {} Apply a Rewrite Plan", "•".green());
+    println!("    Flag: --apply-plan <FILE>");
+    println!("    Applies the per-commit edits listed in a JSON file without prompting");
+    println!("    Example: git-editor --apply-plan plan.json");
+    println!();
+    println!("  {} Replace File Contents", "•".green());
+    println!("    Flag: --replace-in-files <PATTERN=REPLACEMENT>");
+    println!("    Replaces text in every file across the history, e.g. to scrub a leaked secret");
+    println!("    Example: git-editor --replace-in-files \"hunter2=REDACTED\"");
     println!();
     println!("  {} Simulation Mode (Dry-Run)", "•".green());
-    println!("    Flag: --simulate");
+    println!("    Command: simulate [--show-diff] (alias: dry-run)");
     println!("    Shows what changes would be made without applying them");
-    println!("    Example: git-editor --simulate --name \"Author\" --email \"author@example.com\"");
-    println!("    Example: git-editor --simulate --show-diff --name \"Author\" --email \"author@example.com\"");
+    println!("    --show-diff in pick or range mode implies --simulate");
+    println!("    Example: git-editor simulate --name \"Author\" --email \"author@example.com\"");
+    println!("    Example: git-editor simulate --show-diff --name \"Author\" --email \"author@example.com\"");
+    println!();
+    println!(
+        "{}",
+        "The old mode flags (-p, -x, -i, --simulate, --show-diff, --docs) still work but are deprecated."
+            .white()
+            .italic()
+    );
     println!();
     println!("{}", "OPTIONS:".yellow().bold());
     println!(
@@ -55,23 +80,19 @@ pub fn print_help() {
         "  {:<25} End date (YYYY-MM-DD HH:MM:SS)",
         "-e, --end <DATE>".cyan()
     );
-    println!("  {:<25} Show commit history", "-s, --show-history".cyan());
-    println!(
-        "  {:<25} Interactive commit selection",
-        "-p, --pick-specific-commits".cyan()
-    );
-    println!("  {:<25} Interactive range editing", "-x, --range".cyan());
-    println!(
-        "  {:<25} Dry-run mode - preview changes without applying",
-        "--simulate".cyan()
-    );
     println!(
-        "  {:<25} Show detailed diff in simulation (requires --simulate)",
-        "--show-diff".cyan()
+        "  {:<25} Show updated commit history after rewriting",
+        "-s, --show-history".cyan()
     );
     println!("  {:<25} Print help information", "-h, --help".cyan());
     println!("  {:<25} Print version information", "-V, --version".cyan());
     println!();
+    println!("{}", "EXIT CODES:".yellow().bold());
+    println!("  {:<25} Success", "0".cyan());
+    println!("  {:<25} Error", "1".cyan());
+    println!("  {:<25} Cancelled by the user", "2".cyan());
+    println!("  {:<25} Nothing to do", "3".cyan());
+    println!();
     println!(
         "{}",
         "For more detailed usage information, use: git-editor --help"
@@ -0,0 +1,222 @@
+use crate::utils::types::Result;
+
+/// One line of a mailmap file. `commit_email` (and `commit_name`, when given)
+/// select the identities the line applies to; the proper fields replace them.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MailmapEntry {
+    pub proper_name: Option<String>,
+    pub proper_email: Option<String>,
+    pub commit_name: Option<String>,
+    pub commit_email: String,
+}
+
+/// Identity mapping in git's mailmap format, used to normalize authors and
+/// committers during a full rewrite.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Mailmap {
+    entries: Vec<MailmapEntry>,
+}
+
+impl Mailmap {
+    pub fn from_file(path: &str) -> Result<Mailmap> {
+        let content = std::fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read mailmap file {path}: {e}"))?;
+        Mailmap::parse(&content).map_err(|e| format!("{path}: {e}").into())
+    }
+
+    pub fn parse(content: &str) -> Result<Mailmap> {
+        let mut entries = Vec::new();
+        for (i, line) in content.lines().enumerate() {
+            if let Some(entry) = parse_line(line).map_err(|e| format!("line {}: {e}", i + 1))? {
+                entries.push(entry);
+            }
+        }
+        Ok(Mailmap { entries })
+    }
+
+    /// Maps a name and email to its proper identity. Lines that also name the
+    /// commit author take precedence over email-only lines, and among equally
+    /// specific lines the last one wins, as in git. Unmapped identities are
+    /// returned unchanged.
+    pub fn resolve(&self, name: &str, email: &str) -> (String, String) {
+        let matches_email = |entry: &&MailmapEntry| entry.commit_email.eq_ignore_ascii_case(email);
+
+        let entry = self
+            .entries
+            .iter()
+            .rev()
+            .filter(matches_email)
+            .find(|entry| {
+                entry
+                    .commit_name
+                    .as_deref()
+                    .is_some_and(|n| n.eq_ignore_ascii_case(name))
+            })
+            .or_else(|| {
+                self.entries
+                    .iter()
+                    .rev()
+                    .filter(matches_email)
+                    .find(|entry| entry.commit_name.is_none())
+            });
+
+        match entry {
+            Some(entry) => (
+                entry
+                    .proper_name
+                    .clone()
+                    .unwrap_or_else(|| name.to_string()),
+                entry
+                    .proper_email
+                    .clone()
+                    .unwrap_or_else(|| email.to_string()),
+            ),
+            None => (name.to_string(), email.to_string()),
+        }
+    }
+}
+
+// Accepts the four forms git understands:
+//   Proper Name <commit@email>
+//   <proper@email> <commit@email>
+//   Proper Name <proper@email> <commit@email>
+//   Proper Name <proper@email> Commit Name <commit@email>
+// A `#` at the start of the line or after the last email starts a comment.
+fn parse_line(line: &str) -> Result<Option<MailmapEntry>> {
+    let line = line.trim();
+    if line.is_empty() || line.starts_with('#') {
+        return Ok(None);
+    }
+
+    let mut pairs: Vec<(Option<String>, String)> = Vec::new();
+    let mut rest = line;
+    while pairs.len() < 2 {
+        let Some(open) = rest.find('<') else {
+            break;
+        };
+        let close = rest[open..]
+            .find('>')
+            .map(|i| open + i)
+            .ok_or("missing '>' after email")?;
+        let name = rest[..open].trim();
+        let email = rest[open + 1..close].trim();
+        if email.is_empty() {
+            return Err("empty email address".into());
+        }
+        pairs.push((
+            (!name.is_empty()).then(|| name.to_string()),
+            email.to_string(),
+        ));
+        rest = &rest[close + 1..];
+    }
+
+    let trailing = rest.trim();
+    if !trailing.is_empty() && !trailing.starts_with('#') {
+        return Err(format!("unexpected text '{trailing}'").into());
+    }
+
+    match pairs.as_slice() {
+        [(Some(name), email)] => Ok(Some(MailmapEntry {
+            proper_name: Some(name.clone()),
+            proper_email: None,
+            commit_name: None,
+            commit_email: email.clone(),
+        })),
+        [(proper_name, proper_email), (commit_name, commit_email)] => Ok(Some(MailmapEntry {
+            proper_name: proper_name.clone(),
+            proper_email: Some(proper_email.clone()),
+            commit_name: commit_name.clone(),
+            commit_email: commit_email.clone(),
+        })),
+        _ => Err("expected 'Proper Name <proper@email> <commit@email>'".into()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_line_forms() {
+        let mailmap = Mailmap::parse(
+            "# comment\n\
+             \n\
+             Joe Developer <joe@example.com>\n\
+             <jane@example.com> <jane@laptop.local>\n\
+             Jane Doe <jane@example.com> <jdoe@old.example.com>  # trailing comment\n\
+             Other Author <other@example.com> nick2 <bugs@example.com>\n",
+        )
+        .unwrap();
+        assert_eq!(mailmap.entries.len(), 4);
+
+        assert_eq!(
+            mailmap.entries[0],
+            MailmapEntry {
+                proper_name: Some("Joe Developer".to_string()),
+                proper_email: None,
+                commit_name: None,
+                commit_email: "joe@example.com".to_string(),
+            }
+        );
+        assert_eq!(mailmap.entries[1].proper_name, None);
+        assert_eq!(
+            mailmap.entries[1].proper_email.as_deref(),
+            Some("jane@example.com")
+        );
+        assert_eq!(mailmap.entries[2].commit_email, "jdoe@old.example.com");
+        assert_eq!(mailmap.entries[3].commit_name.as_deref(), Some("nick2"));
+    }
+
+    #[test]
+    fn test_parse_rejects_malformed_lines() {
+        assert!(Mailmap::parse("Joe Developer").is_err());
+        assert!(Mailmap::parse("<joe@example.com>").is_err());
+        assert!(Mailmap::parse("Joe <joe@example.com").is_err());
+        assert!(Mailmap::parse("Joe <>").is_err());
+        let err = Mailmap::parse("Ok <ok@example.com>\nJoe <a@b.c> <d@e.f> extra")
+            .unwrap_err()
+            .to_string();
+        assert!(err.starts_with("line 2"));
+    }
+
+    #[test]
+    fn test_resolve() {
+        let mailmap = Mailmap::parse(
+            "Joe Developer <joe@example.com>\n\
+             <jane@example.com> <jane@laptop.local>\n\
+             Jane Doe <jane@example.com> <JDOE@old.example.com>\n\
+             Other Author <other@example.com> nick2 <bugs@example.com>\n\
+             Fallback <fallback@example.com> <bugs@example.com>\n",
+        )
+        .unwrap();
+
+        let resolve = |name: &str, email: &str| {
+            let (name, email) = mailmap.resolve(name, email);
+            format!("{name} <{email}>")
+        };
+        assert_eq!(
+            resolve("joe", "joe@example.com"),
+            "Joe Developer <joe@example.com>"
+        );
+        assert_eq!(
+            resolve("Jane", "jane@laptop.local"),
+            "Jane <jane@example.com>"
+        );
+        assert_eq!(
+            resolve("jd", "jdoe@old.example.com"),
+            "Jane Doe <jane@example.com>"
+        );
+        assert_eq!(
+            resolve("nick2", "bugs@example.com"),
+            "Other Author <other@example.com>"
+        );
+        assert_eq!(
+            resolve("nick1", "bugs@example.com"),
+            "Fallback <fallback@example.com>"
+        );
+        assert_eq!(
+            resolve("Someone", "someone@example.com"),
+            "Someone <someone@example.com>"
+        );
+    }
+}
@@ -0,0 +1,216 @@
+use crate::args::Args;
+use crate::utils::types::{CommitInfo, EditOptions, Result};
+use regex::Regex;
+
+/// The bulk message edits requested with `--message-strip`,
+/// `--message-prepend` and `--message-append`. They always run in that order,
+/// so a stripped sign-off never takes an appended suffix with it.
+#[derive(Debug, Clone, Default)]
+pub struct MessageTransforms {
+    strip: Option<Regex>,
+    prepend: Option<String>,
+    append: Option<String>,
+}
+
+impl MessageTransforms {
+    pub fn from_args(args: &Args) -> Result<MessageTransforms> {
+        let strip = args
+            .message_strip
+            .as_deref()
+            .map(|pattern| {
+                Regex::new(pattern).map_err(|e| format!("Invalid --message-strip regex: {e}"))
+            })
+            .transpose()?;
+
+        Ok(MessageTransforms {
+            strip,
+            prepend: args.message_prepend.clone(),
+            append: args.message_append.clone(),
+        })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.strip.is_none() && self.prepend.is_none() && self.append.is_none()
+    }
+
+    /// Runs the pipeline over one message. Prepended text goes before the
+    /// first character and appended text after the last non-whitespace one,
+    /// so the message keeps its trailing newline.
+    pub fn apply(&self, message: &str) -> String {
+        let mut body = message.trim_end().to_string();
+        let trailing = &message[body.len()..];
+
+        if let Some(strip) = &self.strip {
+            // Removing a line leaves blank lines behind at the end
+            body = strip.replace_all(&body, "").trim_end().to_string();
+        }
+        if let Some(prepend) = &self.prepend {
+            body.insert_str(0, prepend);
+        }
+        if let Some(append) = &self.append {
+            body.push_str(append);
+        }
+
+        body + trailing
+    }
+}
+
+/// Whether two messages differ only in line endings or trailing whitespace,
+/// as a message pasted into an editor often does. Such an edit changes
+/// nothing worth rewriting a commit for.
+pub fn same_message(a: &str, b: &str) -> bool {
+    fn normalized(message: &str) -> Vec<&str> {
+        let mut lines: Vec<&str> = message.lines().map(str::trim_end).collect();
+        while lines.last() == Some(&"") {
+            lines.pop();
+        }
+        lines
+    }
+    normalized(a) == normalized(b)
+}
+
+/// Appends a `Signed-off-by: name <email>` trailer (`--signoff`) unless the
+/// message already carries that exact one. Like `git commit --signoff`, the
+/// trailer joins a closing block of trailers and otherwise starts its own
+/// paragraph.
+pub fn add_signoff(message: &str, name: &str, email: &str) -> String {
+    let trailer = format!("Signed-off-by: {name} <{email}>");
+    if message.lines().any(|line| line.trim_end() == trailer) {
+        return message.to_string();
+    }
+
+    let body = message.trim_end();
+    if body.is_empty() {
+        return format!("{trailer}\n");
+    }
+    let trailer_re = Regex::new(r"^[A-Za-z0-9-]+: ").unwrap();
+    let ends_with_trailers = body
+        .rsplit_once("\n\n")
+        .is_some_and(|(_, last)| last.lines().all(|line| trailer_re.is_match(line)));
+    let separator = if ends_with_trailers { "\n" } else { "\n\n" };
+    format!("{body}{separator}{trailer}\n")
+}
+
+/// Signs off an edit of `commit` for the author it will be written with,
+/// turning it into a message edit.
+pub fn sign_off_edit(options: &mut EditOptions, commit: &CommitInfo) {
+    let message = options.message.as_deref().unwrap_or(&commit.message);
+    let name = options
+        .author_name
+        .as_deref()
+        .unwrap_or(&commit.author_name);
+    let email = options
+        .author_email
+        .as_deref()
+        .unwrap_or(&commit.author_email);
+    options.message = Some(add_signoff(message, name, email));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn transforms(
+        strip: Option<&str>,
+        prepend: Option<&str>,
+        append: Option<&str>,
+    ) -> MessageTransforms {
+        MessageTransforms::from_args(&Args {
+            message_strip: strip.map(str::to_string),
+            message_prepend: prepend.map(str::to_string),
+            message_append: append.map(str::to_string),
+            ..Default::default()
+        })
+        .unwrap()
+    }
+
+    #[test]
+    fn test_prepend() {
+        let t = transforms(None, Some("ABC-123: "), None);
+        assert_eq!(t.apply("Fix login\n"), "ABC-123: Fix login\n");
+    }
+
+    #[test]
+    fn test_append() {
+        let t = transforms(None, None, Some(" (#42)"));
+        assert_eq!(t.apply("Fix login\n\n"), "Fix login (#42)\n\n");
+        assert_eq!(t.apply("Fix login"), "Fix login (#42)");
+    }
+
+    #[test]
+    fn test_strip() {
+        let t = transforms(Some(r"(?m)^Signed-off-by: .*$"), None, None);
+        assert_eq!(
+            t.apply("Fix login\n\nBody\n\nSigned-off-by: Jane <jane@example.com>\n"),
+            "Fix login\n\nBody\n"
+        );
+        assert_eq!(t.apply("Untouched\n"), "Untouched\n");
+    }
+
+    #[test]
+    fn test_combined_pipeline() {
+        let t = transforms(
+            Some(r"(?m)^Signed-off-by: .*$"),
+            Some("[core] "),
+            Some("\n\nRefs: #7"),
+        );
+        assert_eq!(
+            t.apply("Fix login\n\nSigned-off-by: Jane <jane@example.com>\n"),
+            "[core] Fix login\n\nRefs: #7\n"
+        );
+        assert!(!t.is_empty());
+        assert!(transforms(None, None, None).is_empty());
+    }
+
+    #[test]
+    fn test_same_message_ignores_line_endings_and_trailing_whitespace() {
+        assert!(same_message("msg\r\n", "msg\n"));
+        assert!(same_message(
+            "Subject  \r\n\r\nBody\t\r\n",
+            "Subject\n\nBody\n"
+        ));
+        assert!(same_message("msg", "msg\n\n"));
+
+        assert!(!same_message("msg\n", "Msg\n"));
+        assert!(!same_message("  msg\n", "msg\n"));
+        assert!(!same_message("Subject\nBody\n", "Subject\n\nBody\n"));
+    }
+
+    #[test]
+    fn test_add_signoff() {
+        let signed = "Fix login\n\nSigned-off-by: Jane <jane@example.com>\n";
+        assert_eq!(
+            add_signoff("Fix login\n", "Jane", "jane@example.com"),
+            signed
+        );
+        assert_eq!(add_signoff("Fix login", "Jane", "jane@example.com"), signed);
+
+        // An existing sign-off by the same identity is not repeated
+        assert_eq!(add_signoff(signed, "Jane", "jane@example.com"), signed);
+        let twice = add_signoff(
+            &add_signoff("Fix login\n", "Jane", "jane@example.com"),
+            "Jane",
+            "jane@example.com",
+        );
+        assert_eq!(twice.matches("Signed-off-by:").count(), 1);
+
+        // Another identity joins the existing trailer block
+        assert_eq!(
+            add_signoff(signed, "Joe", "joe@example.com"),
+            "Fix login\n\nSigned-off-by: Jane <jane@example.com>\nSigned-off-by: Joe <joe@example.com>\n"
+        );
+        assert_eq!(
+            add_signoff("Fix login\n\nBody text\n", "Jane", "jane@example.com"),
+            "Fix login\n\nBody text\n\nSigned-off-by: Jane <jane@example.com>\n"
+        );
+    }
+
+    #[test]
+    fn test_invalid_strip_regex() {
+        let args = Args {
+            message_strip: Some("(unclosed".to_string()),
+            ..Default::default()
+        };
+        assert!(MessageTransforms::from_args(&args).is_err());
+    }
+}
@@ -1,9 +1,19 @@
+pub mod anonymize;
+pub mod backup;
+pub mod change_summary;
 pub mod commit_history;
+pub mod config;
 pub mod datetime;
+pub mod editor;
 pub mod git_clone;
 pub mod git_config;
+pub mod git_ref;
 pub mod help;
+pub mod mailmap;
+pub mod message_transform;
 pub mod prompt;
+pub mod rewrite_state;
 pub mod simulation;
+pub mod theme;
 pub mod types;
 pub mod validator;
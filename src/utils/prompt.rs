@@ -1,6 +1,6 @@
 use crate::utils::types::Result;
 use colored::*;
-use std::io::{self, Write};
+use std::io::{self, BufRead, IsTerminal, Write};
 
 pub fn prompt_for_input(prompt: &str) -> Result<String> {
     print!("{prompt}: ");
@@ -25,6 +25,53 @@ pub fn prompt_for_missing_arg(arg_name: &str) -> Result<String> {
     prompt_for_input(&hint)
 }
 
+/// Asks a y/n question before changes are applied. `--yes` answers it up
+/// front; without a terminal on stdin nobody can answer, so that is an error
+/// rather than a read that may never return.
+pub fn confirm(question: &str, assume_yes: bool) -> Result<bool> {
+    if assume_yes {
+        return Ok(true);
+    }
+    if !io::stdin().is_terminal() {
+        return Err(format!(
+            "Cannot ask \"{question}\" because stdin is not a terminal (pass --yes to confirm)"
+        )
+        .into());
+    }
+
+    let answer = prompt_for_input(&format!("\n{} (y/n)", question.bold()))?;
+    Ok(matches!(answer.to_lowercase().as_str(), "y" | "yes"))
+}
+
+/// Asks a y/n question that has no safe default, such as one whose "no"
+/// throws work away: Enter or any other answer asks again. Callers make sure
+/// there is a terminal to answer on.
+pub fn ask_yes_no(question: &str) -> Result<bool> {
+    read_yes_no(question, &mut io::stdin().lock())
+}
+
+fn read_yes_no(question: &str, input: &mut impl BufRead) -> Result<bool> {
+    loop {
+        print!("\n{} (y/n): ", question.bold());
+        io::stdout()
+            .flush()
+            .map_err(|e| format!("Failed to flush stdout: {e}"))?;
+
+        let mut answer = String::new();
+        let read = input
+            .read_line(&mut answer)
+            .map_err(|e| format!("Failed to read line: {e}"))?;
+        if read == 0 {
+            return Err(format!("No answer to \"{question}\"").into());
+        }
+        match answer.trim().to_lowercase().as_str() {
+            "y" | "yes" => return Ok(true),
+            "n" | "no" => return Ok(false),
+            _ => println!("{}", "Please answer y or n.".yellow()),
+        }
+    }
+}
+
 // Prompts for input with a suggested default value shown in faded color. If the user presses Enter, the default is used. If they type something, that's used instead.
 pub fn prompt_with_default(prompt: &str, default_value: &str) -> Result<String> {
     print!(
@@ -74,4 +121,32 @@ mod tests {
         let _prompt_missing_fn: fn(&str) -> Result<String> = prompt_for_missing_arg;
         let _prompt_with_default_fn: fn(&str, &str) -> Result<String> = prompt_with_default;
     }
+
+    #[test]
+    fn test_confirm_assume_yes_skips_the_prompt() {
+        assert!(confirm("Apply these changes?", true).unwrap());
+    }
+
+    #[test]
+    fn test_confirm_without_terminal_is_an_error() {
+        // Test runs have no terminal on stdin, so this must not block
+        if io::stdin().is_terminal() {
+            return;
+        }
+        let err = confirm("Apply these changes?", false).unwrap_err();
+        assert!(err.to_string().contains("--yes"), "{err}");
+    }
+
+    #[test]
+    fn test_read_yes_no_needs_an_explicit_answer() {
+        let mut input = io::Cursor::new("\nmaybe\nN\n");
+        assert!(!read_yes_no("Discard it?", &mut input).unwrap());
+
+        let mut input = io::Cursor::new("\nyes\n");
+        assert!(read_yes_no("Discard it?", &mut input).unwrap());
+
+        // Running out of input is not an answer
+        let mut input = io::Cursor::new("\n");
+        assert!(read_yes_no("Discard it?", &mut input).is_err());
+    }
 }
@@ -0,0 +1,186 @@
+use crate::args::Args;
+use crate::utils::types::{EditOptions, Result};
+use git2::{Oid, Repository};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+/// The file, inside the git directory, that records a rewrite in progress.
+pub const STATE_FILE: &str = "git-editor-state.json";
+
+// How many newly written commits go by between saves of the progress. The
+// commits a lost save leaves out are simply written again (to the same oids)
+// on resume, so saving after every commit is not worth the I/O.
+pub const SAVE_INTERVAL: usize = 100;
+
+/// What a rewrite driven by per-commit edits (pick, `--apply-plan`) needs to
+/// carry on after an interruption: the edits, the ref and tip it started
+/// from, and the commits written so far. It is saved before the first commit
+/// is written and removed once the ref has moved, so a leftover file means
+/// the rewrite never finished.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RewriteState {
+    pub operation: String,
+    // --ref of the interrupted run; unset for the checked-out branch
+    pub rewrite_ref: Option<String>,
+    pub preserve_committer_identity: bool,
+    pub original_tip: String,
+    // Commits in the walk, for reporting how far the rewrite got
+    pub total_commits: usize,
+    pub edits: Vec<(String, EditOptions)>,
+    // Original and rewritten oid of every commit written so far, oldest first
+    pub written: Vec<(String, String)>,
+}
+
+impl RewriteState {
+    pub fn new(
+        operation: &str,
+        args: &Args,
+        original_tip: Oid,
+        total_commits: usize,
+        edits: &HashMap<Oid, &EditOptions>,
+    ) -> Self {
+        let mut edits: Vec<(String, EditOptions)> = edits
+            .iter()
+            .map(|(oid, options)| (oid.to_string(), (*options).clone()))
+            .collect();
+        edits.sort_by(|a, b| a.0.cmp(&b.0));
+
+        Self {
+            operation: operation.to_string(),
+            rewrite_ref: args.rewrite_ref.clone(),
+            preserve_committer_identity: args.preserve_committer_identity,
+            original_tip: original_tip.to_string(),
+            total_commits,
+            edits,
+            written: Vec::new(),
+        }
+    }
+
+    pub fn path(repo: &Repository) -> PathBuf {
+        repo.path().join(STATE_FILE)
+    }
+
+    /// The state left by an interrupted rewrite, if there is one.
+    pub fn load(repo: &Repository) -> Result<Option<Self>> {
+        let path = Self::path(repo);
+        let content = match std::fs::read_to_string(&path) {
+            Ok(content) => content,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(format!("Failed to read {}: {e}", path.display()).into()),
+        };
+        let state = serde_json::from_str(&content).map_err(|e| {
+            format!(
+                "Invalid rewrite state in {} ({e}); delete it to start over",
+                path.display()
+            )
+        })?;
+        Ok(Some(state))
+    }
+
+    /// Writes the state next to its final path and renames it into place, so
+    /// an interruption while saving leaves the previous save intact.
+    pub fn save(&self, repo: &Repository) -> Result<()> {
+        let path = Self::path(repo);
+        let partial = path.with_extension("json.partial");
+        std::fs::write(&partial, serde_json::to_string_pretty(self)?)?;
+        std::fs::rename(&partial, &path)?;
+        Ok(())
+    }
+
+    /// Forgets the interrupted rewrite; nothing to do when there is none.
+    pub fn discard(repo: &Repository) -> Result<()> {
+        match std::fs::remove_file(Self::path(repo)) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e.into()),
+            _ => Ok(()),
+        }
+    }
+
+    pub fn record(&mut self, orig: Oid, new: Oid) {
+        self.written.push((orig.to_string(), new.to_string()));
+    }
+
+    pub fn original_tip(&self) -> Result<Oid> {
+        Ok(Oid::from_str(&self.original_tip)?)
+    }
+
+    pub fn edits(&self) -> Result<Vec<(Oid, EditOptions)>> {
+        self.edits
+            .iter()
+            .map(|(oid, options)| Ok((Oid::from_str(oid)?, options.clone())))
+            .collect()
+    }
+
+    pub fn written(&self) -> Result<HashMap<Oid, Oid>> {
+        self.written
+            .iter()
+            .map(|(orig, new)| Ok((Oid::from_str(orig)?, Oid::from_str(new)?)))
+            .collect()
+    }
+
+    /// One line for the user: the operation, and how many commits it wrote.
+    pub fn describe(&self) -> String {
+        format!(
+            "{} rewrite ({} of {} commit(s) written)",
+            self.operation,
+            self.written.len(),
+            self.total_commits
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_rewrite_state_round_trip() {
+        let temp_dir = TempDir::new().unwrap();
+        let repo = Repository::init(temp_dir.path()).unwrap();
+        assert_eq!(RewriteState::load(&repo).unwrap(), None);
+
+        let (a, b) = (Oid::from_str("aa").unwrap(), Oid::from_str("bb").unwrap());
+        let options = EditOptions {
+            author_name: Some("Jane".to_string()),
+            timestamp: Some(
+                chrono::DateTime::from_timestamp(1_700_000_000, 0)
+                    .unwrap()
+                    .naive_utc(),
+            ),
+            ..Default::default()
+        };
+        let args = Args {
+            rewrite_ref: Some("main".to_string()),
+            ..Default::default()
+        };
+        let mut state =
+            RewriteState::new("pick edit", &args, a, 4, &HashMap::from([(b, &options)]));
+        state.record(a, b);
+        state.save(&repo).unwrap();
+
+        let loaded = RewriteState::load(&repo).unwrap().unwrap();
+        assert_eq!(loaded, state);
+        assert_eq!(loaded.original_tip().unwrap(), a);
+        assert_eq!(loaded.written().unwrap(), HashMap::from([(a, b)]));
+        assert_eq!(
+            loaded.edits().unwrap()[0].1.author_name.as_deref(),
+            Some("Jane")
+        );
+        assert_eq!(
+            loaded.describe(),
+            "pick edit rewrite (1 of 4 commit(s) written)"
+        );
+
+        RewriteState::discard(&repo).unwrap();
+        assert_eq!(RewriteState::load(&repo).unwrap(), None);
+        // Discarding twice is fine
+        RewriteState::discard(&repo).unwrap();
+
+        std::fs::write(RewriteState::path(&repo), "{").unwrap();
+        assert!(RewriteState::load(&repo)
+            .unwrap_err()
+            .to_string()
+            .contains("delete it to start over"));
+    }
+}
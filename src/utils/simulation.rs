@@ -1,7 +1,12 @@
 use crate::args::Args;
-use crate::utils::types::{CommitInfo, Result};
+use crate::utils::anonymize::AnonymousIdentities;
+use crate::utils::mailmap::Mailmap;
+use crate::utils::message_transform::{add_signoff, same_message};
+use crate::utils::types::{display_email, CommitInfo, EditOptions, Result};
+use crate::utils::validator::normalize_email;
 use chrono::NaiveDateTime;
 use colored::Colorize;
+use std::collections::HashMap;
 
 #[derive(Debug, Clone)]
 pub struct SimulationChange {
@@ -61,8 +66,8 @@ impl SimulationChange {
             if new_email != &self.original_email {
                 changes.push(format!(
                     "Email: {} → {}",
-                    self.original_email.red(),
-                    new_email.green()
+                    display_email(&self.original_email).red(),
+                    display_email(new_email).green()
                 ));
             }
         }
@@ -92,6 +97,16 @@ impl SimulationChange {
                     original_first_line.red(),
                     new_first_line.green()
                 ));
+            } else {
+                // Same subject: show the lines the body gains, such as a --signoff trailer
+                let added: Vec<&str> = new_message
+                    .lines()
+                    .filter(|line| !line.trim().is_empty())
+                    .filter(|line| !self.original_message.lines().any(|old| old == *line))
+                    .collect();
+                if !added.is_empty() {
+                    changes.push(format!("Message: + {}", added.join(" / ").green()));
+                }
             }
         }
 
@@ -226,11 +241,59 @@ pub fn create_full_rewrite_simulation(
     args: &Args,
 ) -> Result<SimulationResult> {
     let mut changes = Vec::new();
-    let new_author = args.name.as_ref().unwrap();
-    let new_email = args.email.as_ref().unwrap();
+    let mailmap = args
+        .mailmap
+        .as_deref()
+        .map(Mailmap::from_file)
+        .transpose()?;
+    // A path, or a committer email to normalize, is only known from the commit itself
+    let repo = if args.path_filter.is_some() || args.normalize_emails || args.anonymize {
+        Some(git2::Repository::open(args.repo_path.as_ref().unwrap())?)
+    } else {
+        None
+    };
+    // Placeholders are numbered oldest commit first, as in the rewrite
+    let anonymous = match &repo {
+        Some(repo) if args.anonymize => {
+            let oldest_first: Vec<_> = commits.iter().rev().map(|c| c.oid).collect();
+            Some(AnonymousIdentities::from_commits(repo, &oldest_first)?)
+        }
+        _ => None,
+    };
 
     for (i, commit) in commits.iter().enumerate() {
-        let new_timestamp = timestamps.get(i).copied();
+        // Commits are listed newest first while timestamps are generated oldest first
+        let new_timestamp = commits
+            .len()
+            .checked_sub(i + 1)
+            .and_then(|ts_idx| timestamps.get(ts_idx).copied())
+            .filter(|ts| *ts != commit.timestamp);
+
+        // Commits filtered out by author or path are left untouched by the rewrite
+        let selected = match &repo {
+            Some(repo) => args.is_commit_selected(repo, &repo.find_commit(commit.oid)?)?,
+            None => {
+                args.is_author_selected(&commit.author_name, &commit.author_email)
+                    && args.is_date_selected(commit.author_timestamp)?
+            }
+        };
+        let (new_author, new_email) = match (&anonymous, &mailmap) {
+            (Some(anonymous), _) => anonymous.resolve(&commit.author_email),
+            (None, Some(mailmap)) => mailmap.resolve(&commit.author_name, &commit.author_email),
+            _ if args.find_author.is_some() => {
+                args.replace_found_author(&commit.author_name, &commit.author_email)
+            }
+            _ if args.normalize_emails => (
+                commit.author_name.clone(),
+                normalize_email(&commit.author_email, args.lowercase_local_part),
+            ),
+            _ if args.keep_author => (commit.author_name.clone(), commit.author_email.clone()),
+            _ => (args.name.clone().unwrap(), args.email.clone().unwrap()),
+        };
+        // Messages only change to carry --signoff
+        let new_message = (selected && args.signoff)
+            .then(|| add_signoff(&commit.message, &new_author, &new_email))
+            .filter(|message| *message != commit.message);
 
         let change = SimulationChange {
             commit_oid: commit.oid,
@@ -239,10 +302,10 @@ pub fn create_full_rewrite_simulation(
             original_email: commit.author_email.clone(),
             original_timestamp: commit.timestamp,
             original_message: commit.message.clone(),
-            new_author: Some(new_author.clone()),
-            new_email: Some(new_email.clone()),
-            new_timestamp,
-            new_message: None, // Full rewrite doesn't change messages
+            new_author: Some(new_author).filter(|n| selected && *n != commit.author_name),
+            new_email: Some(new_email).filter(|e| selected && *e != commit.author_email),
+            new_timestamp: new_timestamp.filter(|_| selected),
+            new_message,
         };
 
         changes.push(change);
@@ -258,20 +321,22 @@ pub fn create_full_rewrite_simulation(
     })
 }
 
+/// Simulation of a range edit. `edits` holds the per-commit changes made in
+/// the table editor; commits outside `selected_range` are never changed.
 pub fn create_range_simulation(
     commits: &[CommitInfo],
     selected_range: (usize, usize),
-    range_timestamps: &[NaiveDateTime],
-    args: &Args,
+    edits: &HashMap<git2::Oid, EditOptions>,
 ) -> Result<SimulationResult> {
     let mut changes = Vec::new();
     let (start_idx, end_idx) = selected_range;
 
     for (i, commit) in commits.iter().enumerate() {
-        let change = if i >= start_idx && i <= end_idx {
-            let timestamp_idx = i - start_idx;
-            let new_timestamp = range_timestamps.get(timestamp_idx).copied();
+        let edit = edits
+            .get(&commit.oid)
+            .filter(|_| i >= start_idx && i <= end_idx);
 
+        let change = if let Some(options) = edit {
             SimulationChange {
                 commit_oid: commit.oid,
                 short_hash: commit.short_hash.clone(),
@@ -279,13 +344,16 @@ pub fn create_range_simulation(
                 original_email: commit.author_email.clone(),
                 original_timestamp: commit.timestamp,
                 original_message: commit.message.clone(),
-                new_author: args.name.clone(),
-                new_email: args.email.clone(),
-                new_timestamp,
-                new_message: None,
+                new_author: options.author_name.clone(),
+                new_email: options.author_email.clone(),
+                new_timestamp: options.timestamp,
+                new_message: options
+                    .message
+                    .clone()
+                    .filter(|message| !same_message(message, &commit.message)),
             }
         } else {
-            // Commits outside range remain unchanged
+            // Unedited commits and commits outside the range remain unchanged
             SimulationChange {
                 commit_oid: commit.oid,
                 short_hash: commit.short_hash.clone(),
@@ -313,6 +381,17 @@ pub fn create_range_simulation(
     })
 }
 
+/// The simulation of the commits picked with `pick --interactive-pick`,
+/// wherever they are in the history.
+pub fn create_picked_commits_simulation(
+    commits: &[CommitInfo],
+    edits: &HashMap<git2::Oid, EditOptions>,
+) -> Result<SimulationResult> {
+    let mut result = create_range_simulation(commits, (0, commits.len().saturating_sub(1)), edits)?;
+    result.operation_mode = format!("Specific Commit Edit ({} commits)", edits.len());
+    Ok(result)
+}
+
 pub fn create_specific_commit_simulation(
     commits: &[CommitInfo],
     selected_commit_idx: usize,
@@ -335,7 +414,10 @@ pub fn create_specific_commit_simulation(
                 new_author: new_author.clone(),
                 new_email: new_email.clone(),
                 new_timestamp,
-                new_message: new_message.clone(),
+                // A message differing only in whitespace is not a change
+                new_message: new_message
+                    .clone()
+                    .filter(|message| !same_message(message, &commit.message)),
             }
         } else {
             // Other commits remain unchanged
@@ -366,11 +448,37 @@ pub fn create_specific_commit_simulation(
     })
 }
 
-pub fn print_detailed_diff(result: &SimulationResult) {
+/// Prints the summary of a simulation, followed by the per-commit diff when
+/// `--show-diff` was given. `preview_rows` caps how many changed commits the
+/// diff lists; the summary always covers all of them.
+pub fn print_simulation_result(
+    result: &SimulationResult,
+    show_diff: bool,
+    preview_rows: Option<usize>,
+) {
+    result.stats.print_summary(&result.operation_mode);
+    if show_diff {
+        print_detailed_diff(result, preview_rows);
+    }
+}
+
+/// The changed commits the detailed diff lists, at most `preview_rows` of
+/// them, and how many more changed commits were left out.
+pub fn preview_changes(
+    result: &SimulationResult,
+    preview_rows: Option<usize>,
+) -> (Vec<&SimulationChange>, usize) {
+    let mut changes: Vec<_> = result.changes.iter().filter(|c| c.has_changes()).collect();
+    let hidden = preview_rows.map_or(0, |rows| changes.len().saturating_sub(rows));
+    changes.truncate(changes.len() - hidden);
+    (changes, hidden)
+}
+
+pub fn print_detailed_diff(result: &SimulationResult, preview_rows: Option<usize>) {
     println!("\n{}", "📋 DETAILED CHANGE PREVIEW".bold().cyan());
     println!("{}", "=".repeat(70).cyan());
 
-    let changes_to_show: Vec<_> = result.changes.iter().filter(|c| c.has_changes()).collect();
+    let (changes_to_show, hidden) = preview_changes(result, preview_rows);
 
     if changes_to_show.is_empty() {
         println!("{}", "No changes to display.".green());
@@ -397,6 +505,9 @@ pub fn print_detailed_diff(result: &SimulationResult) {
             println!("{}", "─".repeat(50).bright_black());
         }
     }
+    if hidden > 0 {
+        println!("\n   {}", format!("… and {hidden} more").bright_black());
+    }
 
     println!(
         "\n{}",
@@ -421,10 +532,12 @@ mod tests {
         timestamp_str: &str,
         message: &str,
     ) -> CommitInfo {
+        let timestamp = NaiveDateTime::parse_from_str(timestamp_str, "%Y-%m-%d %H:%M:%S").unwrap();
         CommitInfo {
             oid: git2::Oid::from_str(oid_str).unwrap(),
             short_hash: oid_str[..8].to_string(),
-            timestamp: NaiveDateTime::parse_from_str(timestamp_str, "%Y-%m-%d %H:%M:%S").unwrap(),
+            timestamp,
+            author_timestamp: timestamp,
             author_name: author.to_string(),
             author_email: email.to_string(),
             message: message.to_string(),
@@ -541,7 +654,7 @@ mod tests {
             edit_author: false,
             edit_time: false,
             docs: false,
-            _temp_dir: None,
+            ..Default::default()
         };
 
         let result = create_full_rewrite_simulation(&commits, &timestamps, &args).unwrap();
@@ -596,4 +709,164 @@ mod tests {
         // Second commit should not have changes
         assert!(!result.changes[1].has_changes());
     }
+
+    #[test]
+    fn test_whitespace_only_message_edit_is_not_a_change() {
+        let commits = vec![create_test_commit(
+            "1234567890abcdef1234567890abcdef12345678",
+            "User1",
+            "user1@example.com",
+            "2023-01-01 10:00:00",
+            "msg\n",
+        )];
+
+        let result = create_specific_commit_simulation(
+            &commits,
+            0,
+            None,
+            None,
+            None,
+            Some("msg\r\n".to_string()),
+        )
+        .unwrap();
+        assert!(!result.changes[0].has_changes());
+        assert_eq!(result.stats.commits_to_change, 0);
+
+        let edits = HashMap::from([(
+            commits[0].oid,
+            EditOptions {
+                message: Some("msg  \r\n".to_string()),
+                ..Default::default()
+            },
+        )]);
+        let result = create_range_simulation(&commits, (0, 0), &edits).unwrap();
+        assert!(!result.changes[0].has_changes());
+    }
+
+    #[test]
+    fn test_create_range_simulation() {
+        let commits = vec![
+            create_test_commit(
+                "1234567890abcdef1234567890abcdef12345678",
+                "User1",
+                "user1@example.com",
+                "2023-01-03 10:00:00",
+                "Third commit",
+            ),
+            create_test_commit(
+                "abcdef1234567890abcdef1234567890abcdef12",
+                "User2",
+                "user2@example.com",
+                "2023-01-02 15:30:00",
+                "Second commit",
+            ),
+            create_test_commit(
+                "567890abcdef1234567890abcdef1234567890ab",
+                "User3",
+                "user3@example.com",
+                "2023-01-01 09:00:00",
+                "First commit",
+            ),
+        ];
+        let new_time =
+            NaiveDateTime::parse_from_str("2023-01-02 12:00:00", "%Y-%m-%d %H:%M:%S").unwrap();
+        let edit = |timestamp| EditOptions {
+            timestamp: Some(timestamp),
+            message: Some("Reworded".to_string()),
+            ..Default::default()
+        };
+        // The edit of the first commit lies outside the range and is ignored
+        let edits = HashMap::from([
+            (commits[1].oid, edit(new_time)),
+            (commits[2].oid, edit(new_time)),
+        ]);
+
+        let result = create_range_simulation(&commits, (0, 1), &edits).unwrap();
+
+        assert_eq!(result.operation_mode, "Range Edit (commits 1-2)");
+        assert_eq!(result.stats.commits_to_change, 1);
+        assert!(!result.changes[0].has_changes());
+        assert_eq!(result.changes[1].new_timestamp, Some(new_time));
+        assert_eq!(result.changes[1].new_message.as_deref(), Some("Reworded"));
+        assert_eq!(result.changes[1].new_author, None);
+        assert!(!result.changes[2].has_changes());
+    }
+
+    #[test]
+    fn test_preview_changes_caps_rows_but_not_stats() {
+        let commits: Vec<_> = (1..=5)
+            .map(|i| {
+                create_test_commit(
+                    &format!("{i:040x}"),
+                    "Old User",
+                    "old@example.com",
+                    &format!("2023-01-0{i} 10:00:00"),
+                    "Commit",
+                )
+            })
+            .collect();
+        let timestamps = commits.iter().map(|c| c.timestamp).collect::<Vec<_>>();
+        let args = Args {
+            repo_path: Some("./test".to_string()),
+            email: Some("new@example.com".to_string()),
+            name: Some("New User".to_string()),
+            simulate: true,
+            show_diff: true,
+            ..Default::default()
+        };
+        let result = create_full_rewrite_simulation(&commits, &timestamps, &args).unwrap();
+        assert_eq!(result.stats.commits_to_change, 5);
+        assert_eq!(result.stats.authors_changed, 5);
+
+        let (shown, hidden) = preview_changes(&result, Some(2));
+        assert_eq!(shown.len(), 2);
+        assert_eq!(hidden, 3);
+        assert_eq!(shown[0].commit_oid, commits[0].oid);
+        assert_eq!(shown[1].commit_oid, commits[1].oid);
+
+        let (all, hidden) = preview_changes(&result, None);
+        assert_eq!((all.len(), hidden), (5, 0));
+        assert_eq!(preview_changes(&result, Some(5)).1, 0);
+        assert_eq!(preview_changes(&result, Some(10)).0.len(), 5);
+    }
+
+    #[test]
+    fn test_create_full_rewrite_simulation_maps_timestamps_oldest_first() {
+        let commits = vec![
+            create_test_commit(
+                "abcdef1234567890abcdef1234567890abcdef12",
+                "Old User",
+                "old@example.com",
+                "2023-01-02 10:00:00",
+                "Second commit",
+            ),
+            create_test_commit(
+                "1234567890abcdef1234567890abcdef12345678",
+                "Old User",
+                "old@example.com",
+                "2023-01-01 10:00:00",
+                "First commit",
+            ),
+        ];
+        let early =
+            NaiveDateTime::parse_from_str("2023-06-01 09:00:00", "%Y-%m-%d %H:%M:%S").unwrap();
+        let late =
+            NaiveDateTime::parse_from_str("2023-06-02 09:00:00", "%Y-%m-%d %H:%M:%S").unwrap();
+
+        let args = Args {
+            email: Some("new@example.com".to_string()),
+            name: Some("New User".to_string()),
+            ..Default::default()
+        };
+
+        let result = create_full_rewrite_simulation(&commits, &[early, late], &args).unwrap();
+        assert_eq!(result.changes[0].new_timestamp, Some(late));
+        assert_eq!(result.changes[1].new_timestamp, Some(early));
+
+        // Timestamps identical to the originals (KEEP_ORIGINAL) are not reported as changes
+        let originals = [commits[1].timestamp, commits[0].timestamp];
+        let result = create_full_rewrite_simulation(&commits, &originals, &args).unwrap();
+        assert!(result.changes.iter().all(|c| c.new_timestamp.is_none()));
+        assert_eq!(result.stats.timestamps_changed, 0);
+    }
 }
@@ -0,0 +1,133 @@
+use crate::utils::types::Result;
+use colored::{Color, ColoredString, Colorize};
+
+/// Colors of the commit fields in the table editor and the range listings.
+/// The defaults are the table's original palette; the config file can change
+/// any of them with `color.<element> = "<color>"`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Theme {
+    pub author: Color,
+    pub email: Color,
+    pub timestamp: Color,
+    pub message: Color,
+    // The `*` in front of an edited cell; unset, it keeps its column's color
+    pub modified: Option<Color>,
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self {
+            author: Color::Cyan,
+            email: Color::Blue,
+            timestamp: Color::Magenta,
+            message: Color::Green,
+            modified: None,
+        }
+    }
+}
+
+impl Theme {
+    /// Sets one element (author, email, timestamp, message or modified) to a
+    /// color name such as `yellow` or `bright blue`.
+    pub fn set(&mut self, element: &str, color: &str) -> Result<()> {
+        let color = parse_color(color)?;
+        match element {
+            "author" => self.author = color,
+            "email" => self.email = color,
+            "timestamp" => self.timestamp = color,
+            "message" => self.message = color,
+            "modified" => self.modified = Some(color),
+            _ => {
+                return Err(format!(
+                    "unknown color element '{element}' (expected author, email, timestamp, message or modified)"
+                )
+                .into())
+            }
+        }
+        Ok(())
+    }
+
+    pub fn author(&self, text: &str) -> ColoredString {
+        text.color(self.author)
+    }
+
+    pub fn email(&self, text: &str) -> ColoredString {
+        text.color(self.email)
+    }
+
+    pub fn timestamp(&self, text: &str) -> ColoredString {
+        text.color(self.timestamp)
+    }
+
+    pub fn message(&self, text: &str) -> ColoredString {
+        text.color(self.message)
+    }
+
+    /// Colors a table cell in `color`, giving the leading `*` of an edited
+    /// cell the `modified` color when one is set.
+    pub fn cell(&self, text: &str, color: Color, modified: bool) -> String {
+        match (self.modified, text.strip_prefix('*')) {
+            (Some(marker), Some(rest)) if modified => {
+                format!("{}{}", "*".color(marker), rest.color(color))
+            }
+            _ => text.color(color).to_string(),
+        }
+    }
+}
+
+// Accepts the names `colored` knows, with `_` or `-` allowed in place of the
+// space in `bright red` and friends
+fn parse_color(name: &str) -> Result<Color> {
+    name.replace(['_', '-'], " ")
+        .parse()
+        .map_err(|_| format!("unknown color '{name}'").into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default_theme_is_the_original_palette() {
+        let theme = Theme::default();
+        assert_eq!(theme.author("Jane").fgcolor, Some(Color::Cyan));
+        assert_eq!(theme.email("jane@example.com").fgcolor, Some(Color::Blue));
+        assert_eq!(
+            theme.timestamp("2023-01-01 00:00:00").fgcolor,
+            Some(Color::Magenta)
+        );
+        assert_eq!(theme.message("Fix bug").fgcolor, Some(Color::Green));
+    }
+
+    #[test]
+    fn test_swapped_theme_colors_are_applied() {
+        let mut theme = Theme::default();
+        theme.set("author", "yellow").unwrap();
+        theme.set("email", "bright_white").unwrap();
+        theme.set("timestamp", "bright-red").unwrap();
+        theme.set("message", "White").unwrap();
+        theme.set("modified", "red").unwrap();
+
+        assert_eq!(theme.author("Jane").fgcolor, Some(Color::Yellow));
+        assert_eq!(theme.email("j@x").fgcolor, Some(Color::BrightWhite));
+        assert_eq!(theme.timestamp("t").fgcolor, Some(Color::BrightRed));
+        assert_eq!(theme.message("m").fgcolor, Some(Color::White));
+
+        // The edit marker gets its own color, the rest of the cell its column's
+        assert_eq!(
+            theme.cell("*Jane", theme.author, true),
+            format!("{}{}", "*".red(), "Jane".yellow())
+        );
+        assert_eq!(
+            theme.cell("*Jane", theme.author, false),
+            "*Jane".yellow().to_string()
+        );
+        assert_eq!(
+            Theme::default().cell("*Jane", Color::Cyan, true),
+            "*Jane".cyan().to_string()
+        );
+
+        assert!(theme.set("author", "mauve").is_err());
+        assert!(theme.set("hash", "red").is_err());
+    }
+}
@@ -1,18 +1,44 @@
 use chrono::NaiveDateTime;
+use serde::{Deserialize, Serialize, Serializer};
 
 pub type Result<T> = std::result::Result<T, Box<dyn std::error::Error>>;
 
-#[derive(Debug, Clone)]
+// Timestamps serialize as ISO 8601 and the oid as its full hex string
+#[derive(Debug, Clone, Serialize)]
 pub struct CommitInfo {
+    #[serde(serialize_with = "serialize_oid")]
     pub oid: git2::Oid,
     pub short_hash: String,
+    // The committer date, which orders the history
     pub timestamp: NaiveDateTime,
+    // Only the range table's committer column tells the two dates apart
+    #[serde(skip)]
+    pub author_timestamp: NaiveDateTime,
     pub author_name: String,
+    // Empty for a commit recorded without an author email (`Name <>`)
     pub author_email: String,
     pub message: String,
     pub parent_count: usize,
 }
-#[derive(Default)]
+
+/// How an author email is shown to the user: `(none)` when it is empty.
+pub fn display_email(email: &str) -> &str {
+    if email.is_empty() {
+        "(none)"
+    } else {
+        email
+    }
+}
+
+fn serialize_oid<S: Serializer>(
+    oid: &git2::Oid,
+    serializer: S,
+) -> std::result::Result<S::Ok, S::Error> {
+    serializer.collect_str(oid)
+}
+
+// Also recorded in the state file of an interrupted rewrite
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
 pub struct EditOptions {
     pub author_name: Option<String>,
     pub author_email: Option<String>,
@@ -20,10 +46,37 @@ pub struct EditOptions {
     pub message: Option<String>,
 }
 
+/// How a run ended when nothing went wrong, so scripts can tell a finished
+/// rewrite from one the user declined or one with nothing to change.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Outcome {
+    Done,
+    Cancelled,
+    NothingToDo,
+}
+
+impl Outcome {
+    /// Errors exit with 1, which leaves 2 and 3 for the quiet endings.
+    pub fn exit_code(self) -> i32 {
+        match self {
+            Outcome::Done => 0,
+            Outcome::Cancelled => 2,
+            Outcome::NothingToDo => 3,
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_outcome_exit_codes() {
+        assert_eq!(Outcome::Done.exit_code(), 0);
+        assert_eq!(Outcome::Cancelled.exit_code(), 2);
+        assert_eq!(Outcome::NothingToDo.exit_code(), 3);
+    }
+
     #[test]
     fn test_commit_info_creation() {
         let oid = git2::Oid::from_str("1234567890abcdef1234567890abcdef12345678").unwrap();
@@ -35,6 +88,7 @@ mod tests {
             oid,
             short_hash: "12345678".to_string(),
             timestamp,
+            author_timestamp: timestamp,
             author_name: "Test User".to_string(),
             author_email: "test@example.com".to_string(),
             message: "Test commit message".to_string(),
@@ -50,6 +104,38 @@ mod tests {
         assert_eq!(commit_info.parent_count, 1);
     }
 
+    #[test]
+    fn test_commit_info_serializes_to_json() {
+        let commit_info = CommitInfo {
+            oid: git2::Oid::from_str("1234567890abcdef1234567890abcdef12345678").unwrap(),
+            short_hash: "12345678".to_string(),
+            timestamp: chrono::DateTime::from_timestamp(1234567890, 0)
+                .unwrap()
+                .naive_utc(),
+            author_timestamp: chrono::DateTime::from_timestamp(1234567890, 0)
+                .unwrap()
+                .naive_utc(),
+            author_name: "Test User".to_string(),
+            author_email: "test@example.com".to_string(),
+            message: "Test commit message".to_string(),
+            parent_count: 1,
+        };
+
+        let json = serde_json::to_value(&commit_info).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!({
+                "oid": "1234567890abcdef1234567890abcdef12345678",
+                "short_hash": "12345678",
+                "timestamp": "2009-02-13T23:31:30",
+                "author_name": "Test User",
+                "author_email": "test@example.com",
+                "message": "Test commit message",
+                "parent_count": 1
+            })
+        );
+    }
+
     #[test]
     fn test_edit_options_default() {
         let options = EditOptions::default();
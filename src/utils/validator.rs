@@ -1,7 +1,14 @@
 use crate::args::Args;
+use crate::rewrite::rewrite_content::parse_replacement;
+use crate::rewrite::rewrite_range::parse_timestamp_input;
+use crate::utils::datetime::{check_not_future, format_hint, parse_datetime};
+use crate::utils::git_clone::is_git_url;
+use crate::utils::mailmap::Mailmap;
+use crate::utils::message_transform::MessageTransforms;
 use crate::utils::types::Result;
+use chrono::NaiveDateTime;
+use colored::Colorize;
 use regex::Regex;
-use url::Url;
 
 pub fn validate_inputs(args: &Args) -> Result<()> {
     // Skip all validation for docs mode
@@ -15,14 +22,17 @@ pub fn validate_inputs(args: &Args) -> Result<()> {
     if repo_path.is_empty() {
         return Err("Repository path cannot be empty".into());
     }
-    if Url::parse(repo_path).is_err() && !std::path::Path::new(repo_path).exists() {
+    if !is_git_url(repo_path) && !std::path::Path::new(repo_path).exists() {
         return Err(format!("Invalid repository path or URL: {repo_path}").into());
     }
     if std::path::Path::new(repo_path).exists() {
         if !std::path::Path::new(repo_path).is_dir() {
             return Err(format!("Repository path is not a directory: {repo_path}").into());
         }
-        if !std::path::Path::new(repo_path).join(".git").exists() {
+        // Discovering the repository the way git does, rather than looking for
+        // a .git directory, also accepts subdirectories, linked worktrees
+        // (where .git is a file) and bare repositories
+        if git2::Repository::discover(repo_path).is_err() {
             return Err(format!(
                 "Repository path does not contain a valid Git repository: {repo_path}"
             )
@@ -30,57 +40,269 @@ pub fn validate_inputs(args: &Args) -> Result<()> {
         }
     }
 
-    // Skip validation for email, name, start, end if using show_history, pick_specific_commits, range, simulate, or docs
-    if args.show_history || args.pick_specific_commits || args.range || args.simulate || args.docs {
+    // Which options go with which mode is up to clap: each subcommand only
+    // takes its own, and the modes without one declare their conflicts. What
+    // is left here are the values themselves.
+    if args.limit == Some(0) {
+        return Err("--limit must be greater than 0".into());
+    }
+
+    if let Some(email) = args.email.as_ref().or(args.replace_email.as_ref()) {
+        check_email_domain(email, &args.allowed_email_domains)?;
+    }
+
+    if args.preview_rows == Some(0) {
+        return Err("--preview-rows must be greater than 0".into());
+    }
+
+    if args.min_gap_minutes == Some(0) {
+        return Err("--min-gap-minutes must be greater than 0".into());
+    }
+
+    // range --batch reads its edits from stdin and draws nothing
+    if args.quiet && (args.pick_specific_commits || (args.range && !args.batch) || args.interactive)
+    {
+        return Err(
+            "--quiet cannot be used with pick, range (without --batch) or rewrite --interactive, which need to draw their editor"
+                .into(),
+        );
+    }
+
+    if args.graph && args.path_filter.is_some() {
+        return Err("--graph cannot be combined with --path".into());
+    }
+
+    // clap drops a `requires` whose target conflicts with another argument,
+    // so this one is checked here
+    if args.keep_backups.is_some() && !args.prune_backups {
+        return Err("--keep is only supported with --prune-backups".into());
+    }
+
+    if args.print_head && args.author_date_order && !args.fix {
+        return Err("--print-head is only supported with operations that rewrite history".into());
+    }
+
+    if args.continue_rewrite {
         return Ok(());
     }
 
-    // Validate email, name, start, end only for full rewrite operations
-    let email = args.email.as_ref().unwrap();
-    let name = args.name.as_ref().unwrap();
-    let start = args.start.as_ref().unwrap();
-    let end = args.end.as_ref().unwrap();
+    if args.count
+        && args.range
+        && args.range_from.is_none()
+        && args.range_to.is_none()
+        && args.range_tail.is_none()
+        && args.range_head.is_none()
+    {
+        return Err("--count with range needs --from/--to or --tail/--head".into());
+    }
 
-    let email_re = Regex::new(r"(?i)^[A-Z0-9._%+-]+@[A-Z0-9.-]+\.[A-Z]{2,}$")?;
-    if !email_re.is_match(email) {
-        return Err(format!("Invalid email format: {email}").into());
+    if args.committer_column && !args.get_editable_fields().2 {
+        return Err("--committer-column cannot be used when timestamps are not editable".into());
     }
 
-    if name.trim().is_empty() {
-        return Err("Name cannot be empty".into());
+    let transforms = MessageTransforms::from_args(args)?;
+    if !transforms.is_empty() && args.range && !args.get_editable_fields().3 {
+        return Err("Message transforms cannot be used when messages are not editable".into());
     }
 
-    // Allow special "KEEP_ORIGINAL" value to skip timestamp validation
-    if start != "KEEP_ORIGINAL" {
-        let start_re = Regex::new(r"^\d{4}-\d{2}-\d{2} \d{2}:\d{2}:\d{2}$")?;
-        if !start_re.is_match(start) {
-            return Err(format!(
-                "Invalid start date format (expected YYYY-MM-DD HH:MM:SS): {start}"
-            )
-            .into());
+    if let Some(anchor) = &args.shift_anchor {
+        if !args.get_editable_fields().2 {
+            return Err("--shift-anchor cannot be used when timestamps are not editable".into());
+        }
+        parse_timestamp_input(anchor, args.date_format())
+            .map_err(|e| format!("Invalid --shift-anchor '{anchor}': {e}"))?;
+    }
+
+    if args.modified_after.is_some() || args.modified_before.is_some() {
+        let parse = |flag: &str, date: &Option<String>| -> Result<Option<NaiveDateTime>> {
+            date.as_deref()
+                .map(|date| {
+                    parse_datetime(date, args.date_format())
+                        .map_err(|e| format!("{flag}: {e}").into())
+                })
+                .transpose()
+        };
+        if let (Some(after), Some(before)) = (
+            parse("--modified-after", &args.modified_after)?,
+            parse("--modified-before", &args.modified_before)?,
+        ) {
+            if after >= before {
+                return Err("--modified-after must be before --modified-before".into());
+            }
+        }
+    }
+
+    if let (Some(name), Some(email)) = (&args.replace_name, &args.replace_email) {
+        if name.trim().is_empty() {
+            return Err("--replace-name cannot be empty".into());
+        }
+        validate_email(email)?;
+    }
+
+    if !args.replace_in_files.is_empty() {
+        for spec in &args.replace_in_files {
+            parse_replacement(spec)?;
+        }
+        return Ok(());
+    }
+
+    if args.is_backup_mode() || args.author_date_order || args.round.is_some() {
+        return Ok(());
+    }
+
+    if let Some(plan_path) = &args.apply_plan {
+        if !std::path::Path::new(plan_path).is_file() {
+            return Err(format!("Plan file not found: {plan_path}").into());
         }
+        return Ok(());
     }
 
-    if end != "KEEP_ORIGINAL" {
-        let end_re = Regex::new(r"^\d{4}-\d{2}-\d{2} \d{2}:\d{2}:\d{2}$")?;
-        if !end_re.is_match(end) {
+    // Skip validation for email, name, start, end if using show_history, pick_specific_commits, range, interactive, simulate, docs, or only counting
+    if args.is_history_mode()
+        || args.pick_specific_commits
+        || args.range
+        || args.interactive
+        || args.simulate
+        || args.docs
+        || args.count
+    {
+        return Ok(());
+    }
+
+    // Validate email, name, start, end only for full rewrite operations
+    let start = args.start.as_ref().unwrap();
+    let end = args.end.as_ref().unwrap();
+
+    if let Some(mailmap) = &args.mailmap {
+        Mailmap::from_file(mailmap)?;
+    } else if args.keep_author {
+        if args.should_keep_original_timestamps() {
             return Err(
-                format!("Invalid end date format (expected YYYY-MM-DD HH:MM:SS): {end}").into(),
+                "--keep-author with the original timestamps leaves nothing to rewrite".into(),
             );
         }
+    } else if args.find_author.is_none() && !args.normalize_emails && !args.anonymize {
+        let email = args.email.as_ref().unwrap();
+        let name = args.name.as_ref().unwrap();
+
+        if let Some(warning) = check_identity_order(name, email, args.strict)? {
+            eprintln!("{} {}", "⚠️  Warning:".yellow().bold(), warning.yellow());
+        }
+        validate_email(email)?;
+
+        if name.trim().is_empty() {
+            return Err("Name cannot be empty".into());
+        }
     }
 
+    // Allow special "KEEP_ORIGINAL" value to skip timestamp validation
+    let format = args.date_format();
+    let parse = |value: &str, which: &str| -> Result<Option<NaiveDateTime>> {
+        if value == "KEEP_ORIGINAL" {
+            return Ok(None);
+        }
+        NaiveDateTime::parse_from_str(value, format)
+            .map(Some)
+            .map_err(|_| {
+                format!(
+                    "Invalid {which} date format (expected {}): {value}",
+                    format_hint(format)
+                )
+                .into()
+            })
+    };
+    let start_dt = parse(start, "start")?;
+    let end_dt = parse(end, "end")?;
+
     // Skip date comparison if using KEEP_ORIGINAL
-    if start != "KEEP_ORIGINAL" && end != "KEEP_ORIGINAL" && start >= end {
-        return Err("Start date must be before end date".into());
+    if let (Some(start_dt), Some(end_dt)) = (start_dt, end_dt) {
+        if start_dt >= end_dt {
+            return Err("Start date must be before end date".into());
+        }
+    }
+    for (what, date) in [("--begin", start_dt), ("--end", end_dt)] {
+        if let Some(date) = date {
+            check_not_future(args, what, date)?;
+        }
+    }
+
+    Ok(())
+}
+
+// Catches --name and --email passed the wrong way round before the whole
+// history is rewritten with them. A name that merely looks like an email next
+// to a valid email is only suspicious, so it is a warning unless --strict.
+fn check_identity_order(name: &str, email: &str, strict: bool) -> Result<Option<String>> {
+    if validate_email(name).is_err() {
+        return Ok(None);
+    }
+    if validate_email(email).is_err() {
+        return Err(format!(
+            "--name and --email look swapped: the name is '{name}' and the email is '{email}'"
+        )
+        .into());
+    }
+
+    let warning = format!("--name '{name}' looks like an email address");
+    if strict {
+        return Err(format!("{warning} (rejected by --strict)").into());
+    }
+    Ok(Some(warning))
+}
+
+pub fn validate_email(email: &str) -> Result<()> {
+    let email_re = Regex::new(r"(?i)^[A-Z0-9._%+-]+@[A-Z0-9.-]+\.[A-Z]{2,}$")?;
+    if !email_re.is_match(email) {
+        return Err(format!("Invalid email format: {email}").into());
+    }
+    Ok(())
+}
+
+/// With `--allowed-email-domain` set, the part after the `@` must be one of
+/// the listed domains. Domains compare case-insensitively and a leading `@`
+/// in the list is ignored.
+pub fn check_email_domain(email: &str, allowed: &[String]) -> Result<()> {
+    if allowed.is_empty() {
+        return Ok(());
     }
 
+    let domain = email.rsplit_once('@').map_or("", |(_, domain)| domain);
+    let is_allowed = allowed
+        .iter()
+        .any(|entry| entry.trim_start_matches('@').eq_ignore_ascii_case(domain));
+    if !is_allowed {
+        return Err(format!(
+            "Email domain '{domain}' of {email} is not allowed (allowed: {})",
+            allowed.join(", ")
+        )
+        .into());
+    }
     Ok(())
 }
 
+/// `--normalize-emails`: trims surrounding whitespace and lowercases the
+/// domain, and with `lowercase_local` the part before the `@` as well.
+pub fn normalize_email(email: &str, lowercase_local: bool) -> String {
+    let email = email.trim();
+    let (local, domain) = match email.rsplit_once('@') {
+        Some((local, domain)) => (local, Some(domain)),
+        None => (email, None),
+    };
+    let local = if lowercase_local {
+        local.to_lowercase()
+    } else {
+        local.to_string()
+    };
+    match domain {
+        Some(domain) => format!("{local}@{}", domain.to_lowercase()),
+        None => local,
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::args::Granularity;
     use std::fs;
     use tempfile::TempDir;
 
@@ -133,7 +355,7 @@ mod tests {
             edit_author: false,
             edit_time: false,
             docs: false,
-            _temp_dir: None,
+            ..Default::default()
         };
 
         let result = validate_inputs(&args);
@@ -158,7 +380,7 @@ mod tests {
             edit_author: false,
             edit_time: false,
             docs: false,
-            _temp_dir: None,
+            ..Default::default()
         };
 
         let result = validate_inputs(&args);
@@ -183,7 +405,7 @@ mod tests {
             edit_author: false,
             edit_time: false,
             docs: false,
-            _temp_dir: None,
+            ..Default::default()
         };
 
         let result = validate_inputs(&args);
@@ -208,7 +430,7 @@ mod tests {
             edit_author: false,
             edit_time: false,
             docs: false,
-            _temp_dir: None,
+            ..Default::default()
         };
 
         // This test would normally call process::exit, so we can't test it directly
@@ -236,7 +458,7 @@ mod tests {
             edit_author: false,
             edit_time: false,
             docs: false,
-            _temp_dir: None,
+            ..Default::default()
         };
 
         let start_re = Regex::new(r"^\d{4}-\d{2}-\d{2} \d{2}:\d{2}:\d{2}$").unwrap();
@@ -261,7 +483,7 @@ mod tests {
             edit_author: false,
             edit_time: false,
             docs: false,
-            _temp_dir: None,
+            ..Default::default()
         };
 
         // This would normally call process::exit, so we test the path validation logic
@@ -372,7 +594,7 @@ mod tests {
             edit_author: false,
             edit_time: false,
             docs: false,
-            _temp_dir: None,
+            ..Default::default()
         };
 
         let result = validate_inputs(&args);
@@ -397,7 +619,7 @@ mod tests {
             edit_author: false,
             edit_time: false,
             docs: true,
-            _temp_dir: None,
+            ..Default::default()
         };
 
         let result = validate_inputs(&args);
@@ -422,7 +644,7 @@ mod tests {
             edit_author: false,
             edit_time: false,
             docs: true,
-            _temp_dir: None,
+            ..Default::default()
         };
 
         let result = validate_inputs(&args);
@@ -447,10 +669,650 @@ mod tests {
             edit_author: false,
             edit_time: false,
             docs: true, // Docs mode should skip all validation
-            _temp_dir: None,
+            ..Default::default()
         };
 
         let result = validate_inputs(&args);
         assert!(result.is_ok());
     }
+
+    #[test]
+    fn test_validate_inputs_limit() {
+        let (_temp_dir, repo_path) = create_test_repo();
+        let args = Args {
+            repo_path: Some(repo_path.clone()),
+            range: true,
+            limit: Some(5),
+            ..Default::default()
+        };
+        assert!(validate_inputs(&args).is_ok());
+
+        let args = Args {
+            repo_path: Some(repo_path),
+            show_history: true,
+            limit: Some(0),
+            ..Default::default()
+        };
+        assert!(validate_inputs(&args).is_err());
+    }
+
+    #[test]
+    fn test_validate_inputs_min_gap_minutes() {
+        let (_temp_dir, repo_path) = create_test_repo();
+        let validate = |min_gap_minutes: u32| {
+            validate_inputs(&Args {
+                repo_path: Some(repo_path.clone()),
+                email: Some("test@example.com".to_string()),
+                name: Some("Test User".to_string()),
+                start: Some("2023-01-01 00:00:00".to_string()),
+                end: Some("2023-01-02 00:00:00".to_string()),
+                min_gap_minutes: Some(min_gap_minutes),
+                ..Default::default()
+            })
+        };
+
+        assert!(validate(1).is_ok());
+        assert!(validate(0).is_err());
+    }
+
+    #[test]
+    fn test_validate_inputs_date_format() {
+        let (_temp_dir, repo_path) = create_test_repo();
+        let validate = |format: &str, start: &str, end: &str| {
+            validate_inputs(&Args {
+                repo_path: Some(repo_path.clone()),
+                email: Some("test@example.com".to_string()),
+                name: Some("Test User".to_string()),
+                start: Some(start.to_string()),
+                end: Some(end.to_string()),
+                date_format: Some(format.to_string()),
+                ..Default::default()
+            })
+        };
+
+        assert!(validate(
+            "%Y-%m-%dT%H:%M:%S",
+            "2023-01-01T00:00:00",
+            "2023-01-02T00:00:00"
+        )
+        .is_ok());
+        assert!(validate("%d/%m/%Y %H:%M", "01/01/2023 00:00", "02/01/2023 00:00").is_ok());
+        // Dates are compared once parsed, not as strings
+        assert!(validate("%d/%m/%Y %H:%M", "31/12/2022 00:00", "02/01/2023 00:00").is_ok());
+        assert!(validate("%d/%m/%Y %H:%M", "02/01/2023 00:00", "31/12/2022 00:00").is_err());
+        assert!(validate(
+            "%d/%m/%Y %H:%M",
+            "2023-01-01 00:00:00",
+            "2023-01-02 00:00:00"
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_validate_inputs_mailmap_replaces_identity() {
+        let (temp_dir, repo_path) = create_test_repo();
+        let mailmap_path = temp_dir.path().join("mailmap");
+        std::fs::write(
+            &mailmap_path,
+            "Proper <proper@example.com> <old@example.com>\n",
+        )
+        .unwrap();
+        let mailmap = mailmap_path.to_string_lossy().to_string();
+
+        let args = Args {
+            repo_path: Some(repo_path.clone()),
+            mailmap: Some(mailmap.clone()),
+            start: Some("KEEP_ORIGINAL".to_string()),
+            end: Some("KEEP_ORIGINAL".to_string()),
+            ..Default::default()
+        };
+        assert!(validate_inputs(&args).is_ok());
+
+        let args = Args {
+            repo_path: Some(repo_path),
+            mailmap: Some(
+                temp_dir
+                    .path()
+                    .join("missing")
+                    .to_string_lossy()
+                    .to_string(),
+            ),
+            start: Some("KEEP_ORIGINAL".to_string()),
+            end: Some("KEEP_ORIGINAL".to_string()),
+            ..Default::default()
+        };
+        assert!(validate_inputs(&args).is_err());
+    }
+
+    #[test]
+    fn test_validate_inputs_keep_author() {
+        let (_temp_dir, repo_path) = create_test_repo();
+        let keep_author = |start: &str| {
+            validate_inputs(&Args {
+                repo_path: Some(repo_path.clone()),
+                keep_author: true,
+                start: Some(start.to_string()),
+                end: Some(start.replace("01 00", "10 00")),
+                ..Default::default()
+            })
+        };
+
+        assert!(keep_author("2023-01-01 00:00:00").is_ok());
+        // With the original timestamps nothing would change
+        assert!(keep_author("KEEP_ORIGINAL").is_err());
+    }
+
+    #[test]
+    fn test_normalize_email() {
+        assert_eq!(normalize_email("Me@Example.COM", false), "Me@example.com");
+        assert_eq!(normalize_email("Me@Example.COM", true), "me@example.com");
+        assert_eq!(
+            normalize_email("  me@example.com \t", false),
+            "me@example.com"
+        );
+        assert_eq!(
+            normalize_email(" Dev.Ops@Corp.IO ", true),
+            "dev.ops@corp.io"
+        );
+        // Only the last @ starts the domain
+        assert_eq!(
+            normalize_email("\"A@B\"@EXAMPLE.com", false),
+            "\"A@B\"@example.com"
+        );
+        assert_eq!(normalize_email(" No-Domain ", false), "No-Domain");
+        assert_eq!(normalize_email("me@example.com", true), "me@example.com");
+    }
+
+    #[test]
+    fn test_validate_inputs_normalize_emails() {
+        let (_temp_dir, repo_path) = create_test_repo();
+        let args = Args {
+            repo_path: Some(repo_path),
+            normalize_emails: true,
+            lowercase_local_part: true,
+            start: Some("KEEP_ORIGINAL".to_string()),
+            end: Some("KEEP_ORIGINAL".to_string()),
+            ..Default::default()
+        };
+        assert!(validate_inputs(&args).is_ok());
+    }
+
+    #[test]
+    fn test_validate_inputs_preview_rows() {
+        let (_temp_dir, repo_path) = create_test_repo();
+        let range = |preview_rows: usize, show_diff: bool| {
+            validate_inputs(&Args {
+                repo_path: Some(repo_path.clone()),
+                range: true,
+                simulate: show_diff,
+                show_diff,
+                preview_rows: Some(preview_rows),
+                ..Default::default()
+            })
+        };
+
+        assert!(range(3, true).is_ok());
+        let err = range(0, true).unwrap_err();
+        assert!(err.to_string().contains("greater than 0"), "{err}");
+    }
+
+    #[test]
+    fn test_validate_inputs_modified_window() {
+        let (_temp_dir, repo_path) = create_test_repo();
+        let validate = |after: Option<&str>, before: Option<&str>| {
+            validate_inputs(&Args {
+                repo_path: Some(repo_path.clone()),
+                email: Some("test@example.com".to_string()),
+                name: Some("Test User".to_string()),
+                start: Some("2023-01-01 00:00:00".to_string()),
+                end: Some("2023-01-10 00:00:00".to_string()),
+                modified_after: after.map(str::to_string),
+                modified_before: before.map(str::to_string),
+                ..Default::default()
+            })
+        };
+
+        assert!(validate(Some("2022-03-01 00:00:00"), None).is_ok());
+        assert!(validate(Some("2022-03-01 00:00:00"), Some("2022-04-01 00:00:00")).is_ok());
+        let err = validate(Some("2022-04-01 00:00:00"), Some("2022-03-01 00:00:00")).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "--modified-after must be before --modified-before"
+        );
+        let err = validate(None, Some("March 2022")).unwrap_err();
+        assert!(err
+            .to_string()
+            .starts_with("--modified-before: Invalid date"));
+    }
+
+    #[test]
+    fn test_validate_inputs_no_future() {
+        let (_temp_dir, repo_path) = create_test_repo();
+        let rewrite = |end: &str, no_future: bool| {
+            validate_inputs(&Args {
+                repo_path: Some(repo_path.clone()),
+                email: Some("test@example.com".to_string()),
+                name: Some("Test User".to_string()),
+                start: Some("2023-01-01 00:00:00".to_string()),
+                end: Some(end.to_string()),
+                no_future,
+                ..Default::default()
+            })
+        };
+
+        // Off by default: a future end date is still accepted
+        assert!(rewrite("2999-01-01 00:00:00", false).is_ok());
+        let err = rewrite("2999-01-01 00:00:00", true).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "--end 2999-01-01 00:00:00 is in the future (rejected by --no-future)"
+        );
+        assert!(rewrite("2023-06-01 00:00:00", true).is_ok());
+    }
+
+    #[test]
+    fn test_validate_inputs_anonymize() {
+        let (_temp_dir, repo_path) = create_test_repo();
+        let args = Args {
+            repo_path: Some(repo_path),
+            anonymize: true,
+            anonymize_map: Some("map".to_string()),
+            start: Some("KEEP_ORIGINAL".to_string()),
+            end: Some("KEEP_ORIGINAL".to_string()),
+            ..Default::default()
+        };
+        assert!(validate_inputs(&args).is_ok());
+    }
+
+    #[test]
+    fn test_validate_inputs_graph() {
+        let (_temp_dir, repo_path) = create_test_repo();
+        let graph = |path: Option<&str>| {
+            validate_inputs(&Args {
+                repo_path: Some(repo_path.clone()),
+                graph: true,
+                show_history: true,
+                path_filter: path.map(str::to_string),
+                ..Default::default()
+            })
+        };
+
+        assert!(graph(None).is_ok());
+        // -s after a full rewrite with --path lists the filtered history
+        assert!(graph(Some("src")).is_err());
+    }
+
+    #[test]
+    fn test_validate_inputs_find_author() {
+        let (_temp_dir, repo_path) = create_test_repo();
+        let find_author = |replace_name: &str, replace_email: &str| {
+            validate_inputs(&Args {
+                repo_path: Some(repo_path.clone()),
+                find_author: Some("old@example.com".to_string()),
+                replace_name: Some(replace_name.to_string()),
+                replace_email: Some(replace_email.to_string()),
+                start: Some("KEEP_ORIGINAL".to_string()),
+                end: Some("KEEP_ORIGINAL".to_string()),
+                ..Default::default()
+            })
+        };
+
+        assert!(find_author("Test User", "test@example.com").is_ok());
+        assert!(find_author("Test User", "not-an-email").is_err());
+        assert!(find_author(" ", "test@example.com").is_err());
+    }
+
+    #[test]
+    fn test_validate_inputs_replace_in_files() {
+        let (_temp_dir, repo_path) = create_test_repo();
+        let args = Args {
+            repo_path: Some(repo_path.clone()),
+            replace_in_files: vec!["secret=***".to_string()],
+            ..Default::default()
+        };
+        assert!(validate_inputs(&args).is_ok());
+
+        let args = Args {
+            repo_path: Some(repo_path.clone()),
+            replace_in_files: vec!["missing-separator".to_string()],
+            ..Default::default()
+        };
+        assert!(validate_inputs(&args).is_err());
+    }
+
+    #[test]
+    fn test_validate_inputs_author_date_order() {
+        let (_temp_dir, repo_path) = create_test_repo();
+        let validate = |fix: bool| {
+            validate_inputs(&Args {
+                repo_path: Some(repo_path.clone()),
+                author_date_order: true,
+                fix,
+                ..Default::default()
+            })
+        };
+
+        assert!(validate(false).is_ok());
+        assert!(validate(true).is_ok());
+    }
+
+    #[test]
+    fn test_validate_inputs_round() {
+        let (_temp_dir, repo_path) = create_test_repo();
+        let args = Args {
+            repo_path: Some(repo_path),
+            round: Some(Granularity::Hour),
+            ..Default::default()
+        };
+        assert!(validate_inputs(&args).is_ok());
+    }
+
+    #[test]
+    fn test_validate_inputs_continue() {
+        let (_temp_dir, repo_path) = create_test_repo();
+        let args = Args {
+            repo_path: Some(repo_path),
+            continue_rewrite: true,
+            ..Default::default()
+        };
+        assert!(validate_inputs(&args).is_ok());
+    }
+
+    #[test]
+    fn test_validate_inputs_linked_worktree_and_bare_repo() {
+        let (temp_dir, repo_path) = create_test_repo();
+        let repo = git2::Repository::open(&repo_path).unwrap();
+        let worktree_path = temp_dir.path().join("linked");
+        repo.worktree("linked", &worktree_path, None).unwrap();
+        assert!(worktree_path.join(".git").is_file());
+
+        let bare_dir = TempDir::new().unwrap();
+        git2::Repository::init_bare(bare_dir.path()).unwrap();
+
+        for path in [worktree_path.as_path(), bare_dir.path()] {
+            let args = Args {
+                repo_path: Some(path.to_str().unwrap().to_string()),
+                show_history: true,
+                ..Default::default()
+            };
+            assert!(validate_inputs(&args).is_ok(), "{}", path.display());
+        }
+
+        // A plain directory is still rejected
+        let plain_dir = TempDir::new().unwrap();
+        let args = Args {
+            repo_path: Some(plain_dir.path().to_str().unwrap().to_string()),
+            show_history: true,
+            ..Default::default()
+        };
+        assert!(validate_inputs(&args).is_err());
+    }
+
+    #[test]
+    fn test_validate_inputs_url_schemes() {
+        let validate = |repo_path: &str| {
+            validate_inputs(&Args {
+                repo_path: Some(repo_path.to_string()),
+                show_history: true,
+                ..Default::default()
+            })
+        };
+
+        assert!(validate("ssh://git@github.com/user/repo.git").is_ok());
+        assert!(validate("file:///srv/git/repo.git").is_ok());
+        assert!(validate("git@github.com:user/repo.git").is_ok());
+
+        // Parseable as a URL, but not one git can clone
+        assert!(validate("mailto:user@example.com").is_err());
+        assert!(validate("/nonexistent/repo").is_err());
+    }
+
+    #[test]
+    fn test_validate_inputs_swapped_name_and_email() {
+        let (_temp_dir, repo_path) = create_test_repo();
+        let validate = |name: &str, email: &str, strict: bool| {
+            validate_inputs(&Args {
+                repo_path: Some(repo_path.clone()),
+                name: Some(name.to_string()),
+                email: Some(email.to_string()),
+                start: Some("2023-01-01 00:00:00".to_string()),
+                end: Some("2023-01-02 00:00:00".to_string()),
+                strict,
+                ..Default::default()
+            })
+        };
+
+        assert!(validate("My Name", "me@example.com", true).is_ok());
+
+        let err = validate("me@example.com", "My Name", false).unwrap_err();
+        assert!(err.to_string().contains("look swapped"), "{err}");
+
+        // Only the name is off: a warning, or an error with --strict
+        assert!(validate("me@example.com", "me@example.com", false).is_ok());
+        let err = validate("me@example.com", "me@example.com", true).unwrap_err();
+        assert!(err.to_string().contains("--strict"), "{err}");
+    }
+
+    #[test]
+    fn test_check_identity_order() {
+        assert_eq!(
+            check_identity_order("My Name", "me@example.com", false).unwrap(),
+            None
+        );
+        assert!(check_identity_order("me@example.com", "My Name", false).is_err());
+        let warning = check_identity_order("me@example.com", "other@example.com", false)
+            .unwrap()
+            .unwrap();
+        assert!(warning.contains("looks like an email"), "{warning}");
+    }
+
+    #[test]
+    fn test_validate_inputs_quiet_rejects_interactive_modes() {
+        let (_temp_dir, repo_path) = create_test_repo();
+        let args = Args {
+            repo_path: Some(repo_path.clone()),
+            show_history: true,
+            quiet: true,
+            ..Default::default()
+        };
+        assert!(validate_inputs(&args).is_ok());
+
+        for (pick, range, interactive) in [
+            (true, false, false),
+            (false, true, false),
+            (false, false, true),
+        ] {
+            let args = Args {
+                repo_path: Some(repo_path.clone()),
+                pick_specific_commits: pick,
+                range,
+                interactive,
+                quiet: true,
+                ..Default::default()
+            };
+            let err = validate_inputs(&args).unwrap_err().to_string();
+            assert!(err.contains("--quiet cannot be used"), "{err}");
+        }
+
+        // A batch run draws no editor
+        let args = Args {
+            repo_path: Some(repo_path),
+            range: true,
+            batch: true,
+            range_tail: Some(2),
+            quiet: true,
+            ..Default::default()
+        };
+        assert!(validate_inputs(&args).is_ok());
+    }
+
+    #[test]
+    fn test_validate_inputs_quick_range() {
+        let (_temp_dir, repo_path) = create_test_repo();
+        let validate = |tail: Option<usize>, head: Option<usize>, count: bool| {
+            validate_inputs(&Args {
+                repo_path: Some(repo_path.clone()),
+                range: true,
+                range_tail: tail,
+                range_head: head,
+                count,
+                ..Default::default()
+            })
+        };
+
+        assert!(validate(Some(1), None, false).is_ok());
+        assert!(validate(None, Some(1), false).is_ok());
+        assert!(validate(Some(1), None, true).is_ok());
+        // Counting needs the range up front rather than at the prompt
+        assert!(validate(None, None, true).is_err());
+    }
+
+    #[test]
+    fn test_validate_inputs_shift_anchor() {
+        let (_temp_dir, repo_path) = create_test_repo();
+        let args = Args {
+            repo_path: Some(repo_path.clone()),
+            range: true,
+            shift_anchor: Some("2024-01-01 09:00:00".to_string()),
+            ..Default::default()
+        };
+        assert!(validate_inputs(&args).is_ok());
+
+        let args = Args {
+            repo_path: Some(repo_path.clone()),
+            range: true,
+            shift_anchor: Some("2024-01-01".to_string()),
+            ..Default::default()
+        };
+        assert!(validate_inputs(&args).is_err());
+
+        // Range mode with only the message editable cannot move timestamps
+        let args = Args {
+            repo_path: Some(repo_path.clone()),
+            range: true,
+            edit_message: true,
+            shift_anchor: Some("2024-01-01 09:00:00".to_string()),
+            ..Default::default()
+        };
+        assert!(validate_inputs(&args).is_err());
+    }
+
+    #[test]
+    fn test_validate_inputs_committer_column() {
+        let (_temp_dir, repo_path) = create_test_repo();
+        let validate = |edit_message: bool, edit_time: bool| {
+            validate_inputs(&Args {
+                repo_path: Some(repo_path.clone()),
+                committer_column: true,
+                range: true,
+                edit_message,
+                edit_time,
+                ..Default::default()
+            })
+        };
+
+        assert!(validate(false, false).is_ok());
+        assert!(validate(true, true).is_ok());
+        // --message alone leaves no timestamp to split
+        assert!(validate(true, false).is_err());
+    }
+
+    #[test]
+    fn test_validate_inputs_backups() {
+        let (_temp_dir, repo_path) = create_test_repo();
+        let validate = |list_backups: bool, prune_backups: bool, keep_backups: Option<usize>| {
+            validate_inputs(&Args {
+                repo_path: Some(repo_path.clone()),
+                list_backups,
+                prune_backups,
+                keep_backups,
+                ..Default::default()
+            })
+        };
+
+        assert!(validate(true, false, None).is_ok());
+        assert!(validate(false, true, Some(3)).is_ok());
+        assert!(validate(true, false, Some(3)).is_err());
+    }
+
+    #[test]
+    fn test_validate_inputs_message_transforms() {
+        let (_temp_dir, repo_path) = create_test_repo();
+        for (range, pick) in [(true, false), (false, true)] {
+            let args = Args {
+                repo_path: Some(repo_path.clone()),
+                range,
+                pick_specific_commits: pick,
+                message_prepend: Some("ABC-1: ".to_string()),
+                message_strip: Some(r"(?m)^Signed-off-by: .*$".to_string()),
+                ..Default::default()
+            };
+            assert!(validate_inputs(&args).is_ok());
+        }
+
+        let args = Args {
+            repo_path: Some(repo_path.clone()),
+            range: true,
+            message_strip: Some("(unclosed".to_string()),
+            ..Default::default()
+        };
+        assert!(validate_inputs(&args).is_err());
+
+        let args = Args {
+            repo_path: Some(repo_path.clone()),
+            range: true,
+            edit_time: true,
+            message_append: Some(" (#1)".to_string()),
+            ..Default::default()
+        };
+        assert!(validate_inputs(&args).is_err());
+    }
+
+    #[test]
+    fn test_check_email_domain() {
+        let allowed = vec!["corp.com".to_string(), "@Corp.IO".to_string()];
+
+        assert!(check_email_domain("dev@corp.com", &allowed).is_ok());
+        assert!(check_email_domain("dev@CORP.com", &allowed).is_ok());
+        assert!(check_email_domain("dev@corp.io", &allowed).is_ok());
+        // Nothing listed means every domain is fine
+        assert!(check_email_domain("dev@gmail.com", &[]).is_ok());
+
+        let err = check_email_domain("dev@gmail.com", &allowed).unwrap_err();
+        assert!(err.to_string().contains("'gmail.com'"), "{err}");
+        // Subdomains and look-alikes have to be listed themselves
+        assert!(check_email_domain("dev@eu.corp.com", &allowed).is_err());
+        assert!(check_email_domain("dev@corp.com.evil.io", &allowed).is_err());
+    }
+
+    #[test]
+    fn test_validate_inputs_allowed_email_domain() {
+        let (_temp_dir, repo_path) = create_test_repo();
+        let validate = |email: &str, range: bool| {
+            validate_inputs(&Args {
+                repo_path: Some(repo_path.clone()),
+                email: Some(email.to_string()),
+                name: Some("Test User".to_string()),
+                start: Some("2023-01-01 00:00:00".to_string()),
+                end: Some("2023-01-02 00:00:00".to_string()),
+                allowed_email_domains: vec!["example.com".to_string()],
+                range,
+                ..Default::default()
+            })
+        };
+
+        assert!(validate("test@example.com", false).is_ok());
+        assert!(validate("test@other.com", false).is_err());
+        // Range mode pre-fills the editor with --email, so it is checked too
+        assert!(validate("test@other.com", true).is_err());
+    }
+
+    #[test]
+    fn test_validate_email() {
+        assert!(validate_email("test@example.com").is_ok());
+        assert!(validate_email("user+tag@example.co.uk").is_ok());
+        assert!(validate_email("invalid-email").is_err());
+        assert!(validate_email("@").is_err());
+        assert!(validate_email("a@").is_err());
+    }
 }
@@ -1,6 +1,8 @@
 use git_editor::args::Args;
 use git_editor::utils::commit_history::get_commit_history;
 use git_editor::utils::datetime::generate_timestamps;
+use git_editor::utils::rewrite_state::RewriteState;
+use git_editor::utils::types::EditOptions;
 use git_editor::utils::validator::validate_inputs;
 use serial_test::serial;
 use std::fs;
@@ -76,7 +78,7 @@ fn test_show_history_mode_integration() {
         edit_author: false,
         edit_time: false,
         docs: false,
-        _temp_dir: None,
+        ..Default::default()
     };
 
     // Test validation passes for show_history mode
@@ -116,7 +118,7 @@ fn test_pick_specific_commits_mode_integration() {
         edit_author: false,
         edit_time: false,
         docs: false,
-        _temp_dir: None,
+        ..Default::default()
     };
 
     // Test validation passes for pick_specific_commits mode
@@ -159,7 +161,7 @@ fn test_full_rewrite_mode_integration() {
         edit_author: false,
         edit_time: false,
         docs: false,
-        _temp_dir: None,
+        ..Default::default()
     };
 
     // Test validation passes for full rewrite mode
@@ -212,7 +214,7 @@ fn test_mode_flag_precedence() {
         edit_author: false,
         edit_time: false,
         docs: false,
-        _temp_dir: None,
+        ..Default::default()
     };
 
     let validation_result = validate_inputs(&args);
@@ -240,7 +242,7 @@ fn test_invalid_repo_path_all_modes() {
         edit_author: false,
         edit_time: false,
         docs: false,
-        _temp_dir: None,
+        ..Default::default()
     };
 
     let history_result = get_commit_history(&args_show, false);
@@ -262,7 +264,7 @@ fn test_invalid_repo_path_all_modes() {
         edit_author: false,
         edit_time: false,
         docs: false,
-        _temp_dir: None,
+        ..Default::default()
     };
 
     let history_result = get_commit_history(&args_pick, false);
@@ -284,7 +286,7 @@ fn test_invalid_repo_path_all_modes() {
         edit_author: false,
         edit_time: false,
         docs: false,
-        _temp_dir: None,
+        ..Default::default()
     };
 
     let timestamp_result = generate_timestamps(&mut args_full);
@@ -312,7 +314,7 @@ fn test_full_rewrite_mode_insufficient_date_range() {
         edit_author: false,
         edit_time: false,
         docs: false,
-        _temp_dir: None,
+        ..Default::default()
     };
 
     let validation_result = validate_inputs(&args);
@@ -353,7 +355,7 @@ fn test_full_rewrite_mode_invalid_date_format() {
         edit_author: false,
         edit_time: false,
         docs: false,
-        _temp_dir: None,
+        ..Default::default()
     };
 
     let timestamp_result = generate_timestamps(&mut args);
@@ -381,7 +383,7 @@ fn test_workflow_show_history_then_pick_commits() {
         edit_author: false,
         edit_time: false,
         docs: false,
-        _temp_dir: None,
+        ..Default::default()
     };
 
     let history_result = get_commit_history(&args_show, false);
@@ -405,7 +407,7 @@ fn test_workflow_show_history_then_pick_commits() {
         edit_author: false,
         edit_time: false,
         docs: false,
-        _temp_dir: None,
+        ..Default::default()
     };
 
     let validation_result = validate_inputs(&args_pick);
@@ -437,7 +439,7 @@ fn test_simulation_mode_complete_args() {
         edit_author: false,
         edit_time: false,
         docs: false,
-        _temp_dir: None,
+        ..Default::default()
     };
 
     // Test validation passes for simulation mode with complete args
@@ -449,8 +451,8 @@ fn test_simulation_mode_complete_args() {
     assert!(timestamp_result.is_ok());
 
     // Test that simulation args validation passes
-    let simulation_validation = args.validate_simulation_args();
-    assert!(simulation_validation.is_ok());
+    args.validate_simulation_args();
+    assert!(args.simulate);
 }
 
 #[test]
@@ -474,7 +476,7 @@ fn test_simulation_mode_incomplete_args() {
         edit_author: false,
         edit_time: false,
         docs: false,
-        _temp_dir: None,
+        ..Default::default()
     };
 
     // Basic validation should pass for simulation mode
@@ -482,8 +484,8 @@ fn test_simulation_mode_incomplete_args() {
     assert!(validation_result.is_ok());
 
     // Simulation args validation should pass
-    let simulation_validation = args.validate_simulation_args();
-    assert!(simulation_validation.is_ok());
+    args.validate_simulation_args();
+    assert!(args.simulate);
 
     // ensure_all_args_present should pass for simulation mode even with incomplete args
     let ensure_result = args.ensure_all_args_present();
@@ -495,7 +497,7 @@ fn test_simulation_mode_incomplete_args() {
 fn test_simulation_mode_with_show_diff() {
     let (_temp_dir, repo_path) = create_test_repo_with_commits();
 
-    let args = Args {
+    let mut args = Args {
         repo_path: Some(repo_path),
         email: Some("test@example.com".to_string()),
         name: Some("Test User".to_string()),
@@ -510,23 +512,23 @@ fn test_simulation_mode_with_show_diff() {
         edit_author: false,
         edit_time: false,
         docs: false,
-        _temp_dir: None,
+        ..Default::default()
     };
 
     // Test that simulation with show_diff passes validation
     let validation_result = validate_inputs(&args);
     assert!(validation_result.is_ok());
 
-    let simulation_validation = args.validate_simulation_args();
-    assert!(simulation_validation.is_ok());
+    args.validate_simulation_args();
+    assert!(args.simulate);
 }
 
 #[test]
 #[serial]
-fn test_show_diff_without_simulate_fails() {
+fn test_show_diff_without_simulate_implies_simulate() {
     let (_temp_dir, repo_path) = create_test_repo_with_commits();
 
-    let args = Args {
+    let mut args = Args {
         repo_path: Some(repo_path),
         email: Some("test@example.com".to_string()),
         name: Some("Test User".to_string()),
@@ -541,15 +543,14 @@ fn test_show_diff_without_simulate_fails() {
         edit_author: false,
         edit_time: false,
         docs: false,
-        _temp_dir: None,
+        ..Default::default()
     };
 
-    // Test that show_diff without simulate fails validation
-    let simulation_validation = args.validate_simulation_args();
-    assert!(simulation_validation.is_err());
-
-    let error_msg = simulation_validation.unwrap_err().to_string();
-    assert!(error_msg.contains("--show-diff requires --simulate"));
+    // show_diff on its own turns the simulation on instead of failing
+    args.validate_simulation_args();
+    assert!(args.simulate);
+    assert!(args.show_diff);
+    assert!(validate_inputs(&args).is_ok());
 }
 
 #[test]
@@ -574,14 +575,15 @@ fn test_cli_execution_simulate_incomplete_args_no_panic() {
         edit_author: false,
         edit_time: false,
         docs: false,
-        _temp_dir: None,
+        ..Default::default()
     };
 
     // Mock the Args::parse() result by testing the execution flow manually
     // This tests the exact path that was causing the panic: simulate mode with missing args
 
     // First ensure basic validation passes
-    assert!(args.validate_simulation_args().is_ok());
+    args.validate_simulation_args();
+    assert!(args.simulate);
     assert!(validate_inputs(&args).is_ok());
 
     // Now test the critical path: ensure_all_args_present should pass for simulation mode
@@ -617,12 +619,13 @@ fn test_cli_execution_simulate_complete_args_success() {
         edit_author: false,
         edit_time: false,
         docs: false,
-        _temp_dir: None,
+        ..Default::default()
     };
 
     // Test full execution path
     assert!(args.ensure_all_args_present().is_ok());
-    assert!(args.validate_simulation_args().is_ok());
+    args.validate_simulation_args();
+    assert!(args.simulate);
     assert!(validate_inputs(&args).is_ok());
 
     // Test timestamp generation works
@@ -652,7 +655,7 @@ fn test_simulation_execution_function_missing_args() {
         edit_author: false,
         edit_time: false,
         docs: false,
-        _temp_dir: None,
+        ..Default::default()
     };
 
     // The issue was that the old code called generate_timestamps without checking
@@ -794,8 +797,8 @@ fn test_docs_flag_in_help() {
 
     let stdout = String::from_utf8(output.stdout).unwrap();
 
-    // Should contain the docs flag in help output
-    assert!(stdout.contains("--docs"));
+    // Should contain the docs subcommand in help output
+    assert!(stdout.contains("docs"));
     assert!(stdout.contains("Open comprehensive documentation in the browser"));
 }
 
@@ -810,7 +813,6 @@ fn test_docs_mode_precedence_over_other_modes() {
             "--docs",
             "--show-history", // These other modes should be ignored
             "--simulate",
-            "--pick-specific-commits",
         ])
         .env("DISPLAY", ":0")
         .env("GIT_EDITOR_NO_BROWSER", "1") // Disable browser opening during tests
@@ -835,3 +837,620 @@ fn test_docs_mode_precedence_over_other_modes() {
         "Should not show history mode message. Stdout: {stdout}, Stderr: {stderr}"
     );
 }
+
+#[test]
+#[serial]
+fn test_conflicting_modes_are_rejected() {
+    let output = std::process::Command::new("cargo")
+        .args(["run", "--", "--docs", "--pick-specific-commits"])
+        .output()
+        .expect("Failed to execute command");
+    assert!(!output.status.success());
+
+    let output = std::process::Command::new("cargo")
+        .args(["run", "--", "--range", "pick"])
+        .output()
+        .expect("Failed to execute command");
+    assert!(!output.status.success());
+
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(
+        stderr.contains("cannot be used with"),
+        "Expected a conflict error. Stderr: {stderr}"
+    );
+}
+
+#[test]
+#[serial]
+fn test_empty_repository_all_modes() {
+    let temp_dir = TempDir::new().unwrap();
+    let repo_path = temp_dir.path().join("repo");
+    let mut options = git2::RepositoryInitOptions::new();
+    options.initial_head("main");
+    let repo = git2::Repository::init_opts(&repo_path, &options).unwrap();
+    // Staged files do not make the branch any less unborn
+    fs::write(repo_path.join("staged.txt"), "staged").unwrap();
+    let mut index = repo.index().unwrap();
+    index.add_path(std::path::Path::new("staged.txt")).unwrap();
+    index.write().unwrap();
+    let repo_path = repo_path.to_str().unwrap();
+    let plan_path = temp_dir.path().join("plan.json");
+    fs::write(&plan_path, "[]").unwrap();
+
+    let rewrite = [
+        "rewrite",
+        "--email",
+        "test@example.com",
+        "--name",
+        "Test User",
+        "--begin",
+        "2023-01-01 00:00:00",
+        "--end",
+        "2023-01-02 00:00:00",
+    ];
+    let modes: Vec<Vec<&str>> = vec![
+        vec!["history"],
+        vec!["history", "--stats-only"],
+        vec!["pick"],
+        vec!["range"],
+        rewrite.to_vec(),
+        vec!["rewrite", "--interactive"],
+        vec!["simulate"],
+        vec!["--apply-plan", plan_path.to_str().unwrap()],
+        vec!["--replace-in-files", "secret=***"],
+        vec!["--author-date-order", "--fix"],
+        vec!["history", "--ref", "main"],
+        vec!["history", "--rev", "HEAD"],
+    ];
+
+    for mode in modes {
+        let output = std::process::Command::new("cargo")
+            .args(["run", "--", "--repo-path", repo_path])
+            .args(&mode)
+            .stdin(std::process::Stdio::null())
+            .output()
+            .expect("Failed to execute command");
+
+        let stdout = String::from_utf8(output.stdout).unwrap();
+        let stderr = String::from_utf8(output.stderr).unwrap();
+        // Listing an empty history succeeds; every rewrite has nothing to do
+        let expected = if mode[0] == "history" { 0 } else { 3 };
+        assert_eq!(
+            output.status.code(),
+            Some(expected),
+            "{mode:?} exited unexpectedly on an empty repository. Stderr: {stderr}"
+        );
+        let nothing = if mode[0] == "history" {
+            "nothing to show"
+        } else {
+            "nothing to rewrite"
+        };
+        assert!(
+            stdout.contains(&format!("Branch 'main' has no commits yet; {nothing}")),
+            "{mode:?} did not report the empty repository. Stdout: {stdout}"
+        );
+    }
+}
+
+#[test]
+#[serial]
+fn test_no_color_when_stdout_is_not_a_terminal() {
+    let (_temp_dir, repo_path) = create_test_repo_with_commits();
+
+    // Output is captured through a pipe, so colors must be off even without
+    // NO_COLOR or --no-color
+    let output = std::process::Command::new("cargo")
+        .args(["run", "--", "history", "--repo-path", &repo_path])
+        .env_remove("NO_COLOR")
+        .stdin(std::process::Stdio::null())
+        .output()
+        .expect("Failed to execute command");
+
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(output.status.success());
+    assert!(stdout.contains("Commit 1"), "Stdout: {stdout}");
+    assert!(
+        !stdout.contains('\x1b'),
+        "Stdout contains ANSI escape codes: {stdout:?}"
+    );
+}
+
+fn ref_snapshot(repo_path: &str) -> Vec<(String, git2::Oid)> {
+    let repo = git2::Repository::open(repo_path).unwrap();
+    let mut refs: Vec<_> = repo
+        .references()
+        .unwrap()
+        .filter_map(|reference| reference.ok())
+        .filter_map(|reference| Some((reference.name()?.to_string(), reference.target()?)))
+        .collect();
+    refs.push(("HEAD".to_string(), repo.head().unwrap().target().unwrap()));
+    refs
+}
+
+#[test]
+#[serial]
+fn test_simulate_pick_leaves_refs_unchanged() {
+    use std::io::Write;
+
+    let (_temp_dir, repo_path) = create_test_repo_with_commits();
+    let before = ref_snapshot(&repo_path);
+
+    let mut child = std::process::Command::new("cargo")
+        .args(["run", "--", "pick", "--simulate", "--show-diff"])
+        .args(["--repo-path", &repo_path])
+        .stdin(std::process::Stdio::piped())
+        .stdout(std::process::Stdio::piped())
+        .stderr(std::process::Stdio::piped())
+        .spawn()
+        .expect("Failed to execute command");
+    // Select the newest commit and rename its author
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(b"1\n1\nSimulated Name\n")
+        .unwrap();
+    let output = child.wait_with_output().unwrap();
+
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(output.status.success(), "Stderr: {stderr}");
+    assert!(stdout.contains("SIMULATION SUMMARY"), "Stdout: {stdout}");
+    assert!(stdout.contains("Simulated Name"), "Stdout: {stdout}");
+    assert_eq!(ref_snapshot(&repo_path), before);
+}
+
+#[test]
+#[serial]
+fn test_simulate_range_leaves_refs_unchanged() {
+    let (_temp_dir, repo_path) = create_test_repo_with_commits();
+    let before = ref_snapshot(&repo_path);
+
+    // The table editor needs a terminal, so this run stops there; nothing
+    // may be written before or after it
+    let output = std::process::Command::new("cargo")
+        .args(["run", "--", "range", "--simulate", "--from", "HEAD~1"])
+        .args(["--repo-path", &repo_path])
+        .stdin(std::process::Stdio::null())
+        .output()
+        .expect("Failed to execute command");
+
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.contains("SIMULATION MODE"), "Stdout: {stdout}");
+    assert!(
+        stdout.contains("Total: 2 commits selected"),
+        "Stdout: {stdout}"
+    );
+    assert_eq!(ref_snapshot(&repo_path), before);
+}
+
+#[test]
+#[serial]
+fn test_table_editor_without_a_terminal_fails_cleanly() {
+    let (_temp_dir, repo_path) = create_test_repo_with_commits();
+    let before = ref_snapshot(&repo_path);
+
+    // --yes skips the launch prompt, so the run gets as far as the editor
+    let output = std::process::Command::new("cargo")
+        .args(["run", "--", "range", "--tail", "2", "--yes"])
+        .args(["--repo-path", &repo_path])
+        .stdin(std::process::Stdio::null())
+        .output()
+        .expect("Failed to execute command");
+
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(!output.status.success());
+    assert!(
+        stderr.contains("Cannot enter interactive mode: stdin is not a terminal"),
+        "Stderr: {stderr}"
+    );
+    assert_eq!(ref_snapshot(&repo_path), before);
+}
+
+#[test]
+#[serial]
+fn test_quiet_full_rewrite_prints_only_the_updated_ref() {
+    let (_temp_dir, repo_path) = create_test_repo_with_commits();
+
+    let output = std::process::Command::new("cargo")
+        .args(["run", "--", "rewrite", "--quiet", "--repo-path", &repo_path])
+        .args(["--email", "quiet@example.com", "--name", "Quiet User"])
+        .args([
+            "--begin",
+            "2023-01-01 00:00:00",
+            "--end",
+            "2023-01-10 00:00:00",
+        ])
+        .arg("--yes")
+        .stdin(std::process::Stdio::null())
+        .output()
+        .expect("Failed to execute command");
+
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(output.status.success(), "Stderr: {stderr}");
+
+    let repo = git2::Repository::open(&repo_path).unwrap();
+    let head = repo.head().unwrap().peel_to_commit().unwrap();
+    let lines: Vec<&str> = stdout.lines().collect();
+    assert_eq!(lines.len(), 1, "Stdout: {stdout}");
+    assert!(
+        lines[0].starts_with("Updated") && lines[0].ends_with(&head.id().to_string()[..8]),
+        "Stdout: {stdout}"
+    );
+    assert_eq!(head.author().name(), Some("Quiet User"));
+}
+
+#[test]
+#[serial]
+fn test_rewrites_without_a_terminal_need_yes() {
+    let (_temp_dir, repo_path) = create_test_repo_with_commits();
+    let before = ref_snapshot(&repo_path);
+
+    let full_rewrite = [
+        "rewrite",
+        "--email",
+        "test@example.com",
+        "--name",
+        "Test User",
+        "--begin",
+        "2023-01-01 00:00:00",
+        "--end",
+        "2023-01-10 00:00:00",
+    ];
+    let modes: Vec<Vec<&str>> = vec![
+        full_rewrite.to_vec(),
+        vec!["--round", "day"],
+        vec!["--replace-in-files", "content=text"],
+    ];
+    for mode in modes {
+        let output = std::process::Command::new("cargo")
+            .args(["run", "--", "--repo-path", &repo_path])
+            .args(&mode)
+            .stdin(std::process::Stdio::null())
+            .output()
+            .expect("Failed to execute command");
+
+        let stderr = String::from_utf8(output.stderr).unwrap();
+        assert!(!output.status.success(), "{mode:?} rewrote without --yes");
+        assert!(
+            stderr.contains("stdin is not a terminal (pass --yes to confirm)"),
+            "{mode:?} Stderr: {stderr}"
+        );
+        assert_eq!(ref_snapshot(&repo_path), before, "{mode:?}");
+    }
+}
+
+#[test]
+#[serial]
+fn test_quiet_full_rewrite_still_needs_confirmation() {
+    let (_temp_dir, repo_path) = create_test_repo_with_commits();
+    let before = ref_snapshot(&repo_path);
+
+    let output = std::process::Command::new("cargo")
+        .args(["run", "--", "rewrite", "-q", "--repo-path", &repo_path])
+        .args(["--email", "quiet@example.com", "--name", "Quiet User"])
+        .args([
+            "--begin",
+            "2023-01-01 00:00:00",
+            "--end",
+            "2023-01-10 00:00:00",
+        ])
+        .stdin(std::process::Stdio::null())
+        .output()
+        .expect("Failed to execute command");
+
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(!output.status.success());
+    assert!(
+        stderr.contains("stdin is not a terminal (pass --yes to confirm)"),
+        "Stderr: {stderr}"
+    );
+    assert_eq!(ref_snapshot(&repo_path), before);
+}
+
+#[test]
+#[serial]
+fn test_quiet_range_batch_prints_only_the_updated_ref() {
+    use std::io::Write;
+    let (_temp_dir, repo_path) = create_test_repo_with_commits();
+
+    let mut child = std::process::Command::new("cargo")
+        .args(["run", "--", "range", "--batch", "--yes", "--quiet"])
+        .args(["--tail", "2", "--repo-path", &repo_path])
+        .stdin(std::process::Stdio::piped())
+        .stdout(std::process::Stdio::piped())
+        .stderr(std::process::Stdio::piped())
+        .spawn()
+        .expect("Failed to execute command");
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(b"1:name=Zed\n")
+        .unwrap();
+    let output = child.wait_with_output().unwrap();
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(output.status.success(), "Stderr: {stderr}");
+
+    let repo = git2::Repository::open(&repo_path).unwrap();
+    let head = repo.head().unwrap().peel_to_commit().unwrap();
+    let lines: Vec<&str> = stdout.lines().collect();
+    assert_eq!(lines.len(), 1, "Stdout: {stdout}");
+    assert!(
+        lines[0].starts_with("Updated") && lines[0].ends_with(&head.id().to_string()[..8]),
+        "Stdout: {stdout}"
+    );
+    assert_eq!(head.author().name(), Some("Zed"));
+}
+
+#[test]
+#[serial]
+fn test_print_head_prints_only_the_new_tip() {
+    let (_temp_dir, repo_path) = create_test_repo_with_commits();
+    let run = |flags: &[&str]| {
+        let output = std::process::Command::new("cargo")
+            .args(["run", "--"])
+            .args(flags)
+            .args([
+                "--quiet",
+                "--yes",
+                "--print-head",
+                "--repo-path",
+                &repo_path,
+            ])
+            .stdin(std::process::Stdio::null())
+            .output()
+            .expect("Failed to execute command");
+        let stderr = String::from_utf8(output.stderr).unwrap();
+        assert!(output.status.success(), "Stderr: {stderr}");
+        String::from_utf8(output.stdout).unwrap()
+    };
+    let head = || {
+        let repo = git2::Repository::open(&repo_path).unwrap();
+        let head = repo.head().unwrap().peel_to_commit().unwrap().id();
+        head.to_string()
+    };
+
+    let stdout = run(&[
+        "rewrite",
+        "--email",
+        "head@example.com",
+        "--name",
+        "Head User",
+        "--begin",
+        "2023-01-01 00:00:07",
+        "--end",
+        "2023-01-10 00:00:00",
+    ]);
+    assert_eq!(stdout, format!("{}\n", head()));
+
+    // The other rewrites report their tip the same way
+    let stdout = run(&["--round", "hour"]);
+    assert_eq!(stdout, format!("{}\n", head()));
+}
+
+#[test]
+#[serial]
+fn test_verbose_lists_each_rewritten_commit() {
+    let run = |verbose: &str| {
+        let (temp_dir, repo_path) = create_test_repo_with_commits();
+        let before: Vec<String> = {
+            let repo = git2::Repository::open(&repo_path).unwrap();
+            let mut revwalk = repo.revwalk().unwrap();
+            revwalk.push_head().unwrap();
+            let mut oids: Vec<_> = revwalk.map(|oid| oid.unwrap().to_string()).collect();
+            oids.reverse();
+            oids
+        };
+        let output = std::process::Command::new("cargo")
+            .args(["run", "--", "--round", "hour", "--yes", verbose])
+            .args(["--repo-path", &repo_path])
+            .stdin(std::process::Stdio::null())
+            .output()
+            .expect("Failed to execute command");
+        let stderr = String::from_utf8(output.stderr).unwrap();
+        assert!(output.status.success(), "Stderr: {stderr}");
+        drop(temp_dir);
+        (before, String::from_utf8(output.stdout).unwrap())
+    };
+
+    // One line per commit, oldest first, naming the fields that changed
+    let (before, stdout) = run("-v");
+    let logged: Vec<&str> = stdout
+        .lines()
+        .filter(|line| line.ends_with("changed)"))
+        .collect();
+    assert_eq!(logged.len(), 3, "Stdout: {stdout}");
+    for (line, oid) in logged.iter().zip(&before) {
+        assert!(line.starts_with(&oid[..8]), "Stdout: {stdout}");
+        assert!(
+            line.ends_with("(author date, commit date changed)"),
+            "Stdout: {stdout}"
+        );
+    }
+    assert!(!stdout.contains("author date: "), "Stdout: {stdout}");
+
+    // -vv adds the old and new values
+    let (_, stdout) = run("-vv");
+    assert!(
+        stdout.contains("  author date: 2009-02-14 00:31:30 +0000 -> 2009-02-14 01:00:00 +0000"),
+        "Stdout: {stdout}"
+    );
+}
+
+#[test]
+#[serial]
+fn test_exit_codes_for_cancelled_and_nothing_to_do() {
+    use std::io::Write;
+
+    let (_temp_dir, repo_path) = create_test_repo_with_commits();
+    let before = ref_snapshot(&repo_path);
+
+    let output = std::process::Command::new("cargo")
+        .args(["run", "--", "--replace-in-files", "no-such-text=***"])
+        .args(["--repo-path", &repo_path])
+        .stdin(std::process::Stdio::null())
+        .output()
+        .expect("Failed to execute command");
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert_eq!(output.status.code(), Some(3), "Stderr: {stderr}");
+
+    let mut child = std::process::Command::new("cargo")
+        .args([
+            "run",
+            "--",
+            "range",
+            "--tail",
+            "2",
+            "--repo-path",
+            &repo_path,
+        ])
+        .stdin(std::process::Stdio::piped())
+        .stdout(std::process::Stdio::piped())
+        .stderr(std::process::Stdio::piped())
+        .spawn()
+        .expect("Failed to execute command");
+    // Back out before the table editor opens
+    child.stdin.take().unwrap().write_all(b"q\n").unwrap();
+    let output = child.wait_with_output().unwrap();
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert_eq!(output.status.code(), Some(2), "Stderr: {stderr}");
+    assert!(!stdout.contains("Operation completed successfully"));
+
+    assert_eq!(ref_snapshot(&repo_path), before);
+}
+
+#[test]
+#[serial]
+fn test_dry_run_show_diff_matches_simulate_show_diff() {
+    let (_temp_dir, repo_path) = create_test_repo_with_commits();
+    let before = ref_snapshot(&repo_path);
+
+    let run = |flags: &[&str]| {
+        let output = std::process::Command::new("cargo")
+            .args(["run", "--"])
+            .args(flags)
+            .args(["--repo-path", &repo_path])
+            .args(["--email", "test@example.com", "--name", "Test User"])
+            .args([
+                "--begin",
+                "2025-01-01 00:00:00",
+                "--end",
+                "2025-01-10 00:00:00",
+            ])
+            .args(["--seed", "7"])
+            .stdin(std::process::Stdio::null())
+            .output()
+            .expect("Failed to execute command");
+        let stderr = String::from_utf8(output.stderr).unwrap();
+        assert!(output.status.success(), "Stderr: {stderr}");
+        String::from_utf8(output.stdout).unwrap()
+    };
+
+    let simulated = run(&["--simulate", "--show-diff"]);
+    assert!(simulated.contains("SIMULATION MODE"), "Stdout: {simulated}");
+    assert_eq!(run(&["--dry-run", "--show-diff"]), simulated);
+    assert_eq!(run(&["--show-diff"]), simulated);
+    assert_eq!(run(&["dry-run", "--show-diff"]), simulated);
+
+    assert_eq!(ref_snapshot(&repo_path), before);
+}
+
+#[test]
+#[serial]
+fn test_confirmation_without_terminal_needs_yes() {
+    let (_temp_dir, repo_path) = create_test_repo_with_commits();
+    let before = ref_snapshot(&repo_path);
+
+    let run = |extra: &[&str]| {
+        std::process::Command::new("cargo")
+            .args(["run", "--", "rewrite", "--repo-path", &repo_path])
+            .args(["--email", "ci@example.com", "--name", "CI User"])
+            .args([
+                "--begin",
+                "2023-01-01 00:00:00",
+                "--end",
+                "2023-01-10 00:00:00",
+            ])
+            .args(extra)
+            .stdin(std::process::Stdio::null())
+            .output()
+            .expect("Failed to execute command")
+    };
+
+    // Nobody can answer the prompt, so the run fails instead of waiting
+    let output = run(&[]);
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert_eq!(output.status.code(), Some(1), "Stderr: {stderr}");
+    assert!(stderr.contains("--yes"), "Stderr: {stderr}");
+    assert_eq!(ref_snapshot(&repo_path), before);
+
+    let output = run(&["--yes"]);
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(output.status.success(), "Stderr: {stderr}");
+    let repo = git2::Repository::open(&repo_path).unwrap();
+    let head = repo.head().unwrap().peel_to_commit().unwrap();
+    assert_eq!(head.author().name(), Some("CI User"));
+}
+
+#[test]
+#[serial]
+fn test_continue_finishes_an_interrupted_rewrite() {
+    let (_temp_dir, repo_path) = create_test_repo_with_commits();
+    let repo = git2::Repository::open(&repo_path).unwrap();
+    let tip = repo.head().unwrap().target().unwrap();
+    let middle = repo.find_commit(tip).unwrap().parent_id(0).unwrap();
+
+    // A pick edit of the middle commit, interrupted before anything was written
+    let options = EditOptions {
+        author_name: Some("Resumed Author".to_string()),
+        ..Default::default()
+    };
+    let edits = std::collections::HashMap::from([(middle, &options)]);
+    let state = RewriteState::new("pick edit", &Args::default(), tip, 3, &edits);
+    state.save(&repo).unwrap();
+
+    // Another rewrite without a terminal to ask on only points at --continue
+    let output = std::process::Command::new("cargo")
+        .args(["run", "--", "pick", "--yes", "--repo-path", &repo_path])
+        .stdin(std::process::Stdio::null())
+        .output()
+        .expect("Failed to execute command");
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(
+        stderr.contains("An interrupted pick edit rewrite (0 of 3 commit(s) written) was found"),
+        "Stderr: {stderr}"
+    );
+    assert!(RewriteState::load(&repo).unwrap().is_some());
+
+    let output = std::process::Command::new("cargo")
+        .args(["run", "--", "--continue", "--repo-path", &repo_path])
+        .stdin(std::process::Stdio::null())
+        .output()
+        .expect("Failed to execute command");
+
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(output.status.success(), "Stdout: {stdout}");
+    assert!(stdout.contains("Resuming pick edit rewrite (0 of 3 commit(s) written)"));
+    let new_tip = repo.head().unwrap().peel_to_commit().unwrap();
+    assert_ne!(new_tip.id(), tip);
+    assert_eq!(
+        new_tip.parent(0).unwrap().author().name(),
+        Some("Resumed Author")
+    );
+    assert!(RewriteState::load(&repo).unwrap().is_none());
+
+    // Nothing is left to continue
+    let output = std::process::Command::new("cargo")
+        .args(["run", "--", "--continue", "--repo-path", &repo_path])
+        .stdin(std::process::Stdio::null())
+        .output()
+        .expect("Failed to execute command");
+    assert!(!output.status.success());
+    assert!(String::from_utf8(output.stderr)
+        .unwrap()
+        .contains("No interrupted rewrite to continue"));
+}
@@ -0,0 +1,96 @@
+//! Allocation measurements for walking large histories. This file is its own
+//! test binary so the counting allocator below sees nothing but this test.
+
+use git_editor::args::Args;
+use git_editor::utils::commit_history::{collect_commits, walk_commits};
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use tempfile::TempDir;
+
+struct CountingAllocator;
+
+static LIVE: AtomicUsize = AtomicUsize::new(0);
+static PEAK: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let ptr = System.alloc(layout);
+        if !ptr.is_null() {
+            let live = LIVE.fetch_add(layout.size(), Ordering::Relaxed) + layout.size();
+            PEAK.fetch_max(live, Ordering::Relaxed);
+        }
+        ptr
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout);
+        LIVE.fetch_sub(layout.size(), Ordering::Relaxed);
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+// The most bytes held at once while `f` ran, above what was live before it
+fn peak_bytes(f: impl FnOnce()) -> usize {
+    let before = LIVE.load(Ordering::Relaxed);
+    PEAK.store(before, Ordering::Relaxed);
+    f();
+    PEAK.load(Ordering::Relaxed) - before
+}
+
+// A linear history of `count` empty commits with long messages
+fn create_large_repo(count: usize) -> (TempDir, String) {
+    let temp_dir = TempDir::new().unwrap();
+    let repo_path = temp_dir.path().to_str().unwrap().to_string();
+    let repo = git2::Repository::init(&repo_path).unwrap();
+    let tree = repo
+        .find_tree(repo.index().unwrap().write_tree().unwrap())
+        .unwrap();
+    let sig = git2::Signature::new("Author", "author@example.com", &git2::Time::new(0, 0)).unwrap();
+
+    let mut parent: Option<git2::Commit> = None;
+    for i in 0..count {
+        let message = format!("Commit {i}\n\n{}", "Body text. ".repeat(20));
+        let parents: Vec<&git2::Commit> = parent.iter().collect();
+        let oid = repo
+            .commit(Some("HEAD"), &sig, &sig, &message, &tree, &parents)
+            .unwrap();
+        parent = Some(repo.find_commit(oid).unwrap());
+    }
+    drop(parent);
+    drop(tree);
+
+    (temp_dir, repo_path)
+}
+
+#[test]
+fn test_streaming_walk_holds_far_less_than_collecting() {
+    let count = 5_000;
+    let (_temp_dir, repo_path) = create_large_repo(count);
+    let args = Args {
+        repo_path: Some(repo_path),
+        ..Default::default()
+    };
+
+    let mut streamed = 0;
+    let streaming = peak_bytes(|| {
+        walk_commits(&args, None, None, |_| {
+            streamed += 1;
+            Ok(())
+        })
+        .unwrap();
+    });
+    let collecting = peak_bytes(|| {
+        assert_eq!(collect_commits(&args, None).unwrap().len(), count);
+    });
+    assert_eq!(streamed, count);
+
+    println!("peak heap for {count} commits: streaming {streaming} B, collecting {collecting} B");
+    // Collecting keeps every message; streaming only ever holds one commit
+    assert!(collecting > count * 200);
+    assert!(
+        streaming * 10 < collecting,
+        "streaming peaked at {streaming} B, collecting at {collecting} B"
+    );
+}